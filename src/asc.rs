@@ -0,0 +1,271 @@
+//! Vector ASC trace format
+//!
+//! Text traces as written by CANoe and CANalyzer. Only CAN frames are
+//! imported, other events are skipped.
+
+use std::io::{BufRead, Result, Write};
+use chrono::TimeZone;
+
+use super::rics;
+use super::server;
+use super::record::{Record, RecordWriter, ChannelMap};
+
+/// Header date formats, with and without milliseconds, 12 and 24 hours
+const DATE_FORMATS: [&str; 4] = ["%a %b %d %I:%M:%S%.f %p %Y", "%a %b %d %I:%M:%S %p %Y", "%a %b %d %H:%M:%S%.f %Y", "%a %b %d %H:%M:%S %Y"];
+
+/// Parse the date of the `date` header line
+fn parse_date(s: &str) -> Option<f64> {
+    for fmt in DATE_FORMATS.iter() {
+        if let Ok(time) = chrono::NaiveDateTime::parse_from_str(s.trim(), fmt) {
+            let time = chrono::Local.from_local_datetime(&time).earliest()?;
+            return Some(time.timestamp() as f64 + time.timestamp_subsec_nanos() as f64 * 1e-9);
+        }
+    }
+    None
+}
+
+/// Format a timestamp for the `date` and `Begin Triggerblock` lines
+fn format_date(timestamp: f64) -> String {
+    let millis = (timestamp * 1e3).round() as i64;
+    let datetime = chrono::Local.timestamp_opt(millis.div_euclid(1000), (millis.rem_euclid(1000) * 1_000_000) as u32).unwrap();
+    format!("{}", datetime.format("%a %b %d %I:%M:%S%.3f %P %Y"))
+}
+
+/// ASC CAN FD message flags
+const FD_EDL: u32 = 0x1000;
+const FD_BRS: u32 = 0x2000;
+const FD_ESI: u32 = 0x4000;
+
+/// Parse an identifier, extended identifiers end with `x`
+fn parse_id(id: &str, radix: u32) -> Option<(i32, bool)> {
+    let extended = id.ends_with('x');
+    let id = if extended { &id[..id.len()-1] } else { id };
+    Some((u32::from_str_radix(id, radix).ok()? as i32, extended))
+}
+
+/// Parse a CAN FD frame event line, the `CANFD` keyword follows the time.
+/// `time CANFD ch dir id [name] brs esi dlc len data...`
+fn parse_fd_frame(fields: &[&str], hex: bool) -> Option<(f64, u32, rics::RICS_Data)> {
+    let radix = if hex { 16 } else { 10 };
+    let time = fields[0].parse::<f64>().ok()?;
+    let channel = fields.get(2)?.parse::<u32>().ok()?;
+    let (id, extended) = parse_id(fields.get(4)?, radix)?;
+
+    // The symbolic name is optional
+    let mut i = 5;
+    if *fields.get(i)? != "0" && *fields.get(i)? != "1" {
+        i += 1;
+    }
+
+    let brs = *fields.get(i)? == "1";
+    let esi = *fields.get(i + 1)? == "1";
+    let len = fields.get(i + 3)?.parse::<usize>().ok()?.min(server::CANFD_MAX_DLEN);
+    let dat = fields.get(i+4..i+4+len)?.iter().map(|x| u8::from_str_radix(x, radix).ok()).collect::<Option<Vec<u8>>>()?;
+    // The EDL flag is cleared for classic frames logged on a CAN FD channel
+    let edl = fields.get(i+4+len+2).and_then(|x| u32::from_str_radix(x, 16).ok()).map(|x| x & FD_EDL != 0).unwrap_or(true);
+    let flags = server::CanFlags { extended: extended, fd: edl, brs: brs && edl, esi: esi && edl, ..Default::default() };
+    Some((time, channel, server::can_frame(id, dat, flags)))
+}
+
+/// Parse a CAN frame event line, relative to the measurement start
+fn parse_frame(line: &str, hex: bool) -> Option<(f64, u32, rics::RICS_Data)> {
+    let radix = if hex { 16 } else { 10 };
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() == 3 && fields[2] == "ErrorFrame" {
+        let time = fields[0].parse::<f64>().ok()?;
+        let channel = fields[1].parse::<u32>().ok()?;
+        return Some((time, channel, server::can_frame(0, vec![], server::CanFlags { error: true, ..Default::default() })));
+    }
+    if fields.len() < 4 {
+        return None;
+    }
+    if fields[1] == "CANFD" {
+        return parse_fd_frame(&fields, hex);
+    }
+
+    let time = fields[0].parse::<f64>().ok()?;
+    let channel = fields[1].parse::<u32>().ok()?;
+    let (id, extended) = parse_id(fields[2], radix)?;
+    let flags = server::CanFlags { extended: extended, ..Default::default() };
+
+    // Direction is optional in old traces
+    let mut i = 3;
+    if fields[i] == "Rx" || fields[i] == "Tx" {
+        i += 1;
+    }
+
+    match *fields.get(i)? {
+        "d" => {
+            let dlc = usize::from_str_radix(fields.get(i + 1)?, 16).ok()?.min(8);
+            let dat = fields.get(i+2..i+2+dlc)?.iter().map(|x| u8::from_str_radix(x, radix).ok()).collect::<Option<Vec<u8>>>()?;
+            Some((time, channel, server::can_frame(id, dat, flags)))
+        },
+        "r" => {
+            // The requested length is missing in old traces
+            let dlc = fields.get(i + 1).and_then(|x| usize::from_str_radix(x, 16).ok()).unwrap_or(0).min(8);
+            Some((time, channel, server::can_frame(id, vec![0; dlc], server::CanFlags { rtr: true, ..flags })))
+        },
+        _ => None,
+    }
+}
+
+/// Read the CAN frames of an ASC trace
+pub fn read_asc(reader: impl BufRead) -> Result<Vec<Record>> {
+    let mut records = vec![];
+    let mut start = 0.0;
+    let mut hex = true;
+    let mut relative = false;
+    let mut last = 0.0;
+
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.starts_with("date ") {
+            start = parse_date(&line[5..]).unwrap_or_else(|| { warn!("Invalid ASC date: {}", line); 0.0 });
+        } else if line.starts_with("base ") {
+            hex = !line.contains("base dec");
+            relative = line.contains("timestamps relative");
+        } else if let Some((time, channel, data)) = parse_frame(line, hex) {
+            let time = if relative { last + time } else { time };
+            last = time;
+            records.push(Record {
+                timestamp: start + time,
+                source: Some(format!("CAN{}", channel)),
+                channel: Some(channel),
+                data: data,
+            });
+        }
+    }
+
+    Ok(records)
+}
+
+/// Writes records as an ASC trace
+pub struct AscWriter<W: Write> {
+    out: W,
+    /// Measurement start, set by the first record
+    start: Option<f64>,
+    channels: ChannelMap,
+}
+
+impl<W: Write> AscWriter<W> {
+    pub fn new(out: W) -> AscWriter<W> {
+        AscWriter {
+            out: out,
+            start: None,
+            channels: ChannelMap::new(),
+        }
+    }
+
+    fn write_header(&mut self, start: f64) -> Result<()> {
+        let date = format_date(start);
+        writeln!(self.out, "date {}", date)?;
+        writeln!(self.out, "base hex  timestamps absolute")?;
+        writeln!(self.out, "internal events logged")?;
+        writeln!(self.out, "// version 9.0.0")?;
+        writeln!(self.out, "Begin Triggerblock {}", date)?;
+        writeln!(self.out, "{:>11.6} Start of measurement", 0.0)
+    }
+}
+
+impl<W: Write> RecordWriter for AscWriter<W> {
+    fn write_record(&mut self, rec: &Record) -> Result<()> {
+        let start = match self.start {
+            Some(start) => start,
+            None => {
+                self.write_header(rec.timestamp)?;
+                self.start = Some(rec.timestamp);
+                rec.timestamp
+            }
+        };
+
+        let channel = self.channels.channel(rec);
+        let data = &rec.data;
+        let id = data.get_id() as u32;
+        let id = if data.get_extended() { format!("{:X}x", id) } else { format!("{:X}", id) };
+        let bytes = data.get_data().iter().map(|x| format!("{:02X}", x)).collect::<Vec<String>>().join(" ");
+        let line = if data.get_error() {
+            format!("{:>11.6} {:<2} ErrorFrame", rec.timestamp - start, channel)
+        } else if data.get_rtr() {
+            format!("{:>11.6} {:<2} {:<15} Rx   r {:x}", rec.timestamp - start, channel, id, data.get_data().len())
+        } else if data.get_fd() {
+            let mut flags = FD_EDL;
+            if data.get_brs() { flags |= FD_BRS; }
+            if data.get_esi() { flags |= FD_ESI; }
+            // Duration, length, CRC and bit timings are not known
+            format!("{:>11.6} CANFD {:>3} Rx {:>8} {:>32} {} {} {:x} {:>2} {} {:>8} {:>4} {:>8X} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                    rec.timestamp - start, channel, id, "", data.get_brs() as u8, data.get_esi() as u8,
+                    server::can_fd_dlc(data.get_data().len()), data.get_data().len(), bytes, 0, 0, flags, 0, 0, 0, 0, 0)
+        } else {
+            format!("{:>11.6} {:<2} {:<15} Rx   d {:x} {}", rec.timestamp - start, channel, id, data.get_data().len(), bytes)
+        };
+        writeln!(self.out, "{}", line.trim_end())?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for AscWriter<W> {
+    fn drop(&mut self) {
+        if self.start.is_some() {
+            if let Err(e) = writeln!(self.out, "End TriggerBlock") {
+                error!("Can't close ASC trace: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: f64, data: rics::RICS_Data) -> Record {
+        Record { timestamp: timestamp, source: Some("CAN1".to_string()), channel: Some(1), data: data }
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = vec![
+            record(1000.0, server::can_frame(0x123, vec![1, 2, 3], Default::default())),
+            record(1000.5, server::can_frame(0x1234567, vec![4; 8], server::CanFlags { extended: true, ..Default::default() })),
+            record(1001.0, server::can_frame(0x10, vec![0; 4], server::CanFlags { rtr: true, ..Default::default() })),
+            record(1001.25, server::can_frame(0x20, vec![5; 12], server::CanFlags { fd: true, brs: true, ..Default::default() })),
+            record(1002.0, server::can_frame(0, vec![], server::CanFlags { error: true, ..Default::default() })),
+        ];
+        let mut out = vec![];
+        {
+            let mut writer = AscWriter::new(&mut out);
+            for rec in &records {
+                writer.write_record(rec).unwrap();
+            }
+        }
+        let read = read_asc(&out[..]).unwrap();
+        assert_eq!(read.len(), records.len());
+        for (a, b) in records.iter().zip(read.iter()) {
+            assert_eq!(a.data, b.data);
+            assert_eq!(b.channel, Some(1));
+            assert!((a.timestamp - b.timestamp).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn decimal_relative() {
+        let trace = "base dec  timestamps relative\n0.5 1 291 Rx d 2 1 255\n0.25 2 100x Rx d 1 16\n1.0 1 what\n";
+        let read = read_asc(trace.as_bytes()).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].data, server::can_frame(291, vec![1, 255], Default::default()));
+        assert_eq!(read[1].data, server::can_frame(100, vec![16], server::CanFlags { extended: true, ..Default::default() }));
+        assert_eq!(read[1].channel, Some(2));
+        assert!((read[1].timestamp - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fd_frame() {
+        let line = "1.5 CANFD 1 Rx 123 Name 1 0 9 12 0 1 2 3 4 5 6 7 8 9 A B 0 0 3000";
+        let (time, channel, data) = parse_frame(line, true).unwrap();
+        assert_eq!((time, channel), (1.5, 1));
+        let dat: Vec<u8> = (0..12).collect();
+        assert_eq!(data, server::can_frame(0x123, dat, server::CanFlags { fd: true, brs: true, ..Default::default() }));
+        assert!(parse_frame("1.5 CANFD 1 Rx 123 1 0 9 12 0 1", true).is_none());
+    }
+}
@@ -0,0 +1,503 @@
+//! Vector BLF binary trace format
+//!
+//! A BLF file is a `LOGG` header followed by `LOBJ` objects. The log objects
+//! are usually stored inside zlib compressed container objects.
+
+use std::io::{Read, Result, Seek, SeekFrom, Write, Error, ErrorKind};
+use std::time::{Duration, Instant};
+use chrono::{Datelike, Timelike, TimeZone};
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+use super::server;
+use super::record::{Record, RecordWriter, ChannelMap};
+
+const FILE_HEADER_SIZE: usize = 144;
+const OBJ_HEADER_BASE_SIZE: usize = 16;
+const OBJ_HEADER_V1_SIZE: usize = 16;
+const LOG_CONTAINER_SIZE: usize = 16;
+
+const CAN_MESSAGE: u32 = 1;
+const CAN_ERROR: u32 = 2;
+const LOG_CONTAINER: u32 = 10;
+const CAN_MESSAGE2: u32 = 86;
+const CAN_FD_MESSAGE: u32 = 100;
+const CAN_FD_MESSAGE_64: u32 = 101;
+
+const NO_COMPRESSION: u16 = 0;
+const ZLIB_DEFLATE: u16 = 2;
+
+const TIME_TEN_MICS: u32 = 0x00000001;
+const TIME_ONE_NANS: u32 = 0x00000002;
+
+const CAN_MSG_EXT: u32 = 0x80000000;
+const REMOTE_FLAG: u8 = 0x80;
+
+/// CAN_FD_MESSAGE flags
+const FD_EDL: u8 = 0x01;
+const FD_BRS: u8 = 0x02;
+const FD_ESI: u8 = 0x04;
+/// CAN_FD_MESSAGE_64 flags
+const FD64_REMOTE: u32 = 0x0010;
+const FD64_EDL: u32 = 0x1000;
+const FD64_BRS: u32 = 0x2000;
+const FD64_ESI: u32 = 0x4000;
+
+/// Size of a CAN_FD_MESSAGE object without the header
+const CAN_FD_MESSAGE_SIZE: usize = 84;
+/// Size of a CAN_FD_MESSAGE_64 object before the data
+const CAN_FD_MESSAGE_64_HEADER: usize = 40;
+
+/// Maximum uncompressed size of a container
+const MAX_CONTAINER_SIZE: usize = 128 * 1024;
+/// Containers are written at least this often, so that an interrupted recording is still readable
+const FLUSH_PERIOD: Duration = Duration::from_secs(1);
+
+fn u16_at(buf: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([buf[pos], buf[pos+1]])
+}
+
+fn u32_at(buf: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([buf[pos], buf[pos+1], buf[pos+2], buf[pos+3]])
+}
+
+fn u64_at(buf: &[u8], pos: usize) -> u64 {
+    (u32_at(buf, pos) as u64) | ((u32_at(buf, pos + 4) as u64) << 32)
+}
+
+/// Convert a SYSTEMTIME structure to a timestamp
+fn systemtime_to_timestamp(buf: &[u8]) -> Option<f64> {
+    let f = |i: usize| u16_at(buf, 2 * i) as u32;
+    // Index 2 is the day of the week
+    let time = chrono::Local.with_ymd_and_hms(f(0) as i32, f(1), f(3), f(4), f(5), f(6)).earliest()?;
+    Some(time.timestamp() as f64 + f(7) as f64 * 1e-3)
+}
+
+/// Convert a timestamp to a SYSTEMTIME structure
+fn timestamp_to_systemtime(timestamp: f64) -> Vec<u8> {
+    let millis = (timestamp * 1e3).round() as i64;
+    let time = chrono::Local.timestamp_opt(millis.div_euclid(1000), (millis.rem_euclid(1000) * 1_000_000) as u32).unwrap();
+    [time.year() as u32, time.month(), time.weekday().num_days_from_sunday(), time.day(),
+     time.hour(), time.minute(), time.second(), (millis.rem_euclid(1000)) as u32]
+        .iter().flat_map(|x| (*x as u16).to_le_bytes().to_vec()).collect()
+}
+
+/// Parse the log objects of `buf`, returns the number of bytes consumed
+fn parse_objects(buf: &[u8], start: f64, records: &mut Vec<Record>) -> usize {
+    let mut pos = 0;
+    while pos + OBJ_HEADER_BASE_SIZE <= buf.len() {
+        if &buf[pos..pos+4] != b"LOBJ" {
+            warn!("Invalid BLF object signature, skipping the rest of the container");
+            return buf.len();
+        }
+        let header_size = u16_at(buf, pos + 4) as usize;
+        let header_version = u16_at(buf, pos + 6);
+        let obj_size = u32_at(buf, pos + 8) as usize;
+        let obj_type = u32_at(buf, pos + 12);
+        if header_size < OBJ_HEADER_BASE_SIZE + OBJ_HEADER_V1_SIZE || obj_size < header_size {
+            warn!("Invalid BLF object header, skipping the rest of the container");
+            return buf.len();
+        }
+        if pos + obj_size > buf.len() {
+            break;
+        }
+
+        // Both header versions start with the flags and have the timestamp at the same offset
+        let flags = u32_at(buf, pos + OBJ_HEADER_BASE_SIZE);
+        let ts = match header_version {
+            1 | 2 => u64_at(buf, pos + OBJ_HEADER_BASE_SIZE + 8),
+            _ => 0,
+        };
+        let factor = if flags & TIME_TEN_MICS != 0 { 1e-5 } else { 1e-9 };
+        let timestamp = start + ts as f64 * factor;
+
+        let obj = &buf[pos + header_size .. pos + obj_size];
+        match obj_type {
+            CAN_MESSAGE | CAN_MESSAGE2 if obj.len() >= 16 => {
+                let channel = u16_at(obj, 0) as u32;
+                let msg_flags = obj[2];
+                let dlc = (obj[3] as usize).min(8);
+                let id = u32_at(obj, 4);
+                let flags = server::CanFlags {
+                    extended: id & CAN_MSG_EXT != 0,
+                    rtr: msg_flags & REMOTE_FLAG != 0,
+                    ..Default::default()
+                };
+                records.push(Record {
+                    timestamp: timestamp,
+                    source: Some(format!("CAN{}", channel)),
+                    channel: Some(channel),
+                    data: server::can_frame((id & !CAN_MSG_EXT) as i32, obj[8..8+dlc].to_vec(), flags),
+                });
+            },
+            CAN_ERROR if obj.len() >= 4 => {
+                let channel = u16_at(obj, 0) as u32;
+                records.push(Record {
+                    timestamp: timestamp,
+                    source: Some(format!("CAN{}", channel)),
+                    channel: Some(channel),
+                    data: server::can_frame(0, vec![], server::CanFlags { error: true, ..Default::default() }),
+                });
+            },
+            CAN_FD_MESSAGE if obj.len() >= CAN_FD_MESSAGE_SIZE => {
+                let channel = u16_at(obj, 0) as u32;
+                let msg_flags = obj[2];
+                let id = u32_at(obj, 4);
+                let fd_flags = obj[13];
+                let len = (obj[14] as usize).min(server::CANFD_MAX_DLEN);
+                let flags = server::CanFlags {
+                    extended: id & CAN_MSG_EXT != 0,
+                    rtr: msg_flags & REMOTE_FLAG != 0,
+                    fd: fd_flags & FD_EDL != 0,
+                    brs: fd_flags & FD_BRS != 0,
+                    esi: fd_flags & FD_ESI != 0,
+                    ..Default::default()
+                };
+                records.push(Record {
+                    timestamp: timestamp,
+                    source: Some(format!("CAN{}", channel)),
+                    channel: Some(channel),
+                    data: server::can_frame((id & !CAN_MSG_EXT) as i32, obj[20..20+len].to_vec(), flags),
+                });
+            },
+            CAN_FD_MESSAGE_64 if obj.len() >= CAN_FD_MESSAGE_64_HEADER => {
+                let channel = obj[0] as u32;
+                let len = (obj[2] as usize).min(server::CANFD_MAX_DLEN).min(obj.len() - CAN_FD_MESSAGE_64_HEADER);
+                let id = u32_at(obj, 4);
+                let fd_flags = u32_at(obj, 12);
+                let flags = server::CanFlags {
+                    extended: id & CAN_MSG_EXT != 0,
+                    rtr: fd_flags & FD64_REMOTE != 0,
+                    fd: fd_flags & FD64_EDL != 0,
+                    brs: fd_flags & FD64_BRS != 0,
+                    esi: fd_flags & FD64_ESI != 0,
+                    ..Default::default()
+                };
+                records.push(Record {
+                    timestamp: timestamp,
+                    source: Some(format!("CAN{}", channel)),
+                    channel: Some(channel),
+                    data: server::can_frame((id & !CAN_MSG_EXT) as i32, obj[CAN_FD_MESSAGE_64_HEADER..CAN_FD_MESSAGE_64_HEADER+len].to_vec(), flags),
+                });
+            },
+            _ => trace!("Skipping BLF object type {}", obj_type),
+        }
+
+        pos += obj_size + obj_size % 4;
+    }
+    pos.min(buf.len())
+}
+
+/// Read the CAN frames of a BLF trace
+pub fn read_blf(mut reader: impl Read) -> Result<Vec<Record>> {
+    let mut file = vec![];
+    reader.read_to_end(&mut file)?;
+    if file.len() < FILE_HEADER_SIZE || &file[0..4] != b"LOGG" {
+        return Err(Error::new(ErrorKind::InvalidData, "Not a BLF file"));
+    }
+    let start = systemtime_to_timestamp(&file[40..56]).unwrap_or(0.0);
+
+    let mut records = vec![];
+    // Log objects can be split between two containers
+    let mut data = vec![];
+    let mut pos = u32_at(&file, 4) as usize;
+    while pos + OBJ_HEADER_BASE_SIZE <= file.len() && &file[pos..pos+4] == b"LOBJ" {
+        let obj_size = u32_at(&file, pos + 8) as usize;
+        let obj_type = u32_at(&file, pos + 12);
+        if pos + obj_size > file.len() || obj_size < OBJ_HEADER_BASE_SIZE {
+            warn!("Truncated BLF file");
+            break;
+        }
+
+        if obj_type == LOG_CONTAINER {
+            if obj_size < OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE {
+                warn!("Invalid BLF container");
+                break;
+            }
+            let method = u16_at(&file, pos + OBJ_HEADER_BASE_SIZE);
+            let content = &file[pos + OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE .. pos + obj_size];
+            match method {
+                NO_COMPRESSION => data.extend_from_slice(content),
+                ZLIB_DEFLATE => { ZlibDecoder::new(content).read_to_end(&mut data)?; },
+                _ => warn!("Unknown BLF compression method {}", method),
+            }
+        } else {
+            data.extend_from_slice(&file[pos .. (pos + obj_size + obj_size % 4).min(file.len())]);
+        }
+
+        let n = parse_objects(&data, start, &mut records);
+        data.drain(..n);
+        pos += obj_size + obj_size % 4;
+    }
+
+    Ok(records)
+}
+
+/// Writes records as a BLF trace.
+///
+/// The file header is rewritten each time a container is written.
+pub struct BlfWriter<W: Write + Seek> {
+    out: W,
+    /// Measurement start, set by the first record
+    start: Option<f64>,
+    stop: f64,
+    channels: ChannelMap,
+    /// Uncompressed log objects waiting to be written
+    buffer: Vec<u8>,
+    last_flush: Instant,
+    object_count: u32,
+    uncompressed_size: u64,
+}
+
+impl<W: Write + Seek> BlfWriter<W> {
+    pub fn new(out: W) -> Result<BlfWriter<W>> {
+        let mut writer = BlfWriter {
+            out: out,
+            start: None,
+            stop: 0.0,
+            channels: ChannelMap::new(),
+            buffer: vec![],
+            last_flush: Instant::now(),
+            object_count: 0,
+            uncompressed_size: FILE_HEADER_SIZE as u64,
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        let end = self.out.seek(SeekFrom::End(0))?.max(FILE_HEADER_SIZE as u64);
+        let start = self.start.unwrap_or(0.0);
+
+        let mut header = vec![];
+        header.extend_from_slice(b"LOGG");
+        header.extend_from_slice(&(FILE_HEADER_SIZE as u32).to_le_bytes());
+        // Application id and version, then the BL log format version
+        header.extend_from_slice(&[5, 0, 0, 0, 2, 6, 8, 1]);
+        header.extend_from_slice(&end.to_le_bytes());
+        header.extend_from_slice(&self.uncompressed_size.to_le_bytes());
+        header.extend_from_slice(&self.object_count.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&timestamp_to_systemtime(start));
+        header.extend_from_slice(&timestamp_to_systemtime(self.stop.max(start)));
+        header.resize(FILE_HEADER_SIZE, 0);
+
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&header)?;
+        self.out.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+
+    /// Compress the buffered objects to a container
+    fn write_container(&mut self) -> Result<()> {
+        self.last_flush = Instant::now();
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(&self.buffer)?;
+        let compressed = encoder.finish()?;
+        let obj_size = OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE + compressed.len();
+
+        let mut obj = vec![];
+        obj.extend_from_slice(b"LOBJ");
+        obj.extend_from_slice(&(OBJ_HEADER_BASE_SIZE as u16).to_le_bytes());
+        obj.extend_from_slice(&1u16.to_le_bytes());
+        obj.extend_from_slice(&(obj_size as u32).to_le_bytes());
+        obj.extend_from_slice(&LOG_CONTAINER.to_le_bytes());
+        obj.extend_from_slice(&ZLIB_DEFLATE.to_le_bytes());
+        obj.extend_from_slice(&[0; 6]);
+        obj.extend_from_slice(&(self.buffer.len() as u32).to_le_bytes());
+        obj.extend_from_slice(&[0; 4]);
+        obj.extend_from_slice(&compressed);
+        obj.resize(obj_size + obj_size % 4, 0);
+
+        self.out.write_all(&obj)?;
+        self.uncompressed_size += (OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE + self.buffer.len()) as u64;
+        self.buffer.clear();
+        self.write_header()?;
+        self.out.flush()
+    }
+
+    /// Append a log object with a version 1 header
+    fn push_object(&mut self, obj_type: u32, timestamp: f64, content: &[u8]) {
+        let start = *self.start.get_or_insert(timestamp);
+        let header_size = OBJ_HEADER_BASE_SIZE + OBJ_HEADER_V1_SIZE;
+        let obj_size = header_size + content.len();
+
+        self.buffer.extend_from_slice(b"LOBJ");
+        self.buffer.extend_from_slice(&(header_size as u16).to_le_bytes());
+        self.buffer.extend_from_slice(&1u16.to_le_bytes());
+        self.buffer.extend_from_slice(&(obj_size as u32).to_le_bytes());
+        self.buffer.extend_from_slice(&obj_type.to_le_bytes());
+        self.buffer.extend_from_slice(&TIME_ONE_NANS.to_le_bytes());
+        // Client index and object version
+        self.buffer.extend_from_slice(&[0; 4]);
+        self.buffer.extend_from_slice(&(((timestamp - start).max(0.0) * 1e9) as u64).to_le_bytes());
+        self.buffer.extend_from_slice(content);
+        self.buffer.resize(self.buffer.len() + obj_size % 4, 0);
+
+        self.object_count += 1;
+        self.stop = timestamp;
+    }
+}
+
+impl<W: Write + Seek> RecordWriter for BlfWriter<W> {
+    fn write_record(&mut self, rec: &Record) -> Result<()> {
+        let channel = self.channels.channel(rec);
+        let data = &rec.data;
+        let dat = data.get_data();
+        let mut id = data.get_id() as u32;
+        if data.get_extended() {
+            id |= CAN_MSG_EXT;
+        }
+
+        let mut obj = vec![];
+        obj.extend_from_slice(&(channel as u16).to_le_bytes());
+        if data.get_error() {
+            // Error frame length, unknown
+            obj.extend_from_slice(&0u16.to_le_bytes());
+            self.push_object(CAN_ERROR, rec.timestamp, &obj);
+        } else if data.get_fd() {
+            // Flags, received frame
+            obj.push(0);
+            let len = dat.len().min(server::CANFD_MAX_DLEN);
+            let mut fd_flags = FD_EDL;
+            if data.get_brs() { fd_flags |= FD_BRS; }
+            if data.get_esi() { fd_flags |= FD_ESI; }
+            obj.push(server::can_fd_dlc(len));
+            obj.extend_from_slice(&id.to_le_bytes());
+            // Frame length and arbitration bit count are unknown
+            obj.extend_from_slice(&[0; 5]);
+            obj.push(fd_flags);
+            obj.push(len as u8);
+            obj.extend_from_slice(&[0; 5]);
+            obj.extend_from_slice(&dat[..len]);
+            obj.resize(CAN_FD_MESSAGE_SIZE, 0);
+            self.push_object(CAN_FD_MESSAGE, rec.timestamp, &obj);
+        } else {
+            // Flags, received frame
+            obj.push(if data.get_rtr() { REMOTE_FLAG } else { 0 });
+            let len = dat.len().min(8);
+            obj.push(len as u8);
+            obj.extend_from_slice(&id.to_le_bytes());
+            obj.extend_from_slice(&dat[..len]);
+            obj.resize(16, 0);
+            self.push_object(CAN_MESSAGE, rec.timestamp, &obj);
+        }
+
+        if self.buffer.len() >= MAX_CONTAINER_SIZE || self.last_flush.elapsed() >= FLUSH_PERIOD {
+            self.write_container()?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> Drop for BlfWriter<W> {
+    fn drop(&mut self) {
+        if let Err(e) = self.write_container() {
+            error!("Can't close BLF trace: {}", e);
+        }
+    }
+}
+
+/// Check the BLF file signature
+pub fn is_blf(head: &[u8]) -> bool {
+    head.starts_with(b"LOGG")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn record(timestamp: f64, data: crate::rics::RICS_Data) -> Record {
+        Record { timestamp: timestamp, source: Some("CAN1".to_string()), channel: Some(1), data: data }
+    }
+
+    fn write(records: &[Record]) -> Vec<u8> {
+        let mut out = Cursor::new(vec![]);
+        {
+            let mut writer = BlfWriter::new(&mut out).unwrap();
+            for rec in records {
+                writer.write_record(rec).unwrap();
+            }
+        }
+        out.into_inner()
+    }
+
+    /// BLF file with the given objects stored outside of any container
+    fn file(objects: &[u8]) -> Vec<u8> {
+        let mut file = write(&[]);
+        file.extend_from_slice(objects);
+        file
+    }
+
+    fn object_header(header_size: u16, obj_size: u32, obj_type: u32) -> Vec<u8> {
+        let mut obj = b"LOBJ".to_vec();
+        obj.extend_from_slice(&header_size.to_le_bytes());
+        obj.extend_from_slice(&1u16.to_le_bytes());
+        obj.extend_from_slice(&obj_size.to_le_bytes());
+        obj.extend_from_slice(&obj_type.to_le_bytes());
+        obj
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = vec![
+            record(1000.0, server::can_frame(0x123, vec![1, 2, 3], Default::default())),
+            record(1000.5, server::can_frame(0x1234567, vec![4; 8], server::CanFlags { extended: true, ..Default::default() })),
+            record(1001.0, server::can_frame(0x10, vec![0; 4], server::CanFlags { rtr: true, ..Default::default() })),
+            record(1001.25, server::can_frame(0x20, vec![5; 12], server::CanFlags { fd: true, brs: true, ..Default::default() })),
+            record(1002.0, server::can_frame(0, vec![], server::CanFlags { error: true, ..Default::default() })),
+        ];
+        let read = read_blf(&write(&records)[..]).unwrap();
+        assert_eq!(read.len(), records.len());
+        for (a, b) in records.iter().zip(read.iter()) {
+            assert_eq!(a.data, b.data);
+            assert_eq!(b.channel, Some(1));
+            assert!((a.timestamp - b.timestamp).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn not_blf() {
+        assert!(read_blf(&b"LOGG"[..]).is_err());
+        assert!(read_blf(&[0u8; FILE_HEADER_SIZE][..]).is_err());
+    }
+
+    #[test]
+    fn empty_object_header() {
+        // Inner object with null sizes in an uncompressed container
+        let mut container = object_header(OBJ_HEADER_BASE_SIZE as u16, (OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE + 16) as u32, LOG_CONTAINER);
+        container.extend_from_slice(&[0; LOG_CONTAINER_SIZE]);
+        container.extend_from_slice(&object_header(0, 0, CAN_MESSAGE));
+        assert!(read_blf(&file(&container)[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn short_object_header() {
+        let mut obj = object_header(OBJ_HEADER_BASE_SIZE as u16, OBJ_HEADER_BASE_SIZE as u32, CAN_MESSAGE);
+        obj.extend_from_slice(&[0; 2]);
+        assert!(read_blf(&file(&obj)[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn short_container() {
+        let mut obj = object_header(OBJ_HEADER_BASE_SIZE as u16, 20, LOG_CONTAINER);
+        obj.extend_from_slice(&[0; 4]);
+        assert!(read_blf(&file(&obj)[..]).unwrap().is_empty());
+        // Container header ending at the end of the file
+        let obj = object_header(OBJ_HEADER_BASE_SIZE as u16, OBJ_HEADER_BASE_SIZE as u32, LOG_CONTAINER);
+        assert!(read_blf(&file(&obj)[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unpadded_last_object() {
+        let mut obj = object_header(32, 49, CAN_MESSAGE);
+        obj.extend_from_slice(&[0; 33]);
+        assert_eq!(read_blf(&file(&obj)[..]).unwrap().len(), 1);
+    }
+}
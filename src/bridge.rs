@@ -0,0 +1,163 @@
+//! TCP and UDP socket bridges
+//!
+//! TCP connections are carried by STREAM packets and UDP datagrams by
+//! DATAGRAM packets, so that networked tools can talk to simulated devices.
+//! A listening bridge numbers its clients from 1 and uses the number as the
+//! id of their packets. Packets with the id 0 are written to every client,
+//! and an empty packet closes the connection of its id, or tells that the
+//! client disconnected when it comes from the bridge.
+
+use std::collections::HashMap;
+use std::io::{Read, Result, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::rics;
+use super::server::{self, RICSServer};
+
+/// Address to bind, `:PORT` listening on every interface
+fn bind_address(addr: &str) -> String {
+    if addr.starts_with(':') {
+        format!("0.0.0.0{}", addr)
+    } else {
+        addr.to_string()
+    }
+}
+
+fn stream_packet(dat: Vec<u8>, id: i32) -> rics::RICS_Data {
+    let mut data = server::stream_packet(dat);
+    data.set_id(id);
+    data
+}
+
+/// Data of the packets of a type received by the node
+fn packets(responses: impl Iterator<Item = rics::RICS_Response>, kind: rics::RICS_Data_RICS_DataType) -> impl Iterator<Item = rics::RICS_Data> {
+    responses.filter(|x| x.has_data())
+        .map(|mut x| x.take_data())
+        .filter(move |x| x.get_field_type() == kind)
+}
+
+/// Send what is read from a connection as STREAM packets, until it closes
+fn forward(mut stream: &TcpStream, id: i32, server: &Mutex<RICSServer>) {
+    let mut buffer = [0u8;2048];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => server.lock().unwrap().send_packet(stream_packet(buffer[..n].to_vec(), id)),
+            Err(e) => {
+                warn!("Can't read from client {}: {}", id, e);
+                break;
+            },
+        }
+    }
+}
+
+/// Accept TCP clients on `addr`, such as `:5000`, and bridge them to the
+/// STREAM packets of the node. Only returns if the address can't be bound.
+pub fn tcp_listen(mut server: RICSServer, addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(bind_address(addr))?;
+    info!("Listening on {}", listener.local_addr()?);
+    let responses = server.listen_response();
+    let server = Arc::new(Mutex::new(server));
+    let clients: Arc<Mutex<HashMap<i32, TcpStream>>> = Arc::new(Mutex::new(HashMap::new()));
+
+    let clients_copy = clients.clone();
+    thread::spawn(move || {
+        for data in packets(responses.into_iter(), rics::RICS_Data_RICS_DataType::STREAM) {
+            let mut clients = clients_copy.lock().unwrap();
+            let ids: Vec<i32> = if data.get_id() == 0 {
+                clients.keys().cloned().collect()
+            } else {
+                vec![data.get_id()]
+            };
+            for id in ids {
+                let client = match clients.get_mut(&id) {
+                    Some(client) => client,
+                    None => {
+                        debug!("No client {} for the packet", id);
+                        continue;
+                    },
+                };
+                if data.get_data().is_empty() {
+                    info!("Closing client {}", id);
+                    client.shutdown(Shutdown::Both).ok();
+                } else if let Err(e) = client.write_all(data.get_data()) {
+                    warn!("Can't write to client {}: {}", id, e);
+                    client.shutdown(Shutdown::Both).ok();
+                }
+            }
+        }
+    });
+
+    let mut next_id = 1;
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Can't accept a client: {}", e);
+                continue;
+            },
+        };
+        let id = next_id;
+        next_id += 1;
+        info!("Client {} connected from {}", id, stream.peer_addr().map(|x| x.to_string()).unwrap_or_default());
+        clients.lock().unwrap().insert(id, stream.try_clone()?);
+        let server = server.clone();
+        let clients = clients.clone();
+        thread::spawn(move || {
+            forward(&stream, id, &server);
+            clients.lock().unwrap().remove(&id);
+            server.lock().unwrap().send_packet(stream_packet(vec![], id));
+            info!("Client {} disconnected", id);
+        });
+    }
+    Ok(())
+}
+
+/// Connect to the TCP server at `addr` and bridge the connection to the
+/// STREAM packets of the node, until either side closes it
+pub fn tcp_connect(mut server: RICSServer, addr: &str) -> Result<()> {
+    let stream = TcpStream::connect(addr)?;
+    info!("Connected to {}", stream.peer_addr()?);
+    let responses = server.listen_response();
+    let mut output = stream.try_clone()?;
+    thread::spawn(move || {
+        for data in packets(responses.into_iter(), rics::RICS_Data_RICS_DataType::STREAM) {
+            if data.get_data().is_empty() {
+                break;
+            }
+            if let Err(e) = output.write_all(data.get_data()) {
+                warn!("Can't write to the connection: {}", e);
+                break;
+            }
+        }
+        output.shutdown(Shutdown::Both).ok();
+    });
+    forward(&stream, 0, &Mutex::new(server));
+    Ok(())
+}
+
+/// Send the DATAGRAM packets of the node on `port` to `peer` as UDP datagrams,
+/// and the datagrams received on `bind` as DATAGRAM packets on `port`. Only
+/// returns on errors.
+pub fn udp(mut server: RICSServer, bind: &str, peer: &str, port: i32) -> Result<()> {
+    let socket = UdpSocket::bind(bind_address(bind))?;
+    info!("Receiving datagrams on {}", socket.local_addr()?);
+    let responses = server.listen_response();
+    let output = socket.try_clone()?;
+    let peer = peer.to_string();
+    thread::spawn(move || {
+        for data in packets(responses.into_iter(), rics::RICS_Data_RICS_DataType::DATAGRAM).filter(|x| x.get_id() == port) {
+            if let Err(e) = output.send_to(data.get_data(), &peer) {
+                warn!("Can't send a datagram to {}: {}", peer, e);
+            }
+        }
+    });
+    let mut buffer = [0u8;65536];
+    loop {
+        let (n, from) = socket.recv_from(&mut buffer)?;
+        trace!("Datagram of {} bytes from {}", n, from);
+        server.send_packet(server::datagram_packet(port, buffer[..n].to_vec()));
+    }
+}
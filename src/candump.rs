@@ -0,0 +1,163 @@
+//! can-utils candump log format
+//!
+//! Each line is formatted as `(timestamp) iface id#data`, as written by
+//! `candump -l` and read by `canplayer`. The interface name is used to
+//! store the source node name.
+
+use std::io::{Result, Write};
+
+use super::rics;
+use super::server;
+use super::record::{Record, RecordWriter};
+
+/// CAN FD flags, written as a single hex digit after `##`
+const CANFD_BRS: u8 = 0x01;
+const CANFD_ESI: u8 = 0x02;
+
+/// Error frames have this flag in their 8 digit id
+const CAN_ERR_FLAG: u32 = 0x20000000;
+
+/// Format a CAN packet as a candump log line
+pub fn data_to_candump_string(timestamp: f64, iface: &str, data: &rics::RICS_Data) -> String {
+    let id = data.get_id() as u32;
+    let id = if data.get_error() {
+        format!("{:08X}", id | CAN_ERR_FLAG)
+    } else if data.get_extended() {
+        format!("{:08X}", id)
+    } else {
+        format!("{:03X}", id)
+    };
+    // Remote frames only carry their length
+    if data.get_rtr() {
+        let len = data.get_data().len();
+        return format!("({:.6}) {} {}#R{}", timestamp, iface.replace(char::is_whitespace, "_"), id,
+                       if len > 0 { len.to_string() } else { "".to_string() });
+    }
+    let sep = if data.get_fd() {
+        let mut flags = 0;
+        if data.get_brs() { flags |= CANFD_BRS; }
+        if data.get_esi() { flags |= CANFD_ESI; }
+        format!("##{:X}", flags)
+    } else {
+        "#".to_string()
+    };
+    format!("({:.6}) {} {}{}{}", timestamp, iface.replace(char::is_whitespace, "_"), id, sep,
+            data.get_data().iter().map(|x| format!("{:02X}", x)).collect::<Vec<String>>().join(""))
+}
+
+/// Parse a candump log line
+pub fn parse_candump_line(line: &str) -> Option<Record> {
+    let mut fields = line.split_whitespace();
+    let timestamp = fields.next()?;
+    if !timestamp.starts_with('(') || !timestamp.ends_with(')') {
+        return None;
+    }
+    let timestamp = timestamp[1..timestamp.len()-1].parse::<f64>().ok()?;
+    let iface = fields.next()?;
+    let frame = fields.next()?;
+
+    let sep = frame.find('#')?;
+    let id = u32::from_str_radix(&frame[..sep], 16).ok()?;
+    let payload = &frame[sep+1..];
+
+    // Extended and error frames have an 8 digit id
+    let mut flags = server::CanFlags::default();
+    if sep == 8 {
+        flags.error = id & CAN_ERR_FLAG != 0;
+        flags.extended = !flags.error;
+    }
+    let id = (id & !CAN_ERR_FLAG) as i32;
+
+    // CAN FD frames use `##` followed by the flags
+    let payload = if payload.starts_with('#') {
+        let fd_flags = u8::from_str_radix(payload.get(1..2)?, 16).ok()?;
+        flags.fd = true;
+        flags.brs = fd_flags & CANFD_BRS != 0;
+        flags.esi = fd_flags & CANFD_ESI != 0;
+        &payload[2..]
+    } else {
+        if payload.len() > 2 * server::CAN_MAX_DLEN && !payload.starts_with('R') {
+            flags.fd = true;
+        }
+        payload
+    };
+
+    // Remote frames carry no data, only an optional length
+    let dat = if payload.starts_with('R') {
+        flags.rtr = true;
+        vec![0; payload[1..].parse::<usize>().unwrap_or(0).min(server::CAN_MAX_DLEN)]
+    } else {
+        server::parse_hex(payload)?
+    };
+    if dat.len() > server::CANFD_MAX_DLEN {
+        return None;
+    }
+
+    Some(Record {
+        timestamp: timestamp,
+        source: Some(iface.to_string()),
+        channel: None,
+        data: server::can_frame(id, dat, flags),
+    })
+}
+
+/// Writes records as a candump log
+pub struct CandumpWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> CandumpWriter<W> {
+    pub fn new(out: W) -> CandumpWriter<W> {
+        CandumpWriter { out: out }
+    }
+}
+
+impl<W: Write> RecordWriter for CandumpWriter<W> {
+    fn write_record(&mut self, rec: &Record) -> Result<()> {
+        writeln!(self.out, "{}", data_to_candump_string(rec.timestamp, rec.source.as_ref().map(|x| x.as_str()).unwrap_or("rics"), &rec.data))?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(data: rics::RICS_Data) {
+        let line = data_to_candump_string(12.5, "can 0", &data);
+        let rec = parse_candump_line(&line).unwrap();
+        assert_eq!(rec.timestamp, 12.5);
+        assert_eq!(rec.source.as_deref(), Some("can_0"));
+        assert_eq!(rec.data, data);
+    }
+
+    #[test]
+    fn frames() {
+        round_trip(server::can_frame(0x123, vec![0xDE, 0xAD], Default::default()));
+        round_trip(server::can_frame(0x123, vec![], Default::default()));
+        round_trip(server::can_frame(0x1ABCDEF, vec![1; 8], server::CanFlags { extended: true, ..Default::default() }));
+        round_trip(server::can_frame(0x7FF, vec![0; 3], server::CanFlags { rtr: true, ..Default::default() }));
+        round_trip(server::can_frame(0x4, vec![0; 8], server::CanFlags { error: true, ..Default::default() }));
+        round_trip(server::can_frame(0x42, (0..48).collect(), server::CanFlags { fd: true, brs: true, esi: true, ..Default::default() }));
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(data_to_candump_string(1.0, "vcan0", &server::can_packet(0x12, vec![0xAB])), "(1.000000) vcan0 012#AB");
+        let rec = parse_candump_line("(1600000000.123456) can1 18FEF100#0102030405060708").unwrap();
+        assert_eq!(rec.data.get_id(), 0x18FEF100);
+        assert!(rec.data.get_extended());
+        // Classic frames longer than 8 bytes are read as CAN FD frames
+        assert!(parse_candump_line("(0.0) can0 123#00112233445566778899").unwrap().data.get_fd());
+    }
+
+    #[test]
+    fn invalid_lines() {
+        assert!(parse_candump_line("1.0 can0 123#00").is_none());
+        assert!(parse_candump_line("(1.0) can0 123#0").is_none());
+        assert!(parse_candump_line("(1.0) can0 123#zz").is_none());
+        assert!(parse_candump_line("(1.0) can0 123#aé1").is_none());
+        assert!(parse_candump_line("(1.0) can0 123##é1").is_none());
+        assert!(parse_candump_line(&format!("(1.0) can0 123##0{}", "00".repeat(65))).is_none());
+    }
+}
@@ -0,0 +1,137 @@
+//! CAN FD access to SocketCAN interfaces
+//!
+//! The socketcan crate only handles classic CAN frames. Once the socket is
+//! switched to CAN_RAW_FD_FRAMES, frames are read and written directly as
+//! `canfd_frame` structures, classic frames keeping the shorter CAN_MTU size.
+//! The extended, remote and error flags of the id are mapped to the packet flags.
+
+use std::io::{Error, ErrorKind, Result};
+use std::mem::size_of;
+use std::os::unix::io::AsRawFd;
+
+use super::rics;
+use super::server;
+
+const SOL_CAN_RAW: libc::c_int = 101;
+const CAN_RAW_ERR_FILTER: libc::c_int = 2;
+const CAN_RAW_FD_FRAMES: libc::c_int = 5;
+
+const CAN_EFF_FLAG: u32 = 0x80000000;
+const CAN_RTR_FLAG: u32 = 0x40000000;
+const CAN_ERR_FLAG: u32 = 0x20000000;
+const CAN_SFF_MASK: u32 = 0x000007FF;
+const CAN_EFF_MASK: u32 = 0x1FFFFFFF;
+const CAN_ERR_MASK: u32 = 0x1FFFFFFF;
+
+const CANFD_BRS: u8 = 0x01;
+const CANFD_ESI: u8 = 0x02;
+
+/// Size of a classic CAN frame on the socket
+const CAN_MTU: usize = 16;
+/// Size of a CAN FD frame on the socket
+const CANFD_MTU: usize = size_of::<CanFdFrame>();
+
+/// Kernel `struct canfd_frame`, classic frames share the same header
+#[repr(C)]
+struct CanFdFrame {
+    can_id: u32,
+    len: u8,
+    flags: u8,
+    res0: u8,
+    res1: u8,
+    data: [u8; server::CANFD_MAX_DLEN],
+}
+
+/// Set a CAN_RAW socket option
+fn set_raw_option<T>(sock: &impl AsRawFd, option: libc::c_int, value: T) -> Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(sock.as_raw_fd(), SOL_CAN_RAW, option,
+                         &value as *const _ as *const libc::c_void, size_of::<T>() as libc::socklen_t)
+    };
+    if ret < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Open a SocketCAN interface with CAN FD and error frames enabled
+pub fn open(ifname: &str) -> Result<socketcan::CANSocket> {
+    let sock = socketcan::CANSocket::open(ifname).map_err(|e| Error::new(ErrorKind::Other, e))?;
+    set_raw_option(&sock, CAN_RAW_FD_FRAMES, 1 as libc::c_int)?;
+    set_raw_option(&sock, CAN_RAW_ERR_FILTER, CAN_ERR_MASK)?;
+    Ok(sock)
+}
+
+/// Read a classic or FD frame from the socket
+pub fn read_packet(sock: &impl AsRawFd) -> Result<rics::RICS_Data> {
+    let mut frame: CanFdFrame = unsafe { std::mem::zeroed() };
+    let n = unsafe { libc::read(sock.as_raw_fd(), &mut frame as *mut _ as *mut libc::c_void, CANFD_MTU) };
+    if n < 0 {
+        return Err(Error::last_os_error());
+    }
+
+    let flags = server::CanFlags {
+        extended: frame.can_id & CAN_EFF_FLAG != 0,
+        rtr: frame.can_id & CAN_RTR_FLAG != 0,
+        error: frame.can_id & CAN_ERR_FLAG != 0,
+        fd: n as usize == CANFD_MTU,
+        brs: frame.flags & CANFD_BRS != 0,
+        esi: frame.flags & CANFD_ESI != 0,
+    };
+    let id = if flags.error {
+        frame.can_id & CAN_ERR_MASK
+    } else if flags.extended {
+        frame.can_id & CAN_EFF_MASK
+    } else {
+        frame.can_id & CAN_SFF_MASK
+    } as i32;
+    let max = match n as usize {
+        CAN_MTU => server::CAN_MAX_DLEN,
+        CANFD_MTU => server::CANFD_MAX_DLEN,
+        n => return Err(Error::new(ErrorKind::InvalidData, format!("Invalid CAN frame size {}", n))),
+    };
+    let len = (frame.len as usize).min(max);
+    Ok(server::can_frame(id, frame.data[..len].to_vec(), flags))
+}
+
+/// Write a CAN packet to the socket, as an FD frame if it is flagged as such.
+/// `extended` forces a 29 bit identifier on every frame.
+pub fn write_packet(sock: &impl AsRawFd, data: &rics::RICS_Data, extended: bool) -> Result<()> {
+    let mut frame: CanFdFrame = unsafe { std::mem::zeroed() };
+    frame.can_id = data.get_id() as u32;
+    if extended || data.get_extended() {
+        frame.can_id = (frame.can_id & CAN_EFF_MASK) | CAN_EFF_FLAG;
+    } else {
+        frame.can_id &= CAN_SFF_MASK;
+    }
+    if data.get_rtr() {
+        frame.can_id |= CAN_RTR_FLAG;
+    }
+    if data.get_error() {
+        frame.can_id = (data.get_id() as u32 & CAN_ERR_MASK) | CAN_ERR_FLAG;
+    }
+
+    let dat = data.get_data();
+    let mtu = if data.get_fd() {
+        if data.get_brs() { frame.flags |= CANFD_BRS; }
+        if data.get_esi() { frame.flags |= CANFD_ESI; }
+        CANFD_MTU
+    } else {
+        CAN_MTU
+    };
+    let max = if data.get_fd() { server::CANFD_MAX_DLEN } else { server::CAN_MAX_DLEN };
+    if dat.len() > max {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} bytes don't fit in a CAN frame", dat.len())));
+    }
+    frame.len = dat.len() as u8;
+    // Remote frames only carry the requested length
+    if !data.get_rtr() {
+        frame.data[..dat.len()].copy_from_slice(dat);
+    }
+
+    let n = unsafe { libc::write(sock.as_raw_fd(), &frame as *const _ as *const libc::c_void, mtu) };
+    if n < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
@@ -0,0 +1,928 @@
+//! CANopen (CiA 301) over CAN packets
+//!
+//! Services are identified by their COB-ID: NMT commands on 0x000, SYNC on
+//! 0x080, SDO requests on 0x600 + node and responses on 0x580 + node, and
+//! heartbeats or node guarding on 0x700 + node. RICS SYNC packets, which
+//! the host forwards to every node, are handled as CANopen SYNC by slaves.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use super::eds::{self, ObjectDictionary, Pdo};
+use super::rics;
+use super::server::{self, RICSServer};
+
+pub const COB_NMT: i32 = 0x000;
+pub const COB_SYNC: i32 = 0x080;
+/// SDO responses, server to client
+pub const COB_SDO_TX: i32 = 0x580;
+/// SDO requests, client to server
+pub const COB_SDO_RX: i32 = 0x600;
+pub const COB_HEARTBEAT: i32 = 0x700;
+
+/// Broadcast NMT node id
+pub const NODE_ALL: u8 = 0;
+
+/// SDO command specifiers, client and server
+const CS_DOWNLOAD_SEGMENT: u8 = 0;
+const CS_INITIATE_DOWNLOAD: u8 = 1;
+const CS_INITIATE_UPLOAD: u8 = 2;
+const CS_UPLOAD_SEGMENT: u8 = 3;
+const CS_ABORT: u8 = 4;
+const SCS_UPLOAD_SEGMENT: u8 = 0;
+const SCS_DOWNLOAD_SEGMENT: u8 = 1;
+const SCS_INITIATE_UPLOAD: u8 = 2;
+const SCS_INITIATE_DOWNLOAD: u8 = 3;
+
+pub const ABORT_TOGGLE: u32 = 0x0503_0000;
+pub const ABORT_TIMEOUT: u32 = 0x0504_0000;
+pub const ABORT_COMMAND: u32 = 0x0504_0001;
+pub const ABORT_WRITE_ONLY: u32 = 0x0601_0001;
+pub const ABORT_READ_ONLY: u32 = 0x0601_0002;
+pub const ABORT_NO_OBJECT: u32 = 0x0602_0000;
+pub const ABORT_PDO_LENGTH: u32 = 0x0604_0042;
+pub const ABORT_LENGTH: u32 = 0x0607_0010;
+pub const ABORT_LENGTH_HIGH: u32 = 0x0607_0012;
+pub const ABORT_LENGTH_LOW: u32 = 0x0607_0013;
+pub const ABORT_NO_SUB: u32 = 0x0609_0011;
+pub const ABORT_GENERAL: u32 = 0x0800_0000;
+pub const ABORT_STATE: u32 = 0x0800_0022;
+
+/// Description of the SDO abort codes
+pub fn abort_name(code: u32) -> Option<&'static str> {
+    Some(match code {
+        ABORT_TOGGLE => "Toggle bit not alternated",
+        ABORT_TIMEOUT => "SDO protocol timed out",
+        ABORT_COMMAND => "Command specifier not valid or unknown",
+        0x0601_0000 => "Unsupported access to an object",
+        ABORT_WRITE_ONLY => "Attempt to read a write only object",
+        ABORT_READ_ONLY => "Attempt to write a read only object",
+        ABORT_NO_OBJECT => "Object does not exist in the object dictionary",
+        0x0604_0041 => "Object cannot be mapped to the PDO",
+        ABORT_PDO_LENGTH => "Mapped objects would exceed the PDO length",
+        ABORT_LENGTH => "Data type does not match, length of service parameter does not match",
+        ABORT_LENGTH_HIGH => "Data type does not match, length of service parameter too high",
+        ABORT_LENGTH_LOW => "Data type does not match, length of service parameter too low",
+        ABORT_NO_SUB => "Sub-index does not exist",
+        0x0609_0030 => "Invalid value for parameter",
+        ABORT_GENERAL => "General error",
+        0x0800_0020 => "Data cannot be transferred or stored to the application",
+        ABORT_STATE => "Data cannot be transferred because of the present device state",
+        _ => return None,
+    })
+}
+
+/// Error of an aborted SDO transfer
+fn abort_error(code: u32) -> Error {
+    Error::new(ErrorKind::Other, format!("SDO abort {:08X} ({})", code, abort_name(code).unwrap_or("Unknown")))
+}
+
+/// NMT state of a node, as sent in heartbeats
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NmtState {
+    BootUp,
+    Stopped,
+    Operational,
+    PreOperational,
+}
+
+impl NmtState {
+    pub fn from_byte(b: u8) -> Option<NmtState> {
+        Some(match b {
+            0x00 => NmtState::BootUp,
+            0x04 => NmtState::Stopped,
+            0x05 => NmtState::Operational,
+            0x7F => NmtState::PreOperational,
+            _ => return None,
+        })
+    }
+
+    pub fn to_byte(self) -> u8 {
+        match self {
+            NmtState::BootUp => 0x00,
+            NmtState::Stopped => 0x04,
+            NmtState::Operational => 0x05,
+            NmtState::PreOperational => 0x7F,
+        }
+    }
+}
+
+impl fmt::Display for NmtState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            NmtState::BootUp => "boot-up",
+            NmtState::Stopped => "stopped",
+            NmtState::Operational => "operational",
+            NmtState::PreOperational => "pre-operational",
+        })
+    }
+}
+
+/// NMT module control commands
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NmtCommand {
+    Start = 0x01,
+    Stop = 0x02,
+    PreOperational = 0x80,
+    ResetNode = 0x81,
+    ResetCommunication = 0x82,
+}
+
+impl NmtCommand {
+    /// Command from its `ricsctl canopen nmt` name
+    pub fn from_name(name: &str) -> Option<NmtCommand> {
+        Some(match name {
+            "start" => NmtCommand::Start,
+            "stop" => NmtCommand::Stop,
+            "preop" => NmtCommand::PreOperational,
+            "reset" => NmtCommand::ResetNode,
+            "reset-comm" => NmtCommand::ResetCommunication,
+            _ => return None,
+        })
+    }
+
+    pub fn from_byte(b: u8) -> Option<NmtCommand> {
+        Some(match b {
+            0x01 => NmtCommand::Start,
+            0x02 => NmtCommand::Stop,
+            0x80 => NmtCommand::PreOperational,
+            0x81 => NmtCommand::ResetNode,
+            0x82 => NmtCommand::ResetCommunication,
+            _ => return None,
+        })
+    }
+}
+
+/// NMT command packet, node 0 addresses all nodes
+pub fn nmt_packet(command: NmtCommand, node: u8) -> rics::RICS_Data {
+    server::can_packet(COB_NMT, vec![command as u8, node])
+}
+
+/// CAN packets that can carry CANopen messages
+fn is_frame(data: &rics::RICS_Data) -> bool {
+    data.get_field_type() == rics::RICS_Data_RICS_DataType::CAN && !data.get_extended() && !data.get_error()
+}
+
+/// Wait for the next packet until `deadline`, or forever
+fn next_packet(packets: &Receiver<rics::RICS_Response>, deadline: Option<Instant>) -> Result<rics::RICS_Data> {
+    loop {
+        let resp = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(Error::new(ErrorKind::TimedOut, "CANopen timeout"));
+                }
+                packets.recv_timeout(deadline - now).map_err(|e| match e {
+                    RecvTimeoutError::Timeout => Error::new(ErrorKind::TimedOut, "CANopen timeout"),
+                    RecvTimeoutError::Disconnected => Error::new(ErrorKind::BrokenPipe, "Server connection closed"),
+                })?
+            },
+            None => packets.recv().map_err(|_| Error::new(ErrorKind::BrokenPipe, "Server connection closed"))?,
+        };
+        if resp.has_data() {
+            return Ok(resp.get_data().clone());
+        }
+    }
+}
+
+fn sdo_index(dat: &[u8]) -> (u16, u8) {
+    (dat[1] as u16 | (dat[2] as u16) << 8, dat[3])
+}
+
+/// SDO frame with the multiplexer of `index` and `sub`
+fn sdo_frame(command: u8, index: u16, sub: u8, dat: &[u8]) -> Vec<u8> {
+    let mut frame = vec![command, index as u8, (index >> 8) as u8, sub];
+    frame.extend_from_slice(dat);
+    frame.resize(8, 0);
+    frame
+}
+
+/// SDO segment frame
+fn sdo_segment(command: u8, dat: &[u8]) -> Vec<u8> {
+    let mut frame = vec![command];
+    frame.extend_from_slice(dat);
+    frame.resize(8, 0);
+    frame
+}
+
+fn sdo_abort(index: u16, sub: u8, code: u32) -> Vec<u8> {
+    sdo_frame(CS_ABORT << 5, index, sub, &code.to_le_bytes())
+}
+
+/// CANopen master over a connected RICS node: NMT commands, SYNC and SDO client
+pub struct CanOpen {
+    server: RICSServer,
+    packets: Receiver<rics::RICS_Response>,
+    /// Time to wait for each SDO response
+    pub sdo_timeout: Duration,
+}
+
+impl CanOpen {
+    /// The server must be connected as a node, its packets can't be read elsewhere anymore
+    pub fn new(mut server: RICSServer) -> CanOpen {
+        let packets = server.listen_response();
+        CanOpen {
+            server: server,
+            packets: packets,
+            sdo_timeout: Duration::from_millis(1000),
+        }
+    }
+
+    pub fn nmt(&mut self, command: NmtCommand, node: u8) {
+        self.server.send_packet(nmt_packet(command, node));
+    }
+
+    /// Send a CANopen SYNC frame
+    pub fn sync(&mut self) {
+        self.server.send_packet(server::can_packet(COB_SYNC, vec![]));
+    }
+
+    /// Send a RICS sync pulse, slaves handle it as a SYNC
+    pub fn sync_pulse(&mut self) {
+        self.server.send_packet(server::sync_packet());
+    }
+
+    /// Send a node guarding remote frame
+    pub fn guard(&mut self, node: u8) {
+        let flags = server::CanFlags { rtr: true, ..Default::default() };
+        self.server.send_packet(server::can_frame(COB_HEARTBEAT + node as i32, vec![0], flags));
+    }
+
+    /// Next packet received by the node, until `timeout` or forever
+    pub fn recv(&mut self, timeout: Option<Duration>) -> Result<rics::RICS_Data> {
+        next_packet(&self.packets, timeout.map(|x| Instant::now() + x))
+    }
+
+    /// Send an SDO request and wait for the response of the node.
+    /// Aborts from the node and timeouts are returned as errors.
+    fn sdo_request(&mut self, node: u8, index: u16, sub: u8, request: Vec<u8>) -> Result<Vec<u8>> {
+        self.server.send_packet(server::can_packet(COB_SDO_RX + node as i32, request));
+        let deadline = Instant::now() + self.sdo_timeout;
+        loop {
+            let data = match next_packet(&self.packets, Some(deadline)) {
+                Ok(data) => data,
+                Err(ref e) if e.kind() == ErrorKind::TimedOut => {
+                    self.server.send_packet(server::can_packet(COB_SDO_RX + node as i32, sdo_abort(index, sub, ABORT_TIMEOUT)));
+                    return Err(Error::new(ErrorKind::TimedOut, "SDO timeout"));
+                },
+                Err(e) => return Err(e),
+            };
+            if !is_frame(&data) || data.get_rtr() || data.get_id() != COB_SDO_TX + node as i32 || data.get_data().len() != 8 {
+                continue;
+            }
+            let dat = data.get_data();
+            if dat[0] >> 5 == CS_ABORT {
+                return Err(abort_error(u32::from_le_bytes([dat[4], dat[5], dat[6], dat[7]])));
+            }
+            return Ok(dat.to_vec());
+        }
+    }
+
+    fn unexpected(&mut self, node: u8, index: u16, sub: u8) -> Error {
+        self.server.send_packet(server::can_packet(COB_SDO_RX + node as i32, sdo_abort(index, sub, ABORT_COMMAND)));
+        Error::new(ErrorKind::InvalidData, "Unexpected SDO response")
+    }
+
+    /// Read an object of a node, with an expedited or a segmented transfer
+    pub fn sdo_upload(&mut self, node: u8, index: u16, sub: u8) -> Result<Vec<u8>> {
+        let resp = self.sdo_request(node, index, sub, sdo_frame(CS_INITIATE_UPLOAD << 5, index, sub, &[]))?;
+        if resp[0] >> 5 != SCS_INITIATE_UPLOAD || sdo_index(&resp) != (index, sub) {
+            return Err(self.unexpected(node, index, sub));
+        }
+        let expedited = resp[0] & 0x02 != 0;
+        let size_indicated = resp[0] & 0x01 != 0;
+        if expedited {
+            let unused = if size_indicated { (resp[0] >> 2 & 0x03) as usize } else { 0 };
+            return Ok(resp[4..8 - unused].to_vec());
+        }
+
+        let size = if size_indicated { Some(u32::from_le_bytes([resp[4], resp[5], resp[6], resp[7]]) as usize) } else { None };
+        let mut dat = vec![];
+        let mut toggle = 0;
+        loop {
+            let resp = self.sdo_request(node, index, sub, sdo_segment(CS_UPLOAD_SEGMENT << 5 | toggle << 4, &[]))?;
+            if resp[0] >> 5 != SCS_UPLOAD_SEGMENT {
+                return Err(self.unexpected(node, index, sub));
+            }
+            if resp[0] >> 4 & 0x01 != toggle {
+                self.server.send_packet(server::can_packet(COB_SDO_RX + node as i32, sdo_abort(index, sub, ABORT_TOGGLE)));
+                return Err(abort_error(ABORT_TOGGLE));
+            }
+            let unused = (resp[0] >> 1 & 0x07) as usize;
+            dat.extend_from_slice(&resp[1..8 - unused]);
+            if resp[0] & 0x01 != 0 {
+                break;
+            }
+            toggle ^= 1;
+        }
+        if size.map(|x| x != dat.len()).unwrap_or(false) {
+            return Err(Error::new(ErrorKind::InvalidData, format!("SDO upload of {} bytes instead of {}", dat.len(), size.unwrap())));
+        }
+        Ok(dat)
+    }
+
+    /// Write an object of a node, expedited up to 4 bytes and segmented above
+    pub fn sdo_download(&mut self, node: u8, index: u16, sub: u8, dat: &[u8]) -> Result<()> {
+        if !dat.is_empty() && dat.len() <= 4 {
+            let command = CS_INITIATE_DOWNLOAD << 5 | ((4 - dat.len()) as u8) << 2 | 0x03;
+            let resp = self.sdo_request(node, index, sub, sdo_frame(command, index, sub, dat))?;
+            if resp[0] >> 5 != SCS_INITIATE_DOWNLOAD || sdo_index(&resp) != (index, sub) {
+                return Err(self.unexpected(node, index, sub));
+            }
+            return Ok(());
+        }
+
+        let resp = self.sdo_request(node, index, sub, sdo_frame(CS_INITIATE_DOWNLOAD << 5 | 0x01, index, sub, &(dat.len() as u32).to_le_bytes()))?;
+        if resp[0] >> 5 != SCS_INITIATE_DOWNLOAD || sdo_index(&resp) != (index, sub) {
+            return Err(self.unexpected(node, index, sub));
+        }
+        let mut toggle = 0;
+        // Empty transfers still send a last segment
+        let chunks: Vec<&[u8]> = if dat.is_empty() { vec![&[]] } else { dat.chunks(7).collect() };
+        for (i, chunk) in chunks.iter().enumerate() {
+            let last = if i + 1 == chunks.len() { 0x01 } else { 0x00 };
+            let command = CS_DOWNLOAD_SEGMENT << 5 | toggle << 4 | ((7 - chunk.len()) as u8) << 1 | last;
+            let resp = self.sdo_request(node, index, sub, sdo_segment(command, chunk))?;
+            if resp[0] >> 5 != SCS_DOWNLOAD_SEGMENT {
+                return Err(self.unexpected(node, index, sub));
+            }
+            if resp[0] >> 4 & 0x01 != toggle {
+                self.server.send_packet(server::can_packet(COB_SDO_RX + node as i32, sdo_abort(index, sub, ABORT_TOGGLE)));
+                return Err(abort_error(ABORT_TOGGLE));
+            }
+            toggle ^= 1;
+        }
+        Ok(())
+    }
+}
+
+/// Change of a monitored node
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeEvent {
+    BootUp(u8),
+    State(u8, NmtState),
+    HeartbeatLost(u8),
+    GuardingLost(u8),
+    ToggleError(u8),
+}
+
+impl fmt::Display for NodeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeEvent::BootUp(node) => write!(f, "Node {} booted", node),
+            NodeEvent::State(node, state) => write!(f, "Node {} is {}", node, state),
+            NodeEvent::HeartbeatLost(node) => write!(f, "Node {} heartbeat lost", node),
+            NodeEvent::GuardingLost(node) => write!(f, "Node {} life guarding lost", node),
+            NodeEvent::ToggleError(node) => write!(f, "Node {} guarding toggle bit not alternated", node),
+        }
+    }
+}
+
+struct NodeStatus {
+    state: Option<NmtState>,
+    last_seen: Instant,
+    guarded: bool,
+    /// A guarding request is waiting for its response
+    requested: bool,
+    /// Expected toggle bit of the next guarding response
+    toggle: bool,
+    lost: bool,
+}
+
+impl NodeStatus {
+    fn new(guarded: bool) -> NodeStatus {
+        NodeStatus { state: None, last_seen: Instant::now(), guarded: guarded, requested: false, toggle: false, lost: false }
+    }
+}
+
+/// Heartbeat consumer and node guarding master.
+/// Nodes sending heartbeats are monitored once heard of, guarded nodes are
+/// added with `guard` and polled with the nodes of `request_guarding`.
+pub struct Monitor {
+    /// Time after which a node without heartbeat is lost
+    pub heartbeat_timeout: Duration,
+    /// Time after which a guarded node that didn't answer is lost
+    pub life_time: Duration,
+    nodes: BTreeMap<u8, NodeStatus>,
+}
+
+impl Monitor {
+    pub fn new(heartbeat_timeout: Duration, life_time: Duration) -> Monitor {
+        Monitor { heartbeat_timeout: heartbeat_timeout, life_time: life_time, nodes: BTreeMap::new() }
+    }
+
+    pub fn guard(&mut self, node: u8) {
+        self.nodes.insert(node, NodeStatus::new(true));
+    }
+
+    /// Guarded nodes to poll with `CanOpen::guard`, their next frame is taken as the response
+    pub fn request_guarding(&mut self) -> Vec<u8> {
+        self.nodes.iter_mut().filter(|(_, x)| x.guarded).map(|(node, x)| { x.requested = true; *node }).collect()
+    }
+
+    /// Handle heartbeat and node guarding frames
+    pub fn push(&mut self, data: &rics::RICS_Data) -> Vec<NodeEvent> {
+        let id = data.get_id();
+        if !is_frame(data) || data.get_rtr() || id <= COB_HEARTBEAT || id > COB_HEARTBEAT + 127 || data.get_data().is_empty() {
+            return vec![];
+        }
+        let node = (id - COB_HEARTBEAT) as u8;
+        let b = data.get_data()[0];
+        let status = self.nodes.entry(node).or_insert_with(|| NodeStatus::new(false));
+        let mut events = vec![];
+        let state = if status.requested && b != 0 {
+            status.requested = false;
+            if (b & 0x80 != 0) != status.toggle {
+                events.push(NodeEvent::ToggleError(node));
+            }
+            status.toggle = b & 0x80 == 0;
+            NmtState::from_byte(b & 0x7F)
+        } else {
+            NmtState::from_byte(b)
+        };
+        status.last_seen = Instant::now();
+        match state {
+            Some(NmtState::BootUp) => {
+                status.toggle = false;
+                status.state = Some(NmtState::BootUp);
+                status.lost = false;
+                events.push(NodeEvent::BootUp(node));
+            },
+            Some(state) if status.state != Some(state) || status.lost => {
+                status.state = Some(state);
+                status.lost = false;
+                events.push(NodeEvent::State(node, state));
+            },
+            Some(_) => (),
+            None => warn!("Node {} sent an invalid NMT state {:02X}", node, b),
+        }
+        events
+    }
+
+    /// Nodes that went silent since the last call
+    pub fn expire(&mut self) -> Vec<NodeEvent> {
+        let mut events = vec![];
+        for (node, status) in self.nodes.iter_mut() {
+            let timeout = if status.guarded { self.life_time } else { self.heartbeat_timeout };
+            if !status.lost && status.last_seen.elapsed() > timeout {
+                status.lost = true;
+                events.push(if status.guarded { NodeEvent::GuardingLost(*node) } else { NodeEvent::HeartbeatLost(*node) });
+            }
+        }
+        events
+    }
+}
+
+/// Segmented SDO transfer in progress on a slave
+enum SdoTransfer {
+    Upload { index: u16, sub: u8, dat: Vec<u8>, offset: usize, toggle: u8 },
+    Download { index: u16, sub: u8, size: Option<usize>, dat: Vec<u8>, toggle: u8 },
+}
+
+/// Simulated CANopen slave over a connected RICS node.
+/// The object dictionary drives the SDO server, the heartbeat producer
+/// (0x1017) and the PDOs, which are only exchanged when operational.
+pub struct Slave {
+    server: RICSServer,
+    packets: Receiver<rics::RICS_Response>,
+    pub node_id: u8,
+    pub od: ObjectDictionary,
+    /// Dictionary restored on reset
+    initial: ObjectDictionary,
+    state: NmtState,
+    /// Toggle bit of the next node guarding response
+    toggle: bool,
+    transfer: Option<SdoTransfer>,
+    last_heartbeat: Instant,
+    /// SYNCs received by each synchronous TPDO since it was sent
+    sync_counts: HashMap<usize, u32>,
+    /// Time and data of the last transmission of each TPDO
+    sent: HashMap<usize, (Instant, Vec<u8>)>,
+}
+
+impl Slave {
+    /// The server must be connected as a node, its packets can't be read elsewhere anymore
+    pub fn new(mut server: RICSServer, od: ObjectDictionary, node_id: u8) -> Slave {
+        let packets = server.listen_response();
+        Slave {
+            server: server,
+            packets: packets,
+            node_id: node_id,
+            initial: od.clone(),
+            od: od,
+            state: NmtState::BootUp,
+            toggle: false,
+            transfer: None,
+            last_heartbeat: Instant::now(),
+            sync_counts: HashMap::new(),
+            sent: HashMap::new(),
+        }
+    }
+
+    /// Boot, then serve the network until the server connection closes
+    pub fn run(&mut self) -> Result<()> {
+        self.boot_up();
+        loop {
+            let deadline = self.next_deadline();
+            match next_packet(&self.packets, Some(deadline)) {
+                Ok(data) => self.handle(&data),
+                Err(ref e) if e.kind() == ErrorKind::TimedOut => (),
+                Err(e) => return Err(e),
+            }
+            self.tick();
+        }
+    }
+
+    /// Send the boot-up message and enter pre-operational
+    fn boot_up(&mut self) {
+        info!("CANopen node {} boot-up", self.node_id);
+        self.state = NmtState::PreOperational;
+        self.toggle = false;
+        self.transfer = None;
+        self.sync_counts.clear();
+        self.sent.clear();
+        self.send(COB_HEARTBEAT, vec![NmtState::BootUp.to_byte()]);
+        self.last_heartbeat = Instant::now();
+    }
+
+    fn send(&mut self, cob: i32, dat: Vec<u8>) {
+        self.server.send_packet(server::can_packet(cob + self.node_id as i32, dat));
+    }
+
+    fn heartbeat_period(&self) -> Option<Duration> {
+        Some(self.od.value(0x1017, 0)).filter(|x| *x > 0).map(Duration::from_millis)
+    }
+
+    /// Event driven TPDOs
+    fn event_tpdos(&self) -> Vec<Pdo> {
+        self.od.tpdos().into_iter().filter(|x| x.valid() && x.transmission_type >= 254).collect()
+    }
+
+    /// Next heartbeat or event timer expiry
+    fn next_deadline(&self) -> Instant {
+        let mut deadline = Instant::now() + Duration::from_millis(100);
+        if let Some(period) = self.heartbeat_period() {
+            deadline = deadline.min(self.last_heartbeat + period);
+        }
+        if self.state == NmtState::Operational {
+            for pdo in self.event_tpdos().iter().filter(|x| x.event_timer > 0) {
+                if let Some((time, _)) = self.sent.get(&pdo.number) {
+                    deadline = deadline.min(*time + Duration::from_millis(pdo.event_timer as u64));
+                }
+            }
+        }
+        deadline
+    }
+
+    /// Produce heartbeats and event driven TPDOs
+    fn tick(&mut self) {
+        if let Some(period) = self.heartbeat_period() {
+            if self.last_heartbeat.elapsed() >= period {
+                self.send(COB_HEARTBEAT, vec![self.state.to_byte()]);
+                self.last_heartbeat = Instant::now();
+            }
+        }
+        if self.state != NmtState::Operational {
+            return;
+        }
+        for pdo in self.event_tpdos() {
+            let dat = self.od.pdo_data(&pdo);
+            let due = match self.sent.get(&pdo.number) {
+                Some((time, last)) => *last != dat || (pdo.event_timer > 0 && time.elapsed() >= Duration::from_millis(pdo.event_timer as u64)),
+                None => true,
+            };
+            if due {
+                self.send_pdo(&pdo, dat);
+            }
+        }
+    }
+
+    fn send_pdo(&mut self, pdo: &Pdo, dat: Vec<u8>) {
+        self.server.send_packet(server::can_packet(pdo.can_id(), dat.clone()));
+        self.sent.insert(pdo.number, (Instant::now(), dat));
+    }
+
+    fn handle(&mut self, data: &rics::RICS_Data) {
+        if data.get_field_type() == rics::RICS_Data_RICS_DataType::SYNC {
+            self.sync();
+            return;
+        }
+        if !is_frame(data) {
+            return;
+        }
+        let id = data.get_id();
+        let dat = data.get_data();
+        if id == COB_NMT && dat.len() >= 2 {
+            if dat[1] == NODE_ALL || dat[1] == self.node_id {
+                match NmtCommand::from_byte(dat[0]) {
+                    Some(command) => self.nmt(command),
+                    None => warn!("Unknown NMT command {:02X}", dat[0]),
+                }
+            }
+        } else if id == COB_SYNC && !data.get_rtr() {
+            self.sync();
+        } else if id == COB_HEARTBEAT + self.node_id as i32 && data.get_rtr() {
+            // Node guarding
+            let toggle = if self.toggle { 0x80 } else { 0x00 };
+            self.send(COB_HEARTBEAT, vec![toggle | self.state.to_byte()]);
+            self.toggle = !self.toggle;
+        } else if id == COB_SDO_RX + self.node_id as i32 && dat.len() == 8 && self.state != NmtState::Stopped {
+            let resp = self.sdo(dat);
+            if let Some(resp) = resp {
+                self.send(COB_SDO_TX, resp);
+            }
+        } else if self.state == NmtState::Operational && !data.get_rtr() {
+            for pdo in self.od.rpdos().into_iter().filter(|x| x.valid() && x.can_id() == id) {
+                self.od.write_pdo(&pdo, dat);
+            }
+        }
+    }
+
+    fn nmt(&mut self, command: NmtCommand) {
+        info!("CANopen node {} NMT {:?}", self.node_id, command);
+        match command {
+            NmtCommand::Start => self.state = NmtState::Operational,
+            NmtCommand::Stop => self.state = NmtState::Stopped,
+            NmtCommand::PreOperational => self.state = NmtState::PreOperational,
+            NmtCommand::ResetNode => {
+                self.od = self.initial.clone();
+                self.boot_up();
+            },
+            NmtCommand::ResetCommunication => {
+                // Restore the communication profile area
+                for (key, entry) in self.initial.entries.range((0x1000, 0)..(0x2000, 0)) {
+                    self.od.entries.insert(*key, entry.clone());
+                }
+                self.boot_up();
+            },
+        }
+    }
+
+    /// Send the synchronous TPDOs due at this SYNC
+    fn sync(&mut self) {
+        if self.state != NmtState::Operational {
+            return;
+        }
+        for pdo in self.od.tpdos().into_iter().filter(|x| x.valid() && x.transmission_type <= 240) {
+            let dat = self.od.pdo_data(&pdo);
+            let due = if pdo.transmission_type == 0 {
+                // Acyclic, sent on the SYNC following a change
+                self.sent.get(&pdo.number).map(|(_, last)| *last != dat).unwrap_or(true)
+            } else {
+                let count = self.sync_counts.entry(pdo.number).or_insert(0);
+                *count += 1;
+                *count >= pdo.transmission_type as u32
+            };
+            if due {
+                self.sync_counts.insert(pdo.number, 0);
+                self.send_pdo(&pdo, dat);
+            }
+        }
+    }
+
+    /// Check that an object can be accessed, returns an abort code otherwise
+    fn check_access(&self, index: u16, sub: u8, write: bool) -> std::result::Result<(), u32> {
+        match self.od.get(index, sub) {
+            None if self.od.has_index(index) => Err(ABORT_NO_SUB),
+            None => Err(ABORT_NO_OBJECT),
+            Some(entry) if write && !entry.access.writable() => Err(ABORT_READ_ONLY),
+            Some(entry) if !write && !entry.access.readable() => Err(ABORT_WRITE_ONLY),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Store a downloaded value, checking its length against the data type
+    fn write(&mut self, index: u16, sub: u8, dat: Vec<u8>) -> std::result::Result<(), u32> {
+        self.check_access(index, sub, true)?;
+        let entry = self.od.get_mut(index, sub).unwrap();
+        if let Some(size) = eds::type_size(entry.data_type) {
+            if dat.len() > size {
+                return Err(ABORT_LENGTH_HIGH);
+            } else if dat.len() < size {
+                return Err(ABORT_LENGTH_LOW);
+            }
+        }
+        debug!("SDO write {:04X}sub{} = {:02X?}", index, sub, dat);
+        let previous = std::mem::replace(&mut entry.value, dat);
+        let mapping = (eds::RPDO_MAPPING..eds::RPDO_MAPPING + 512).contains(&index)
+            || (eds::TPDO_MAPPING..eds::TPDO_MAPPING + 512).contains(&index);
+        if mapping && self.od.mapping_len(index) > eds::PDO_MAX_LEN {
+            self.od.get_mut(index, sub).unwrap().value = previous;
+            return Err(ABORT_PDO_LENGTH);
+        }
+        Ok(())
+    }
+
+    /// Handle an SDO request, returns the response
+    fn sdo(&mut self, req: &[u8]) -> Option<Vec<u8>> {
+        let command = req[0] >> 5;
+        let toggle = req[0] >> 4 & 0x01;
+        match (command, self.transfer.take()) {
+            (CS_ABORT, _) => None,
+            (CS_INITIATE_DOWNLOAD, _) => {
+                let (index, sub) = sdo_index(req);
+                if let Err(code) = self.check_access(index, sub, true) {
+                    return Some(sdo_abort(index, sub, code));
+                }
+                let size_indicated = req[0] & 0x01 != 0;
+                if req[0] & 0x02 != 0 {
+                    let mut dat = if size_indicated { req[4..8 - (req[0] >> 2 & 0x03) as usize].to_vec() } else { req[4..8].to_vec() };
+                    if !size_indicated {
+                        // Without size, the data type tells the length
+                        let data_type = self.od.get(index, sub).unwrap().data_type;
+                        dat.truncate(eds::type_size(data_type).unwrap_or(4));
+                    }
+                    return Some(match self.write(index, sub, dat) {
+                        Ok(()) => sdo_frame(SCS_INITIATE_DOWNLOAD << 5, index, sub, &[]),
+                        Err(code) => sdo_abort(index, sub, code),
+                    });
+                }
+                let size = if size_indicated { Some(u32::from_le_bytes([req[4], req[5], req[6], req[7]]) as usize) } else { None };
+                self.transfer = Some(SdoTransfer::Download { index: index, sub: sub, size: size, dat: vec![], toggle: 0 });
+                Some(sdo_frame(SCS_INITIATE_DOWNLOAD << 5, index, sub, &[]))
+            },
+            (CS_DOWNLOAD_SEGMENT, Some(SdoTransfer::Download { index, sub, size, mut dat, toggle: expected })) => {
+                if toggle != expected {
+                    return Some(sdo_abort(index, sub, ABORT_TOGGLE));
+                }
+                dat.extend_from_slice(&req[1..8 - (req[0] >> 1 & 0x07) as usize]);
+                if req[0] & 0x01 == 0 {
+                    self.transfer = Some(SdoTransfer::Download { index: index, sub: sub, size: size, dat: dat, toggle: toggle ^ 1 });
+                    return Some(sdo_segment(SCS_DOWNLOAD_SEGMENT << 5 | toggle << 4, &[]));
+                }
+                if size.map(|x| x != dat.len()).unwrap_or(false) {
+                    return Some(sdo_abort(index, sub, ABORT_LENGTH));
+                }
+                Some(match self.write(index, sub, dat) {
+                    Ok(()) => sdo_segment(SCS_DOWNLOAD_SEGMENT << 5 | toggle << 4, &[]),
+                    Err(code) => sdo_abort(index, sub, code),
+                })
+            },
+            (CS_INITIATE_UPLOAD, _) => {
+                let (index, sub) = sdo_index(req);
+                if let Err(code) = self.check_access(index, sub, false) {
+                    return Some(sdo_abort(index, sub, code));
+                }
+                let dat = self.od.get(index, sub).unwrap().value.clone();
+                if !dat.is_empty() && dat.len() <= 4 {
+                    let command = SCS_INITIATE_UPLOAD << 5 | ((4 - dat.len()) as u8) << 2 | 0x03;
+                    return Some(sdo_frame(command, index, sub, &dat));
+                }
+                let size = (dat.len() as u32).to_le_bytes();
+                self.transfer = Some(SdoTransfer::Upload { index: index, sub: sub, dat: dat, offset: 0, toggle: 0 });
+                Some(sdo_frame(SCS_INITIATE_UPLOAD << 5 | 0x01, index, sub, &size))
+            },
+            (CS_UPLOAD_SEGMENT, Some(SdoTransfer::Upload { index, sub, dat, offset, toggle: expected })) => {
+                if toggle != expected {
+                    return Some(sdo_abort(index, sub, ABORT_TOGGLE));
+                }
+                let end = (offset + 7).min(dat.len());
+                let chunk = dat[offset..end].to_vec();
+                let last = if end == dat.len() { 0x01 } else { 0x00 };
+                if last == 0 {
+                    self.transfer = Some(SdoTransfer::Upload { index: index, sub: sub, dat: dat, offset: end, toggle: toggle ^ 1 });
+                }
+                Some(sdo_segment(SCS_UPLOAD_SEGMENT << 5 | toggle << 4 | ((7 - chunk.len()) as u8) << 1 | last, &chunk))
+            },
+            (_, transfer) => {
+                let (index, sub) = match transfer {
+                    Some(SdoTransfer::Upload { index, sub, .. }) | Some(SdoTransfer::Download { index, sub, .. }) => (index, sub),
+                    None => sdo_index(req),
+                };
+                Some(sdo_abort(index, sub, ABORT_COMMAND))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testutil::{self, Output};
+
+    const EDS: &str = "[1017]\nDataType=0x0006\nDefaultValue=0\n\
+                       [1008]\nDataType=0x0009\nAccessType=ro\nDefaultValue=Hello RICS!\n\
+                       [2000]\nDataType=0x0007\nDefaultValue=0x12345678\n\
+                       [2001]\nDataType=0x000A\nDefaultValue=00\n";
+
+    fn slave() -> Slave {
+        let server = RICSServer::new_from(testutil::responses(vec![]), Output::default());
+        Slave::new(server, ObjectDictionary::parse(EDS, 5).unwrap(), 5)
+    }
+
+    /// Client receiving the given SDO responses from node 5
+    fn client(responses: Vec<Vec<u8>>) -> (CanOpen, Output) {
+        let output = Output::default();
+        let input = testutil::responses(responses.into_iter().map(|x| server::can_packet(COB_SDO_TX + 5, x)).collect());
+        (CanOpen::new(RICSServer::new_from(input, output.clone())), output)
+    }
+
+    /// SDO requests sent to node 5
+    fn requests(output: &Output) -> Vec<Vec<u8>> {
+        output.requests().iter().map(|x| x.get_data())
+            .filter(|x| x.get_id() == COB_SDO_RX + 5).map(|x| x.get_data().to_vec()).collect()
+    }
+
+    fn abort_code(frame: &[u8]) -> Option<u32> {
+        if frame[0] >> 5 == CS_ABORT { Some(u32::from_le_bytes([frame[4], frame[5], frame[6], frame[7]])) } else { None }
+    }
+
+    #[test]
+    fn server_expedited() {
+        let mut slave = slave();
+        assert_eq!(slave.sdo(&sdo_frame(0x40, 0x2000, 0, &[])), Some(vec![0x43, 0x00, 0x20, 0x00, 0x78, 0x56, 0x34, 0x12]));
+        assert_eq!(slave.sdo(&sdo_frame(0x23, 0x2000, 0, &[1, 2, 3, 4])), Some(sdo_frame(0x60, 0x2000, 0, &[])));
+        assert_eq!(slave.od.get(0x2000, 0).unwrap().value, vec![1, 2, 3, 4]);
+
+        // Without size, the data type gives the length
+        assert_eq!(slave.sdo(&sdo_frame(0x22, 0x1017, 0, &[0xE8, 0x03, 0xFF, 0xFF])), Some(sdo_frame(0x60, 0x1017, 0, &[])));
+        assert_eq!(slave.od.get(0x1017, 0).unwrap().value, vec![0xE8, 0x03]);
+        assert_eq!(abort_code(&slave.sdo(&sdo_frame(0x27, 0x2000, 0, &[1, 2, 3])).unwrap()), Some(ABORT_LENGTH_LOW));
+    }
+
+    #[test]
+    fn server_segmented() {
+        let mut slave = slave();
+        assert_eq!(slave.sdo(&sdo_frame(0x40, 0x1008, 0, &[])), Some(sdo_frame(0x41, 0x1008, 0, &[11, 0, 0, 0])));
+        assert_eq!(slave.sdo(&sdo_segment(0x60, &[])), Some(sdo_segment(0x00, b"Hello R")));
+        assert_eq!(slave.sdo(&sdo_segment(0x70, &[])), Some(sdo_segment(0x17, b"ICS!")));
+        assert_eq!(abort_code(&slave.sdo(&sdo_segment(0x60, &[])).unwrap()), Some(ABORT_COMMAND));
+
+        assert_eq!(slave.sdo(&sdo_frame(0x21, 0x2001, 0, &[9, 0, 0, 0])), Some(sdo_frame(0x60, 0x2001, 0, &[])));
+        assert_eq!(slave.sdo(&sdo_segment(0x00, &[1, 2, 3, 4, 5, 6, 7])), Some(sdo_segment(0x20, &[])));
+        assert_eq!(slave.sdo(&sdo_segment(0x1B, &[8, 9])), Some(sdo_segment(0x30, &[])));
+        assert_eq!(slave.od.get(0x2001, 0).unwrap().value, vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        // The indicated size must match the data
+        slave.sdo(&sdo_frame(0x21, 0x2001, 0, &[3, 0, 0, 0]));
+        assert_eq!(abort_code(&slave.sdo(&sdo_segment(0x0B, &[1, 2])).unwrap()), Some(ABORT_LENGTH));
+    }
+
+    #[test]
+    fn server_abort() {
+        let mut slave = slave();
+        assert_eq!(abort_code(&slave.sdo(&sdo_frame(0x40, 0x3000, 0, &[])).unwrap()), Some(ABORT_NO_OBJECT));
+        assert_eq!(abort_code(&slave.sdo(&sdo_frame(0x40, 0x2000, 1, &[])).unwrap()), Some(ABORT_NO_SUB));
+        assert_eq!(abort_code(&slave.sdo(&sdo_frame(0x2F, 0x1008, 0, &[0])).unwrap()), Some(ABORT_READ_ONLY));
+
+        // Wrong toggle bit
+        slave.sdo(&sdo_frame(0x40, 0x1008, 0, &[]));
+        assert_eq!(abort_code(&slave.sdo(&sdo_segment(0x70, &[])).unwrap()), Some(ABORT_TOGGLE));
+
+        // An abort from the client ends the transfer without response
+        slave.sdo(&sdo_frame(0x40, 0x1008, 0, &[]));
+        assert_eq!(slave.sdo(&sdo_abort(0x1008, 0, ABORT_GENERAL)), None);
+        assert_eq!(abort_code(&slave.sdo(&sdo_segment(0x60, &[])).unwrap()), Some(ABORT_COMMAND));
+    }
+
+    #[test]
+    fn client_expedited() {
+        let (mut client, output) = client(vec![
+            vec![0x4B, 0x00, 0x20, 0x01, 0x34, 0x12, 0x00, 0x00],
+            sdo_frame(0x60, 0x2000, 1, &[]),
+        ]);
+        assert_eq!(client.sdo_upload(5, 0x2000, 1).unwrap(), vec![0x34, 0x12]);
+        client.sdo_download(5, 0x2000, 1, &[0xAB]).unwrap();
+        assert_eq!(requests(&output), vec![sdo_frame(0x40, 0x2000, 1, &[]), sdo_frame(0x2F, 0x2000, 1, &[0xAB])]);
+    }
+
+    #[test]
+    fn client_segmented() {
+        let (mut client, output) = client(vec![
+            sdo_frame(0x41, 0x1008, 0, &[11, 0, 0, 0]),
+            sdo_segment(0x00, b"Hello R"),
+            sdo_segment(0x17, b"ICS!"),
+            sdo_frame(0x60, 0x2001, 0, &[]),
+            sdo_segment(0x20, &[]),
+            sdo_segment(0x30, &[]),
+        ]);
+        assert_eq!(client.sdo_upload(5, 0x1008, 0).unwrap(), b"Hello RICS!".to_vec());
+        client.sdo_download(5, 0x2001, 0, &[1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        assert_eq!(requests(&output), vec![
+            sdo_frame(0x40, 0x1008, 0, &[]),
+            sdo_segment(0x60, &[]),
+            sdo_segment(0x70, &[]),
+            sdo_frame(0x21, 0x2001, 0, &[9, 0, 0, 0]),
+            sdo_segment(0x00, &[1, 2, 3, 4, 5, 6, 7]),
+            sdo_segment(0x1B, &[8, 9]),
+        ]);
+    }
+
+    #[test]
+    fn client_abort() {
+        let (mut client, output) = client(vec![
+            sdo_abort(0x3000, 0, ABORT_NO_OBJECT),
+            sdo_frame(0x41, 0x1008, 0, &[11, 0, 0, 0]),
+            sdo_segment(0x10, b"Hello R"),
+        ]);
+        let err = client.sdo_upload(5, 0x3000, 0).unwrap_err();
+        assert_eq!(err.to_string(), "SDO abort 06020000 (Object does not exist in the object dictionary)");
+
+        // Wrong toggle bit in the response
+        assert!(client.sdo_upload(5, 0x1008, 0).is_err());
+        assert_eq!(requests(&output).last().and_then(|x| abort_code(x)), Some(ABORT_TOGGLE));
+    }
+}
@@ -0,0 +1,686 @@
+//! CAN database (DBC) files
+//!
+//! Messages are described by `BO_` lines, followed by the `SG_` lines of
+//! their signals. Each signal gives its position in the frame, its byte
+//! order, signedness, scaling, range and unit. `VAL_` lines attach value
+//! tables to signals, and `SIG_VALTYPE_` lines mark IEEE float signals.
+//! Multiplexed signals are only present when the multiplexor signal of
+//! their message (`M`) holds their multiplexer value (`m<value>`).
+//! Message and signal attributes come from `BA_` lines, with the defaults of
+//! their `BA_DEF_DEF_` line.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+use super::rics;
+use super::server;
+
+/// Bit 31 of DBC message ids marks extended identifiers
+const EXTENDED_FLAG: u32 = 0x8000_0000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Multiplex {
+    /// Always present
+    Plain,
+    /// Selects the multiplexed signals of its message
+    Multiplexor,
+    /// Present when the multiplexor holds this value
+    Multiplexed(u64),
+}
+
+/// Raw value encoding of a signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueType {
+    Unsigned,
+    Signed,
+    Float32,
+    Float64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Signal {
+    pub name: String,
+    /// Start bit, the least significant bit for little endian signals and the
+    /// most significant one for big endian signals
+    pub start: u32,
+    pub size: u32,
+    /// Intel byte order, Motorola otherwise
+    pub little_endian: bool,
+    pub value_type: ValueType,
+    pub factor: f64,
+    pub offset: f64,
+    pub min: f64,
+    pub max: f64,
+    pub unit: String,
+    pub receivers: Vec<String>,
+    pub multiplex: Multiplex,
+    /// Value table, by raw value
+    pub values: BTreeMap<i64, String>,
+    pub attributes: HashMap<String, String>,
+}
+
+impl Signal {
+    /// Unsigned signal without scaling, range or unit
+    pub fn new(name: impl Into<String>, start: u32, size: u32, little_endian: bool) -> Signal {
+        Signal {
+            name: name.into(),
+            start: start,
+            size: size,
+            little_endian: little_endian,
+            value_type: ValueType::Unsigned,
+            factor: 1.0,
+            offset: 0.0,
+            min: 0.0,
+            max: 0.0,
+            unit: String::new(),
+            receivers: vec![],
+            multiplex: Multiplex::Plain,
+            values: BTreeMap::new(),
+            attributes: HashMap::new(),
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        if self.size >= 64 { u64::max_value() } else { (1 << self.size) - 1 }
+    }
+
+    /// Positions of the signal bits in the frame, most significant first.
+    /// Bit `n` is bit `n % 8` of byte `n / 8`.
+    fn bits(&self) -> Vec<u32> {
+        if self.little_endian {
+            (0..self.size).rev().map(|i| self.start + i).collect()
+        } else {
+            let mut bits = Vec::with_capacity(self.size as usize);
+            let mut bit = self.start;
+            for _ in 0..self.size {
+                bits.push(bit);
+                bit = if bit % 8 == 0 { bit + 15 } else { bit - 1 };
+            }
+            bits
+        }
+    }
+
+    /// Raw value of the signal in a frame, None if the frame is too short
+    pub fn raw(&self, dat: &[u8]) -> Option<u64> {
+        let mut raw = 0u64;
+        for bit in self.bits() {
+            let byte = *dat.get(bit as usize / 8)?;
+            raw = raw << 1 | (byte >> (bit % 8) & 1) as u64;
+        }
+        Some(raw)
+    }
+
+    /// Raw value as a number, sign extended or converted from float as needed
+    pub fn raw_value(&self, raw: u64) -> f64 {
+        match self.value_type {
+            ValueType::Unsigned => raw as f64,
+            ValueType::Signed if self.size > 0 && self.size < 64 => {
+                let shift = 64 - self.size;
+                (((raw << shift) as i64) >> shift) as f64
+            },
+            ValueType::Signed => raw as i64 as f64,
+            ValueType::Float32 => f32::from_bits(raw as u32) as f64,
+            ValueType::Float64 => f64::from_bits(raw),
+        }
+    }
+
+    /// Physical value of a raw value
+    pub fn physical(&self, raw: u64) -> f64 {
+        self.raw_value(raw) * self.factor + self.offset
+    }
+
+    /// Store a raw value in a frame
+    pub fn set_raw(&self, dat: &mut [u8], raw: u64) {
+        for (i, bit) in self.bits().iter().rev().enumerate() {
+            if let Some(byte) = dat.get_mut(*bit as usize / 8) {
+                let mask = 1 << (bit % 8);
+                if raw >> i & 1 != 0 { *byte |= mask } else { *byte &= !mask }
+            }
+        }
+    }
+
+    /// Raw value of a physical value, checked against the signal range and size
+    pub fn to_raw(&self, physical: f64) -> Result<u64, String> {
+        if self.min < self.max && (physical < self.min || physical > self.max) {
+            return Err(format!("{} is out of the range of {} [{}|{}]", physical, self.name, self.min, self.max));
+        }
+        let value = (physical - self.offset) / self.factor;
+        let mask = self.mask();
+        let raw = match self.value_type {
+            ValueType::Float32 => (value as f32).to_bits() as u64,
+            ValueType::Float64 => value.to_bits(),
+            ValueType::Unsigned => {
+                let raw = value.round();
+                if raw < 0.0 || raw > mask as f64 {
+                    return Err(format!("{} doesn't fit in the {} bits of {}", physical, self.size, self.name));
+                }
+                raw as u64
+            },
+            ValueType::Signed => {
+                let raw = value.round();
+                let limit = (mask / 2) as f64;
+                if raw < -limit - 1.0 || raw > limit {
+                    return Err(format!("{} doesn't fit in the {} bits of {}", physical, self.size, self.name));
+                }
+                raw as i64 as u64 & mask
+            },
+        };
+        Ok(raw)
+    }
+
+    /// Raw initial value, from the `GenSigStartValue` attribute
+    pub fn start_value(&self) -> u64 {
+        let value = self.attributes.get("GenSigStartValue").and_then(|x| x.parse::<f64>().ok()).unwrap_or(0.0);
+        match self.value_type {
+            ValueType::Float32 => (value as f32).to_bits() as u64,
+            ValueType::Float64 => value.to_bits(),
+            _ => value.round() as i64 as u64 & self.mask(),
+        }
+    }
+
+    /// Physical value of a number or of a value table label
+    pub fn parse_value(&self, s: &str) -> Option<f64> {
+        s.parse::<f64>().ok().or_else(|| self.label_value(s))
+    }
+
+    /// Physical value of a value table label
+    pub fn label_value(&self, label: &str) -> Option<f64> {
+        self.values.iter().find(|(_, x)| x.as_str() == label).map(|(raw, _)| *raw as f64 * self.factor + self.offset)
+    }
+
+    /// Value table label of a raw value
+    pub fn label(&self, raw: u64) -> Option<&str> {
+        let key = match self.value_type {
+            ValueType::Signed => self.raw_value(raw) as i64,
+            _ => raw as i64,
+        };
+        self.values.get(&key).map(|x| x.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub id: i32,
+    pub extended: bool,
+    pub name: String,
+    pub size: usize,
+    pub transmitter: String,
+    pub signals: Vec<Signal>,
+    pub attributes: HashMap<String, String>,
+}
+
+impl Message {
+    pub fn signal(&self, name: &str) -> Option<&Signal> {
+        self.signals.iter().find(|x| x.name == name)
+    }
+
+    /// Multiplexor signal, if the message has multiplexed signals
+    pub fn multiplexor(&self) -> Option<&Signal> {
+        self.signals.iter().find(|x| x.multiplex == Multiplex::Multiplexor)
+    }
+
+    fn duration_attribute(&self, name: &str) -> Option<Duration> {
+        let ms = self.attributes.get(name)?.parse::<f64>().ok()?;
+        if ms > 0.0 { Some(Duration::from_secs_f64(ms / 1e3)) } else { None }
+    }
+
+    /// Period of a cyclic message, from the `GenMsgCycleTime` attribute
+    pub fn cycle_time(&self) -> Option<Duration> {
+        self.duration_attribute("GenMsgCycleTime")
+    }
+
+    /// Delay of the first transmission, from the `GenMsgStartDelayTime` attribute
+    pub fn start_delay(&self) -> Duration {
+        self.duration_attribute("GenMsgStartDelayTime").unwrap_or(Duration::from_secs(0))
+    }
+
+    /// Data with every signal at its start value, the multiplexed signals
+    /// being the ones selected by the start value of the multiplexor
+    pub fn initial_data(&self) -> Vec<u8> {
+        let mut dat = vec![0; self.size];
+        let mux = self.multiplexor().map(|x| x.start_value());
+        for signal in self.signals.iter() {
+            match signal.multiplex {
+                Multiplex::Multiplexed(value) if Some(value) != mux => continue,
+                _ => signal.set_raw(&mut dat, signal.start_value()),
+            }
+        }
+        dat
+    }
+
+    /// Store the physical value of a signal in a frame, selecting it with the
+    /// multiplexor if it is multiplexed
+    pub fn set_signal(&self, dat: &mut [u8], name: &str, physical: f64) -> Result<(), String> {
+        let signal = self.signal(name).ok_or_else(|| format!("Unknown signal {} in {}", name, self.name))?;
+        let raw = signal.to_raw(physical)?;
+        if let (Multiplex::Multiplexed(value), Some(multiplexor)) = (signal.multiplex, self.multiplexor()) {
+            multiplexor.set_raw(dat, value);
+        }
+        signal.set_raw(dat, raw);
+        Ok(())
+    }
+
+    /// CAN packet of the message with this data
+    pub fn frame(&self, dat: Vec<u8>) -> rics::RICS_Data {
+        let flags = server::CanFlags { extended: self.extended, fd: dat.len() > server::CAN_MAX_DLEN, ..Default::default() };
+        server::can_frame(self.id, dat, flags)
+    }
+}
+
+/// Decoded signal value
+#[derive(Clone, Debug)]
+pub struct SignalValue {
+    pub name: String,
+    pub raw: u64,
+    pub physical: f64,
+    pub unit: String,
+    pub label: Option<String>,
+}
+
+impl fmt::Display for SignalValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, format_physical(self.physical))?;
+        if !self.unit.is_empty() {
+            write!(f, " {}", self.unit)?;
+        }
+        if let Some(label) = &self.label {
+            write!(f, " ({})", label)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decoded message
+#[derive(Clone, Debug)]
+pub struct DecodedMessage {
+    pub name: String,
+    pub signals: Vec<SignalValue>,
+}
+
+impl fmt::Display for DecodedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.signals.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", "))
+    }
+}
+
+/// Format a physical value without float noise
+pub fn format_physical(value: f64) -> String {
+    if value.is_finite() {
+        format!("{}", (value * 1e6).round() / 1e6)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// Split a line into tokens, quoted strings being a single token without their quotes
+fn tokens(line: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut token = String::new();
+            while let Some(c) = chars.next() {
+                if c == '"' {
+                    break;
+                }
+                token.push(c);
+            }
+            tokens.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+/// Parse the `SG_` line of a signal
+fn parse_signal(line: &str) -> Option<Signal> {
+    // SG_ name [M|m<n>] : start|size@order sign (factor,offset) [min|max] "unit" receivers
+    let colon = line.find(':')?;
+    let head: Vec<&str> = line[..colon].split_whitespace().collect();
+    let multiplex = match head.get(2) {
+        None => Multiplex::Plain,
+        Some(&"M") => Multiplex::Multiplexor,
+        Some(m) if m.starts_with('m') => Multiplex::Multiplexed(m[1..].trim_end_matches('M').parse().ok()?),
+        Some(_) => return None,
+    };
+    let rest = &line[colon + 1..];
+    let layout_end = rest.find('(')?;
+    let layout = rest[..layout_end].trim();
+    let (position, encoding) = layout.split_at(layout.find('@')?);
+    let mut position = position.split('|');
+    let start = position.next()?.trim().parse().ok()?;
+    let size = position.next()?.trim().parse().ok()?;
+    let little_endian = encoding.get(1..2)? == "1";
+    let signed = encoding.get(2..3)? == "-";
+
+    let scaling_end = rest.find(')')?;
+    let mut scaling = rest.get(layout_end + 1..scaling_end)?.split(',');
+    let factor = scaling.next()?.trim().parse().ok()?;
+    let offset = scaling.next()?.trim().parse().ok()?;
+
+    let rest = &rest[scaling_end + 1..];
+    let (range_start, range_end) = (rest.find('[')?, rest.find(']')?);
+    let mut range = rest.get(range_start + 1..range_end)?.split('|');
+    let min = range.next()?.trim().parse().ok()?;
+    let max = range.next()?.trim().parse().ok()?;
+
+    let tail = tokens(&rest[range_end + 1..]);
+    let unit = tail.get(0).cloned().unwrap_or_default();
+    let receivers = tail.iter().skip(1).flat_map(|x| x.split(',')).filter(|x| !x.is_empty()).map(|x| x.to_string()).collect();
+    Some(Signal {
+        name: head.get(1)?.to_string(),
+        start: start,
+        size: size,
+        little_endian: little_endian,
+        value_type: if signed { ValueType::Signed } else { ValueType::Unsigned },
+        factor: factor,
+        offset: offset,
+        min: min,
+        max: max,
+        unit: unit,
+        receivers: receivers,
+        multiplex: multiplex,
+        values: BTreeMap::new(),
+        attributes: HashMap::new(),
+    })
+}
+
+/// CAN database
+#[derive(Clone, Debug, Default)]
+pub struct Dbc {
+    /// Messages by id, with bit 31 set for extended ids
+    pub messages: HashMap<u32, Message>,
+}
+
+impl Dbc {
+    pub fn parse(content: &str) -> Result<Dbc, String> {
+        let mut dbc = Dbc::default();
+        let mut current: Option<u32> = None;
+        // Object type and default value of the attributes
+        let mut kinds: HashMap<String, String> = HashMap::new();
+        let mut defaults: HashMap<String, String> = HashMap::new();
+        // Comments and attributes may span several lines
+        let mut in_string = false;
+        for (n, line) in content.lines().enumerate() {
+            let quotes = line.matches('"').count();
+            if in_string {
+                in_string = quotes % 2 == 0;
+                continue;
+            }
+            in_string = quotes % 2 == 1;
+            let line = line.trim();
+            let error = || format!("Line {}: invalid {}", n + 1, line);
+
+            if line.starts_with("BO_ ") {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let key = fields.get(1).and_then(|x| x.parse::<u32>().ok()).ok_or_else(error)?;
+                let name = fields.get(2).ok_or_else(error)?.trim_end_matches(':').to_string();
+                let size = fields.get(3).and_then(|x| x.parse::<usize>().ok())
+                    .filter(|x| *x <= server::CANFD_MAX_DLEN).ok_or_else(error)?;
+                dbc.messages.insert(key, Message {
+                    id: (key & !EXTENDED_FLAG) as i32,
+                    extended: key & EXTENDED_FLAG != 0,
+                    name: name,
+                    size: size,
+                    transmitter: fields.get(4).map(|x| x.to_string()).unwrap_or_default(),
+                    signals: vec![],
+                    attributes: HashMap::new(),
+                });
+                current = Some(key);
+            } else if line.starts_with("SG_ ") {
+                let signal = parse_signal(line).ok_or_else(error)?;
+                match current.and_then(|x| dbc.messages.get_mut(&x)) {
+                    Some(message) => message.signals.push(signal),
+                    None => return Err(format!("Line {}: signal outside of a message", n + 1)),
+                }
+            } else if line.starts_with("VAL_ ") {
+                let fields = tokens(line.trim_end_matches(';'));
+                let key = fields.get(1).and_then(|x| x.parse::<u32>().ok()).ok_or_else(error)?;
+                let signal = dbc.messages.get_mut(&key).and_then(|m| m.signals.iter_mut().find(|s| Some(&s.name) == fields.get(2)));
+                if let Some(signal) = signal {
+                    for pair in fields[3..].chunks(2).filter(|x| x.len() == 2) {
+                        let value = pair[0].parse::<i64>().map_err(|_| error())?;
+                        signal.values.insert(value, pair[1].clone());
+                    }
+                }
+            } else if line.starts_with("SIG_VALTYPE_ ") {
+                let fields: Vec<&str> = line.trim_end_matches(';').split_whitespace().collect();
+                let key = fields.get(1).and_then(|x| x.parse::<u32>().ok()).ok_or_else(error)?;
+                let value_type = match fields.last() {
+                    Some(&"1") => ValueType::Float32,
+                    Some(&"2") => ValueType::Float64,
+                    _ => continue,
+                };
+                let signal = dbc.messages.get_mut(&key).and_then(|m| m.signals.iter_mut().find(|s| Some(&s.name.as_str()) == fields.get(2)));
+                if let Some(signal) = signal {
+                    signal.value_type = value_type;
+                }
+            } else if line.starts_with("BA_DEF_ ") {
+                let fields = tokens(line.trim_end_matches(';'));
+                match fields.get(1).map(|x| x.as_str()) {
+                    Some(kind @ "BO_") | Some(kind @ "SG_") => { kinds.insert(fields.get(2).ok_or_else(error)?.clone(), kind.to_string()); },
+                    _ => (),
+                }
+            } else if line.starts_with("BA_DEF_DEF_ ") {
+                let fields = tokens(line.trim_end_matches(';'));
+                if let (Some(name), Some(value)) = (fields.get(1), fields.get(2)) {
+                    defaults.insert(name.clone(), value.clone());
+                }
+            } else if line.starts_with("BA_ ") {
+                let fields = tokens(line.trim_end_matches(';'));
+                let key = fields.get(3).and_then(|x| x.parse::<u32>().ok());
+                let message = key.and_then(|x| dbc.messages.get_mut(&x));
+                match (fields.get(2).map(|x| x.as_str()), message) {
+                    (Some("BO_"), Some(message)) => {
+                        message.attributes.insert(fields[1].clone(), fields.get(4).ok_or_else(error)?.clone());
+                    },
+                    (Some("SG_"), Some(message)) => {
+                        let value = fields.get(5).ok_or_else(error)?.clone();
+                        if let Some(signal) = message.signals.iter_mut().find(|s| s.name == fields[4]) {
+                            signal.attributes.insert(fields[1].clone(), value);
+                        }
+                    },
+                    _ => (),
+                }
+            } else if !line.is_empty() {
+                current = None;
+            }
+        }
+
+        for (name, value) in defaults.iter() {
+            for message in dbc.messages.values_mut() {
+                match kinds.get(name).map(|x| x.as_str()) {
+                    Some("BO_") => { message.attributes.entry(name.clone()).or_insert_with(|| value.clone()); },
+                    Some("SG_") => for signal in message.signals.iter_mut() {
+                        signal.attributes.entry(name.clone()).or_insert_with(|| value.clone());
+                    },
+                    _ => (),
+                }
+            }
+        }
+        Ok(dbc)
+    }
+
+    pub fn load(path: &str) -> Result<Dbc, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+        Dbc::parse(&content)
+    }
+
+    /// Message of a CAN id
+    pub fn message(&self, id: i32, extended: bool) -> Option<&Message> {
+        self.messages.get(&(id as u32 | if extended { EXTENDED_FLAG } else { 0 }))
+    }
+
+    /// Message by name
+    pub fn message_by_name(&self, name: &str) -> Option<&Message> {
+        self.messages.values().find(|x| x.name == name)
+    }
+
+    /// Encode a message from the physical values of its signals.
+    /// Signals that aren't given are zero, and the multiplexor defaults to
+    /// the multiplexer value of the given multiplexed signals.
+    pub fn encode(&self, message: &str, values: &[(&str, f64)]) -> Result<rics::RICS_Data, String> {
+        let message = self.message_by_name(message).ok_or_else(|| format!("Unknown message {}", message))?;
+        let mut dat = vec![0; message.size];
+        let mut mux = None;
+        for (name, _) in values {
+            let signal = message.signal(name).ok_or_else(|| format!("Unknown signal {} in {}", name, message.name))?;
+            if let Multiplex::Multiplexed(value) = signal.multiplex {
+                if mux.map(|x| x != value).unwrap_or(false) {
+                    return Err(format!("Signals of different multiplexer values in {}", message.name));
+                }
+                mux = Some(value);
+            }
+        }
+        if let (Some(multiplexor), Some(value)) = (message.multiplexor(), mux) {
+            if let Some((_, physical)) = values.iter().find(|(name, _)| *name == multiplexor.name) {
+                if multiplexor.to_raw(*physical)? != value {
+                    return Err(format!("{} selects other signals than the ones given", multiplexor.name));
+                }
+            }
+            multiplexor.set_raw(&mut dat, value);
+        }
+        for (name, physical) in values {
+            let signal = message.signal(name).unwrap();
+            signal.set_raw(&mut dat, signal.to_raw(*physical)?);
+        }
+        Ok(message.frame(dat))
+    }
+
+    /// Decode the signals of a CAN packet, None if the database doesn't describe it
+    pub fn decode(&self, data: &rics::RICS_Data) -> Option<DecodedMessage> {
+        if data.get_field_type() != rics::RICS_Data_RICS_DataType::CAN || data.get_rtr() || data.get_error() {
+            return None;
+        }
+        let message = self.message(data.get_id(), data.get_extended())?;
+        let dat = data.get_data();
+        let mux = message.multiplexor().and_then(|x| x.raw(dat));
+        let signals = message.signals.iter().filter(|x| match x.multiplex {
+            Multiplex::Multiplexed(value) => mux == Some(value),
+            _ => true,
+        }).filter_map(|signal| {
+            let raw = signal.raw(dat)?;
+            Some(SignalValue {
+                name: signal.name.clone(),
+                raw: raw,
+                physical: signal.physical(raw),
+                unit: signal.unit.clone(),
+                label: signal.label(raw).map(|x| x.to_string()),
+            })
+        }).collect();
+        Some(DecodedMessage { name: message.name.clone(), signals: signals })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DBC: &str = r#"
+VERSION ""
+
+BO_ 256 Engine: 8 ECU
+ SG_ Speed : 0|16@1+ (0.1,0) [0|6553.5] "km/h" Dash
+ SG_ Temp : 16|8@1- (1,-10) [-50|100] "C" Dash
+ SG_ Gear : 31|4@0+ (1,0) [0|15] "" Dash
+ SG_ Torque : 39|12@0- (0.5,0) [0|0] "Nm" Dash
+
+BO_ 2147484160 Mux: 8 ECU
+ SG_ Page M : 0|8@1+ (1,0) [0|255] "" Dash
+ SG_ A m1 : 8|16@1+ (1,0) [0|0] "" Dash
+ SG_ B m2 : 8|8@1+ (1,0) [0|0] "" Dash
+
+VAL_ 256 Gear 0 "Neutral" 1 "First" ;
+BA_DEF_ BO_ "GenMsgCycleTime" INT 0 10000;
+BA_DEF_DEF_ "GenMsgCycleTime" 100;
+BA_ "GenMsgCycleTime" BO_ 256 20;
+"#;
+
+    fn value(decoded: &DecodedMessage, name: &str) -> f64 {
+        decoded.signals.iter().find(|x| x.name == name).unwrap().physical
+    }
+
+    #[test]
+    fn intel_layout() {
+        let signal = Signal::new("S", 4, 12, true);
+        let mut dat = vec![0; 3];
+        signal.set_raw(&mut dat, 0xABC);
+        assert_eq!(dat, vec![0xC0, 0xAB, 0x00]);
+        assert_eq!(signal.raw(&dat), Some(0xABC));
+    }
+
+    #[test]
+    fn motorola_layout() {
+        let signal = Signal::new("S", 7, 12, false);
+        let mut dat = vec![0; 3];
+        signal.set_raw(&mut dat, 0xABC);
+        assert_eq!(dat, vec![0xAB, 0xC0, 0x00]);
+        assert_eq!(signal.raw(&dat), Some(0xABC));
+        assert_eq!(signal.raw(&dat[..1]), None);
+    }
+
+    #[test]
+    fn signed_values() {
+        let signal = Signal { value_type: ValueType::Signed, ..Signal::new("S", 0, 8, true) };
+        assert_eq!(signal.to_raw(-1.0), Ok(0xFF));
+        assert_eq!(signal.raw_value(0x80), -128.0);
+        assert!(signal.to_raw(128.0).is_err());
+        assert!(signal.to_raw(-129.0).is_err());
+    }
+
+    #[test]
+    fn encode_decode() {
+        let dbc = Dbc::parse(DBC).unwrap();
+        let message = dbc.message(256, false).unwrap();
+        assert_eq!(message.cycle_time(), Some(Duration::from_millis(20)));
+        assert_eq!(message.signal("Gear").unwrap().values.get(&1).map(|x| x.as_str()), Some("First"));
+
+        let data = dbc.encode("Engine", &[("Speed", 123.4), ("Temp", -20.0), ("Gear", 1.0), ("Torque", -100.5)]).unwrap();
+        assert_eq!(data.get_id(), 256);
+        assert_eq!(&data.get_data()[..3], &[0xD2, 0x04, 0xF6]);
+        let decoded = dbc.decode(&data).unwrap();
+        assert_eq!(value(&decoded, "Speed"), 123.4);
+        assert_eq!(value(&decoded, "Temp"), -20.0);
+        assert_eq!(value(&decoded, "Torque"), -100.5);
+        assert_eq!(decoded.signals.iter().find(|x| x.name == "Gear").unwrap().label.as_deref(), Some("First"));
+        assert!(dbc.encode("Engine", &[("Temp", 200.0)]).is_err());
+    }
+
+    #[test]
+    fn multiplexing() {
+        let dbc = Dbc::parse(DBC).unwrap();
+        let data = dbc.encode("Mux", &[("B", 7.0)]).unwrap();
+        assert!(data.get_extended());
+        assert_eq!(&data.get_data()[..2], &[2, 7]);
+        let decoded = dbc.decode(&data).unwrap();
+        assert_eq!(decoded.signals.iter().map(|x| x.name.as_str()).collect::<Vec<&str>>(), vec!["Page", "B"]);
+        assert!(dbc.encode("Mux", &[("A", 1.0), ("B", 1.0)]).is_err());
+        assert!(dbc.encode("Mux", &[("Page", 1.0), ("B", 1.0)]).is_err());
+    }
+
+    #[test]
+    fn message_size() {
+        assert!(Dbc::parse("BO_ 1 Fd: 64 ECU").is_ok());
+        assert_eq!(Dbc::parse("BO_ 1 Foo: 100 ECU").unwrap_err(), "Line 1: invalid BO_ 1 Foo: 100 ECU");
+    }
+
+    #[test]
+    fn malformed_signals() {
+        assert!(parse_signal(r#"SG_ S : 0|8@1+ (1,0) [0|1] "" Dash"#).is_some());
+        assert!(parse_signal(r#"SG_ S : 0|8@1+ ) (1,0 [0|1] "" Dash"#).is_none());
+        assert!(parse_signal(r#"SG_ S : 0|8@1+ (1,0) ] 0|1 [ "" Dash"#).is_none());
+    }
+}
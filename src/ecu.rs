@@ -0,0 +1,589 @@
+//! Simulated UDS ECU
+//!
+//! Answers diagnostic requests from a declarative TOML description of its
+//! sessions, security levels, data identifiers and DTCs:
+//!
+//! ```toml
+//! tx_id = 0x7E8
+//! rx_id = 0x7E0
+//! sessions = [1, 3]
+//!
+//! [[security]]
+//! level = 1
+//! seed = "12 34"
+//! key = "ED CB"
+//!
+//! [[did]]
+//! id = 0xF190
+//! ascii = "WVWZZZ1JZXW000001"
+//! writable = true
+//! sessions = [3]
+//! security = 1
+//!
+//! [[dtc]]
+//! code = 0x012345
+//! status = 0x09
+//! ```
+//!
+//! A request hook can answer requests before the configuration does.
+
+use std::time::{Duration, Instant};
+
+use super::isotp::{IsoTp, IsoTpOptions};
+use super::server;
+use super::uds::*;
+
+pub const SID_CLEAR_DIAGNOSTIC_INFORMATION: u8 = 0x14;
+pub const SID_READ_DTC_INFORMATION: u8 = 0x19;
+
+/// ReadDTCInformation sub-functions
+const REPORT_NUMBER_OF_DTC_BY_STATUS_MASK: u8 = 0x01;
+const REPORT_DTC_BY_STATUS_MASK: u8 = 0x02;
+const REPORT_SUPPORTED_DTC: u8 = 0x0A;
+/// DTC format identifier of ISO 14229-1 DTCs
+const DTC_FORMAT_ISO14229_1: u8 = 0x01;
+
+const DEFAULT_SESSION: u8 = 0x01;
+/// Time without requests after which a non default session ends
+const S3_SERVER: Duration = Duration::from_millis(5000);
+/// Services with a sub-function, which can suppress their positive response
+const SUB_FUNCTION_SERVICES: [u8; 5] = [SID_DIAGNOSTIC_SESSION_CONTROL, SID_ECU_RESET, SID_SECURITY_ACCESS,
+                                        SID_TESTER_PRESENT, SID_READ_DTC_INFORMATION];
+const SUPPRESS_POSITIVE_RESPONSE: u8 = 0x80;
+
+/// Security level unlocked with a seed and key exchange
+#[derive(Clone, Debug)]
+pub struct SecurityLevel {
+    /// Odd request seed sub-function
+    pub level: u8,
+    /// Fixed seed, random if None
+    pub seed: Option<Vec<u8>>,
+    /// Expected key, computed by the key function if None
+    pub key: Option<Vec<u8>>,
+}
+
+/// Data identifier
+#[derive(Clone, Debug)]
+pub struct Did {
+    pub id: u16,
+    pub data: Vec<u8>,
+    pub writable: bool,
+    /// Sessions in which the identifier is available, all if empty
+    pub sessions: Vec<u8>,
+    /// Security level needed to write the identifier
+    pub security: Option<u8>,
+}
+
+/// Diagnostic trouble code
+#[derive(Clone, Debug)]
+pub struct Dtc {
+    /// 24 bit DTC number
+    pub code: u32,
+    pub status: u8,
+}
+
+/// ECU description
+#[derive(Clone, Debug)]
+pub struct EcuConfig {
+    /// Node name
+    pub name: Option<String>,
+    pub isotp: IsoTpOptions,
+    /// Supported diagnostic sessions
+    pub sessions: Vec<u8>,
+    /// P2 and P2* server timings reported on session changes
+    pub p2: Duration,
+    pub p2_star: Duration,
+    pub security: Vec<SecurityLevel>,
+    pub dids: Vec<Did>,
+    pub dtcs: Vec<Dtc>,
+    /// DTC status bits supported by the ECU
+    pub dtc_status_mask: u8,
+}
+
+/// Bytes given as a hex string or an array of integers
+pub(crate) fn toml_bytes(value: &toml::Value) -> Result<Vec<u8>, String> {
+    match value {
+        toml::Value::String(s) => server::parse_hex(s).ok_or_else(|| format!("Invalid hex data {}", s)),
+        toml::Value::Array(a) => a.iter().map(|x| x.as_integer().map(|x| x as u8).ok_or_else(|| format!("Invalid byte {}", x))).collect(),
+        _ => Err(format!("Invalid data {}", value)),
+    }
+}
+
+pub(crate) fn toml_int(table: &toml::Value, key: &str) -> Result<Option<i64>, String> {
+    match table.get(key) {
+        Some(v) => v.as_integer().map(Some).ok_or_else(|| format!("{} must be an integer", key)),
+        None => Ok(None),
+    }
+}
+
+fn toml_required_int(table: &toml::Value, key: &str) -> Result<i64, String> {
+    toml_int(table, key)?.ok_or_else(|| format!("Missing {}", key))
+}
+
+fn toml_list(table: &toml::Value, key: &str) -> Result<Vec<u8>, String> {
+    match table.get(key) {
+        Some(v) => toml_bytes(v),
+        None => Ok(vec![]),
+    }
+}
+
+pub(crate) fn toml_tables<'a>(table: &'a toml::Value, key: &str) -> Vec<&'a toml::Value> {
+    table.get(key).and_then(|x| x.as_array()).map(|x| x.iter().collect()).unwrap_or_default()
+}
+
+impl EcuConfig {
+    /// Parse a TOML ECU description
+    pub fn parse(content: &str) -> Result<EcuConfig, String> {
+        let root: toml::Value = content.parse().map_err(|e| format!("{}", e))?;
+
+        let mut isotp = IsoTpOptions::new(toml_required_int(&root, "tx_id")? as i32, toml_required_int(&root, "rx_id")? as i32);
+        isotp.extended = root.get("extended").and_then(|x| x.as_bool()).unwrap_or(false);
+        if root.get("fd").and_then(|x| x.as_bool()).unwrap_or(false) {
+            let tx_dl = toml_int(&root, "tx_dl")?.unwrap_or(server::CANFD_MAX_DLEN as i64);
+            if tx_dl < server::CAN_MAX_DLEN as i64 || tx_dl > server::CANFD_MAX_DLEN as i64 || server::can_fd_len(tx_dl as usize) != tx_dl as usize {
+                return Err(format!("Invalid CAN FD data length {}", tx_dl));
+            }
+            isotp = isotp.with_fd(tx_dl as usize, root.get("brs").and_then(|x| x.as_bool()).unwrap_or(false));
+        }
+        isotp.padding = toml_int(&root, "padding")?.map(|x| x as u8);
+
+        let mut sessions = toml_list(&root, "sessions")?;
+        if sessions.is_empty() {
+            sessions = vec![DEFAULT_SESSION, 0x02, 0x03];
+        }
+
+        let mut security = vec![];
+        for table in toml_tables(&root, "security") {
+            let level = toml_required_int(table, "level")?;
+            if level % 2 == 0 || level < 0x01 || level > 0x7D {
+                return Err(format!("Security level {} must be odd, from 0x01 to 0x7D", level));
+            }
+            security.push(SecurityLevel {
+                level: level as u8,
+                seed: table.get("seed").map(toml_bytes).transpose()?,
+                key: table.get("key").map(toml_bytes).transpose()?,
+            });
+        }
+
+        let mut dids = vec![];
+        for table in toml_tables(&root, "did") {
+            let data = match (table.get("data"), table.get("ascii").and_then(|x| x.as_str())) {
+                (Some(data), _) => toml_bytes(data)?,
+                (None, Some(ascii)) => ascii.as_bytes().to_vec(),
+                (None, None) => vec![],
+            };
+            dids.push(Did {
+                id: toml_required_int(table, "id")? as u16,
+                data: data,
+                writable: table.get("writable").and_then(|x| x.as_bool()).unwrap_or(false),
+                sessions: toml_list(table, "sessions")?,
+                security: toml_int(table, "security")?.map(|x| x as u8),
+            });
+        }
+
+        let mut dtcs = vec![];
+        for table in toml_tables(&root, "dtc") {
+            dtcs.push(Dtc {
+                code: toml_required_int(table, "code")? as u32 & 0xFFFFFF,
+                status: toml_int(table, "status")?.unwrap_or(0x09) as u8,
+            });
+        }
+
+        Ok(EcuConfig {
+            name: root.get("name").and_then(|x| x.as_str()).map(|x| x.to_string()),
+            isotp: isotp,
+            sessions: sessions,
+            p2: Duration::from_millis(toml_int(&root, "p2")?.unwrap_or(50) as u64),
+            p2_star: Duration::from_millis(toml_int(&root, "p2_star")?.unwrap_or(5000) as u64),
+            security: security,
+            dids: dids,
+            dtcs: dtcs,
+            dtc_status_mask: toml_int(&root, "dtc_status_mask")?.unwrap_or(0xFF) as u8,
+        })
+    }
+
+    /// Load a TOML ECU description file
+    pub fn load(path: &str) -> Result<EcuConfig, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+        EcuConfig::parse(&content)
+    }
+}
+
+/// Answers a request with the active session, or returns None to let the configuration answer.
+/// An empty response sends nothing.
+pub type RequestHook = Box<dyn FnMut(&[u8], u8) -> Option<Vec<u8>>>;
+/// Computes the key of a seed for a security level
+pub type KeyFunction = Box<dyn FnMut(u8, &[u8]) -> Option<Vec<u8>>>;
+
+fn negative(sid: u8, nrc: u8) -> Vec<u8> {
+    vec![NEGATIVE_RESPONSE, sid, nrc]
+}
+
+/// Simulated ECU state
+pub struct Ecu {
+    pub config: EcuConfig,
+    session: u8,
+    unlocked: Option<u8>,
+    /// Level and seed of the last seed sent
+    pending_seed: Option<(u8, Vec<u8>)>,
+    last_request: Instant,
+    hook: Option<RequestHook>,
+    key: Option<KeyFunction>,
+}
+
+impl Ecu {
+    pub fn new(config: EcuConfig) -> Ecu {
+        Ecu {
+            config: config,
+            session: DEFAULT_SESSION,
+            unlocked: None,
+            pending_seed: None,
+            last_request: Instant::now(),
+            hook: None,
+            key: None,
+        }
+    }
+
+    pub fn set_request_hook(&mut self, hook: RequestHook) {
+        self.hook = Some(hook);
+    }
+
+    /// Key function used by security levels without a fixed key
+    pub fn set_key_function(&mut self, key: KeyFunction) {
+        self.key = Some(key);
+    }
+
+    /// Answer requests forever
+    pub fn serve(&mut self, isotp: &mut IsoTp) {
+        loop {
+            let req = match isotp.recv(None) {
+                Ok(req) => req,
+                Err(e) => { warn!("UDS request reception failed: {}", e); continue; },
+            };
+            debug!("UDS request {:02X?}", req);
+            if let Some(resp) = self.handle(&req) {
+                debug!("UDS response {:02X?}", resp);
+                if let Err(e) = isotp.send(&resp) {
+                    warn!("UDS response failed: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Response to a request, None if no response must be sent
+    pub fn handle(&mut self, req: &[u8]) -> Option<Vec<u8>> {
+        if self.session != DEFAULT_SESSION && self.last_request.elapsed() > S3_SERVER {
+            info!("Session 0x{:02X} timed out", self.session);
+            self.session = DEFAULT_SESSION;
+            self.unlocked = None;
+        }
+        self.last_request = Instant::now();
+
+        let sid = *req.get(0)?;
+        if let Some(hook) = self.hook.as_mut() {
+            if let Some(resp) = hook(req, self.session) {
+                return if resp.is_empty() { None } else { Some(resp) };
+            }
+        }
+
+        let mut req = req.to_vec();
+        let suppress = SUB_FUNCTION_SERVICES.contains(&sid) && req.len() > 1 && req[1] & SUPPRESS_POSITIVE_RESPONSE != 0;
+        if suppress {
+            req[1] &= !SUPPRESS_POSITIVE_RESPONSE;
+        }
+        let resp = match sid {
+            SID_DIAGNOSTIC_SESSION_CONTROL => self.session_control(&req),
+            SID_TESTER_PRESENT => if req.len() == 2 && req[1] == 0 { vec![sid + POSITIVE_RESPONSE_OFFSET, 0] } else { negative(sid, NRC_SUB_FUNCTION_NOT_SUPPORTED) },
+            SID_SECURITY_ACCESS => self.security_access(&req),
+            SID_READ_DATA_BY_IDENTIFIER => self.read_data_by_identifier(&req),
+            SID_WRITE_DATA_BY_IDENTIFIER => self.write_data_by_identifier(&req),
+            SID_READ_DTC_INFORMATION => self.read_dtc_information(&req),
+            SID_CLEAR_DIAGNOSTIC_INFORMATION => self.clear_diagnostic_information(&req),
+            _ => negative(sid, NRC_SERVICE_NOT_SUPPORTED),
+        };
+        if suppress && resp[0] != NEGATIVE_RESPONSE {
+            None
+        } else {
+            Some(resp)
+        }
+    }
+
+    fn session_control(&mut self, req: &[u8]) -> Vec<u8> {
+        if req.len() != 2 {
+            return negative(req[0], NRC_INCORRECT_MESSAGE_LENGTH);
+        }
+        if !self.config.sessions.contains(&req[1]) {
+            return negative(req[0], NRC_SUB_FUNCTION_NOT_SUPPORTED);
+        }
+        info!("Entering session 0x{:02X}", req[1]);
+        self.session = req[1];
+        self.unlocked = None;
+        self.pending_seed = None;
+        let mut resp = vec![req[0] + POSITIVE_RESPONSE_OFFSET, req[1]];
+        resp.extend_from_slice(&(self.config.p2.as_millis() as u16).to_be_bytes());
+        resp.extend_from_slice(&((self.config.p2_star.as_millis() / 10) as u16).to_be_bytes());
+        resp
+    }
+
+    fn security_access(&mut self, req: &[u8]) -> Vec<u8> {
+        if req.len() < 2 {
+            return negative(req[0], NRC_INCORRECT_MESSAGE_LENGTH);
+        }
+        if req[1] == 0 {
+            return negative(req[0], NRC_SUB_FUNCTION_NOT_SUPPORTED);
+        }
+        let level = if req[1] % 2 == 1 { req[1] } else { req[1] - 1 };
+        let security = match self.config.security.iter().find(|x| x.level == level) {
+            Some(security) => security.clone(),
+            None => return negative(req[0], NRC_SUB_FUNCTION_NOT_SUPPORTED),
+        };
+
+        if req[1] == level {
+            // Request seed, a null seed if already unlocked
+            let seed = if self.unlocked == Some(level) {
+                vec![0; security.seed.as_ref().map(|x| x.len()).unwrap_or(4)]
+            } else {
+                let seed = security.seed.clone().unwrap_or_else(|| (0..4).map(|_| rand::random::<u8>()).collect());
+                self.pending_seed = Some((level, seed.clone()));
+                seed
+            };
+            let mut resp = vec![req[0] + POSITIVE_RESPONSE_OFFSET, req[1]];
+            resp.extend_from_slice(&seed);
+            return resp;
+        }
+
+        // Send key
+        let seed = match self.pending_seed.take() {
+            Some((l, seed)) if l == level => seed,
+            _ => return negative(req[0], NRC_REQUEST_SEQUENCE_ERROR),
+        };
+        let expected = match (security.key, self.key.as_mut()) {
+            (Some(key), _) => Some(key),
+            (None, Some(key)) => key(level, &seed),
+            (None, None) => None,
+        };
+        if expected.as_ref().map(|x| &x[..]) != Some(&req[2..]) {
+            warn!("Invalid key {:02X?} for security level 0x{:02X}", &req[2..], level);
+            return negative(req[0], NRC_INVALID_KEY);
+        }
+        info!("Security level 0x{:02X} unlocked", level);
+        self.unlocked = Some(level);
+        vec![req[0] + POSITIVE_RESPONSE_OFFSET, req[1]]
+    }
+
+    /// Identifier available in the active session
+    fn did(&mut self, id: u16) -> Option<&mut Did> {
+        let session = self.session;
+        self.config.dids.iter_mut().find(|x| x.id == id && (x.sessions.is_empty() || x.sessions.contains(&session)))
+    }
+
+    fn read_data_by_identifier(&mut self, req: &[u8]) -> Vec<u8> {
+        if req.len() < 3 || req.len() % 2 == 0 {
+            return negative(req[0], NRC_INCORRECT_MESSAGE_LENGTH);
+        }
+        let mut resp = vec![req[0] + POSITIVE_RESPONSE_OFFSET];
+        for id in req[1..].chunks(2) {
+            let id = u16::from_be_bytes([id[0], id[1]]);
+            match self.did(id) {
+                Some(did) => {
+                    resp.extend_from_slice(&id.to_be_bytes());
+                    resp.extend_from_slice(&did.data);
+                },
+                None => return negative(req[0], NRC_REQUEST_OUT_OF_RANGE),
+            }
+        }
+        resp
+    }
+
+    fn write_data_by_identifier(&mut self, req: &[u8]) -> Vec<u8> {
+        if req.len() < 4 {
+            return negative(req[0], NRC_INCORRECT_MESSAGE_LENGTH);
+        }
+        let unlocked = self.unlocked;
+        let did = match self.did(u16::from_be_bytes([req[1], req[2]])) {
+            Some(did) if did.writable => did,
+            _ => return negative(req[0], NRC_REQUEST_OUT_OF_RANGE),
+        };
+        if did.security.is_some() && did.security != unlocked {
+            return negative(req[0], NRC_SECURITY_ACCESS_DENIED);
+        }
+        did.data = req[3..].to_vec();
+        info!("DID 0x{:04X} written", did.id);
+        vec![req[0] + POSITIVE_RESPONSE_OFFSET, req[1], req[2]]
+    }
+
+    fn read_dtc_information(&mut self, req: &[u8]) -> Vec<u8> {
+        if req.len() < 2 {
+            return negative(req[0], NRC_INCORRECT_MESSAGE_LENGTH);
+        }
+        let available = self.config.dtc_status_mask;
+        let mask = match req[1] {
+            REPORT_NUMBER_OF_DTC_BY_STATUS_MASK | REPORT_DTC_BY_STATUS_MASK if req.len() == 3 => req[2],
+            REPORT_SUPPORTED_DTC if req.len() == 2 => 0xFF,
+            REPORT_NUMBER_OF_DTC_BY_STATUS_MASK | REPORT_DTC_BY_STATUS_MASK | REPORT_SUPPORTED_DTC => {
+                return negative(req[0], NRC_INCORRECT_MESSAGE_LENGTH);
+            },
+            _ => return negative(req[0], NRC_SUB_FUNCTION_NOT_SUPPORTED),
+        };
+        let dtcs: Vec<&Dtc> = self.config.dtcs.iter()
+            .filter(|x| req[1] == REPORT_SUPPORTED_DTC || x.status & available & mask != 0).collect();
+
+        let mut resp = vec![req[0] + POSITIVE_RESPONSE_OFFSET, req[1], available];
+        if req[1] == REPORT_NUMBER_OF_DTC_BY_STATUS_MASK {
+            resp.push(DTC_FORMAT_ISO14229_1);
+            resp.extend_from_slice(&(dtcs.len() as u16).to_be_bytes());
+        } else {
+            for dtc in dtcs {
+                resp.extend_from_slice(&dtc.code.to_be_bytes()[1..]);
+                resp.push(dtc.status & available);
+            }
+        }
+        resp
+    }
+
+    fn clear_diagnostic_information(&mut self, req: &[u8]) -> Vec<u8> {
+        if req.len() != 4 {
+            return negative(req[0], NRC_INCORRECT_MESSAGE_LENGTH);
+        }
+        let group = u32::from_be_bytes([0, req[1], req[2], req[3]]);
+        if group == 0xFFFFFF {
+            self.config.dtcs.clear();
+        } else {
+            let before = self.config.dtcs.len();
+            self.config.dtcs.retain(|x| x.code != group);
+            if self.config.dtcs.len() == before {
+                return negative(req[0], NRC_REQUEST_OUT_OF_RANGE);
+            }
+        }
+        info!("DTCs 0x{:06X} cleared", group);
+        vec![req[0] + POSITIVE_RESPONSE_OFFSET]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        tx_id = 0x7E8
+        rx_id = 0x7E0
+        sessions = [1, 3]
+        p2 = 50
+        p2_star = 2000
+
+        [[security]]
+        level = 1
+        seed = "12 34"
+        key = "ED CB"
+
+        [[did]]
+        id = 0xF190
+        ascii = "VIN"
+        writable = true
+        sessions = [3]
+        security = 1
+    "#;
+
+    fn ecu() -> Ecu {
+        Ecu::new(EcuConfig::parse(CONFIG).unwrap())
+    }
+
+    #[test]
+    fn config() {
+        let config = EcuConfig::parse(CONFIG).unwrap();
+        assert_eq!((config.isotp.tx_id, config.isotp.rx_id, config.isotp.fd), (0x7E8, 0x7E0, false));
+        assert_eq!(config.sessions, vec![1, 3]);
+        assert_eq!(config.security[0].key, Some(vec![0xED, 0xCB]));
+        assert_eq!(config.dids[0].data, b"VIN".to_vec());
+        assert_eq!(config.dids[0].security, Some(1));
+
+        let fd = |tx_dl: &str| EcuConfig::parse(&format!("tx_id = 1\nrx_id = 2\nfd = true\n{}", tx_dl));
+        assert_eq!(fd("").unwrap().isotp.tx_dl, 64);
+        assert_eq!(fd("tx_dl = 12").unwrap().isotp.tx_dl, 12);
+        for tx_dl in ["tx_dl = 13", "tx_dl = 4", "tx_dl = 72", "tx_dl = -1"].iter() {
+            assert!(fd(tx_dl).is_err(), "{} accepted", tx_dl);
+        }
+
+        let level = |level: i64| EcuConfig::parse(&format!("tx_id = 1\nrx_id = 2\n[[security]]\nlevel = {}\n", level));
+        assert!(level(0x7D).is_ok());
+        for invalid in [0, 2, 0x7F, 0x101].iter() {
+            assert!(level(*invalid).is_err(), "level {} accepted", invalid);
+        }
+        assert!(EcuConfig::parse("tx_id = 1").is_err());
+    }
+
+    #[test]
+    fn sessions() {
+        let mut ecu = ecu();
+        assert_eq!(ecu.handle(&[0x10, 0x03]), Some(vec![0x50, 0x03, 0x00, 0x32, 0x00, 0xC8]));
+        assert_eq!(ecu.handle(&[0x10, 0x02]), Some(negative(0x10, NRC_SUB_FUNCTION_NOT_SUPPORTED)));
+        assert_eq!(ecu.handle(&[0x10]), Some(negative(0x10, NRC_INCORRECT_MESSAGE_LENGTH)));
+        // The identifier is only available in the extended session
+        assert_eq!(ecu.handle(&[0x22, 0xF1, 0x90]), Some(vec![0x62, 0xF1, 0x90, b'V', b'I', b'N']));
+        ecu.handle(&[0x10, 0x01]);
+        assert_eq!(ecu.handle(&[0x22, 0xF1, 0x90]), Some(negative(0x22, NRC_REQUEST_OUT_OF_RANGE)));
+        assert_eq!(ecu.handle(&[0x85, 0x01]), Some(negative(0x85, NRC_SERVICE_NOT_SUPPORTED)));
+    }
+
+    #[test]
+    fn security() {
+        let mut ecu = ecu();
+        ecu.handle(&[0x10, 0x03]);
+        assert_eq!(ecu.handle(&[0x2E, 0xF1, 0x90, 0x41]), Some(negative(0x2E, NRC_SECURITY_ACCESS_DENIED)));
+        assert_eq!(ecu.handle(&[0x27, 0x02, 0xED, 0xCB]), Some(negative(0x27, NRC_REQUEST_SEQUENCE_ERROR)));
+        assert_eq!(ecu.handle(&[0x27, 0x03]), Some(negative(0x27, NRC_SUB_FUNCTION_NOT_SUPPORTED)));
+
+        assert_eq!(ecu.handle(&[0x27, 0x01]), Some(vec![0x67, 0x01, 0x12, 0x34]));
+        assert_eq!(ecu.handle(&[0x27, 0x02, 0x00, 0x00]), Some(negative(0x27, NRC_INVALID_KEY)));
+        // A failed key needs a new seed
+        assert_eq!(ecu.handle(&[0x27, 0x02, 0xED, 0xCB]), Some(negative(0x27, NRC_REQUEST_SEQUENCE_ERROR)));
+        ecu.handle(&[0x27, 0x01]);
+        assert_eq!(ecu.handle(&[0x27, 0x02, 0xED, 0xCB]), Some(vec![0x67, 0x02]));
+
+        // Unlocked levels send a null seed
+        assert_eq!(ecu.handle(&[0x27, 0x01]), Some(vec![0x67, 0x01, 0x00, 0x00]));
+        assert_eq!(ecu.handle(&[0x2E, 0xF1, 0x90, 0x41]), Some(vec![0x6E, 0xF1, 0x90]));
+        assert_eq!(ecu.handle(&[0x22, 0xF1, 0x90]), Some(vec![0x62, 0xF1, 0x90, 0x41]));
+
+        // Session changes lock the ECU again
+        ecu.handle(&[0x10, 0x03]);
+        assert_eq!(ecu.handle(&[0x2E, 0xF1, 0x90, 0x42]), Some(negative(0x2E, NRC_SECURITY_ACCESS_DENIED)));
+    }
+
+    #[test]
+    fn key_function() {
+        let mut config = EcuConfig::parse(CONFIG).unwrap();
+        config.security[0].key = None;
+        let mut ecu = Ecu::new(config);
+        ecu.set_key_function(Box::new(|level, seed| Some(seed.iter().map(|x| x ^ level).collect())));
+        ecu.handle(&[0x27, 0x01]);
+        assert_eq!(ecu.handle(&[0x27, 0x02, 0x13, 0x35]), Some(vec![0x67, 0x02]));
+    }
+
+    #[test]
+    fn suppress_positive_response() {
+        let mut ecu = ecu();
+        assert_eq!(ecu.handle(&[0x3E, 0x80]), None);
+        assert_eq!(ecu.handle(&[0x3E, 0x00]), Some(vec![0x7E, 0x00]));
+        assert_eq!(ecu.handle(&[0x10, 0x83]), None);
+        assert_eq!(ecu.handle(&[0x22, 0xF1, 0x90]), Some(vec![0x62, 0xF1, 0x90, b'V', b'I', b'N']));
+        // Negative responses are still sent
+        assert_eq!(ecu.handle(&[0x10, 0x82]), Some(negative(0x10, NRC_SUB_FUNCTION_NOT_SUPPORTED)));
+        assert_eq!(ecu.handle(&[0x3E, 0x81]), Some(negative(0x3E, NRC_SUB_FUNCTION_NOT_SUPPORTED)));
+        // Services without sub-function don't have the bit
+        assert_eq!(ecu.handle(&[0x22, 0x80, 0x00]), Some(negative(0x22, NRC_REQUEST_OUT_OF_RANGE)));
+    }
+
+    #[test]
+    fn request_hook() {
+        let mut ecu = ecu();
+        ecu.set_request_hook(Box::new(|req, session| match req[0] {
+            0x31 => Some(vec![0x71, session]),
+            0x11 => Some(vec![]),
+            _ => None,
+        }));
+        assert_eq!(ecu.handle(&[0x31, 0x01]), Some(vec![0x71, 0x01]));
+        assert_eq!(ecu.handle(&[0x11, 0x01]), None);
+        assert_eq!(ecu.handle(&[0x3E, 0x00]), Some(vec![0x7E, 0x00]));
+    }
+}
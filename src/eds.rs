@@ -0,0 +1,400 @@
+//! CANopen object dictionary from EDS and DCF files
+//!
+//! Electronic data sheets are INI files describing each object of a device.
+//! A section is named after the hex index of the object, `[1018]`, and its
+//! sub-objects follow in `[1018sub0]`, `[1018sub1]`... Device configuration
+//! files use the same layout with a `ParameterValue` overriding the default.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::server;
+
+pub const TYPE_BOOLEAN: u16 = 0x01;
+pub const TYPE_INTEGER8: u16 = 0x02;
+pub const TYPE_INTEGER16: u16 = 0x03;
+pub const TYPE_INTEGER32: u16 = 0x04;
+pub const TYPE_UNSIGNED8: u16 = 0x05;
+pub const TYPE_UNSIGNED16: u16 = 0x06;
+pub const TYPE_UNSIGNED32: u16 = 0x07;
+pub const TYPE_REAL32: u16 = 0x08;
+pub const TYPE_VISIBLE_STRING: u16 = 0x09;
+pub const TYPE_OCTET_STRING: u16 = 0x0A;
+pub const TYPE_DOMAIN: u16 = 0x0F;
+pub const TYPE_INTEGER24: u16 = 0x10;
+pub const TYPE_REAL64: u16 = 0x11;
+pub const TYPE_INTEGER64: u16 = 0x15;
+pub const TYPE_UNSIGNED24: u16 = 0x16;
+pub const TYPE_UNSIGNED64: u16 = 0x1B;
+
+/// PDO communication and mapping parameter ranges
+pub const RPDO_COMMUNICATION: u16 = 0x1400;
+pub const RPDO_MAPPING: u16 = 0x1600;
+pub const TPDO_COMMUNICATION: u16 = 0x1800;
+pub const TPDO_MAPPING: u16 = 0x1A00;
+
+/// Largest PDO data length, in bytes
+pub const PDO_MAX_LEN: usize = 8;
+
+/// Size in bytes of the fixed size data types
+pub fn type_size(data_type: u16) -> Option<usize> {
+    Some(match data_type {
+        TYPE_BOOLEAN | TYPE_INTEGER8 | TYPE_UNSIGNED8 => 1,
+        TYPE_INTEGER16 | TYPE_UNSIGNED16 => 2,
+        TYPE_INTEGER24 | TYPE_UNSIGNED24 => 3,
+        TYPE_INTEGER32 | TYPE_UNSIGNED32 | TYPE_REAL32 => 4,
+        TYPE_INTEGER64 | TYPE_UNSIGNED64 | TYPE_REAL64 => 8,
+        _ => return None,
+    })
+}
+
+fn is_signed(data_type: u16) -> bool {
+    match data_type {
+        TYPE_INTEGER8 | TYPE_INTEGER16 | TYPE_INTEGER24 | TYPE_INTEGER32 | TYPE_INTEGER64 => true,
+        _ => false,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+    Const,
+}
+
+impl Access {
+    fn parse(s: &str) -> Option<Access> {
+        Some(match s.to_lowercase().as_str() {
+            "ro" => Access::ReadOnly,
+            "wo" => Access::WriteOnly,
+            "rw" | "rwr" | "rww" => Access::ReadWrite,
+            "const" => Access::Const,
+            _ => return None,
+        })
+    }
+
+    pub fn readable(&self) -> bool {
+        *self != Access::WriteOnly
+    }
+
+    pub fn writable(&self) -> bool {
+        *self == Access::WriteOnly || *self == Access::ReadWrite
+    }
+}
+
+/// Object dictionary entry, a variable or the sub-object of a record or array
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub index: u16,
+    pub sub: u8,
+    pub name: String,
+    pub data_type: u16,
+    pub access: Access,
+    /// The entry can be mapped into a PDO
+    pub pdo_mapping: bool,
+    /// Little endian value
+    pub value: Vec<u8>,
+}
+
+impl Entry {
+    /// Value as an unsigned integer, for entries of at most 8 bytes
+    pub fn to_u64(&self) -> u64 {
+        self.value.iter().take(8).rev().fold(0, |acc, x| acc << 8 | *x as u64)
+    }
+
+    /// Value formatted according to the data type
+    pub fn format_value(&self) -> String {
+        format_value(self.data_type, &self.value)
+    }
+}
+
+/// Format a little endian value according to its data type
+pub fn format_value(data_type: u16, value: &[u8]) -> String {
+    let unsigned = value.iter().take(8).rev().fold(0u64, |acc, x| acc << 8 | *x as u64);
+    match data_type {
+        TYPE_VISIBLE_STRING => String::from_utf8_lossy(value).into_owned(),
+        TYPE_REAL32 if value.len() == 4 => format!("{}", f32::from_bits(unsigned as u32)),
+        TYPE_REAL64 if value.len() == 8 => format!("{}", f64::from_bits(unsigned)),
+        t if is_signed(t) && !value.is_empty() && value.len() <= 8 => {
+            let shift = 64 - value.len() * 8;
+            format!("{}", ((unsigned << shift) as i64) >> shift)
+        },
+        t if type_size(t).is_some() && value.len() <= 8 => format!("{}", unsigned),
+        _ => value.iter().map(|x| format!("{:02X}", x)).collect::<Vec<String>>().join(" "),
+    }
+}
+
+/// Parse a number in decimal, `0x` hexadecimal or `0` octal notation
+fn parse_int(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (negative, s) = if s.starts_with('-') { (true, &s[1..]) } else { (false, s) };
+    let value = if s.starts_with("0x") || s.starts_with("0X") {
+        u64::from_str_radix(&s[2..], 16).ok()? as i64
+    } else if s.len() > 1 && s.starts_with('0') {
+        i64::from_str_radix(&s[1..], 8).ok()?
+    } else {
+        s.parse::<i64>().ok()?
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// Parse an integer value, `$NODEID` may be added to it as in `$NODEID+0x180`
+fn parse_node_int(s: &str, node_id: u8) -> Option<i64> {
+    s.split('+').map(|x| if x.trim().eq_ignore_ascii_case("$NODEID") { Some(node_id as i64) } else { parse_int(x) })
+        .sum()
+}
+
+/// Convert the text value of an EDS entry to its little endian representation
+pub fn parse_value(data_type: u16, s: &str, node_id: u8) -> Option<Vec<u8>> {
+    let s = s.trim();
+    match data_type {
+        TYPE_VISIBLE_STRING => Some(s.as_bytes().to_vec()),
+        TYPE_OCTET_STRING | TYPE_DOMAIN => server::parse_hex(s.trim_start_matches("0x")),
+        TYPE_REAL32 => Some(s.parse::<f32>().ok()?.to_bits().to_le_bytes().to_vec()),
+        TYPE_REAL64 => Some(s.parse::<f64>().ok()?.to_bits().to_le_bytes().to_vec()),
+        _ => {
+            let size = type_size(data_type)?;
+            let value = if s.is_empty() { 0 } else { parse_node_int(s, node_id)? };
+            Some(value.to_le_bytes()[..size].to_vec())
+        },
+    }
+}
+
+/// Process data object, from its communication and mapping parameters
+#[derive(Clone, Debug)]
+pub struct Pdo {
+    /// PDO number, starting at 1
+    pub number: usize,
+    pub cob_id: u32,
+    pub transmission_type: u8,
+    /// Event timer of TPDOs in ms, 0 if disabled
+    pub event_timer: u16,
+    /// Mapped index, sub-index and length in bits
+    pub mapping: Vec<(u16, u8, u8)>,
+}
+
+impl Pdo {
+    /// Bit 31 of the COB-ID disables the PDO
+    pub fn valid(&self) -> bool {
+        self.cob_id & 0x8000_0000 == 0
+    }
+
+    /// CAN identifier of the PDO
+    pub fn can_id(&self) -> i32 {
+        (self.cob_id & 0x1FFF_FFFF) as i32
+    }
+
+    /// Data length of the PDO, mapped lengths being rounded to whole bytes
+    pub fn data_len(&self) -> usize {
+        self.mapping.iter().map(|(_, _, bits)| (*bits as usize + 7) / 8).sum()
+    }
+}
+
+/// Object dictionary of a CANopen device
+#[derive(Clone, Debug, Default)]
+pub struct ObjectDictionary {
+    pub entries: BTreeMap<(u16, u8), Entry>,
+}
+
+impl ObjectDictionary {
+    /// Parse an EDS or DCF file, `$NODEID` in values is replaced by `node_id`
+    pub fn parse(content: &str, node_id: u8) -> Result<ObjectDictionary, String> {
+        // Sections by upper case name, with lower case keys
+        let mut sections: Vec<(String, HashMap<String, String>)> = vec![];
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                sections.push((line[1..line.len()-1].trim().to_uppercase(), HashMap::new()));
+            } else if let Some(pos) = line.find('=') {
+                match sections.last_mut() {
+                    Some(section) => { section.1.insert(line[..pos].trim().to_lowercase(), line[pos+1..].trim().to_string()); },
+                    None => return Err(format!("Line {}: entry outside of a section", n + 1)),
+                }
+            } else {
+                return Err(format!("Line {}: invalid line {}", n + 1, line));
+            }
+        }
+
+        let mut od = ObjectDictionary::default();
+        for (name, keys) in sections.iter() {
+            let (index, sub) = match name.find("SUB") {
+                Some(pos) if pos == 4 => (&name[..4], Some(&name[7..])),
+                None if name.len() == 4 => (&name[..], None),
+                _ => continue,
+            };
+            let index = match u16::from_str_radix(index, 16) {
+                Ok(index) => index,
+                Err(_) => continue,
+            };
+            let sub = match sub {
+                Some(sub) => u8::from_str_radix(sub, 16).map_err(|_| format!("Invalid section [{}]", name))?,
+                // Records and arrays only describe their sub-objects
+                None if keys.get("subnumber").and_then(|x| parse_int(x)).unwrap_or(0) > 0 => continue,
+                None => 0,
+            };
+            let data_type = keys.get("datatype").and_then(|x| parse_int(x))
+                .ok_or_else(|| format!("Missing DataType in [{}]", name))? as u16;
+            let access = match keys.get("accesstype") {
+                Some(access) => Access::parse(access).ok_or_else(|| format!("Invalid AccessType {} in [{}]", access, name))?,
+                None => Access::ReadWrite,
+            };
+            let text = keys.get("parametervalue").or_else(|| keys.get("defaultvalue")).map(|x| x.as_str()).unwrap_or("");
+            let value = parse_value(data_type, text, node_id)
+                .ok_or_else(|| format!("Invalid value {} in [{}]", text, name))?;
+            od.entries.insert((index, sub), Entry {
+                index: index,
+                sub: sub,
+                name: keys.get("parametername").cloned().unwrap_or_default(),
+                data_type: data_type,
+                access: access,
+                pdo_mapping: keys.get("pdomapping").map(|x| x.trim() == "1").unwrap_or(false),
+                value: value,
+            });
+        }
+        for index in (RPDO_MAPPING..RPDO_MAPPING + 512).chain(TPDO_MAPPING..TPDO_MAPPING + 512) {
+            if od.mapping_len(index) > PDO_MAX_LEN {
+                warn!("PDO mapping {:04X} is longer than {} bytes, the PDO is disabled", index, PDO_MAX_LEN);
+            }
+        }
+        Ok(od)
+    }
+
+    pub fn load(path: &str, node_id: u8) -> Result<ObjectDictionary, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+        ObjectDictionary::parse(&content, node_id)
+    }
+
+    pub fn get(&self, index: u16, sub: u8) -> Option<&Entry> {
+        self.entries.get(&(index, sub))
+    }
+
+    pub fn get_mut(&mut self, index: u16, sub: u8) -> Option<&mut Entry> {
+        self.entries.get_mut(&(index, sub))
+    }
+
+    /// Whether any sub-object of `index` exists
+    pub fn has_index(&self, index: u16) -> bool {
+        self.entries.range((index, 0)..=(index, 255)).next().is_some()
+    }
+
+    /// Unsigned value of an entry, 0 if it doesn't exist
+    pub fn value(&self, index: u16, sub: u8) -> u64 {
+        self.get(index, sub).map(|x| x.to_u64()).unwrap_or(0)
+    }
+
+    /// Mapped index, sub-index and length in bits of the PDO mapping parameter at `index`
+    fn mapping(&self, index: u16) -> Vec<(u16, u8, u8)> {
+        let count = self.value(index, 0) as u8;
+        (1..=count).map(|sub| self.value(index, sub) as u32)
+            .map(|x| ((x >> 16) as u16, (x >> 8) as u8, x as u8)).collect()
+    }
+
+    /// Data length of the PDO mapping parameter at `index`
+    pub fn mapping_len(&self, index: u16) -> usize {
+        self.mapping(index).iter().map(|(_, _, bits)| (*bits as usize + 7) / 8).sum()
+    }
+
+    /// PDOs defined by the communication parameters at `communication` and the mapping at `mapping`.
+    /// PDOs mapping more than `PDO_MAX_LEN` bytes are skipped.
+    fn pdos(&self, communication: u16, mapping: u16) -> Vec<Pdo> {
+        (0..512u16).filter(|n| self.get(communication + n, 1).is_some()).map(|n| {
+            Pdo {
+                number: n as usize + 1,
+                cob_id: self.value(communication + n, 1) as u32,
+                transmission_type: self.value(communication + n, 2) as u8,
+                event_timer: self.value(communication + n, 5) as u16,
+                mapping: self.mapping(mapping + n),
+            }
+        }).filter(|x| x.data_len() <= PDO_MAX_LEN).collect()
+    }
+
+    /// Transmit PDOs of the device
+    pub fn tpdos(&self) -> Vec<Pdo> {
+        self.pdos(TPDO_COMMUNICATION, TPDO_MAPPING)
+    }
+
+    /// Receive PDOs of the device
+    pub fn rpdos(&self) -> Vec<Pdo> {
+        self.pdos(RPDO_COMMUNICATION, RPDO_MAPPING)
+    }
+
+    /// Data of a PDO from the current values of its mapped entries.
+    /// Mapped lengths are rounded to whole bytes.
+    pub fn pdo_data(&self, pdo: &Pdo) -> Vec<u8> {
+        let mut dat = vec![];
+        for (index, sub, bits) in pdo.mapping.iter() {
+            let len = (*bits as usize + 7) / 8;
+            let mut value = self.get(*index, *sub).map(|x| x.value.clone()).unwrap_or_default();
+            value.resize(len, 0);
+            dat.extend(value);
+        }
+        dat
+    }
+
+    /// Split the data of a PDO into its mapped entries, as `(index, sub, value)`
+    pub fn pdo_values(&self, pdo: &Pdo, dat: &[u8]) -> Vec<(u16, u8, Vec<u8>)> {
+        let mut values = vec![];
+        let mut offset = 0;
+        for (index, sub, bits) in pdo.mapping.iter() {
+            let len = (*bits as usize + 7) / 8;
+            if offset + len > dat.len() {
+                break;
+            }
+            values.push((*index, *sub, dat[offset..offset+len].to_vec()));
+            offset += len;
+        }
+        values
+    }
+
+    /// Store the data of a received PDO into its mapped entries
+    pub fn write_pdo(&mut self, pdo: &Pdo, dat: &[u8]) {
+        for (index, sub, value) in self.pdo_values(pdo, dat) {
+            if let Some(entry) = self.get_mut(index, sub) {
+                entry.value = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// TPDO 1 mapping `count` 32 bit entries
+    fn eds(count: usize) -> String {
+        let mut eds = String::from("[1800]\nSubNumber=2\n[1800sub0]\nDataType=0x0005\nDefaultValue=1\n[1800sub1]\nDataType=0x0007\nDefaultValue=$NODEID+0x180\n");
+        eds += &format!("[1A00]\nSubNumber={}\n[1A00sub0]\nDataType=0x0005\nDefaultValue={}\n", count + 1, count);
+        for i in 1..=count {
+            eds += &format!("[1A00sub{}]\nDataType=0x0007\nDefaultValue=0x20000{}20\n", i, i);
+        }
+        eds
+    }
+
+    #[test]
+    fn pdo_mapping() {
+        let od = ObjectDictionary::parse(&eds(2), 5).unwrap();
+        let pdos = od.tpdos();
+        assert_eq!(pdos.len(), 1);
+        assert_eq!(pdos[0].can_id(), 0x185);
+        assert_eq!(pdos[0].mapping, vec![(0x2000, 1, 32), (0x2000, 2, 32)]);
+        assert_eq!(pdos[0].data_len(), 8);
+    }
+
+    #[test]
+    fn pdo_too_long() {
+        let od = ObjectDictionary::parse(&eds(3), 5).unwrap();
+        assert_eq!(od.mapping_len(TPDO_MAPPING), 12);
+        assert!(od.tpdos().is_empty());
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(parse_value(TYPE_UNSIGNED16, "$NODEID+0x100", 3), Some(vec![0x03, 0x01]));
+        assert_eq!(parse_value(TYPE_INTEGER8, "-1", 0), Some(vec![0xFF]));
+        assert_eq!(parse_value(TYPE_OCTET_STRING, "0102 AB", 0), Some(vec![0x01, 0x02, 0xAB]));
+        assert_eq!(parse_value(TYPE_OCTET_STRING, "012", 0), None);
+    }
+}
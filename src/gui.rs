@@ -9,6 +9,8 @@ use super::gio;
 use super::glib;
 use super::server;
 use super::rics;
+use super::record;
+use super::dbc;
 
 use std::rc::{Rc};
 use std::path::PathBuf;
@@ -34,11 +36,7 @@ fn apply_filter(lua: &Lua, can_store: &mut gtk::ListStore) {
         can_store.foreach(move |m, p, i| {
             // TODO check for the from_str_radix unwrap
             let id = u32::from_str_radix(&m.get_value(i, 0).downcast::<String>().unwrap().get().unwrap(), 16).unwrap();
-            let len = u8::from_str_radix(&m.get_value(i, 1).downcast::<String>().unwrap().get().unwrap(), 16).unwrap();
-            let mut dat = vec![];
-            for j in 0..len {
-                dat.push(u8::from_str_radix(&m.get_value(i, (2 + j).into()).downcast::<String>().unwrap().get().unwrap(), 16).unwrap());
-            }
+            let dat = row_data(m, i);
             let (fil,data,color) = filter_can(lua, id, dat);
             if fil {
                 m.downcast_ref::<gtk::ListStore>().unwrap().set_value(i, 10, &data.to_value());
@@ -56,6 +54,65 @@ fn apply_filter(lua: &Lua, can_store: &mut gtk::ListStore) {
 
 }
 
+/// Show the DBC decoding of the rows that have no parsed data yet
+fn apply_dbc(dbc: &dbc::Dbc, can_store: &gtk::ListStore) {
+    can_store.foreach(|m, _p, i| {
+        let parsed = m.get_value(i, 10).downcast::<String>().ok().and_then(|x| x.get()).unwrap_or_default();
+        if parsed.is_empty() {
+            if let Some(msg) = dbc.decode(&row_to_record(m, i).data) {
+                m.downcast_ref::<gtk::ListStore>().unwrap().set_value(i, 10, &msg.to_string().to_value());
+            }
+        }
+        false
+    });
+}
+
+/// Get the data bytes of a can_store row.
+/// The first 8 bytes have their own columns, the CAN FD bytes after them share column 13.
+fn row_data(m: &gtk::TreeModel, i: &gtk::TreeIter) -> Vec<u8> {
+    let col = |n: i32| m.get_value(i, n).downcast::<String>().ok().and_then(|x| x.get()).unwrap_or_default();
+    let len = usize::from_str_radix(&col(1), 16).unwrap_or(0).min(server::CANFD_MAX_DLEN);
+    let mut dat: Vec<u8> = (0..len.min(8)).map(|j| u8::from_str_radix(&col((2 + j) as i32), 16).unwrap_or(0)).collect();
+    dat.extend(col(13).split_whitespace().take(len.saturating_sub(8)).map(|x| u8::from_str_radix(x, 16).unwrap_or(0)));
+    dat
+}
+
+/// Convert a can_store row to a record
+fn row_to_record(m: &gtk::TreeModel, i: &gtk::TreeIter) -> record::Record {
+    let col = |n: i32| m.get_value(i, n).downcast::<String>().ok().and_then(|x| x.get()).unwrap_or_default();
+    let id = u32::from_str_radix(&col(0), 16).unwrap_or(0) as i32;
+    let mut data = server::can_packet(id, row_data(m, i));
+    server::set_data_flags(&mut data, &col(14));
+    record::Record {
+        timestamp: record::parse_time(&col(12)).unwrap_or(0.0),
+        source: None,
+        channel: None,
+        data: data,
+    }
+}
+
+/// Append a CAN packet to the can_store
+fn insert_data(can_store: &gtk::ListStore, data: &rics::RICS_Data, parsed: &str, color: &str, time: &str) {
+    let dat = data.get_data();
+    let byte = |j: usize| dat.get(j).map(|x|format!("{:x}",x)).unwrap_or("".to_string());
+    can_store.insert_with_values(None, &(0..15 as u32).collect::<Vec<_>>()[..],
+                                 &[&format!("{:x}", data.get_id()), &format!("{:x}", dat.len()),
+                                   &byte(0), &byte(1), &byte(2), &byte(3),
+                                   &byte(4), &byte(5), &byte(6), &byte(7),
+                                   // Lua parsing of messages
+                                   &parsed.to_string(),
+                                   &color.to_string(),
+                                   &time.to_string(),
+                                   // CAN FD data after the first 8 bytes
+                                   &dat.iter().skip(8).map(|x|format!("{:x}",x)).collect::<Vec<String>>().join(" "),
+                                   &server::data_flags(data),
+                                 ]);
+}
+
+/// Append a record to the can_store
+fn insert_record(can_store: &gtk::ListStore, rec: &record::Record) {
+    insert_data(can_store, &rec.data, "", "", &record::format_time(rec.timestamp));
+}
 
 fn load_script(lua: &Lua, filename: PathBuf) {
     lua.context(|ctx| {
@@ -112,6 +169,7 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
     let tree_view = Rc::new(RefCell::new(builder.get_object::<gtk::TreeView>("tree_view").unwrap()));
     let filter_cont = Rc::new(builder.get_object::<gtk::CheckMenuItem>("filter_cont").unwrap());
     let status = Rc::new(builder.get_object::<gtk::Statusbar>("status").unwrap());
+    let dbc: Rc<RefCell<Option<dbc::Dbc>>> = Rc::new(RefCell::new(None));
 
     // Server connect
     let server_clone = Rc::clone(&server);
@@ -134,8 +192,23 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
     let window_clone = Rc::clone(&window);
     let can_store_clone = Rc::clone(&can_store);
     let tree_view_clone = Rc::clone(&tree_view);
+    let dbc_clone = Rc::clone(&dbc);
     builder.get_object::<gtk::MenuItem>("file_open").unwrap().connect_activate(move |_| {(|| -> Option<()> {
         if let Some(file) = dialog_open_file(&window_clone.borrow_mut(), "Open File", "Open", gtk::FileChooserAction::Open) {
+            if record::LogFormat::from_path(&file.to_string_lossy()) != record::LogFormat::Csv {
+                let records = record::read_log(&file.to_string_lossy()).ok()?;
+                let can_store = can_store_clone.borrow_mut();
+                can_store.clear();
+                for rec in records.iter() {
+                    insert_record(&can_store, rec);
+                }
+                if let Some(dbc) = dbc_clone.borrow().as_ref() {
+                    apply_dbc(dbc, &can_store);
+                }
+                tree_view_clone.borrow_mut().set_model(Some(&*can_store));
+                debug!("Done opening file");
+                return Some(());
+            }
             let mut rdr = csv::Reader::from_path(file).unwrap();
             let can_store = can_store_clone.borrow_mut();
 
@@ -147,7 +220,7 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
                 let record = result.ok()?;
                 trace!("Record {:?}", record);
 
-                can_store.insert_with_values(None, &(0..13 as u32).collect::<Vec<_>>()[..],
+                can_store.insert_with_values(None, &(0..15 as u32).collect::<Vec<_>>()[..],
                                              &[&record.get(1).unwrap().to_string(),
                                                &record.get(2).unwrap().to_string(),
                                                &record.get(3).unwrap_or("").to_string(),
@@ -162,6 +235,8 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
                                                &record.get(11).unwrap_or("").to_string(),
                                                &record.get(12).unwrap_or("").to_string(),
                                                &record.get(0).unwrap_or("").to_string(),
+                                               &record.get(13).unwrap_or("").to_string(),
+                                               &record.get(14).unwrap_or("").to_string(),
                                              ]);
 
                 let tree_view = tree_view_clone.borrow_mut();
@@ -183,6 +258,26 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
         }
     });
 
+    let window_clone = Rc::clone(&window);
+    let can_store_clone = Rc::clone(&can_store);
+    let status_clone = Rc::clone(&status);
+    let dbc_clone = Rc::clone(&dbc);
+    builder.get_object::<gtk::MenuItem>("filter_dbc").unwrap().connect_activate(move |_| {
+        if let Some(file) = dialog_open_file(&window_clone.borrow_mut(), "Load DBC", "Load", gtk::FileChooserAction::Open) {
+            match dbc::Dbc::load(&file.to_string_lossy()) {
+                Ok(db) => {
+                    apply_dbc(&db, &can_store_clone.borrow());
+                    *dbc_clone.borrow_mut() = Some(db);
+                    status_clone.push(0, "DBC loaded");
+                },
+                Err(err) => {
+                    error!("{}", err);
+                    status_clone.push(0, "Error ! DBC NOT loaded");
+                },
+            }
+        }
+    });
+
     let window_clone = Rc::clone(&window);
     let lua_clone = Rc::clone(&lua);
     let can_store_clone = Rc::clone(&can_store);
@@ -191,9 +286,22 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
         if let Some(file) = dialog_open_file(&window_clone.borrow_mut(), "Save", "Save", gtk::FileChooserAction::Save) {
             let can_store = can_store_clone.borrow_mut();
             debug!("Opening file");
-            if let Ok(mut wrt) = csv::Writer::from_path(file) {
+            let format = record::LogFormat::from_path(&file.to_string_lossy());
+            if format != record::LogFormat::Csv {
+            if let Ok(mut wrt) = record::create_record_file(&file.to_string_lossy(), format) {
+            can_store.foreach(move |m, _p, i| {
+                wrt.write_record(&row_to_record(m, i)).unwrap();
+                false
+            });
+            debug!("Saving done");
+            status_clone.push(0, "Data saved");
+            } else {
+                error!("Error saving file");
+                status_clone.push(0, "Error ! Data NOT saved");
+            }
+            } else if let Ok(mut wrt) = csv::Writer::from_path(file) {
             can_store.foreach(move |m, p, i| {
-                let vec: Vec<String> = [12,0,1,2,3,4,5,6,7,8,9,10,11].iter().map(|n| m.get_value(i, *n).downcast::<String>().unwrap().get().unwrap()).collect();
+                let vec: Vec<String> = [12,0,1,2,3,4,5,6,7,8,9,10,11,13,14].iter().map(|n| m.get_value(i, *n).downcast::<String>().unwrap().get().unwrap()).collect();
                 wrt.write_record(&vec[..]).unwrap();
                 wrt.flush();
                 false
@@ -224,6 +332,7 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
     let lua_clone = Rc::clone(&lua);
     let status_clone = Rc::clone(&status);
     let tree_view_clone = Rc::clone(&tree_view);
+    let dbc_clone = Rc::clone(&dbc);
     conn_fork.connect_activate(move |_| {
         let mut svr: RefMut<_> = server_clone.borrow_mut();
         if let Some(resp) = svr.as_mut().map(|s|s.listen_response()) {
@@ -231,6 +340,7 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
             let tree_view_clone = Rc::clone(&tree_view_clone);
             let lua_clone = Rc::clone(&lua_clone);
             let filter_cont_clone = Rc::clone(&filter_cont);
+            let dbc_clone = Rc::clone(&dbc_clone);
             status_clone.push(0, "Starting listening thread");
             gtk::idle_add(move || {
                 if let Ok(packet) = resp.try_recv() {
@@ -242,33 +352,21 @@ pub fn gui_main(opt_svr: Option<server::RICSServer>) {
                             // data.get_data();
                             let can_store = can_store.borrow_mut();
 
-                            let columns = data.get_data().len();
-                            let len = data.get_data().len() as u8;
-
-                            let (fil,parsed,color) = if filter_cont_clone.get_active() {
+                            let (fil,mut parsed,color) = if filter_cont_clone.get_active() {
                                 filter_can(&*lua_clone, data.get_id() as u32, data.get_data().to_vec())
                             } else {
                                 (true, "".to_string(), "black".to_string())
                             };
+                            if parsed.is_empty() {
+                                if let Some(msg) = dbc_clone.borrow().as_ref().and_then(|x| x.decode(data)) {
+                                    parsed = msg.to_string();
+                                }
+                            }
 
                             if fil {
-                                let time = std::time::SystemTime::now();
+                                let time = server::data_time(data);
                                 let datetime: chrono::DateTime<chrono::offset::Local> = time.into();
-                                can_store.insert_with_values(None, &(0..13 as u32).collect::<Vec<_>>()[..],
-                                                             &[&format!("{:x}", data.get_id()), &format!("{:x}",len),
-                                                               &data.get_data().get(0).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               &data.get_data().get(1).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               &data.get_data().get(2).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               &data.get_data().get(3).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               &data.get_data().get(4).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               &data.get_data().get(5).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               &data.get_data().get(6).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               &data.get_data().get(7).map(|x|format!("{:x}",x)).unwrap_or("".to_string()),
-                                                               // Lua parsing of messages
-                                                               &parsed,
-                                                               &color,
-                                                               &format!("{}", datetime.format("%Y-%m-%d %T%.3f")),
-                                                             ]);
+                                insert_data(&can_store, data, &parsed, &color, &format!("{}", datetime.format("%Y-%m-%d %T%.3f")));
                             }
                             let tree_view = tree_view_clone.borrow_mut();
                             tree_view.set_model(Some(&*can_store));
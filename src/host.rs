@@ -10,14 +10,103 @@ use std::sync::{RwLock, Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
 use std::process;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use protobuf::{Message, CodedInputStream};
 
 use std::collections::{HashMap};
 use super::rics;
+use super::server;
 use rand;
 use rand::Rng;
 
+type CanErrorState = rics::RICS_Response_RICS_CanState_RICS_ErrorState;
+type SubscriptionType = rics::RICS_Subscription_RICS_SubscriptionType;
+
+/// SocketCAN error frame classes, used as the id of error frames
+const CAN_ERR_CRTL: i32 = 0x0004;
+const CAN_ERR_PROT: i32 = 0x0008;
+const CAN_ERR_ACK: i32 = 0x0020;
+const CAN_ERR_BUSOFF: i32 = 0x0040;
+const CAN_ERR_BUSERROR: i32 = 0x0080;
+const CAN_ERR_RESTARTED: i32 = 0x0100;
+const CAN_ERR_CNT: i32 = 0x0200;
+
+/// Controller status, in byte 1 of error frames
+const CAN_ERR_CRTL_RX_WARNING: u8 = 0x04;
+const CAN_ERR_CRTL_TX_WARNING: u8 = 0x08;
+const CAN_ERR_CRTL_RX_PASSIVE: u8 = 0x10;
+const CAN_ERR_CRTL_TX_PASSIVE: u8 = 0x20;
+const CAN_ERR_CRTL_ACTIVE: u8 = 0x40;
+
+/// Protocol violation type, in byte 2 of error frames
+const CAN_ERR_PROT_BIT: u8 = 0x01;
+const CAN_ERR_PROT_STUFF: u8 = 0x04;
+const CAN_ERR_PROT_TX: u8 = 0x80;
+
+/// CAN error counters of a node, following the ISO 11898-1 fault confinement rules
+#[derive(Clone, Copy, Default, Debug)]
+struct CanCounters {
+    /// Transmit error counter
+    tec: u32,
+    /// Receive error counter
+    rec: u32,
+    bus_off: bool,
+    /// Number of times the node went bus-off, to match the automatic recoveries
+    bus_off_count: u32,
+}
+
+impl CanCounters {
+    fn state(&self) -> CanErrorState {
+        if self.bus_off {
+            CanErrorState::BUS_OFF
+        } else if self.tec >= 128 || self.rec >= 128 {
+            CanErrorState::ERROR_PASSIVE
+        } else {
+            CanErrorState::ERROR_ACTIVE
+        }
+    }
+
+    /// Controller status bits reported in error frames
+    fn status(&self) -> u8 {
+        let mut status = 0;
+        if self.tec >= 128 { status |= CAN_ERR_CRTL_TX_PASSIVE; } else if self.tec >= 96 { status |= CAN_ERR_CRTL_TX_WARNING; }
+        if self.rec >= 128 { status |= CAN_ERR_CRTL_RX_PASSIVE; } else if self.rec >= 96 { status |= CAN_ERR_CRTL_RX_WARNING; }
+        if status == 0 { CAN_ERR_CRTL_ACTIVE } else { status }
+    }
+
+    /// Error frame in the SocketCAN format, with the counters in the last two bytes
+    fn error_frame(&self, class: i32, prot: u8) -> rics::RICS_Data {
+        let mut dat = vec![0u8; 8];
+        dat[1] = self.status();
+        dat[2] = prot;
+        dat[6] = self.tec.min(255) as u8;
+        dat[7] = self.rec.min(255) as u8;
+        server::can_frame(class | CAN_ERR_CNT, dat, server::CanFlags { error: true, ..Default::default() })
+    }
+}
+
+
+/// Whether a subscription asks for a data packet.
+/// CAN error frames are never subscribed by id, their id being the error class.
+fn subscribed(subscription: &rics::RICS_Subscription, data: &rics::RICS_Data) -> bool {
+    let in_range = |kind| data.get_field_type() == kind
+        && data.get_id() >= subscription.get_id_min() && data.get_id() <= subscription.get_id_max();
+    match subscription.get_field_type() {
+        SubscriptionType::CAN => in_range(rics::RICS_Data_RICS_DataType::CAN) && !data.get_error(),
+        SubscriptionType::DATAGRAM => in_range(rics::RICS_Data_RICS_DataType::DATAGRAM),
+        SubscriptionType::TOPIC => data.has_topic() && data.get_topic() == subscription.get_topic(),
+    }
+}
+
+/// Whether a receive filter accepts a data packet. CAN error frames and
+/// sync pulses aren't filtered by id, their id not being a message id.
+fn accepted(filter: &rics::RICS_ReceiveFilter, data: &rics::RICS_Data) -> bool {
+    let has_id = !data.get_error() && data.get_field_type() != rics::RICS_Data_RICS_DataType::SYNC;
+    (filter.get_ids().is_empty() || !has_id || filter.get_ids().iter().any(|x| data.get_id() & x.get_mask() == x.get_id() & x.get_mask()))
+        && (filter.get_types().is_empty() || filter.get_types().contains(&data.get_field_type()))
+        && (filter.get_sources().is_empty() || filter.get_sources().contains(&data.get_source()))
+}
 
 /// Contains server permanent state
 pub struct ServerState {
@@ -27,6 +116,12 @@ pub struct ServerState {
     /// in CAN mode, if a message is dropped, no one receives the
     /// message
     can_drop_chance: f32,
+    /// Flag for the CAN error confinement emulation
+    can_errors: bool,
+    /// Delay before a bus-off node recovers by itself, 0 to only recover on request
+    can_restart_ms: u32,
+    /// CAN error counters of the nodes
+    can_counters: Mutex<HashMap<i32, CanCounters>>,
     /// Internal flag for node id allocation
     node_allocator: i32,
     /// Holds the self described names of the nodes
@@ -37,6 +132,14 @@ pub struct ServerState {
     node_outputs: HashMap<i32, Arc<Mutex<dyn Write + Send + Sync>>>,
     /// Current loading routes
     node_routing: HashMap<i32, Vec<i32>>,
+    /// Packets delivered to the nodes regardless of routes
+    node_subscriptions: HashMap<i32, Vec<rics::RICS_Subscription>>,
+    /// Packets accepted by the nodes, the others aren't written to them
+    node_filters: HashMap<i32, rics::RICS_ReceiveFilter>,
+    /// Server start time, in nanoseconds since the unix epoch
+    start_time: u64,
+    /// Monotonic clock reference taken at server start
+    start_instant: Instant,
 }
 
 impl ServerState {
@@ -45,14 +148,27 @@ impl ServerState {
         ServerState {
             can_broadcast: false,
             can_drop_chance: 0.00,
+            can_errors: false,
+            can_restart_ms: 0,
+            can_counters: Mutex::new(HashMap::new()),
             node_allocator: 0,
             node_names: HashMap::new(),
             node_inputs: HashMap::new(),
             node_outputs: HashMap::new(),
             node_routing: HashMap::new(),
+            node_subscriptions: HashMap::new(),
+            node_filters: HashMap::new(),
+            start_time: SystemTime::now().duration_since(UNIX_EPOCH).expect("Invalid current time").as_nanos() as u64,
+            start_instant: Instant::now(),
         }
     }
 
+    /// Monotonic timestamp in nanoseconds since the unix epoch.
+    /// The wall clock is only read at server start so the timestamps never go back.
+    fn timestamp(&self) -> u64 {
+        self.start_time + self.start_instant.elapsed().as_nanos() as u64
+    }
+
     fn get_can_broadcast(&self) -> bool {self.can_broadcast}
 
     fn set_can_broadcast(&mut self, broadcast: bool) {
@@ -73,6 +189,9 @@ impl ServerState {
         self.node_names.remove(&node);
         self.node_outputs.remove(&node);
         self.node_routing.remove(&node);
+        self.node_subscriptions.remove(&node);
+        self.node_filters.remove(&node);
+        self.can_counters.lock().unwrap().remove(&node);
     }
 
     fn new_node_raw<T>(&mut self, name_op: Option<T>, is_op: Option<Arc<Mutex<dyn Read + Send + Sync>>>, os_op: Option<Arc<Mutex<dyn Write + Send + Sync>>>) -> i32 where T: Into<String> {
@@ -133,6 +252,29 @@ impl ServerState {
         }
     }
 
+    fn subscribe(&mut self, node: i32, subscription: rics::RICS_Subscription) {
+        let subscriptions = self.node_subscriptions.entry(node).or_default();
+        if !subscriptions.contains(&subscription) {
+            info!("Node {} subscribed to {:?}", node, subscription);
+            subscriptions.push(subscription);
+        }
+    }
+
+    fn unsubscribe(&mut self, node: i32, subscription: &rics::RICS_Subscription) {
+        if let Some(subscriptions) = self.node_subscriptions.get_mut(&node) {
+            subscriptions.retain(|x| x != subscription);
+        }
+    }
+
+    fn set_receive_filter(&mut self, node: i32, filter: rics::RICS_ReceiveFilter) {
+        info!("Node {} receive filter set to {:?}", node, filter);
+        if filter.get_ids().is_empty() && filter.get_types().is_empty() && filter.get_sources().is_empty() {
+            self.node_filters.remove(&node);
+        } else {
+            self.node_filters.insert(node, filter);
+        }
+    }
+
     fn set_can_drop_chance(&mut self, v: f32) {
         if v >= 0.0 && v <= 1.0 {
             self.can_drop_chance = v;
@@ -141,6 +283,163 @@ impl ServerState {
             warn!("Invalid CAN drop value: {}", v);
         }
     }
+
+    fn set_can_errors(&mut self, v: bool) {
+        info!("Changing CAN error emulation to {}", v);
+        self.can_errors = v;
+        if !v {
+            self.can_counters.lock().unwrap().clear();
+        }
+    }
+
+    /// Nodes receiving a data packet from `node`, through routes or subscriptions
+    fn receivers(&self, node: Option<i32>, data: &rics::RICS_Data) -> Vec<i32> {
+        let is_can = data.get_field_type() == rics::RICS_Data_RICS_DataType::CAN;
+        let is_sync = data.get_field_type() == rics::RICS_Data_RICS_DataType::SYNC;
+        let mut targets: Vec<i32> = if (self.get_can_broadcast() && is_can) || is_sync {
+            // CAN broadcast forwarding, sync pulses always reach every node
+            self.node_outputs.keys().cloned().filter(|n| Some(*n) != node).collect()
+        } else if data.has_target() {
+            vec![data.get_target()]
+        } else {
+            node.and_then(|n| self.node_routing.get(&n).cloned()).unwrap_or(vec![])
+        };
+        for (n, subscriptions) in self.node_subscriptions.iter() {
+            if Some(*n) != node && !targets.contains(n) && subscriptions.iter().any(|x| subscribed(x, data)) {
+                targets.push(*n);
+            }
+        }
+        targets.into_iter().filter(|n| self.node_outputs.contains_key(n) && !(is_can && self.is_bus_off(*n))).collect()
+    }
+
+    /// Write a message to a node, unless its receive filter rejects it
+    fn send_to(&self, node: i32, msg: &rics::RICS_Response) {
+        if let (true, Some(filter)) = (msg.has_data(), self.node_filters.get(&node)) {
+            if !accepted(filter, msg.get_data()) {
+                trace!("Node {} filters out {:?}", node, msg.get_data());
+                return;
+            }
+        }
+        if let Some(writer) = self.node_outputs.get(&node) {
+            msg.write_length_delimited_to_writer(&mut *(writer.lock().unwrap())).expect("Socket error");
+        }
+    }
+
+    /// Send a packet generated by the host on behalf of `node` to `targets`
+    fn send_host_data(&self, node: i32, mut data: rics::RICS_Data, targets: &[i32]) {
+        data.set_source(node);
+        data.set_timestamp(self.timestamp());
+        let mut msg = rics::RICS_Response::new();
+        msg.set_data(data);
+        for target in targets {
+            self.send_to(*target, &msg);
+        }
+    }
+
+    fn is_bus_off(&self, node: i32) -> bool {
+        self.can_errors && self.can_counters.lock().unwrap().get(&node).map(|c| c.bus_off).unwrap_or(false)
+    }
+
+    /// Change the error counters of a node, and notify it of its state changes.
+    /// Returns true if the node just went bus-off.
+    fn update_can_counters(&self, node: i32, f: impl FnOnce(&mut CanCounters)) -> bool {
+        let (old, new) = {
+            let mut counters = self.can_counters.lock().unwrap();
+            let c = counters.entry(node).or_default();
+            let old = *c;
+            f(c);
+            if c.tec >= 256 && !c.bus_off {
+                c.bus_off = true;
+                c.bus_off_count += 1;
+            }
+            (old, *c)
+        };
+
+        if new.bus_off && !old.bus_off {
+            warn!("CAN node {} is bus-off", node);
+            self.send_host_data(node, new.error_frame(CAN_ERR_BUSOFF, 0), &[node]);
+        } else if new.state() != old.state() || new.status() != old.status() {
+            info!("CAN node {} is now {:?} (TEC {}, REC {})", node, new.state(), new.tec, new.rec);
+            self.send_host_data(node, new.error_frame(CAN_ERR_CRTL, 0), &[node]);
+        }
+        new.bus_off && !old.bus_off
+    }
+
+    /// A frame from `node` was received by `receivers`
+    fn can_frame_ok(&self, node: i32, receivers: &[i32]) {
+        self.update_can_counters(node, |c| c.tec = c.tec.saturating_sub(1));
+        for r in receivers {
+            self.update_can_counters(*r, |c| c.rec = if c.rec > 127 { 119 } else { c.rec.saturating_sub(1) });
+        }
+    }
+
+    /// A frame from `node` was not acknowledged.
+    /// Error passive nodes don't count acknowledgement errors, so a lone node never goes bus-off.
+    fn can_ack_error(&self, node: i32) -> bool {
+        let bus_off = self.update_can_counters(node, |c| if c.tec < 128 { c.tec += 8 });
+        let counters = self.can_counters.lock().unwrap().get(&node).cloned().unwrap_or_default();
+        self.send_host_data(node, counters.error_frame(CAN_ERR_ACK | CAN_ERR_BUSERROR, CAN_ERR_PROT_TX), &[node]);
+        bus_off
+    }
+
+    /// Inject a transmit or receive error on a node.
+    /// The error frame is seen by the node and every node receiving its frames.
+    fn can_fault(&self, node: i32, tx: bool) -> bool {
+        if self.is_bus_off(node) {
+            return false;
+        }
+        let bus_off = self.update_can_counters(node, |c| if tx { c.tec += 8 } else { c.rec = (c.rec + 1).min(255) });
+        let counters = self.can_counters.lock().unwrap().get(&node).cloned().unwrap_or_default();
+        let prot = if tx { CAN_ERR_PROT_BIT | CAN_ERR_PROT_TX } else { CAN_ERR_PROT_STUFF };
+        let mut targets = self.receivers(Some(node), &server::can_packet(0, vec![]));
+        targets.push(node);
+        self.send_host_data(node, counters.error_frame(CAN_ERR_PROT | CAN_ERR_BUSERROR, prot), &targets);
+        bus_off
+    }
+
+    /// Bring a bus-off node back to error active.
+    /// `count` must match the bus-off occurence for automatic recoveries.
+    fn can_recover(&self, node: i32, count: Option<u32>) {
+        let counters = {
+            let mut counters = self.can_counters.lock().unwrap();
+            match counters.get_mut(&node) {
+                Some(c) if c.bus_off && count.map(|n| n == c.bus_off_count).unwrap_or(true) => {
+                    *c = CanCounters { bus_off_count: c.bus_off_count, ..Default::default() };
+                    *c
+                },
+                _ => return,
+            }
+        };
+        info!("CAN node {} recovered from bus-off", node);
+        self.send_host_data(node, counters.error_frame(CAN_ERR_RESTARTED, 0), &[node]);
+    }
+
+    /// Error confinement state of every node
+    fn can_states(&self) -> Vec<rics::RICS_Response_RICS_CanState> {
+        let counters = self.can_counters.lock().unwrap();
+        self.node_names.keys().map(|n| {
+            let c = counters.get(n).cloned().unwrap_or_default();
+            let mut state = rics::RICS_Response_RICS_CanState::new();
+            state.set_id(*n);
+            state.set_tec(c.tec);
+            state.set_rec(c.rec);
+            state.set_state(c.state());
+            state
+        }).collect()
+    }
+}
+
+/// Recover a bus-off node after the configured restart delay
+fn schedule_can_restart(server_state: Arc<RwLock<ServerState>>, state: &ServerState, node: i32) {
+    let delay = state.can_restart_ms;
+    let count = state.can_counters.lock().unwrap().get(&node).map(|c| c.bus_off_count).unwrap_or(0);
+    if delay == 0 {
+        return;
+    }
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(delay as u64));
+        server_state.read().unwrap().can_recover(node, Some(count));
+    });
 }
 
 /// Start listening for tcp socket connections (async)
@@ -204,6 +503,8 @@ fn run_client<T>(server_state: Arc<RwLock<ServerState>>, socket: T, input_stream
     let socket_arc = Arc::new(Mutex::new(socket));
     let mut node = None;
     let mut rng = rand::thread_rng();
+    // Sequence number of the next data packet from this client
+    let mut sequence: u32 = 0;
 
     if let Ok(connection) = input_stream.read_message::<rics::RICS_Connection>() {
         if connection.get_connect_as_node() {
@@ -260,6 +561,21 @@ fn run_client<T>(server_state: Arc<RwLock<ServerState>>, socket: T, input_stream
                             server_state.write().unwrap().set_can_broadcast(true),
                         rics::RICS_Request_RICS_Query::CLEAR_FLAG_CAN_BROADCAST =>
                             server_state.write().unwrap().set_can_broadcast(false),
+                        rics::RICS_Request_RICS_Query::SET_FLAG_CAN_ERRORS =>
+                            server_state.write().unwrap().set_can_errors(true),
+                        rics::RICS_Request_RICS_Query::CLEAR_FLAG_CAN_ERRORS =>
+                            server_state.write().unwrap().set_can_errors(false),
+                        rics::RICS_Request_RICS_Query::LIST_CAN_STATE => {
+                            let state = server_state.read().unwrap();
+
+                            let mut response = rics::RICS_Response::new();
+                            let mut states = rics::RICS_Response_RICS_CanStateList::new();
+                            states.set_states(protobuf::RepeatedField::from_vec(state.can_states()));
+                            response.set_can_states(states);
+
+                            let mut writer = socket_arc.lock().unwrap();
+                            response.write_length_delimited_to_writer(&mut *writer).expect("Socket error");
+                        },
                         rics::RICS_Request_RICS_Query::DAEMON_QUIT => process::exit(2),
                     }
                     ()
@@ -270,38 +586,45 @@ fn run_client<T>(server_state: Arc<RwLock<ServerState>>, socket: T, input_stream
                     let mut msg = rics::RICS_Response::new();
                     let mut data = req.get_data().clone();
                     if let Some(n) = node { data.set_source(n); }
+                    data.set_timestamp(state.timestamp());
+                    data.set_sequence(sequence);
+                    sequence = sequence.wrapping_add(1);
                     msg.set_data(data.clone());
 
+                    let is_can = data.get_field_type() == rics::RICS_Data_RICS_DataType::CAN;
+
+                    // Bus-off nodes can't transmit
+                    if is_can && node.map(|n| state.is_bus_off(n)).unwrap_or(false) {
+                        debug!("Node {:?} is bus-off, dropping packet", node);
+                        continue;
+                    }
+
                     // Broadcast Dropping
-                    if state.can_drop_chance != 0.0 && data.get_field_type() == rics::RICS_Data_RICS_DataType::CAN {
+                    if state.can_drop_chance != 0.0 && is_can {
                         if rng.gen::<f32>() < state.can_drop_chance {
                             info!("Server is dropping packet {:?}", data);
                             continue;
                         }
                     }
-                    
 
-                    // Forwarding
-                    if state.get_can_broadcast() && data.get_field_type() == rics::RICS_Data_RICS_DataType::CAN {
-                        // CAN broadcast forwarding
-                        for (n, writer) in state.node_outputs.iter() {
-                            if Some(*n) != node {
-                                msg.write_length_delimited_to_writer(&mut *(writer.lock().unwrap())).expect("Socket error");
-                            }
-                        }
-                    } else {
-                        // Routing forwarding
-                        for target in if data.has_target() {
-                            vec![data.get_target()]
-                        } else {
-                            node.and_then(|n| state.node_routing.get(&n).cloned()).unwrap_or(vec![])
-                        } {
-                            if let Some(writer) = state.node_outputs.get(&target) {
-                                info!("Forwarding to {}", target);
-                                msg.write_length_delimited_to_writer(&mut *(writer.lock().unwrap())).expect("Socket error");
+                    let receivers = state.receivers(node, &data);
+
+                    // Error confinement
+                    if let (true, true, Some(n)) = (state.can_errors, is_can && !data.get_error(), node) {
+                        if receivers.is_empty() {
+                            info!("CAN packet from {} was not acknowledged", n);
+                            if state.can_ack_error(n) {
+                                schedule_can_restart(server_state.clone(), &state, n);
                             }
+                            continue;
                         }
-                        ()
+                        state.can_frame_ok(n, &receivers);
+                    }
+
+                    // Forwarding
+                    for target in receivers {
+                        info!("Forwarding to {}", target);
+                        state.send_to(target, &msg);
                     }
                 } else if req.has_add_route() {
                     let mut state = server_state.write().unwrap();
@@ -317,9 +640,51 @@ fn run_client<T>(server_state: Arc<RwLock<ServerState>>, socket: T, input_stream
                     let j = req.get_del_route().get_to();
 
                     state.del_route(i, j);
+                } else if req.has_subscribe() || req.has_unsubscribe() {
+                    let n = match node {
+                        Some(n) => n,
+                        None => { warn!("Only nodes can subscribe to packets"); continue; },
+                    };
+                    let mut state = server_state.write().unwrap();
+                    if req.has_subscribe() {
+                        state.subscribe(n, req.get_subscribe().clone());
+                    } else {
+                        state.unsubscribe(n, req.get_unsubscribe());
+                    }
+                } else if req.has_receive_filter() {
+                    match node {
+                        Some(n) => server_state.write().unwrap().set_receive_filter(n, req.get_receive_filter().clone()),
+                        None => warn!("Only nodes can filter the packets they receive"),
+                    }
                 } else if req.has_can_drop_chance() {
                     let mut state = server_state.write().unwrap();
                     state.set_can_drop_chance( req.get_can_drop_chance() );
+                } else if req.has_can_fault() {
+                    let fault = req.get_can_fault();
+                    let n = fault.get_node();
+                    let state = server_state.read().unwrap();
+                    if !state.can_errors {
+                        warn!("CAN error emulation is disabled, ignoring fault on node {}", n);
+                        continue;
+                    }
+
+                    let mut bus_off = false;
+                    for _ in 0..fault.get_tx_errors() {
+                        bus_off |= state.can_fault(n, true);
+                    }
+                    for _ in 0..fault.get_rx_errors() {
+                        bus_off |= state.can_fault(n, false);
+                    }
+                    if fault.get_recover() {
+                        state.can_recover(n, None);
+                    }
+                    if bus_off {
+                        schedule_can_restart(server_state.clone(), &state, n);
+                    }
+                } else if req.has_can_restart_ms() {
+                    let mut state = server_state.write().unwrap();
+                    info!("Changing CAN bus-off restart delay to {} ms", req.get_can_restart_ms());
+                    state.can_restart_ms = req.get_can_restart_ms();
                 } else {
                     warn!("Invalid message {:?}", req);
                 }
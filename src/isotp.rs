@@ -0,0 +1,480 @@
+//! ISO-TP (ISO 15765-2) transport over CAN packets
+//!
+//! Payloads that don't fit in a single frame are sent as a first frame
+//! followed by consecutive frames, paced by the flow control frames of the
+//! receiver. Classic CAN carries up to 4095 bytes, larger payloads use the
+//! 32 bit first frame length of ISO 15765-2:2016, mostly useful with CAN FD.
+
+use std::io::{Error, ErrorKind, Result};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::rics;
+use super::server::{self, RICSServer};
+
+/// Largest payload of a classic first frame, with a 12 bit length
+pub const ISOTP_MAX_LEN: usize = 4095;
+
+/// Protocol control information of each frame type, in the high nibble of the first byte
+const PCI_SF: u8 = 0x00;
+const PCI_FF: u8 = 0x10;
+const PCI_CF: u8 = 0x20;
+const PCI_FC: u8 = 0x30;
+
+/// Flow control status
+const FC_CTS: u8 = 0;
+const FC_WAIT: u8 = 1;
+const FC_OVFLW: u8 = 2;
+
+/// Padding of CAN FD frames longer than 8 bytes when none is configured
+const FD_PADDING: u8 = 0xCC;
+
+/// ISO-TP channel parameters
+#[derive(Clone, Debug)]
+pub struct IsoTpOptions {
+    /// CAN id of the frames sent
+    pub tx_id: i32,
+    /// CAN id of the frames received
+    pub rx_id: i32,
+    /// Send 29 bit identifiers, implied by ids above 0x7FF
+    pub extended: bool,
+    /// Send CAN FD frames
+    pub fd: bool,
+    /// CAN FD bit rate switch
+    pub brs: bool,
+    /// Data length of the frames sent, 8 for classic CAN, up to 64 for CAN FD
+    pub tx_dl: usize,
+    /// Byte used to pad frames to 8 bytes, frames are as short as possible without it
+    pub padding: Option<u8>,
+    /// Consecutive frames the sender may send between flow controls, 0 for no limit
+    pub block_size: u8,
+    /// Separation time requested between consecutive frames, as a raw STmin value
+    pub st_min: u8,
+    /// Maximum time to send a frame
+    pub n_as: Duration,
+    /// Maximum time to wait for a flow control frame
+    pub n_bs: Duration,
+    /// Maximum time to wait for a consecutive frame
+    pub n_cr: Duration,
+    /// Number of wait flow controls accepted in a row
+    pub wft_max: u32,
+    /// Largest payload accepted, longer transfers are refused with an overflow
+    pub max_len: usize,
+}
+
+impl IsoTpOptions {
+    /// Classic CAN channel with the default ISO 15765-2 timeouts
+    pub fn new(tx_id: i32, rx_id: i32) -> IsoTpOptions {
+        IsoTpOptions {
+            tx_id: tx_id,
+            rx_id: rx_id,
+            extended: false,
+            fd: false,
+            brs: false,
+            tx_dl: server::CAN_MAX_DLEN,
+            padding: None,
+            block_size: 0,
+            st_min: 0,
+            n_as: Duration::from_millis(1000),
+            n_bs: Duration::from_millis(1000),
+            n_cr: Duration::from_millis(1000),
+            wft_max: 10,
+            max_len: ISOTP_MAX_LEN,
+        }
+    }
+
+    /// Switch to CAN FD frames of `tx_dl` bytes
+    pub fn with_fd(mut self, tx_dl: usize, brs: bool) -> IsoTpOptions {
+        self.fd = true;
+        self.brs = brs;
+        self.tx_dl = tx_dl;
+        self
+    }
+}
+
+/// Duration of a raw STmin value. Reserved values mean the longest separation time.
+pub fn st_min_duration(st_min: u8) -> Duration {
+    match st_min {
+        0x00..=0x7F => Duration::from_millis(st_min as u64),
+        0xF1..=0xF9 => Duration::from_micros((st_min - 0xF0) as u64 * 100),
+        _ => Duration::from_millis(0x7F),
+    }
+}
+
+/// Decoded ISO-TP frame
+#[derive(Debug)]
+enum Frame {
+    Single(Vec<u8>),
+    First(usize, Vec<u8>),
+    Consecutive(u8, Vec<u8>),
+    FlowControl(u8, u8, u8),
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn decode(dat: &[u8]) -> Result<Frame> {
+    let pci = *dat.get(0).ok_or_else(|| invalid("Empty ISO-TP frame"))?;
+    match pci & 0xF0 {
+        PCI_SF => {
+            let (len, start) = if pci & 0x0F != 0 {
+                ((pci & 0x0F) as usize, 1)
+            } else {
+                (*dat.get(1).ok_or_else(|| invalid("Truncated single frame"))? as usize, 2)
+            };
+            dat.get(start..start + len).map(|x| Frame::Single(x.to_vec())).ok_or_else(|| invalid("Truncated single frame"))
+        },
+        PCI_FF => {
+            if dat.len() < 2 {
+                return Err(invalid("Truncated first frame"));
+            }
+            let len = ((pci & 0x0F) as usize) << 8 | dat[1] as usize;
+            let (len, start) = if len > 0 {
+                (len, 2)
+            } else if dat.len() >= 6 {
+                (u32::from_be_bytes([dat[2], dat[3], dat[4], dat[5]]) as usize, 6)
+            } else {
+                return Err(invalid("Truncated first frame"));
+            };
+            // Payloads that fit in a single frame of the same length can't be segmented
+            let single_max = if dat.len() > server::CAN_MAX_DLEN { dat.len() - 2 } else { server::CAN_MAX_DLEN - 1 };
+            if len <= single_max {
+                return Err(invalid(&format!("Invalid first frame length {}", len)));
+            }
+            Ok(Frame::First(len, dat[start..].to_vec()))
+        },
+        PCI_CF => Ok(Frame::Consecutive(pci & 0x0F, dat[1..].to_vec())),
+        PCI_FC => {
+            if dat.len() < 3 {
+                return Err(invalid("Truncated flow control frame"));
+            }
+            Ok(Frame::FlowControl(pci & 0x0F, dat[1], dat[2]))
+        },
+        _ => Err(invalid("Unknown ISO-TP frame type")),
+    }
+}
+
+/// Single frame carrying the whole payload, if it fits in `tx_dl` bytes.
+/// Payloads over 7 bytes need the CAN FD length escape.
+fn single_frame(payload: &[u8], tx_dl: usize) -> Option<Vec<u8>> {
+    let mut dat = if payload.len() < tx_dl.min(server::CAN_MAX_DLEN) {
+        vec![PCI_SF | payload.len() as u8]
+    } else if tx_dl > server::CAN_MAX_DLEN && payload.len() <= tx_dl - 2 {
+        vec![PCI_SF, payload.len() as u8]
+    } else {
+        return None;
+    };
+    dat.extend_from_slice(payload);
+    Some(dat)
+}
+
+/// First frame of a payload filling `tx_dl` bytes, with the number of payload bytes it carries.
+/// Payloads over 4095 bytes use the 32 bit length escape.
+fn first_frame(payload: &[u8], tx_dl: usize) -> (Vec<u8>, usize) {
+    let mut dat = if payload.len() <= ISOTP_MAX_LEN {
+        vec![PCI_FF | (payload.len() >> 8) as u8, payload.len() as u8]
+    } else {
+        let mut dat = vec![PCI_FF, 0];
+        dat.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        dat
+    };
+    let len = tx_dl - dat.len();
+    dat.extend_from_slice(&payload[..len]);
+    (dat, len)
+}
+
+fn consecutive_frame(sn: u8, dat: &[u8]) -> Vec<u8> {
+    let mut frame = vec![PCI_CF | sn];
+    frame.extend_from_slice(dat);
+    frame
+}
+
+fn flow_control_frame(status: u8, block_size: u8, st_min: u8) -> Vec<u8> {
+    vec![PCI_FC | status, block_size, st_min]
+}
+
+/// ISO-TP channel between two CAN ids, over a connected RICS node.
+/// CAN packets with other ids received by the node are discarded.
+pub struct IsoTp {
+    server: RICSServer,
+    packets: Receiver<rics::RICS_Response>,
+    pub options: IsoTpOptions,
+}
+
+impl IsoTp {
+    /// The server must be connected as a node, its packets can't be read elsewhere anymore
+    pub fn new(mut server: RICSServer, options: IsoTpOptions) -> IsoTp {
+        if options.fd {
+            assert!(options.tx_dl >= server::CAN_MAX_DLEN && options.tx_dl <= server::CANFD_MAX_DLEN
+                    && server::can_fd_len(options.tx_dl) == options.tx_dl, "Invalid CAN FD data length {}", options.tx_dl);
+        } else {
+            assert!(options.tx_dl == server::CAN_MAX_DLEN, "Classic CAN frames carry 8 bytes");
+        }
+        let packets = server.listen_response();
+        IsoTp { server: server, packets: packets, options: options }
+    }
+
+    /// Send a payload, blocking until the last frame is sent
+    pub fn send(&mut self, payload: &[u8]) -> Result<()> {
+        let tx_dl = self.options.tx_dl;
+        if payload.len() > u32::max_value() as usize {
+            return Err(Error::new(ErrorKind::InvalidInput, "ISO-TP payloads are limited to 4 GiB"));
+        }
+
+        if let Some(dat) = single_frame(payload, tx_dl) {
+            return self.send_frame(dat);
+        }
+        let (dat, mut offset) = first_frame(payload, tx_dl);
+        self.send_frame(dat)?;
+
+        // Consecutive frames, in blocks acknowledged by flow control frames
+        let mut sn = 1;
+        while offset < payload.len() {
+            let (block_size, st_min) = self.wait_flow_control()?;
+            let mut count = 0;
+            while offset < payload.len() && (block_size == 0 || count < block_size) {
+                if count > 0 {
+                    thread::sleep(st_min);
+                }
+                let end = (offset + tx_dl - 1).min(payload.len());
+                self.send_frame(consecutive_frame(sn, &payload[offset..end]))?;
+                offset = end;
+                sn = (sn + 1) & 0x0F;
+                count += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive the next payload. `timeout` only bounds the wait for its first frame.
+    pub fn recv(&mut self, timeout: Option<Duration>) -> Result<Vec<u8>> {
+        let deadline = timeout.map(|x| Instant::now() + x);
+        let mut pending = None;
+        'transfer: loop {
+            let frame = match pending.take() {
+                Some(frame) => frame,
+                None => self.next_frame(deadline)?,
+            };
+            let (len, mut payload) = match frame {
+                Frame::Single(payload) => return Ok(payload),
+                Frame::First(len, mut payload) => {
+                    payload.truncate(len);
+                    (len, payload)
+                },
+                frame => { debug!("Ignoring ISO-TP frame {:?} outside of a transfer", frame); continue; },
+            };
+            if len > self.options.max_len {
+                self.send_flow_control(FC_OVFLW)?;
+                return Err(invalid(&format!("Refused a {} bytes ISO-TP transfer", len)));
+            }
+
+            let block_size = self.options.block_size;
+            let mut sn = 1;
+            let mut count = 0;
+            self.send_flow_control(FC_CTS)?;
+            while payload.len() < len {
+                match self.next_frame(Some(Instant::now() + self.options.n_cr)) {
+                    Ok(Frame::Consecutive(n, dat)) => {
+                        if n != sn {
+                            return Err(invalid(&format!("Wrong ISO-TP sequence number {}, expected {}", n, sn)));
+                        }
+                        let remaining = len - payload.len();
+                        payload.extend_from_slice(&dat[..dat.len().min(remaining)]);
+                        sn = (sn + 1) & 0x0F;
+                        count += 1;
+                        if block_size > 0 && count == block_size && payload.len() < len {
+                            count = 0;
+                            self.send_flow_control(FC_CTS)?;
+                        }
+                    },
+                    Ok(Frame::FlowControl(..)) => debug!("Ignoring ISO-TP flow control during reception"),
+                    Ok(frame) => {
+                        warn!("ISO-TP reception interrupted by a new transfer");
+                        pending = Some(frame);
+                        continue 'transfer;
+                    },
+                    Err(ref e) if e.kind() == ErrorKind::TimedOut => return Err(Error::new(ErrorKind::TimedOut, "N_Cr timeout")),
+                    Err(e) => return Err(e),
+                }
+            }
+            return Ok(payload);
+        }
+    }
+
+    /// Wait for a clear to send flow control, returns its block size and separation time
+    fn wait_flow_control(&mut self) -> Result<(u8, Duration)> {
+        let mut waits = 0;
+        loop {
+            match self.next_frame(Some(Instant::now() + self.options.n_bs)) {
+                Ok(Frame::FlowControl(FC_CTS, bs, st_min)) => return Ok((bs, st_min_duration(st_min))),
+                Ok(Frame::FlowControl(FC_WAIT, ..)) => {
+                    waits += 1;
+                    if waits > self.options.wft_max {
+                        return Err(Error::new(ErrorKind::TimedOut, "Too many ISO-TP wait flow controls"));
+                    }
+                },
+                Ok(Frame::FlowControl(FC_OVFLW, ..)) => return Err(Error::new(ErrorKind::Other, "ISO-TP receiver overflow")),
+                Ok(Frame::FlowControl(status, ..)) => return Err(invalid(&format!("Invalid flow status {}", status))),
+                Ok(frame) => debug!("Ignoring ISO-TP frame {:?} while waiting for flow control", frame),
+                Err(ref e) if e.kind() == ErrorKind::TimedOut => return Err(Error::new(ErrorKind::TimedOut, "N_Bs timeout")),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn send_flow_control(&mut self, status: u8) -> Result<()> {
+        let dat = flow_control_frame(status, self.options.block_size, self.options.st_min);
+        self.send_frame(dat)
+    }
+
+    /// Pad and send a frame on the tx id
+    fn send_frame(&mut self, mut dat: Vec<u8>) -> Result<()> {
+        let opts = &self.options;
+        if dat.len() > server::CAN_MAX_DLEN {
+            dat.resize(server::can_fd_len(dat.len()), opts.padding.unwrap_or(FD_PADDING));
+        } else if let Some(padding) = opts.padding {
+            dat.resize(server::CAN_MAX_DLEN, padding);
+        }
+        let packet = server::can_frame(opts.tx_id, dat, server::CanFlags {
+            extended: opts.extended || opts.tx_id > server::CAN_SFF_MAX,
+            fd: opts.fd,
+            brs: opts.brs,
+            ..Default::default()
+        });
+        trace!("ISO-TP frame sent: {}", server::data_to_string(&packet));
+
+        let start = Instant::now();
+        self.server.send_packet(packet);
+        if start.elapsed() > opts.n_as {
+            return Err(Error::new(ErrorKind::TimedOut, "N_As timeout"));
+        }
+        Ok(())
+    }
+
+    /// Next frame received on the rx id, waiting until the deadline if any
+    fn next_frame(&mut self, deadline: Option<Instant>) -> Result<Frame> {
+        loop {
+            let resp = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::new(ErrorKind::TimedOut, "ISO-TP timeout"));
+                    }
+                    self.packets.recv_timeout(deadline - now).map_err(|e| match e {
+                        RecvTimeoutError::Timeout => Error::new(ErrorKind::TimedOut, "ISO-TP timeout"),
+                        RecvTimeoutError::Disconnected => Error::new(ErrorKind::BrokenPipe, "Server connection closed"),
+                    })?
+                },
+                None => self.packets.recv().map_err(|_| Error::new(ErrorKind::BrokenPipe, "Server connection closed"))?,
+            };
+            if !resp.has_data() {
+                continue;
+            }
+            let data = resp.get_data();
+            if data.get_field_type() != rics::RICS_Data_RICS_DataType::CAN || data.get_id() != self.options.rx_id
+                || data.get_error() || data.get_rtr() {
+                continue;
+            }
+            trace!("ISO-TP frame received: {}", server::data_to_string(data));
+            match decode(data.get_data()) {
+                Ok(frame) => return Ok(frame),
+                Err(e) => warn!("{}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(frame: Frame) -> Vec<u8> {
+        match frame {
+            Frame::Single(payload) => payload,
+            frame => panic!("Not a single frame: {:?}", frame),
+        }
+    }
+
+    #[test]
+    fn single_frames() {
+        let payload: Vec<u8> = (1..=7).collect();
+        let dat = single_frame(&payload, 8).unwrap();
+        assert_eq!(dat[0], 0x07);
+        assert_eq!(single(decode(&dat).unwrap()), payload);
+        assert!(single_frame(&[0; 8], 8).is_none());
+        assert!(decode(&[0x05, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn fd_single_frames() {
+        let payload: Vec<u8> = (0..62).collect();
+        let dat = single_frame(&payload, 64).unwrap();
+        assert_eq!(&dat[..2], &[0x00, 62]);
+        assert_eq!(single(decode(&dat).unwrap()), payload);
+        // Short payloads keep the classic header on CAN FD
+        assert_eq!(single_frame(&[1, 2], 64).unwrap(), vec![0x02, 1, 2]);
+        assert!(single_frame(&[0; 63], 64).is_none());
+        assert!(decode(&[0x00, 10, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn first_frames() {
+        let payload: Vec<u8> = (0..100).collect();
+        let (dat, len) = first_frame(&payload, 8);
+        assert_eq!(len, 6);
+        assert_eq!(dat, vec![0x10, 100, 0, 1, 2, 3, 4, 5]);
+        match decode(&dat).unwrap() {
+            Frame::First(100, data) => assert_eq!(data, &payload[..6]),
+            frame => panic!("Not a first frame: {:?}", frame),
+        }
+
+        let payload = vec![0xAA; 5000];
+        let (dat, len) = first_frame(&payload, 64);
+        assert_eq!(len, 58);
+        assert_eq!(&dat[..6], &[0x10, 0x00, 0x00, 0x00, 0x13, 0x88]);
+        match decode(&dat).unwrap() {
+            Frame::First(5000, data) => assert_eq!(data.len(), 58),
+            frame => panic!("Not a first frame: {:?}", frame),
+        }
+    }
+
+    #[test]
+    fn first_frame_length() {
+        // Lengths that fit in a single frame of the first frame length
+        assert!(decode(&[0x10, 7, 1, 2, 3, 4, 5, 6]).is_err());
+        assert!(decode(&[0x10, 8, 1, 2, 3, 4, 5, 6]).is_ok());
+        let mut dat = vec![0x10, 62];
+        dat.resize(64, 0);
+        assert!(decode(&dat).is_err());
+        dat[1] = 63;
+        assert!(decode(&dat).is_ok());
+        assert!(decode(&[0x10, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn consecutive_and_flow_control_frames() {
+        let dat = consecutive_frame(0x0F, &[1, 2, 3]);
+        assert_eq!(dat, vec![0x2F, 1, 2, 3]);
+        match decode(&dat).unwrap() {
+            Frame::Consecutive(0x0F, data) => assert_eq!(data, vec![1, 2, 3]),
+            frame => panic!("Not a consecutive frame: {:?}", frame),
+        }
+        let dat = flow_control_frame(FC_WAIT, 8, 0xF3);
+        assert_eq!(dat, vec![0x31, 8, 0xF3]);
+        match decode(&dat).unwrap() {
+            Frame::FlowControl(FC_WAIT, 8, 0xF3) => (),
+            frame => panic!("Not a flow control frame: {:?}", frame),
+        }
+        assert!(decode(&[0x30, 0]).is_err());
+        assert!(decode(&[0x40]).is_err());
+    }
+
+    #[test]
+    fn separation_times() {
+        assert_eq!(st_min_duration(0x14), Duration::from_millis(20));
+        assert_eq!(st_min_duration(0xF5), Duration::from_micros(500));
+        assert_eq!(st_min_duration(0xFA), Duration::from_millis(127));
+    }
+}
@@ -0,0 +1,630 @@
+//! SAE J1939 over CAN packets
+//!
+//! J1939 frames use 29 bit identifiers holding a priority, a parameter group
+//! number (PGN) and the source and destination addresses. Nodes claim their
+//! address with a 64 bit NAME, and messages longer than 8 bytes are sent with
+//! the BAM (broadcast) or CMDT (connection mode) transport protocol.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::rics;
+use super::server::{self, RICSServer};
+
+/// Global destination address
+pub const ADDRESS_GLOBAL: u8 = 0xFF;
+/// Source address of nodes without an address
+pub const ADDRESS_NULL: u8 = 0xFE;
+
+pub const PGN_REQUEST: u32 = 0xEA00;
+pub const PGN_ADDRESS_CLAIMED: u32 = 0xEE00;
+pub const PGN_TP_CM: u32 = 0xEC00;
+pub const PGN_TP_DT: u32 = 0xEB00;
+
+/// Default priority of most messages
+pub const DEFAULT_PRIORITY: u8 = 6;
+/// Largest transport protocol payload
+pub const TP_MAX_LEN: usize = 255 * 7;
+
+/// TP.CM control bytes
+const TP_CM_RTS: u8 = 16;
+const TP_CM_CTS: u8 = 17;
+const TP_CM_EOMA: u8 = 19;
+const TP_CM_BAM: u8 = 32;
+const TP_CM_ABORT: u8 = 255;
+
+/// Abort reasons
+const ABORT_BUSY: u8 = 1;
+const ABORT_RESOURCES: u8 = 2;
+const ABORT_TIMEOUT: u8 = 3;
+
+/// Time to wait for contending address claims
+const CLAIM_TIMEOUT: Duration = Duration::from_millis(250);
+/// Gap between BAM data packets
+const BAM_GAP: Duration = Duration::from_millis(50);
+/// Time to wait for a CTS or an end of message acknowledgement
+const T3: Duration = Duration::from_millis(1250);
+/// Time after which an incomplete transfer is dropped
+const T1: Duration = Duration::from_millis(750);
+const T2: Duration = Duration::from_millis(1250);
+
+/// Name of the most common parameter groups
+pub fn pgn_name(pgn: u32) -> Option<&'static str> {
+    Some(match pgn {
+        0xE800 => "ACKM",
+        PGN_REQUEST => "RQST",
+        PGN_TP_DT => "TP.DT",
+        PGN_TP_CM => "TP.CM",
+        PGN_ADDRESS_CLAIMED => "ACL",
+        0xF001 => "EBC1",
+        0xF002 => "ETC1",
+        0xF003 => "EEC2",
+        0xF004 => "EEC1",
+        0xFECA => "DM1",
+        0xFECB => "DM2",
+        0xFEDA => "SOFT",
+        0xFEE0 => "VD",
+        0xFEE5 => "HOURS",
+        0xFEE6 => "TD",
+        0xFEE9 => "LFC1",
+        0xFEEC => "VI",
+        0xFEEE => "ET1",
+        0xFEEF => "EFL/P1",
+        0xFEF1 => "CCVS1",
+        0xFEF2 => "LFE1",
+        0xFEF5 => "AMB",
+        0xFEF6 => "IC1",
+        0xFEFC => "DD1",
+        _ => return None,
+    })
+}
+
+/// Fields of a J1939 identifier
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct J1939Id {
+    pub priority: u8,
+    pub pgn: u32,
+    pub source: u8,
+    /// Destination of PDU1 parameter groups, global for PDU2 ones
+    pub destination: u8,
+}
+
+impl J1939Id {
+    /// Split a 29 bit CAN identifier
+    pub fn from_can_id(id: u32) -> J1939Id {
+        let pf = (id >> 16) as u8;
+        let ps = (id >> 8) as u8;
+        let dp = (id >> 24) & 0x03;
+        let (pgn, destination) = if pf < 240 {
+            (dp << 16 | (pf as u32) << 8, ps)
+        } else {
+            (dp << 16 | (pf as u32) << 8 | ps as u32, ADDRESS_GLOBAL)
+        };
+        J1939Id { priority: ((id >> 26) & 0x07) as u8, pgn: pgn, source: id as u8, destination: destination }
+    }
+
+    /// 29 bit CAN identifier
+    pub fn to_can_id(&self) -> u32 {
+        let pf = (self.pgn >> 8) as u8;
+        let ps = if pf < 240 { self.destination as u32 } else { self.pgn & 0xFF };
+        (self.priority as u32 & 0x07) << 26 | (self.pgn & 0x3FF00) << 8 | ps << 8 | self.source as u32
+    }
+}
+
+/// Complete J1939 message, possibly reassembled from a transport protocol transfer
+#[derive(Clone, Debug)]
+pub struct J1939Message {
+    pub id: J1939Id,
+    pub data: Vec<u8>,
+    /// Received through the transport protocol
+    pub transport: bool,
+}
+
+impl J1939Message {
+    /// Message of a CAN packet, None if it isn't an extended data frame
+    pub fn from_data(data: &rics::RICS_Data) -> Option<J1939Message> {
+        if data.get_field_type() != rics::RICS_Data_RICS_DataType::CAN || !data.get_extended() || data.get_rtr() || data.get_error() {
+            return None;
+        }
+        Some(J1939Message { id: J1939Id::from_can_id(data.get_id() as u32), data: data.get_data().to_vec(), transport: false })
+    }
+}
+
+/// `priority PGN name SA -> DA [len] data` description
+impl fmt::Display for J1939Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {:05X} {:<6} {:02X} -> {:02X} [{}] {}{}", self.id.priority, self.id.pgn,
+                pgn_name(self.id.pgn).unwrap_or("-"), self.id.source, self.id.destination, self.data.len(),
+                self.data.iter().map(|x| format!("{:02X}", x)).collect::<Vec<_>>().join(" "),
+                if self.transport { " (TP)" } else { "" })
+    }
+}
+
+/// CAN packet of a single frame J1939 message
+pub fn j1939_packet(id: J1939Id, dat: Vec<u8>) -> rics::RICS_Data {
+    server::can_frame(id.to_can_id() as i32, dat, server::CanFlags { extended: true, ..Default::default() })
+}
+
+/// PGN of a TP.CM message, in its last 3 bytes
+fn tp_pgn(dat: &[u8]) -> u32 {
+    dat[5] as u32 | (dat[6] as u32) << 8 | (dat[7] as u32) << 16
+}
+
+fn tp_cm(control: u8, b1: u8, b2: u8, b3: u8, b4: u8, pgn: u32) -> Vec<u8> {
+    vec![control, b1, b2, b3, b4, pgn as u8, (pgn >> 8) as u8, (pgn >> 16) as u8]
+}
+
+/// NAME of an address claim
+fn claim_name(dat: &[u8]) -> u64 {
+    u64::from_le_bytes([dat[0], dat[1], dat[2], dat[3], dat[4], dat[5], dat[6], dat[7]])
+}
+
+/// Transport protocol transfer in progress
+struct Transfer {
+    priority: u8,
+    pgn: u32,
+    size: usize,
+    packets: u8,
+    data: Vec<u8>,
+    /// Next expected sequence number
+    next: u8,
+    /// Packets allowed per CTS, for connection mode transfers
+    per_cts: Option<u8>,
+    last: Instant,
+}
+
+/// Reassembles transport protocol transfers from the observed traffic
+pub struct Reassembler {
+    /// Transfers by source and destination address
+    transfers: HashMap<(u8, u8), Transfer>,
+}
+
+impl Reassembler {
+    pub fn new() -> Reassembler {
+        Reassembler { transfers: HashMap::new() }
+    }
+
+    /// Feed a received message. TP.CM and TP.DT messages are consumed, and
+    /// the reassembled message is returned once their transfer is complete.
+    pub fn push(&mut self, msg: J1939Message) -> Option<J1939Message> {
+        let key = (msg.id.source, msg.id.destination);
+        let dat = &msg.data;
+        match msg.id.pgn {
+            PGN_TP_CM if dat.len() == 8 => {
+                match dat[0] {
+                    TP_CM_BAM | TP_CM_RTS => {
+                        if self.transfers.remove(&key).is_some() {
+                            debug!("Transfer from {:02X} to {:02X} restarted", key.0, key.1);
+                        }
+                        let (size, packets) = (u16::from_le_bytes([dat[1], dat[2]]) as usize, dat[3]);
+                        if size == 0 || size > TP_MAX_LEN || size > packets as usize * 7 {
+                            warn!("Transfer from {:02X} to {:02X}: invalid size of {} bytes in {} packets", key.0, key.1, size, packets);
+                            return None;
+                        }
+                        self.transfers.insert(key, Transfer {
+                            priority: msg.id.priority,
+                            pgn: tp_pgn(dat),
+                            size: size,
+                            packets: packets,
+                            data: vec![],
+                            next: 1,
+                            per_cts: if dat[0] == TP_CM_RTS { Some(dat[4]) } else { None },
+                            last: Instant::now(),
+                        });
+                    },
+                    TP_CM_ABORT => {
+                        // Aborts are sent by either side of the connection
+                        self.transfers.remove(&key);
+                        self.transfers.remove(&(key.1, key.0));
+                    },
+                    _ => (),
+                }
+                None
+            },
+            PGN_TP_DT if !dat.is_empty() => {
+                let transfer = self.transfers.get_mut(&key)?;
+                if dat[0] != transfer.next {
+                    warn!("Transfer from {:02X} to {:02X}: packet {} instead of {}", key.0, key.1, dat[0], transfer.next);
+                    self.transfers.remove(&key);
+                    return None;
+                }
+                transfer.data.extend_from_slice(&dat[1..]);
+                transfer.last = Instant::now();
+                if transfer.data.len() < transfer.size && transfer.next < transfer.packets {
+                    transfer.next += 1;
+                    return None;
+                }
+                let mut transfer = self.transfers.remove(&key).unwrap();
+                transfer.data.truncate(transfer.size);
+                Some(J1939Message {
+                    id: J1939Id { priority: transfer.priority, pgn: transfer.pgn, source: key.0, destination: key.1 },
+                    data: transfer.data,
+                    transport: true,
+                })
+            },
+            PGN_TP_CM | PGN_TP_DT => None,
+            _ => Some(msg),
+        }
+    }
+
+    /// Packets received, packets expected, packets per CTS and PGN of a connection mode transfer
+    fn progress(&self, source: u8, destination: u8) -> Option<(u8, u8, u8, u32)> {
+        self.transfers.get(&(source, destination)).and_then(|t| t.per_cts.map(|n| (t.next - 1, t.packets, n, t.pgn)))
+    }
+
+    /// Drop stalled transfers, returns their source and destination addresses and PGN
+    fn expire(&mut self) -> Vec<(u8, u8, u32)> {
+        let expired: Vec<(u8, u8, u32)> = self.transfers.iter()
+            .filter(|(_, t)| t.last.elapsed() > if t.next == 1 { T2 } else { T1 })
+            .map(|(k, t)| (k.0, k.1, t.pgn)).collect();
+        for (source, destination, _) in expired.iter() {
+            warn!("Transfer from {:02X} to {:02X} timed out", source, destination);
+            self.transfers.remove(&(*source, *destination));
+        }
+        expired
+    }
+}
+
+/// J1939 node over a connected RICS node
+pub struct J1939 {
+    server: RICSServer,
+    packets: Receiver<rics::RICS_Response>,
+    /// 64 bit NAME used to claim the address
+    pub name: u64,
+    /// Current source address, ADDRESS_NULL if none could be claimed
+    pub address: u8,
+    reassembler: Reassembler,
+    /// Messages received while sending
+    pending: VecDeque<J1939Message>,
+}
+
+impl J1939 {
+    /// The server must be connected as a node, its packets can't be read elsewhere anymore.
+    /// The address is used as is until `claim_address` is called.
+    pub fn new(mut server: RICSServer, name: u64, address: u8) -> J1939 {
+        let packets = server.listen_response();
+        J1939 {
+            server: server,
+            packets: packets,
+            name: name,
+            address: address,
+            reassembler: Reassembler::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// NAME bit allowing the node to pick another address on conflicts
+    fn arbitrary_address_capable(&self) -> bool {
+        self.name >> 63 != 0
+    }
+
+    fn send_frame(&mut self, priority: u8, pgn: u32, destination: u8, dat: Vec<u8>) {
+        let id = J1939Id { priority: priority, pgn: pgn, source: self.address, destination: destination };
+        self.server.send_packet(j1939_packet(id, dat));
+    }
+
+    fn send_address_claimed(&mut self) {
+        let name = self.name.to_le_bytes().to_vec();
+        self.send_frame(DEFAULT_PRIORITY, PGN_ADDRESS_CLAIMED, ADDRESS_GLOBAL, name);
+    }
+
+    /// Claim the current address, moving to another one on conflicts if the NAME allows it
+    pub fn claim_address(&mut self) -> Result<u8> {
+        let mut taken = vec![];
+        loop {
+            info!("Claiming address {:02X}", self.address);
+            self.send_address_claimed();
+            let deadline = Instant::now() + CLAIM_TIMEOUT;
+            let lost = loop {
+                let msg = match self.next_frame(Some(deadline)) {
+                    Ok(msg) => msg,
+                    Err(ref e) if e.kind() == ErrorKind::TimedOut => break false,
+                    Err(e) => return Err(e),
+                };
+                if msg.id.pgn != PGN_ADDRESS_CLAIMED || msg.data.len() != 8 {
+                    if let Some(msg) = self.receive(msg) {
+                        self.pending.push_back(msg);
+                    }
+                } else if msg.id.source == self.address {
+                    if claim_name(&msg.data) < self.name {
+                        break true;
+                    }
+                    self.send_address_claimed();
+                } else if msg.id.source != ADDRESS_NULL {
+                    taken.push(msg.id.source);
+                }
+            };
+            if !lost {
+                return Ok(self.address);
+            }
+
+            // Try the self-configurable range
+            warn!("Address {:02X} is already claimed", self.address);
+            taken.push(self.address);
+            match (128..248).map(|x| x as u8).find(|x| !taken.contains(x)) {
+                Some(address) if self.arbitrary_address_capable() => self.address = address,
+                _ => {
+                    self.address = ADDRESS_NULL;
+                    self.send_address_claimed();
+                    return Err(Error::new(ErrorKind::AddrInUse, "Cannot claim a J1939 address"));
+                },
+            }
+        }
+    }
+
+    /// Send a message, with the transport protocol if it is longer than 8 bytes.
+    /// Global messages use BAM, others use CMDT and wait for the receiver.
+    pub fn send(&mut self, priority: u8, pgn: u32, destination: u8, dat: &[u8]) -> Result<()> {
+        if dat.len() <= server::CAN_MAX_DLEN {
+            self.send_frame(priority, pgn, destination, dat.to_vec());
+            return Ok(());
+        }
+        if dat.len() > TP_MAX_LEN {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("J1939 messages are limited to {} bytes", TP_MAX_LEN)));
+        }
+        let packets = ((dat.len() + 6) / 7) as u8;
+        let size = (dat.len() as u16).to_le_bytes();
+        let packet = |seq: u8| {
+            let mut dt = vec![seq];
+            dt.extend_from_slice(&dat[(seq as usize - 1) * 7..(seq as usize * 7).min(dat.len())]);
+            dt.resize(8, 0xFF);
+            dt
+        };
+
+        if destination == ADDRESS_GLOBAL {
+            self.send_frame(priority, PGN_TP_CM, destination, tp_cm(TP_CM_BAM, size[0], size[1], packets, 0xFF, pgn));
+            for seq in 1..=packets {
+                thread::sleep(BAM_GAP);
+                self.send_frame(priority, PGN_TP_DT, destination, packet(seq));
+            }
+            return Ok(());
+        }
+
+        self.send_frame(priority, PGN_TP_CM, destination, tp_cm(TP_CM_RTS, size[0], size[1], packets, 0xFF, pgn));
+        loop {
+            let cm = self.wait_tp_cm(destination, pgn)?;
+            match cm[0] {
+                TP_CM_CTS => {
+                    if cm[1] == 0 {
+                        // Hold the connection open
+                        continue;
+                    }
+                    let first = cm[2].max(1);
+                    let last = first.saturating_add(cm[1] - 1).min(packets);
+                    for seq in first..=last {
+                        self.send_frame(priority, PGN_TP_DT, destination, packet(seq));
+                    }
+                },
+                TP_CM_EOMA => return Ok(()),
+                TP_CM_ABORT => return Err(Error::new(ErrorKind::ConnectionAborted, format!("Transfer aborted, reason {}", cm[1]))),
+                _ => (),
+            }
+        }
+    }
+
+    /// Wait for a TP.CM answer of the destination for a connection mode transfer
+    fn wait_tp_cm(&mut self, destination: u8, pgn: u32) -> Result<Vec<u8>> {
+        let deadline = Instant::now() + T3;
+        loop {
+            let msg = match self.next_frame(Some(deadline)) {
+                Ok(msg) => msg,
+                Err(ref e) if e.kind() == ErrorKind::TimedOut => {
+                    self.send_frame(DEFAULT_PRIORITY, PGN_TP_CM, destination, tp_cm(TP_CM_ABORT, ABORT_TIMEOUT, 0xFF, 0xFF, 0xFF, pgn));
+                    return Err(Error::new(ErrorKind::TimedOut, "J1939 transport timeout"));
+                },
+                Err(e) => return Err(e),
+            };
+            if msg.id.pgn == PGN_TP_CM && msg.id.source == destination && msg.id.destination == self.address
+                && msg.data.len() == 8 && tp_pgn(&msg.data) == pgn && msg.data[0] != TP_CM_RTS {
+                return Ok(msg.data);
+            }
+            if let Some(msg) = self.receive(msg) {
+                self.pending.push_back(msg);
+            }
+        }
+    }
+
+    /// Receive the next message for this node, or global. `timeout` bounds the whole wait.
+    pub fn recv(&mut self, timeout: Option<Duration>) -> Result<J1939Message> {
+        let deadline = timeout.map(|x| Instant::now() + x);
+        loop {
+            if let Some(msg) = self.pending.pop_front() {
+                return Ok(msg);
+            }
+            let msg = match self.next_frame(deadline.or_else(|| Some(Instant::now() + T1))) {
+                Ok(msg) => msg,
+                Err(ref e) if e.kind() == ErrorKind::TimedOut && deadline.map(|x| Instant::now() < x).unwrap_or(true) => {
+                    self.expire();
+                    continue;
+                },
+                Err(e) => return Err(e),
+            };
+            if let Some(msg) = self.receive(msg) {
+                return Ok(msg);
+            }
+        }
+    }
+
+    /// Abort transfers to this node that stalled
+    fn expire(&mut self) {
+        for (source, destination, pgn) in self.reassembler.expire() {
+            if destination == self.address {
+                self.send_frame(DEFAULT_PRIORITY, PGN_TP_CM, source, tp_cm(TP_CM_ABORT, ABORT_TIMEOUT, 0xFF, 0xFF, 0xFF, pgn));
+            }
+        }
+    }
+
+    /// Handle network management and transport protocol messages, returns the messages for this node
+    fn receive(&mut self, msg: J1939Message) -> Option<J1939Message> {
+        let to_us = msg.id.destination == self.address || msg.id.destination == ADDRESS_GLOBAL;
+        match msg.id.pgn {
+            PGN_REQUEST if to_us && msg.data.len() >= 3 => {
+                let requested = msg.data[0] as u32 | (msg.data[1] as u32) << 8 | (msg.data[2] as u32) << 16;
+                if requested == PGN_ADDRESS_CLAIMED {
+                    self.send_address_claimed();
+                    return None;
+                }
+            },
+            PGN_ADDRESS_CLAIMED if msg.id.source == self.address && self.address != ADDRESS_NULL && msg.data.len() == 8 => {
+                let other = claim_name(&msg.data);
+                if other < self.name {
+                    warn!("Address {:02X} lost to NAME {:016X}", self.address, other);
+                    self.address = ADDRESS_NULL;
+                }
+                self.send_address_claimed();
+                return None;
+            },
+            PGN_TP_CM if msg.id.destination == self.address && msg.data.len() == 8 && msg.data[0] == TP_CM_RTS => {
+                let (source, pgn, packets, per_cts) = (msg.id.source, tp_pgn(&msg.data), msg.data[3], msg.data[4]);
+                if self.reassembler.progress(source, self.address).is_some() {
+                    self.send_frame(DEFAULT_PRIORITY, PGN_TP_CM, source, tp_cm(TP_CM_ABORT, ABORT_BUSY, 0xFF, 0xFF, 0xFF, pgn));
+                    return None;
+                }
+                self.reassembler.push(msg);
+                if self.reassembler.progress(source, self.address).is_none() {
+                    self.send_frame(DEFAULT_PRIORITY, PGN_TP_CM, source, tp_cm(TP_CM_ABORT, ABORT_RESOURCES, 0xFF, 0xFF, 0xFF, pgn));
+                    return None;
+                }
+                self.send_frame(DEFAULT_PRIORITY, PGN_TP_CM, source, tp_cm(TP_CM_CTS, packets.min(per_cts), 1, 0xFF, 0xFF, pgn));
+                return None;
+            },
+            _ => (),
+        }
+
+        let complete = self.reassembler.push(msg)?;
+        if complete.transport && complete.id.destination == self.address {
+            let size = (complete.data.len() as u16).to_le_bytes();
+            let packets = ((complete.data.len() + 6) / 7) as u8;
+            self.send_frame(DEFAULT_PRIORITY, PGN_TP_CM, complete.id.source, tp_cm(TP_CM_EOMA, size[0], size[1], packets, 0xFF, complete.id.pgn));
+        }
+        if complete.id.destination == self.address || complete.id.destination == ADDRESS_GLOBAL {
+            Some(complete)
+        } else {
+            None
+        }
+    }
+
+    /// Next J1939 frame, sending the next CTS of connection mode transfers to this node
+    fn next_frame(&mut self, deadline: Option<Instant>) -> Result<J1939Message> {
+        loop {
+            let resp = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::new(ErrorKind::TimedOut, "J1939 timeout"));
+                    }
+                    self.packets.recv_timeout(deadline - now).map_err(|e| match e {
+                        RecvTimeoutError::Timeout => Error::new(ErrorKind::TimedOut, "J1939 timeout"),
+                        RecvTimeoutError::Disconnected => Error::new(ErrorKind::BrokenPipe, "Server connection closed"),
+                    })?
+                },
+                None => self.packets.recv().map_err(|_| Error::new(ErrorKind::BrokenPipe, "Server connection closed"))?,
+            };
+            if !resp.has_data() {
+                continue;
+            }
+            let msg = match J1939Message::from_data(resp.get_data()) {
+                Some(msg) => msg,
+                None => continue,
+            };
+            trace!("J1939 frame received: {}", msg);
+
+            // Ask for the next block once the current one is received
+            if msg.id.pgn == PGN_TP_DT && msg.id.destination == self.address && !msg.data.is_empty() {
+                if let Some((received, packets, per_cts, pgn)) = self.reassembler.progress(msg.id.source, self.address) {
+                    let received = received + 1;
+                    if msg.data[0] == received && received < packets && received % per_cts.max(1) == 0 {
+                        let count = (packets - received).min(per_cts);
+                        self.send_frame(DEFAULT_PRIORITY, PGN_TP_CM, msg.id.source, tp_cm(TP_CM_CTS, count, received + 1, 0xFF, 0xFF, pgn));
+                    }
+                }
+            }
+            return Ok(msg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(pgn: u32, source: u8, destination: u8, data: Vec<u8>) -> J1939Message {
+        J1939Message { id: J1939Id { priority: DEFAULT_PRIORITY, pgn: pgn, source: source, destination: destination }, data: data, transport: false }
+    }
+
+    fn bam(size: usize, packets: u8) -> J1939Message {
+        let size = (size as u16).to_le_bytes();
+        message(PGN_TP_CM, 0x10, ADDRESS_GLOBAL, tp_cm(TP_CM_BAM, size[0], size[1], packets, 0xFF, 0xFECA))
+    }
+
+    fn dt(sn: u8, dat: &[u8]) -> J1939Message {
+        let mut data = vec![sn];
+        data.extend_from_slice(dat);
+        data.resize(8, 0xFF);
+        message(PGN_TP_DT, 0x10, ADDRESS_GLOBAL, data)
+    }
+
+    #[test]
+    fn pdu1_id() {
+        let id = J1939Id::from_can_id(0x18EA2301);
+        assert_eq!(id, J1939Id { priority: 6, pgn: PGN_REQUEST, source: 0x01, destination: 0x23 });
+        assert_eq!(id.to_can_id(), 0x18EA2301);
+    }
+
+    #[test]
+    fn pdu2_id() {
+        let id = J1939Id::from_can_id(0x0CF00400);
+        assert_eq!(id, J1939Id { priority: 3, pgn: 0xF004, source: 0x00, destination: ADDRESS_GLOBAL });
+        assert_eq!(id.to_can_id(), 0x0CF00400);
+        // Data page and extended data page bits
+        let id = J1939Id { priority: 7, pgn: 0x3FF12, source: 0xAB, destination: ADDRESS_GLOBAL };
+        assert_eq!(id.to_can_id(), 0x1FFF12AB);
+        assert_eq!(J1939Id::from_can_id(0x1FFF12AB), id);
+    }
+
+    #[test]
+    fn bam_transfer() {
+        let mut reassembler = Reassembler::new();
+        assert!(reassembler.push(bam(10, 2)).is_none());
+        assert!(reassembler.push(dt(1, &[1, 2, 3, 4, 5, 6, 7])).is_none());
+        let msg = reassembler.push(dt(2, &[8, 9, 10])).unwrap();
+        assert!(msg.transport);
+        assert_eq!(msg.id.pgn, 0xFECA);
+        assert_eq!(msg.data, (1..=10).collect::<Vec<u8>>());
+        assert!(reassembler.push(dt(3, &[])).is_none());
+    }
+
+    #[test]
+    fn longest_transfer() {
+        let mut reassembler = Reassembler::new();
+        assert!(reassembler.push(bam(TP_MAX_LEN, 255)).is_none());
+        for sn in 1..255 {
+            assert!(reassembler.push(dt(sn, &[sn; 7])).is_none());
+        }
+        assert_eq!(reassembler.push(dt(255, &[255; 7])).unwrap().data.len(), TP_MAX_LEN);
+    }
+
+    #[test]
+    fn invalid_transfer_size() {
+        let mut reassembler = Reassembler::new();
+        // More data than the packets can carry
+        assert!(reassembler.push(bam(1800, 255)).is_none());
+        assert!(reassembler.push(dt(1, &[0; 7])).is_none());
+        assert!(reassembler.transfers.is_empty());
+        assert!(reassembler.push(bam(0, 0)).is_none());
+        assert!(reassembler.transfers.is_empty());
+    }
+
+    #[test]
+    fn wrong_sequence_number() {
+        let mut reassembler = Reassembler::new();
+        reassembler.push(bam(20, 3));
+        reassembler.push(dt(1, &[0; 7]));
+        assert!(reassembler.push(dt(3, &[0; 7])).is_none());
+        assert!(reassembler.transfers.is_empty());
+    }
+}
@@ -12,3 +12,27 @@ pub mod script;
 pub mod server;
 pub mod rics;
 pub mod host;
+pub mod record;
+pub mod pcap;
+pub mod candump;
+pub mod asc;
+pub mod blf;
+#[cfg(target_family="unix")]
+pub mod canfd;
+#[cfg(target_family="unix")]
+pub mod pty;
+pub mod uart;
+pub mod isotp;
+pub mod uds;
+pub mod ecu;
+pub mod j1939;
+pub mod eds;
+pub mod canopen;
+pub mod dbc;
+pub mod scheduler;
+pub mod restbus;
+pub mod reliable;
+pub mod bridge;
+pub mod rpc;
+#[cfg(test)]
+mod testutil;
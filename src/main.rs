@@ -23,20 +23,473 @@ mod script;
 mod server;
 mod rics;
 mod host;
+mod record;
+mod pcap;
+mod candump;
+mod asc;
+mod blf;
+#[cfg(target_family="unix")]
+mod canfd;
+#[cfg(target_family="unix")]
+mod pty;
+mod uart;
+mod isotp;
+mod uds;
+mod ecu;
+mod j1939;
+mod eds;
+mod canopen;
+mod dbc;
+mod scheduler;
+mod restbus;
+mod reliable;
+mod bridge;
+mod rpc;
 mod gui;
+#[cfg(test)]
+mod testutil;
 use host::ServerState;
 
 use std::fs::File;
 use std::thread;
-use std::io::{stdout, Read, Write};
+use std::io::{stdout, BufRead, Read, Write};
 use std::time::Duration;
 #[cfg(target_family="unix")]
 use std::os::unix::io::FromRawFd;
 use std::sync::{Arc, RwLock, Mutex};
-use clap::{Arg, App, SubCommand};
+use clap::{Arg, App, ArgMatches, SubCommand};
 use std::convert::TryInto;
 use std::sync::mpsc::channel;
 
+/// Parse a decimal or 0x prefixed hexadecimal id
+fn parse_id(s: &str) -> i32 {
+    let parsed = if s.starts_with("0x") || s.starts_with("0X") {
+        u32::from_str_radix(&s[2..], 16).map(|x| x as i32).ok()
+    } else {
+        s.parse::<i32>().ok()
+    };
+    parsed.unwrap_or_else(|| { error!("Invalid id: {}", s); std::process::exit(1) })
+}
+
+/// Parse hexadecimal bytes, optionally separated by spaces
+fn parse_hex(s: &str) -> Vec<u8> {
+    server::parse_hex(s).unwrap_or_else(|| { error!("Invalid hex data: {}", s); std::process::exit(1) })
+}
+
+/// Format bytes as space separated hexadecimal
+fn hex_string(dat: &[u8]) -> String {
+    dat.iter().map(|x| format!("{:02X}", x)).collect::<Vec<_>>().join(" ")
+}
+
+/// Arguments shared by the isotp commands
+fn isotp_args<'a, 'b>(cmd: App<'a, 'b>) -> App<'a, 'b> {
+    cmd.arg(Arg::with_name("tx_id")
+            .long("tx-id")
+            .takes_value(true)
+            .required(true)
+            .help("CAN id of the frames sent"))
+        .arg(Arg::with_name("rx_id")
+             .long("rx-id")
+             .takes_value(true)
+             .required(true)
+             .help("CAN id of the frames received"))
+        .arg(Arg::with_name("extended")
+             .short("e")
+             .long("ext")
+             .help("Use 29 bit identifiers, implied by ids above 0x7FF"))
+        .arg(Arg::with_name("fd")
+             .long("fd")
+             .takes_value(true)
+             .min_values(0)
+             .max_values(1)
+             .help("Send CAN FD frames, of 64 bytes unless another length is given"))
+        .arg(Arg::with_name("brs")
+             .long("brs")
+             .requires("fd")
+             .help("Set the CAN FD bit rate switch flag"))
+        .arg(Arg::with_name("padding")
+             .short("p")
+             .long("padding")
+             .takes_value(true)
+             .help("Pad the frames to 8 bytes with this byte"))
+        .arg(Arg::with_name("block_size")
+             .long("bs")
+             .takes_value(true)
+             .help("Block size requested from the sender, 0 for no limit"))
+        .arg(Arg::with_name("st_min")
+             .long("stmin")
+             .takes_value(true)
+             .help("Separation time requested from the sender, as a raw STmin value"))
+        .arg(Arg::with_name("timeout")
+             .long("timeout")
+             .takes_value(true)
+             .help("N_Bs and N_Cr timeouts in milliseconds, 1000 by default"))
+        .arg(Arg::with_name("name")
+             .short("n")
+             .long("name")
+             .takes_value(true)
+             .help("Name of the node"))
+}
+
+/// ISO-TP channel parameters from the isotp command arguments
+fn isotp_options(matches: &ArgMatches) -> isotp::IsoTpOptions {
+    let mut opts = isotp::IsoTpOptions::new(parse_id(matches.value_of("tx_id").unwrap()), parse_id(matches.value_of("rx_id").unwrap()));
+    opts.extended = matches.is_present("extended");
+    if matches.is_present("fd") {
+        let tx_dl = matches.value_of("fd").map(|x| x.parse::<usize>().expect("Invalid CAN FD data length")).unwrap_or(server::CANFD_MAX_DLEN);
+        if tx_dl < server::CAN_MAX_DLEN || tx_dl > server::CANFD_MAX_DLEN || server::can_fd_len(tx_dl) != tx_dl {
+            error!("Invalid CAN FD data length {}", tx_dl);
+            std::process::exit(1);
+        }
+        opts = opts.with_fd(tx_dl, matches.is_present("brs"));
+    }
+    opts.padding = matches.value_of("padding").map(|x| parse_id(x) as u8);
+    opts.block_size = matches.value_of("block_size").map(|x| x.parse::<u8>().expect("Invalid block size")).unwrap_or(0);
+    opts.st_min = matches.value_of("st_min").map(|x| parse_id(x) as u8).unwrap_or(0);
+    if let Some(timeout) = matches.value_of("timeout") {
+        let timeout = Duration::from_millis(timeout.parse::<u64>().expect("Invalid timeout"));
+        opts.n_bs = timeout;
+        opts.n_cr = timeout;
+    }
+    opts
+}
+
+/// Arguments shared by the uds commands
+fn uds_args<'a, 'b>(cmd: App<'a, 'b>) -> App<'a, 'b> {
+    isotp_args(cmd)
+        .arg(Arg::with_name("session")
+             .long("session")
+             .takes_value(true)
+             .help("Enter this diagnostic session first"))
+        .arg(Arg::with_name("security")
+             .long("security")
+             .takes_value(true)
+             .help("Unlock this security level first"))
+        .arg(Arg::with_name("lua")
+             .long("lua")
+             .takes_value(true)
+             .help("Lua file defining the rics_uds_key(level, seed) key function"))
+        .arg(Arg::with_name("dynlib")
+             .short("d")
+             .long("dynlib")
+             .takes_value(true)
+             .conflicts_with("lua")
+             .help("Dynamic library exporting the rics_uds_key key function"))
+}
+
+/// Security access key function from the uds command arguments
+fn uds_key_function(matches: &ArgMatches) -> Box<dyn FnMut(u8, &[u8]) -> Option<Vec<u8>>> {
+    if let Some(path) = matches.value_of("dynlib") {
+        let lib = script::DynlibKey::new(path.to_string()).unwrap_or_else(|e| {
+            error!("{}", e);
+            std::process::exit(1);
+        });
+        return Box::new(move |level, seed| lib.key(level, seed));
+    }
+    if let Some(_path) = matches.value_of("lua") {
+        #[cfg(feature="pluginlua")]
+        {
+        let lua = script::LuaUds::new(_path.to_string());
+        return Box::new(move |level, seed| lua.key(level, seed));
+        }
+        #[cfg(not(feature="pluginlua"))]
+        {
+        error!("Lua key functions need the executable to be build with lua support");
+        std::process::exit(1);
+        }
+    }
+    error!("Security access needs a key function, use --lua or --dynlib");
+    std::process::exit(1);
+}
+
+/// Run a uds command
+fn uds_command(client: &mut uds::UdsClient, cmd: &str, matches: &ArgMatches) -> uds::UdsResult<()> {
+    if let Some(session) = matches.value_of("session") {
+        client.session_control(parse_id(session) as u8)?;
+    }
+    if let Some(level) = matches.value_of("security") {
+        client.security_access(parse_id(level) as u8, &mut *uds_key_function(matches))?;
+    }
+    match cmd {
+        "session" => {
+            let params = client.session_control(parse_id(matches.value_of("SESSION").unwrap()) as u8)?;
+            println!("{}", hex_string(&params));
+        },
+        "security" => {
+            let level = parse_id(matches.value_of("LEVEL").unwrap()) as u8;
+            client.security_access(level, &mut *uds_key_function(matches))?;
+            println!("Security level 0x{:02X} unlocked", level);
+        },
+        "read" => {
+            let data = client.read_data_by_identifier(parse_id(matches.value_of("DID").unwrap()) as u16)?;
+            println!("{}", hex_string(&data));
+        },
+        "write" => {
+            client.write_data_by_identifier(parse_id(matches.value_of("DID").unwrap()) as u16, &parse_hex(matches.value_of("DATA").unwrap()))?;
+        },
+        "routine" => {
+            let control = if matches.is_present("stop") {
+                uds::ROUTINE_STOP
+            } else if matches.is_present("results") {
+                uds::ROUTINE_RESULTS
+            } else {
+                uds::ROUTINE_START
+            };
+            let options = matches.value_of("DATA").map(parse_hex).unwrap_or_default();
+            let status = client.routine_control(control, parse_id(matches.value_of("ROUTINE").unwrap()) as u16, &options)?;
+            println!("{}", hex_string(&status));
+        },
+        "download" => {
+            let data = std::fs::read(matches.value_of("FILE").unwrap()).expect("Can't read download file");
+            let format = matches.value_of("format").map(|x| parse_id(x) as u8).unwrap_or(0);
+            client.download(parse_id(matches.value_of("ADDRESS").unwrap()) as u32, &data, format)?;
+            println!("Downloaded {} bytes", data.len());
+        },
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Arguments shared by the bridge commands
+fn bridge_args<'a, 'b>(cmd: App<'a, 'b>) -> App<'a, 'b> {
+    cmd.arg(Arg::with_name("name")
+            .short("n")
+            .long("name")
+            .takes_value(true)
+            .help("Name of the node"))
+        .arg(filter_arg())
+}
+
+/// Arguments shared by the j1939 node commands
+fn j1939_args<'a, 'b>(cmd: App<'a, 'b>) -> App<'a, 'b> {
+    cmd.arg(Arg::with_name("address")
+            .short("a")
+            .long("address")
+            .takes_value(true)
+            .help("Source address, or preferred address with --claim, 0x80 by default"))
+        .arg(Arg::with_name("claim")
+             .short("c")
+             .long("claim")
+             .takes_value(true)
+             .help("Claim the address with this 64 bit NAME first"))
+        .arg(Arg::with_name("name")
+             .short("n")
+             .long("name")
+             .takes_value(true)
+             .help("Name of the node"))
+}
+
+/// Connect a J1939 node, claiming its address if asked to
+fn j1939_node(mut svr: server::RICSServer, matches: &ArgMatches) -> j1939::J1939 {
+    svr.connect(true);
+    if let Some(name) = matches.value_of("name") {
+        svr.set_name(name);
+    }
+    receive_filter(&mut svr, matches);
+    info!("J1939 on node id {}", svr.who_am_i());
+    let address = matches.value_of("address").map(|x| parse_id(x) as u8).unwrap_or(0x80);
+    let name = matches.value_of("claim").map(|x| u64::from_str_radix(x.trim_start_matches("0x"), 16)
+                                            .unwrap_or_else(|_| { error!("Invalid NAME: {}", x); std::process::exit(1) }));
+    let mut node = j1939::J1939::new(svr, name.unwrap_or(0), address);
+    if name.is_some() {
+        match node.claim_address() {
+            Ok(address) => println!("Claimed address {:02X}", address),
+            Err(e) => { error!("{}", e); std::process::exit(1); },
+        }
+    }
+    node
+}
+
+/// DBC file argument of the loggers
+fn dbc_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("dbc")
+        .long("dbc")
+        .takes_value(true)
+        .help("Decode CAN messages with this DBC file")
+}
+
+fn subscribe_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("subscribe")
+        .short("s")
+        .long("subscribe")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .help("Also receive packets regardless of routes, as can:ID[-ID], datagram:PORT[-PORT] or topic:NAME")
+}
+
+/// Make the `--subscribe` subscriptions of a node
+fn subscribe(svr: &mut server::RICSServer, matches: &ArgMatches) {
+    for spec in matches.values_of("subscribe").into_iter().flatten() {
+        let subscription = server::Subscription::parse(spec).unwrap_or_else(|| { error!("Invalid subscription: {}", spec); std::process::exit(1) });
+        svr.subscribe(&subscription);
+    }
+}
+
+fn filter_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("filter")
+        .long("filter")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .help("Only receive matching packets, as id:ID[/MASK], type:TYPE or source:NODE. Filters of the same kind are alternatives.")
+}
+
+/// Set the `--filter` receive filter of a node
+fn receive_filter(svr: &mut server::RICSServer, matches: &ArgMatches) {
+    let specs: Vec<&str> = match matches.values_of("filter") {
+        Some(specs) => specs.collect(),
+        None => return,
+    };
+    let mut filter = server::ReceiveFilter::default();
+    for spec in specs {
+        let pos = spec.find(':').unwrap_or_else(|| { error!("Invalid filter {}, expected id:ID[/MASK], type:TYPE or source:NODE", spec); std::process::exit(1) });
+        let value = &spec[pos+1..];
+        match &spec[..pos] {
+            "id" => filter.ids.push(match value.find('/') {
+                Some(slash) => (parse_id(&value[..slash]), parse_id(&value[slash+1..])),
+                None => (parse_id(value), -1),
+            }),
+            "type" => filter.types.push(match value.to_lowercase().as_str() {
+                "raw" => rics::RICS_Data_RICS_DataType::RAW,
+                "stream" => rics::RICS_Data_RICS_DataType::STREAM,
+                "can" => rics::RICS_Data_RICS_DataType::CAN,
+                "datagram" => rics::RICS_Data_RICS_DataType::DATAGRAM,
+                "sync" => rics::RICS_Data_RICS_DataType::SYNC,
+                _ => { error!("Invalid packet type {}, expected raw, stream, can, datagram or sync", value); std::process::exit(1) },
+            }),
+            "source" => filter.sources.push(svr.node_from_string(value).unwrap_or_else(|| { error!("Unknown node {}", value); std::process::exit(1) })),
+            _ => { error!("Invalid filter {}, expected id:ID[/MASK], type:TYPE or source:NODE", spec); std::process::exit(1) },
+        }
+    }
+    svr.set_receive_filter(&filter);
+}
+
+fn topic_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("topic")
+        .long("topic")
+        .takes_value(true)
+        .help("Publish on this topic, reaching the nodes subscribed to it")
+}
+
+/// Load the `--dbc` file, if given
+fn load_dbc(matches: &ArgMatches) -> Option<dbc::Dbc> {
+    matches.value_of("dbc").map(|path| dbc::Dbc::load(path).unwrap_or_else(|e| { error!("{}", e); std::process::exit(1) }))
+}
+
+/// Evaluate the Lua expressions of the `can send` id and data
+#[cfg(feature="pluginlua")]
+fn lua_can_frame(matches: &ArgMatches) -> Option<(i32, Vec<u8>)> {
+    Some(rlua::Lua::new().context(|ctx| {
+        let id: i32 = match ctx.load(&matches.value_of("id").unwrap()).eval() {
+            Ok(id) => id,
+            Err(e) => {
+                error!("Invalid format for CAN id: {}", e);
+                std::process::exit(1)
+            }
+        };
+        let data: Vec<u8> = match ctx.load(&matches.value_of("data").unwrap()).eval() {
+            Ok(id) => id,
+            Err(e) => {
+                error!("Invalid format for CAN data: {}", e);
+                std::process::exit(1)
+            }
+        };
+        (id,data)
+    }))
+}
+
+#[cfg(not(feature="pluginlua"))]
+fn lua_can_frame(_matches: &ArgMatches) -> Option<(i32, Vec<u8>)> {
+    println!("This command needs the executable to be build with lua support");
+    None
+}
+
+/// Encode a DBC message from `NAME=VALUE` signal arguments
+fn dbc_encode<'a>(dbc: &dbc::Dbc, message: &str, signals: impl Iterator<Item=&'a str>) -> rics::RICS_Data {
+    let fail = |e: String| -> ! { error!("{}", e); std::process::exit(1) };
+    let msg = dbc.message_by_name(message).unwrap_or_else(|| fail(format!("Unknown message {}", message)));
+    let values: Vec<(&str, f64)> = signals.map(|arg| {
+        let pos = arg.find('=').unwrap_or_else(|| fail(format!("Invalid signal {}, expected NAME=VALUE", arg)));
+        let (name, value) = (&arg[..pos], &arg[pos+1..]);
+        let signal = msg.signal(name).unwrap_or_else(|| fail(format!("Unknown signal {} in {}", name, message)));
+        let physical = value.parse::<f64>().ok().or_else(|| signal.label_value(value))
+            .unwrap_or_else(|| fail(format!("Invalid value {} for {}", value, name)));
+        (name, physical)
+    }).collect();
+    dbc.encode(message, &values).unwrap_or_else(|e| fail(e))
+}
+
+/// Arguments shared by the canopen commands
+fn canopen_args<'a, 'b>(cmd: App<'a, 'b>) -> App<'a, 'b> {
+    cmd.arg(Arg::with_name("name")
+            .short("n")
+            .long("name")
+            .takes_value(true)
+            .help("Name of the node"))
+}
+
+/// Object arguments of the sdo commands
+fn sdo_args<'a, 'b>(cmd: App<'a, 'b>) -> App<'a, 'b> {
+    cmd.arg(Arg::with_name("NODE")
+            .index(1)
+            .required(true)
+            .help("CANopen node id"))
+        .arg(Arg::with_name("INDEX")
+             .index(2)
+             .required(true)
+             .help("Object index"))
+        .arg(Arg::with_name("SUB")
+             .index(3)
+             .required(true)
+             .help("Object sub-index"))
+        .arg(Arg::with_name("timeout")
+             .short("t")
+             .long("timeout")
+             .takes_value(true)
+             .help("SDO response timeout in ms, 1000 by default"))
+}
+
+/// Device arguments of the slave and pdo commands
+fn eds_args<'a, 'b>(cmd: App<'a, 'b>) -> App<'a, 'b> {
+    cmd.arg(Arg::with_name("eds")
+            .long("eds")
+            .takes_value(true)
+            .required(true)
+            .help("EDS or DCF file of the device"))
+        .arg(Arg::with_name("node_id")
+             .long("node-id")
+             .takes_value(true)
+             .required(true)
+             .help("CANopen node id of the device"))
+}
+
+/// Parse a CANopen node id
+fn canopen_node_id(s: &str) -> u8 {
+    match parse_id(s) {
+        id @ 1..=127 => id as u8,
+        _ => { error!("Invalid CANopen node id: {}", s); std::process::exit(1) },
+    }
+}
+
+/// Connect as a node for the canopen commands
+fn canopen_connect(svr: &mut server::RICSServer, matches: &ArgMatches) {
+    svr.connect(true);
+    if let Some(name) = matches.value_of("name") {
+        svr.set_name(name);
+    }
+    receive_filter(svr, matches);
+    info!("CANopen on node id {}", svr.who_am_i());
+}
+
+/// Object dictionary of the `--eds` file
+fn canopen_od(matches: &ArgMatches) -> (eds::ObjectDictionary, u8) {
+    let node_id = canopen_node_id(matches.value_of("node_id").unwrap());
+    let od = eds::ObjectDictionary::load(matches.value_of("eds").unwrap(), node_id)
+        .unwrap_or_else(|e| { error!("{}", e); std::process::exit(1) });
+    (od, node_id)
+}
+
 #[derive(Debug)]
 struct Packet {
     id: i32,
@@ -75,7 +528,8 @@ fn main() {
              .takes_value(true)
              .help("If a client or server is started, it will connect to the given tcp socket"))
         .subcommand(SubCommand::with_name("gui")
-                    .about("Open the gui interface with the given server"))
+                    .about("Open the gui interface with the given server")
+                    .arg(filter_arg()))
         .subcommand(SubCommand::with_name("plugin")
                     .about("Load an external processing plugin")
                     .arg(Arg::with_name("lua")
@@ -92,7 +546,8 @@ fn main() {
                          .number_of_values(1)
                          .conflicts_with("lua")
                          .help("Dynamic library plugin for advanced processing on clients")
-                         .takes_value(true)))
+                         .takes_value(true))
+                    .arg(filter_arg()))
         .subcommand(SubCommand::with_name("list")
                     .about("List available nodes and their names"))
         .subcommand(SubCommand::with_name("start")
@@ -112,9 +567,226 @@ fn main() {
                          .long("sink")
                          .takes_value(false)
                          .required(false)
-                         .help("Do not send messages")))
+                         .help("Do not send messages"))
+                    .arg(Arg::with_name("reliable")
+                         .short("r")
+                         .long("reliable")
+                         .help("Acknowledge, order and retransmit the stream, the peer must use --reliable too"))
+                    .arg(Arg::with_name("target")
+                         .short("t")
+                         .long("target")
+                         .takes_value(true)
+                         .requires("reliable")
+                         .help("Peer node id or name of the reliable stream, the default route otherwise"))
+                    .arg(Arg::with_name("pty")
+                         .long("pty")
+                         .help("Bridge a pseudo-terminal instead of stdin and stdout, printing its path"))
+                    .arg(Arg::with_name("serial")
+                         .long("serial")
+                         .takes_value(true)
+                         .conflicts_with_all(&["pty", "reliable"])
+                         .help("Bridge a serial port instead of stdin and stdout"))
+                    .arg(Arg::with_name("baud")
+                         .long("baud")
+                         .takes_value(true)
+                         .help("Baud rate of the pseudo-terminal or serial port, 115200 by default"))
+                    .arg(Arg::with_name("line")
+                         .long("line")
+                         .takes_value(true)
+                         .help("Data bits, parity and stop bits of the pseudo-terminal or serial port, 8N1 by default"))
+                    .arg(Arg::with_name("link")
+                         .long("link")
+                         .takes_value(true)
+                         .requires("pty")
+                         .help("Symbolic link to the pseudo-terminal, replaced if it exists"))
+                    .arg(Arg::with_name("timeout")
+                         .long("timeout")
+                         .takes_value(true)
+                         .requires("serial")
+                         .help("End packets after this gap in ms on the serial line"))
+                    .arg(Arg::with_name("delimiter")
+                         .long("delimiter")
+                         .takes_value(true)
+                         .conflicts_with("reliable")
+                         .help("End packets after this byte, such as 0x0A"))
+                    .arg(Arg::with_name("max_size")
+                         .long("max-size")
+                         .takes_value(true)
+                         .conflicts_with("reliable")
+                         .help("Largest packet sent, 2048 bytes by default"))
+                    .arg(filter_arg()
+                         .conflicts_with("reliable")))
+        .subcommand(SubCommand::with_name("bridge")
+                    .about("Bridge TCP connections to STREAM packets or UDP datagrams to DATAGRAM packets")
+                    .subcommand(bridge_args(SubCommand::with_name("tcp-listen")
+                                .about("Accept TCP clients, their packets having the client number as id")
+                                .arg(Arg::with_name("ADDRESS")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Address to listen on, such as :5000"))))
+                    .subcommand(bridge_args(SubCommand::with_name("tcp-connect")
+                                .about("Connect to a TCP server until the connection closes")
+                                .arg(Arg::with_name("ADDRESS")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Server address, as host:port"))))
+                    .subcommand(bridge_args(SubCommand::with_name("udp")
+                                .about("Send and receive UDP datagrams")
+                                .arg(Arg::with_name("ADDRESS")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Address the datagrams are sent to, as host:port"))
+                                .arg(Arg::with_name("bind")
+                                     .short("b")
+                                     .long("bind")
+                                     .takes_value(true)
+                                     .help("Address receiving the datagrams, a random port by default"))
+                                .arg(Arg::with_name("port")
+                                     .short("p")
+                                     .long("port")
+                                     .takes_value(true)
+                                     .help("Only bridge the DATAGRAM packets of this port, 0 by default")))))
+        .subcommand(SubCommand::with_name("datagram")
+                    .about("Send and receive DATAGRAM packets, their id being the port of a service")
+                    .subcommand(SubCommand::with_name("send")
+                                .about("Send a datagram")
+                                .arg(Arg::with_name("PORT")
+                                     .index(1)
+                                     .required(true))
+                                .arg(Arg::with_name("DATA")
+                                     .index(2)
+                                     .required(true)
+                                     .help("Content in hexadecimal"))
+                                .arg(Arg::with_name("target")
+                                     .short("t")
+                                     .long("target")
+                                     .takes_value(true)
+                                     .help("Target node id or name, the default route otherwise"))
+                                .arg(topic_arg())
+                                .arg(Arg::with_name("name")
+                                     .short("n")
+                                     .long("name")
+                                     .takes_value(true)
+                                     .help("Name of the node")))
+                    .subcommand(SubCommand::with_name("listen")
+                                .about("Print the datagrams received, with their source node and port")
+                                .arg(Arg::with_name("PORT")
+                                     .index(1)
+                                     .required(false)
+                                     .help("Only print the datagrams sent to this port"))
+                                .arg(filter_arg())
+                                .arg(Arg::with_name("name")
+                                     .short("n")
+                                     .long("name")
+                                     .takes_value(true)
+                                     .help("Name of the node"))))
+        .subcommand(SubCommand::with_name("rpc")
+                    .about("Call an RPC method of a node and print its response")
+                    .arg(Arg::with_name("TARGET")
+                         .index(1)
+                         .required(true)
+                         .help("Node id or name"))
+                    .arg(Arg::with_name("METHOD")
+                         .index(2)
+                         .required(true))
+                    .arg(Arg::with_name("DATA")
+                         .index(3)
+                         .required(false)
+                         .help("Payload in hexadecimal"))
+                    .arg(Arg::with_name("timeout")
+                         .long("timeout")
+                         .takes_value(true)
+                         .help("Time to wait for the response in milliseconds, 1000 by default"))
+                    .arg(Arg::with_name("name")
+                         .short("n")
+                         .long("name")
+                         .takes_value(true)
+                         .help("Name of the node")))
         .subcommand(SubCommand::with_name("log")
-                    .about("Logs every received packet"))
+                    .about("Logs every received packet")
+                    .arg(dbc_arg())
+                    .arg(subscribe_arg())
+                    .arg(filter_arg()))
+        .subcommand(SubCommand::with_name("record")
+                    .about("Record CAN traffic with its source node names")
+                    .arg(Arg::with_name("FILE")
+                         .index(1)
+                         .required(false)
+                         .help("Output file, stdout if not provided"))
+                    .arg(Arg::with_name("format")
+                         .short("f")
+                         .long("format")
+                         .takes_value(true)
+                         .possible_values(&["csv", "pcapng", "candump", "asc", "blf"])
+                         .help("Output format, guessed from the file extension by default"))
+                    .arg(subscribe_arg())
+                    .arg(filter_arg()))
+        .subcommand(SubCommand::with_name("convert")
+                    .about("Convert a CAN log to another format")
+                    .arg(Arg::with_name("INPUT")
+                         .index(1)
+                         .required(true)
+                         .help("CAN log or recording file"))
+                    .arg(Arg::with_name("OUTPUT")
+                         .index(2)
+                         .required(true)
+                         .help("Output file"))
+                    .arg(Arg::with_name("format")
+                         .short("f")
+                         .long("format")
+                         .takes_value(true)
+                         .possible_values(&["csv", "pcapng", "candump", "asc", "blf"])
+                         .help("Output format, guessed from the file extension by default")))
+        .subcommand(SubCommand::with_name("replay")
+                    .about("Replay recorded traffic with its original timing")
+                    .arg(Arg::with_name("FILE")
+                         .index(1)
+                         .required(true)
+                         .help("Recording or CAN log file"))
+                    .arg(Arg::with_name("speed")
+                         .short("s")
+                         .long("speed")
+                         .takes_value(true)
+                         .help("Replay speed factor, 2 is twice as fast"))
+                    .arg(Arg::with_name("loop")
+                         .short("l")
+                         .long("loop")
+                         .help("Restart the replay when the end is reached, after a short pause"))
+                    .arg(Arg::with_name("start")
+                         .long("start")
+                         .takes_value(true)
+                         .help("Start of the replay window, in seconds after the first packet"))
+                    .arg(Arg::with_name("end")
+                         .long("end")
+                         .takes_value(true)
+                         .help("End of the replay window, in seconds after the first packet"))
+                    .arg(Arg::with_name("id")
+                         .short("i")
+                         .long("id")
+                         .takes_value(true)
+                         .multiple(true)
+                         .number_of_values(1)
+                         .help("Only replay packets with this id"))
+                    .arg(Arg::with_name("source")
+                         .long("source")
+                         .takes_value(true)
+                         .multiple(true)
+                         .number_of_values(1)
+                         .help("Only replay packets recorded from this node name"))
+                    .arg(Arg::with_name("as_source")
+                         .long("as-source")
+                         .help("Inject the packets from nodes named after their original source"))
+                    .arg(Arg::with_name("name")
+                         .short("n")
+                         .long("name")
+                         .takes_value(true)
+                         .conflicts_with("as_source")
+                         .help("Name of the node used to inject the packets"))
+                    .arg(Arg::with_name("target")
+                         .short("t")
+                         .long("target")
+                         .takes_value(true)
+                         .help("Node target for the messages")))
         .subcommand(SubCommand::with_name("route")
                     .about("Change sever routing")
                     .arg(Arg::with_name("SRC")
@@ -134,6 +806,221 @@ fn main() {
                          .short("t")
                          .long("to")
                     .help("Destination nodes to add")))
+        .subcommand(SubCommand::with_name("isotp")
+                    .about("ISO-TP transport over CAN")
+                    .subcommand(isotp_args(SubCommand::with_name("send")
+                                .about("Send a payload")
+                                .arg(Arg::with_name("DATA")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Payload in hexadecimal, like '22 F1 90'"))))
+                    .subcommand(isotp_args(SubCommand::with_name("recv")
+                                .about("Print received payloads in hexadecimal")
+                                .arg(Arg::with_name("count")
+                                     .short("c")
+                                     .long("count")
+                                     .takes_value(true)
+                                     .help("Stop after this number of payloads"))
+                                .arg(filter_arg()))))
+        .subcommand(SubCommand::with_name("uds")
+                    .about("UDS diagnostic client over ISO-TP")
+                    .subcommand(uds_args(SubCommand::with_name("session")
+                                .about("Change the diagnostic session")
+                                .arg(Arg::with_name("SESSION")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Session type, like 3 for the extended session"))))
+                    .subcommand(uds_args(SubCommand::with_name("security")
+                                .about("Unlock a security level with a seed and key exchange")
+                                .arg(Arg::with_name("LEVEL")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Odd request seed sub-function"))))
+                    .subcommand(uds_args(SubCommand::with_name("read")
+                                .about("Read a data identifier")
+                                .arg(Arg::with_name("DID")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Data identifier, like 0xF190"))))
+                    .subcommand(uds_args(SubCommand::with_name("write")
+                                .about("Write a data identifier")
+                                .arg(Arg::with_name("DID")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Data identifier"))
+                                .arg(Arg::with_name("DATA")
+                                     .index(2)
+                                     .required(true)
+                                     .help("Data record in hexadecimal"))))
+                    .subcommand(uds_args(SubCommand::with_name("routine")
+                                .about("Start a routine, or stop it or get its results")
+                                .arg(Arg::with_name("ROUTINE")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Routine identifier"))
+                                .arg(Arg::with_name("DATA")
+                                     .index(2)
+                                     .required(false)
+                                     .help("Routine option record in hexadecimal"))
+                                .arg(Arg::with_name("stop")
+                                     .long("stop")
+                                     .help("Stop the routine"))
+                                .arg(Arg::with_name("results")
+                                     .long("results")
+                                     .conflicts_with("stop")
+                                     .help("Request the routine results"))))
+                    .subcommand(uds_args(SubCommand::with_name("download")
+                                .about("Download a file to the ECU memory")
+                                .arg(Arg::with_name("ADDRESS")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Memory address"))
+                                .arg(Arg::with_name("FILE")
+                                     .index(2)
+                                     .required(true)
+                                     .help("Binary file to download"))
+                                .arg(Arg::with_name("format")
+                                     .long("format")
+                                     .takes_value(true)
+                                     .help("Data format identifier, 0 for unencrypted and uncompressed data"))))
+                    .subcommand(SubCommand::with_name("serve")
+                                .about("Simulate an ECU answering UDS requests")
+                                .arg(Arg::with_name("config")
+                                     .short("c")
+                                     .long("config")
+                                     .takes_value(true)
+                                     .required(true)
+                                     .help("TOML description of the ECU"))
+                                .arg(Arg::with_name("lua")
+                                     .long("lua")
+                                     .takes_value(true)
+                                     .help("Lua file with rics_uds_request and rics_uds_key hooks"))
+                                .arg(Arg::with_name("name")
+                                     .short("n")
+                                     .long("name")
+                                     .takes_value(true)
+                                     .help("Name of the node, overrides the configuration"))
+                                .arg(filter_arg())))
+        .subcommand(SubCommand::with_name("j1939")
+                    .about("SAE J1939 over CAN")
+                    .subcommand(j1939_args(SubCommand::with_name("send")
+                                .about("Send a message, with the transport protocol if longer than 8 bytes")
+                                .arg(Arg::with_name("pgn")
+                                     .short("p")
+                                     .long("pgn")
+                                     .takes_value(true)
+                                     .required(true)
+                                     .help("Parameter group number"))
+                                .arg(Arg::with_name("destination")
+                                     .short("d")
+                                     .long("da")
+                                     .takes_value(true)
+                                     .help("Destination address of PDU1 parameter groups, global by default"))
+                                .arg(Arg::with_name("priority")
+                                     .long("priority")
+                                     .takes_value(true)
+                                     .help("Message priority, 6 by default"))
+                                .arg(Arg::with_name("DATA")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Message data in hexadecimal"))))
+                    .subcommand(j1939_args(SubCommand::with_name("recv")
+                                .about("Print the messages received by the node")
+                                .arg(Arg::with_name("count")
+                                     .long("count")
+                                     .takes_value(true)
+                                     .help("Stop after this number of messages"))
+                                .arg(filter_arg())))
+                    .subcommand(j1939_args(SubCommand::with_name("claim")
+                                .about("Claim an address and keep defending it")))
+                    .subcommand(SubCommand::with_name("log")
+                                .about("Log and decode J1939 traffic")
+                                .arg(Arg::with_name("raw")
+                                     .long("raw")
+                                     .help("Also show the transport protocol frames"))
+                                .arg(filter_arg())))
+        .subcommand(SubCommand::with_name("canopen")
+                    .about("CANopen over CAN")
+                    .subcommand(canopen_args(SubCommand::with_name("nmt")
+                                .about("Send an NMT command")
+                                .arg(Arg::with_name("COMMAND")
+                                     .index(1)
+                                     .required(true)
+                                     .possible_values(&["start", "stop", "preop", "reset", "reset-comm"]))
+                                .arg(Arg::with_name("NODE")
+                                     .index(2)
+                                     .help("CANopen node id, all nodes by default"))))
+                    .subcommand(SubCommand::with_name("sdo")
+                                .about("Access the object dictionary of a node")
+                                .subcommand(canopen_args(sdo_args(SubCommand::with_name("read")
+                                            .about("Read an object and print its value"))))
+                                .subcommand(canopen_args(sdo_args(SubCommand::with_name("write")
+                                            .about("Write an object")
+                                            .arg(Arg::with_name("DATA")
+                                                 .index(4)
+                                                 .required(true)
+                                                 .help("Value in hexadecimal, little endian"))))))
+                    .subcommand(canopen_args(SubCommand::with_name("monitor")
+                                .about("Print the state changes of the nodes, from heartbeats and node guarding")
+                                .arg(Arg::with_name("heartbeat_timeout")
+                                     .long("heartbeat-timeout")
+                                     .takes_value(true)
+                                     .help("Time in ms after which a node without heartbeat is lost, 3000 by default"))
+                                .arg(Arg::with_name("guard")
+                                     .long("guard")
+                                     .takes_value(true)
+                                     .multiple(true)
+                                     .number_of_values(1)
+                                     .help("Guard this node id instead of expecting its heartbeat"))
+                                .arg(Arg::with_name("guard_time")
+                                     .long("guard-time")
+                                     .takes_value(true)
+                                     .help("Node guarding period in ms, 1000 by default"))
+                                .arg(Arg::with_name("life_time_factor")
+                                     .long("life-time-factor")
+                                     .takes_value(true)
+                                     .help("Missed guarding periods before a node is lost, 3 by default"))
+                                .arg(filter_arg())))
+                    .subcommand(canopen_args(SubCommand::with_name("sync")
+                                .about("Produce SYNC messages")
+                                .arg(Arg::with_name("period")
+                                     .short("p")
+                                     .long("period")
+                                     .takes_value(true)
+                                     .help("Period in ms, 100 by default"))
+                                .arg(Arg::with_name("count")
+                                     .long("count")
+                                     .takes_value(true)
+                                     .help("Stop after this number of SYNCs"))
+                                .arg(Arg::with_name("rics")
+                                     .long("rics")
+                                     .help("Send RICS sync pulses, reaching every node, instead of CAN frames"))))
+                    .subcommand(canopen_args(eds_args(SubCommand::with_name("slave")
+                                .about("Simulate a CANopen slave from its EDS or DCF file"))))
+                    .subcommand(canopen_args(eds_args(SubCommand::with_name("pdo")
+                                .about("Decode the PDOs of a node from its EDS or DCF file"))
+                                .arg(filter_arg()))))
+        .subcommand(SubCommand::with_name("restbus")
+                    .about("Simulate the messages a DBC node sends, controlled from stdin")
+                    .arg(Arg::with_name("dbc")
+                         .long("dbc")
+                         .takes_value(true)
+                         .required(true)
+                         .help("DBC file describing the node"))
+                    .arg(Arg::with_name("node")
+                         .long("node")
+                         .takes_value(true)
+                         .required(true)
+                         .help("Name of the simulated DBC node"))
+                    .arg(Arg::with_name("lua")
+                         .long("lua")
+                         .takes_value(true)
+                         .help("Lua file defining the rics_restbus_update(message) signal override function"))
+                    .arg(Arg::with_name("name")
+                         .short("n")
+                         .long("name")
+                         .takes_value(true)
+                         .help("Name of the node, the DBC node name by default")))
         .subcommand(SubCommand::with_name("can")
                     .about("Internal can protocol")
                     .subcommand(SubCommand::with_name("broadcast")
@@ -147,41 +1034,151 @@ fn main() {
                                      .index(1)
                                      .help("A lua expression representing a floating point number between 0 and 1")
                                      .required(true)))
+                    .subcommand(SubCommand::with_name("errors")
+                                .about("Set server's can error confinement emulation flag")
+                                .arg(Arg::with_name("ERRORS")
+                                     .index(1)
+                                     .required(true)))
+                    .subcommand(SubCommand::with_name("fault")
+                                .about("Inject CAN errors on a node")
+                                .arg(Arg::with_name("NODE")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Node number or name"))
+                                .arg(Arg::with_name("tx")
+                                     .long("tx")
+                                     .takes_value(true)
+                                     .help("Number of transmit errors, each one raises the TEC by 8"))
+                                .arg(Arg::with_name("rx")
+                                     .long("rx")
+                                     .takes_value(true)
+                                     .help("Number of receive errors, each one raises the REC by 1")))
+                    .subcommand(SubCommand::with_name("recover")
+                                .about("Recover a node from bus-off")
+                                .arg(Arg::with_name("NODE")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Node number or name")))
+                    .subcommand(SubCommand::with_name("restart")
+                                .about("Set the delay before bus-off nodes recover by themselves")
+                                .arg(Arg::with_name("MS")
+                                     .index(1)
+                                     .required(true)
+                                     .help("Delay in milliseconds, 0 to only recover with the recover command")))
+                    .subcommand(SubCommand::with_name("state")
+                                .about("Show the CAN error counters and state of every node"))
                     .subcommand(SubCommand::with_name("connect")
                                 .about("Connect a socketcan interface to the network")
                                 .arg(Arg::with_name("extended")
                                      .required(false)
                                      .short("e")
                                      .long("ext")
-                                     .help("Send messages as extended messages by default"))
+                                     .help("Send every message with a 29 bit identifier"))
                                 .arg(Arg::with_name("CANIFACE")
                                      .index(1)
                                      .required(true)
-                                     .help("The socketcan interface name")))
+                                     .help("The socketcan interface name"))
+                                .arg(filter_arg()))
                     .subcommand(SubCommand::with_name("sendall")
-                                .about("Send all can messages in order from stdin"))
+                                .about("Send all can messages in order from stdin")
+                                .arg(Arg::with_name("format")
+                                     .short("f")
+                                     .long("format")
+                                     .takes_value(true)
+                                     .possible_values(&["raw", "candump"])
+                                     .help("Input format, raw 14 byte frames by default")))
                     .subcommand(SubCommand::with_name("send")
                                 .about("Send a can message")
                                 .arg(Arg::with_name("id")
                                      .short("i")
                                      .long("id")
                                      .takes_value(true)
-                                     .required(true)
+                                     .required_unless("dbc")
                                      .help("CAN id"))
                                 .arg(Arg::with_name("data")
                                      .short("d")
                                      .long("data")
                                      .takes_value(true)
-                                     .required(true)
+                                     .required_unless("dbc")
                                      .help("CAN message content"))
+                                .arg(Arg::with_name("dbc")
+                                     .long("dbc")
+                                     .takes_value(true)
+                                     .requires("message")
+                                     .conflicts_with_all(&["id", "data", "extended", "rtr"])
+                                     .help("Encode the message from its signals with this DBC file"))
+                                .arg(Arg::with_name("message")
+                                     .short("m")
+                                     .long("message")
+                                     .takes_value(true)
+                                     .requires("dbc")
+                                     .help("Name of the DBC message"))
+                                .arg(Arg::with_name("signal")
+                                     .short("s")
+                                     .long("signal")
+                                     .takes_value(true)
+                                     .multiple(true)
+                                     .number_of_values(1)
+                                     .requires("dbc")
+                                     .help("Physical value or value table label of a signal, as NAME=VALUE"))
+                                .arg(Arg::with_name("fd")
+                                     .long("fd")
+                                     .help("Send the message as a CAN FD frame, implied by more than 8 bytes of data"))
+                                .arg(Arg::with_name("brs")
+                                     .long("brs")
+                                     .help("Set the CAN FD bit rate switch flag"))
+                                .arg(Arg::with_name("extended")
+                                     .short("e")
+                                     .long("ext")
+                                     .help("Use a 29 bit identifier, implied by ids above 0x7FF"))
+                                .arg(Arg::with_name("rtr")
+                                     .long("rtr")
+                                     .conflicts_with_all(&["fd", "brs"])
+                                     .help("Send a remote frame requesting as many bytes as given in the data"))
                                 .arg(Arg::with_name("target")
                                      .short("t")
                                      .long("target")
                                      .required(false)
                                      .takes_value(true)
-                                     .help("Node target for the message")))
+                                     .help("Node target for the message"))
+                                .arg(topic_arg())
+                                .arg(Arg::with_name("period")
+                                     .short("p")
+                                     .long("period")
+                                     .takes_value(true)
+                                     .help("Send the message periodically, such as 100ms or 1s"))
+                                .arg(Arg::with_name("count")
+                                     .long("count")
+                                     .takes_value(true)
+                                     .requires("period")
+                                     .help("Stop after this number of periodic messages")))
+                    .subcommand(SubCommand::with_name("cyclic")
+                                .about("Send the cyclic messages of a schedule, controlled from stdin")
+                                .arg(Arg::with_name("config")
+                                     .short("c")
+                                     .long("config")
+                                     .takes_value(true)
+                                     .required(true)
+                                     .help("TOML schedule file"))
+                                .arg(Arg::with_name("name")
+                                     .short("n")
+                                     .long("name")
+                                     .takes_value(true)
+                                     .help("Name of the node")))
                     .subcommand(SubCommand::with_name("log")
-                                .about("Log CAN messages"))
+                                .about("Log CAN messages")
+                                .arg(Arg::with_name("FILE")
+                                     .index(1)
+                                     .required(false)
+                                     .help("Output file, stdout if not provided"))
+                                .arg(Arg::with_name("format")
+                                     .short("f")
+                                     .long("format")
+                                     .takes_value(true)
+                                     .possible_values(&["csv", "pcapng", "candump", "asc", "blf"])
+                                     .help("Output format, guessed from the file extension by default"))
+                                .arg(dbc_arg())
+                                .arg(filter_arg()))
                     .subcommand(SubCommand::with_name("serial")
                                 .about("Push serial CAN messages to the stream and back")
                                 .arg(Arg::with_name("PORT")
@@ -196,7 +1193,8 @@ fn main() {
                                      .short("t")
                                      .long("target")
                                      .takes_value(true)
-                                     .help("Node target for the messages")))
+                                     .help("Node target for the messages"))
+                                .arg(filter_arg()))
         ) .get_matches();
 
 
@@ -237,6 +1235,16 @@ fn main() {
         if let Some(h) = last {
             h.join().unwrap().unwrap();
         }
+    } else if let Some(matches) = matches.subcommand_matches("convert") {
+        //////////////////////// CONVERT ///////////////////////////
+        let output = matches.value_of("OUTPUT").unwrap();
+        let format = matches.value_of("format").and_then(record::LogFormat::from_name).unwrap_or(record::LogFormat::from_path(output));
+        let records = record::read_log(matches.value_of("INPUT").unwrap()).expect("Can't read input file");
+        let mut writer = record::create_record_file(output, format).expect("Can't create output file");
+        for rec in records.iter() {
+            writer.write_record(rec).expect("Can't write output file");
+        }
+        println!("Converted {} packets", records.len());
     } else {
         ////////////////////// CLIENT MODE //////////////////////
 
@@ -244,6 +1252,7 @@ fn main() {
             .or(tcp_servers.get(0).map(|x| server::ConnectTo::Tcp((*x).to_string())))
             .unwrap_or(server::ConnectTo::Default);
 
+        let conn_copy = conn.clone();
         server::RICSServer::with_server(conn, move|mut svr| {
 
             ///////////////////// GUI //////////////////////////////
@@ -252,6 +1261,7 @@ fn main() {
                 {
                     trace!("Opening gui");
                     svr.connect(true);
+                    receive_filter(&mut svr, matches);
                     gui::gui_main(Some(svr));
                 }
 
@@ -282,6 +1292,7 @@ fn main() {
                 trace!("Connecting to server...");
                 svr.connect(true);
                 svr.list_nodes();
+                receive_filter(&mut svr, matches);
 
                 let node = svr.who_am_i();
                 info!("Connecting on node id {}", node);
@@ -317,6 +1328,8 @@ fn main() {
                     }
                 });
 
+                // Methods are all exposed by the plugin
+                let mut rpc = rpc::RpcServer::new();
                 loop {
                     if let Ok(resp) = rx.recv() {
                         if resp.has_data() {
@@ -324,6 +1337,14 @@ fn main() {
                             if p.get_field_type() == rics::RICS_Data_RICS_DataType::CAN {
                                 info!("Sending can message {} to plugin", p.get_id());
                                 engine_arc.iface.lock().unwrap().can_rx(&mut svr_arc.lock().unwrap(), p.get_id() as u32, p.get_data().to_vec());
+                            } else if p.get_field_type() == rics::RICS_Data_RICS_DataType::DATAGRAM {
+                                let engine = engine_arc.iface.lock().unwrap();
+                                let mut svr = svr_arc.lock().unwrap();
+                                let call = |svr: &mut server::RICSServer, source, method: &str, payload: &[u8]| engine.rpc_call(svr, source, method, payload.to_vec());
+                                if !rpc.handle_with(&mut svr, p, call) {
+                                    info!("Sending datagram on port {} to plugin", p.get_id());
+                                    engine.datagram_rx(&mut svr, p.get_source(), p.get_id(), p.get_data().to_vec());
+                                }
                             }
 
                         }
@@ -364,17 +1385,55 @@ fn main() {
                     svr.connect(false);
                     svr.set_can_drop_chance(matches.value_of("DROP").unwrap().parse().expect("invalid format for float DROP"));
                 }
+                else if let Some(matches) = matches.subcommand_matches("errors") {
+                    //////////////////////// CAN ERRORS FLAG ///////////////////
+                    svr.connect(false);
+                    svr.set_can_errors(matches.value_of("ERRORS").unwrap().parse().expect("invalid format for bool ERRORS"));
+                }
+                else if let Some(matches) = matches.subcommand_matches("fault") {
+                    //////////////////////// CAN FAULT /////////////////////////
+                    svr.connect(false);
+                    svr.list_nodes();
+                    let node = svr.node_from_string_cached(matches.value_of("NODE").unwrap()).expect("Invalid node");
+                    let tx = matches.value_of("tx").map(|x| x.parse::<u32>().expect("Invalid number of transmit errors")).unwrap_or(0);
+                    let rx = matches.value_of("rx").map(|x| x.parse::<u32>().expect("Invalid number of receive errors")).unwrap_or(0);
+                    svr.inject_can_fault(node, tx, rx);
+                }
+                else if let Some(matches) = matches.subcommand_matches("recover") {
+                    //////////////////////// CAN RECOVER ///////////////////////
+                    svr.connect(false);
+                    svr.list_nodes();
+                    let node = svr.node_from_string_cached(matches.value_of("NODE").unwrap()).expect("Invalid node");
+                    svr.recover_can_node(node);
+                }
+                else if let Some(matches) = matches.subcommand_matches("restart") {
+                    //////////////////////// CAN RESTART DELAY /////////////////
+                    svr.connect(false);
+                    svr.set_can_restart_ms(matches.value_of("MS").unwrap().parse().expect("invalid format for delay MS"));
+                }
+                else if let Some(_matches) = matches.subcommand_matches("state") {
+                    //////////////////////// CAN STATE /////////////////////////
+                    svr.connect(false);
+                    let names = svr.list_nodes().clone();
+                    let mut states = svr.can_states();
+                    states.sort_by_key(|x| x.get_id());
+                    for state in states {
+                        println!("{}\t{}\tTEC {}\tREC {}\t{:?}", state.get_id(), names.get(&state.get_id()).map(|x| x.as_str()).unwrap_or(""),
+                                 state.get_tec(), state.get_rec(), state.get_state());
+                    }
+                }
                 else if let Some(matches) = matches.subcommand_matches("connect") {
                     /////////////////////// CAN CONNECT /////////////////////
                     svr.connect(true);
+                    receive_filter(&mut svr, matches);
                     let node = svr.who_am_i();
                     println!("Logging on node id {}", node);
 
-                    let eff_field = if matches.is_present("extended") {socketcan::EFF_FLAG} else {0u32};
+                    let extended = matches.is_present("extended");
 
                     #[cfg(target_family="unix")]
                     {
-                        let socketcan = socketcan::CANSocket::open(matches.value_of("CANIFACE").unwrap()).expect("Can't connect to CAN iface");
+                        let socketcan = canfd::open(matches.value_of("CANIFACE").unwrap()).expect("Can't connect to CAN iface");
                         let socketcan_tx = unsafe { socketcan::CANSocket::from_raw_fd(socketcan.as_raw_fd()) };
                         let resp = svr.listen_response();
                         thread::spawn(move|| {
@@ -383,8 +1442,9 @@ fn main() {
                                 if packet.has_data() {
                                     let data = packet.get_data();
                                     if data.get_field_type() == rics::RICS_Data_RICS_DataType::CAN {
-                                        let frame = socketcan::CANFrame::new((data.get_id() as u32) | eff_field, &data.get_data(), false, false).expect("Can't create CAN frame");
-                                        socketcan_tx.write_frame_insist(&frame).expect("Can't send CAN frame");
+                                        if let Err(e) = canfd::write_packet(&socketcan_tx, data, extended) {
+                                            error!("Can't send CAN frame: {}", e);
+                                        }
                                     }
                                 }
                             }
@@ -392,9 +1452,9 @@ fn main() {
 
                         loop {
 
-                            if let Ok(frame) = socketcan.read_frame() {
-                                trace!("FrameRx: {}, {:?}", frame.id(), frame.data());
-                                svr.send_packet(server::can_packet(frame.id().try_into().unwrap(), frame.data().to_vec()));
+                            if let Ok(data) = canfd::read_packet(&socketcan) {
+                                trace!("FrameRx: {}, {:?}", data.get_id(), data.get_data());
+                                svr.send_packet(data);
                             }
 
                         }
@@ -411,6 +1471,15 @@ fn main() {
                     //////////////////////// CAN SEND ALL ////////////////////
                     svr.connect(false);
                     let mut stdin = std::io::stdin();
+                    if matches.value_of("format") == Some("candump") {
+                        for line in stdin.lock().lines() {
+                            if let Some(rec) = candump::parse_candump_line(&line.expect("Can't access stdin")) {
+                                svr.send_packet(rec.data);
+                                std::thread::sleep(Duration::from_millis(100));
+                            }
+                        }
+                        return;
+                    }
                     loop {
                         let mut buffer = [0u8 ; 14];
                         let read = stdin.read(&mut buffer);
@@ -428,57 +1497,202 @@ fn main() {
                 }
                 else if let Some(matches) = matches.subcommand_matches("send") {
                     //////////////////////// CAN SEND FLAG ///////////////////
-                    #[cfg(feature="pluginlua")]
-                    {
-                    svr.connect(false);
-
-                    let (id, data) = rlua::Lua::new().context(|ctx| {
-                        let id: i32 = match ctx.load(&matches.value_of("id").unwrap()).eval() {
-                            Ok(id) => id,
-                            Err(e) => {
-                                error!("Invalid format for CAN id: {}", e);
-                                std::process::exit(1)
-                            }
-                        };
-                        let data: Vec<u8> = match ctx.load(&matches.value_of("data").unwrap()).eval() {
-                            Ok(id) => id,
-                            Err(e) => {
-                                error!("Invalid format for CAN data: {}", e);
-                                std::process::exit(1)
-                            }
+                    let packet = if let Some(dbc) = load_dbc(matches) {
+                        let message = matches.value_of("message").unwrap();
+                        dbc_encode(&dbc, message, matches.values_of("signal").into_iter().flatten())
+                    } else {
+                        let (id, data) = match lua_can_frame(matches) {
+                            Some(frame) => frame,
+                            None => return,
                         };
-                        (id,data)
-                    });
+                        if data.len() > server::CANFD_MAX_DLEN {
+                            error!("CAN messages can't carry more than {} bytes", server::CANFD_MAX_DLEN);
+                            std::process::exit(1);
+                        }
+                        let fd = matches.is_present("fd") || matches.is_present("brs") || data.len() > server::CAN_MAX_DLEN;
+                        server::can_frame(id, data, server::CanFlags {
+                            extended: matches.is_present("extended") || id > server::CAN_SFF_MAX,
+                            rtr: matches.is_present("rtr"),
+                            fd: fd,
+                            brs: matches.is_present("brs"),
+                            ..Default::default()
+                        })
+                    };
+                    let packet = if (matches.is_present("fd") || matches.is_present("brs")) && !packet.get_fd() {
+                        server::can_frame(packet.get_id(), packet.get_data().to_vec(), server::CanFlags {
+                            fd: true,
+                            brs: matches.is_present("brs"),
+                            ..server::can_flags(&packet)
+                        })
+                    } else {
+                        packet
+                    };
 
+                    let mut packet = packet;
                     if matches.is_present("target") {
-                        let target = matches.value_of("target").unwrap().parse::<i32>().expect("Invalid target number");
-                        svr.send_packet_to(server::can_packet(id, data), target);
+                        packet.set_target(matches.value_of("target").unwrap().parse::<i32>().expect("Invalid target number"));
+                    }
+                    if let Some(topic) = matches.value_of("topic") {
+                        packet.set_topic(topic.to_string());
+                    }
+
+                    svr.connect(false);
+                    if let Some(period) = matches.value_of("period") {
+                        let period = scheduler::parse_duration(period).filter(|x| *x > Duration::from_secs(0))
+                            .unwrap_or_else(|| { error!("Invalid period: {}", period); std::process::exit(1) });
+                        let mut message = scheduler::CyclicMessage::new(packet, period);
+                        message.count = matches.value_of("count").map(|x| x.parse::<u64>().ok().filter(|x| *x > 0)
+                            .unwrap_or_else(|| { error!("Invalid count: {}", x); std::process::exit(1) }));
+                        let scheduler = scheduler::Scheduler::new(svr);
+                        scheduler.add("send", message);
+                        scheduler.wait("send");
+                    } else if packet.has_target() {
+                        let target = packet.get_target();
+                        svr.send_packet_to(packet, target);
                     } else {
-                        svr.send_packet(server::can_packet(id, data));
+                        svr.send_packet(packet);
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("cyclic") {
+                    //////////////////////// CAN CYCLIC ///////////////////////
+                    let path = matches.value_of("config").unwrap();
+                    let mut schedule = scheduler::Schedule::load(path).unwrap_or_else(|e| { error!("{}", e); std::process::exit(1) });
+                    // The host only names nodes, which must then keep reading their input
+                    svr.connect(matches.is_present("name"));
+                    if let Some(name) = matches.value_of("name") {
+                        svr.set_name(name);
+                        svr.drain();
                     }
+                    let scheduler = scheduler::Scheduler::new(svr);
+                    for entry in schedule.entries.iter() {
+                        scheduler.add(entry.name.clone(), entry.message.clone());
                     }
+                    info!("{} cyclic messages scheduled", schedule.entries.len());
 
-                    if ! cfg!(feature="pluginlua") {
-                        println!("This command needs the executable to be build with lua support");
+                    for line in std::io::stdin().lock().lines() {
+                        let line = line.expect("Can't read stdin");
+                        let args: Vec<&str> = line.split_whitespace().collect();
+                        match (args.get(0).cloned(), args.get(1).cloned()) {
+                            (Some("start"), Some(name)) => scheduler.start(name),
+                            (Some("stop"), Some(name)) => scheduler.stop(name),
+                            (Some("period"), Some(name)) => match args.get(2).and_then(|x| scheduler::parse_duration(x)).filter(|x| *x > Duration::from_secs(0)) {
+                                Some(period) => scheduler.set_period(name, period),
+                                None => error!("Usage: period NAME DURATION"),
+                            },
+                            (Some("data"), Some(name)) => scheduler.set_data(name, parse_hex(&args[2..].concat())),
+                            (Some("set"), Some(name)) => {
+                                let entry = schedule.entries.iter_mut().find(|x| x.name == name);
+                                match (entry, schedule.dbc.as_ref()) {
+                                    (Some(scheduler::ScheduleEntry { signals: Some((message, values)), .. }), Some(dbc)) => {
+                                        let mut new_values = values.clone();
+                                        for arg in args[2..].iter() {
+                                            let pos = match arg.find('=') {
+                                                Some(pos) => pos,
+                                                None => { error!("Invalid signal {}, expected NAME=VALUE", arg); continue },
+                                            };
+                                            let (signal, value) = (&arg[..pos], &arg[pos+1..]);
+                                            let physical = value.parse::<f64>().ok()
+                                                .or_else(|| dbc.message_by_name(message).and_then(|x| x.signal(signal)).and_then(|x| x.label_value(value)));
+                                            match physical {
+                                                Some(physical) => {
+                                                    new_values.retain(|(x, _)| x != signal);
+                                                    new_values.push((signal.to_string(), physical));
+                                                },
+                                                None => error!("Invalid value {} for {}", value, signal),
+                                            }
+                                        }
+                                        let refs: Vec<(&str, f64)> = new_values.iter().map(|(n, v)| (n.as_str(), *v)).collect();
+                                        match dbc.encode(message, &refs) {
+                                            Ok(data) => {
+                                                scheduler.set_data(name, data.get_data().to_vec());
+                                                *values = new_values;
+                                            },
+                                            Err(e) => error!("{}", e),
+                                        }
+                                    },
+                                    _ => error!("{} isn't a DBC message", name),
+                                }
+                            },
+                            (Some("reload"), _) => {
+                                match scheduler::Schedule::load(path) {
+                                    Ok(new_schedule) => {
+                                        for entry in schedule.entries.iter().filter(|x| !new_schedule.entries.iter().any(|y| y.name == x.name)) {
+                                            scheduler.remove(entry.name.clone());
+                                        }
+                                        for entry in new_schedule.entries.iter() {
+                                            scheduler.add(entry.name.clone(), entry.message.clone());
+                                        }
+                                        schedule = new_schedule;
+                                        info!("Schedule reloaded");
+                                    },
+                                    Err(e) => error!("{}", e),
+                                }
+                            },
+                            (None, _) => (),
+                            _ => error!("Unknown command, use start, stop, period, data, set NAME ... or reload"),
+                        }
+                    }
+                    // Keep sending once stdin is closed
+                    loop {
+                        thread::park();
                     }
                 }
-                else if let Some(_) = matches.subcommand_matches("log") {
+                else if let Some(matches) = matches.subcommand_matches("log") {
                     //////////////////////////////// CAN LOG ///////////////////////////
                     svr.connect(true);
+                    receive_filter(&mut svr, matches);
                     let node = svr.who_am_i();
                     info!("Logging on node id {}", node);
 
-                    let (chan_send, chan_rx) = channel();
+                    let path = matches.value_of("FILE").map(|x| x.to_string());
+                    let format = matches.value_of("format").and_then(record::LogFormat::from_name)
+                        .unwrap_or(path.as_ref().map(|x| record::LogFormat::from_path(x)).unwrap_or(record::LogFormat::Csv));
+                    let dbc = load_dbc(matches);
+                    if dbc.is_some() && format != record::LogFormat::Csv {
+                        error!("DBC decoding is only available with the csv format");
+                        std::process::exit(1);
+                    }
+                    if format == record::LogFormat::Blf && path.is_none() {
+                        error!("BLF traces can only be written to a file");
+                        std::process::exit(1);
+                    }
+                    let (chan_send, chan_rx) = channel::<(f64, rics::RICS_Data)>();
 
                     std::thread::spawn(move || {
-                        loop {
-                            println!("{}",server::data_to_loggable_string(&chan_rx.recv().unwrap()));
+                        if format == record::LogFormat::Csv {
+                            let mut out: Box<dyn Write> = match &path {
+                                Some(path) => Box::new(File::create(path).expect("Can't create log file")),
+                                None => Box::new(stdout()),
+                            };
+                            loop {
+                                let data = chan_rx.recv().unwrap().1;
+                                let line = match dbc.as_ref().and_then(|x| x.decode(&data)) {
+                                    Some(msg) => {
+                                        let datetime: chrono::DateTime<chrono::offset::Local> = server::data_time(&data).into();
+                                        let flags = server::data_flags(&data);
+                                        format!("{} {:x}{}{} {}", datetime.format("%Y-%m-%d %T%.3f"), data.get_id(),
+                                                if flags.is_empty() { "" } else { " " }, flags, msg)
+                                    },
+                                    None => server::data_to_loggable_string(&data),
+                                };
+                                writeln!(out, "{}", line).expect("Can't write log");
+                            }
+                        } else {
+                            let mut writer = match &path {
+                                Some(path) => record::create_record_file(path, format).expect("Can't create log file"),
+                                None => record::record_writer(format, Box::new(stdout())).expect("Can't write to stdout"),
+                            };
+                            loop {
+                                let (timestamp, data) = chan_rx.recv().unwrap();
+                                let rec = record::Record { timestamp: timestamp, source: Some(data.get_source().to_string()), channel: None, data: data };
+                                writer.write_record(&rec).expect("Can't write log");
+                            }
                         }
                     });
                     loop {
                         if let Some(p) = svr.get_packet() {
                             if p.get_field_type() == rics::RICS_Data_RICS_DataType::CAN {
-                                chan_send.send(p).unwrap();
+                                chan_send.send((record::data_timestamp(&p), p)).unwrap();
                             }
                         }
                     }
@@ -486,6 +1700,7 @@ fn main() {
                 else if let Some(matches) = matches.subcommand_matches("serial") {
                     //////////////////////////// KVASER ///////////////////////////
                     svr.connect(true);
+                    receive_filter(&mut svr, matches);
                     let node = svr.who_am_i();
                     println!("Sending on node id {}", node);
 
@@ -548,69 +1763,620 @@ fn main() {
                     }
                 }
             }
+            else if let Some(matches) = matches.subcommand_matches("isotp") {
+                //////////////////////// ISO-TP ///////////////////////////
+                let (cmd, sub_matches) = matches.subcommand();
+                let matches = match sub_matches {
+                    Some(matches) => matches,
+                    None => { println!("{}", matches.usage()); return; },
+                };
+                svr.connect(true);
+                if let Some(name) = matches.value_of("name") {
+                    svr.set_name(name);
+                }
+                receive_filter(&mut svr, matches);
+                info!("ISO-TP on node id {}", svr.who_am_i());
+                let mut channel = isotp::IsoTp::new(svr, isotp_options(matches));
+
+                if cmd == "send" {
+                    let payload = parse_hex(matches.value_of("DATA").unwrap());
+                    if let Err(e) = channel.send(&payload) {
+                        error!("ISO-TP send failed: {}", e);
+                        std::process::exit(1);
+                    }
+                } else {
+                    let count = matches.value_of("count").map(|x| x.parse::<usize>().expect("Invalid count"));
+                    let mut received = 0;
+                    while count.map(|n| received < n).unwrap_or(true) {
+                        match channel.recv(None) {
+                            Ok(payload) => { println!("{}", hex_string(&payload)); received += 1; },
+                            Err(e) => warn!("ISO-TP reception failed: {}", e),
+                        }
+                    }
+                }
+            }
+            else if let Some(matches) = matches.subcommand_matches("uds") {
+                //////////////////////// UDS //////////////////////////////
+                let (cmd, sub_matches) = matches.subcommand();
+                let matches = match sub_matches {
+                    Some(matches) => matches,
+                    None => { println!("{}", matches.usage()); return; },
+                };
+                if cmd == "serve" {
+                    let config = ecu::EcuConfig::load(matches.value_of("config").unwrap()).unwrap_or_else(|e| {
+                        error!("Invalid ECU configuration: {}", e);
+                        std::process::exit(1)
+                    });
+                    svr.connect(true);
+                    if let Some(name) = matches.value_of("name").or(config.name.as_ref().map(|x| x.as_str())) {
+                        svr.set_name(name);
+                    }
+                    receive_filter(&mut svr, matches);
+                    println!("Serving UDS on node id {}", svr.who_am_i());
+                    let mut channel = isotp::IsoTp::new(svr, config.isotp.clone());
+                    let mut ecu = ecu::Ecu::new(config);
+                    if let Some(_path) = matches.value_of("lua") {
+                        #[cfg(feature="pluginlua")]
+                        {
+                        let lua = std::rc::Rc::new(script::LuaUds::new(_path.to_string()));
+                        let hook = lua.clone();
+                        ecu.set_request_hook(Box::new(move |req, session| hook.request(req, session)));
+                        ecu.set_key_function(Box::new(move |level, seed| lua.key(level, seed)));
+                        }
+                        if ! cfg!(feature="pluginlua") {
+                            error!("Lua hooks need the executable to be build with lua support");
+                            std::process::exit(1);
+                        }
+                    }
+                    ecu.serve(&mut channel);
+                    return;
+                }
+                svr.connect(true);
+                if let Some(name) = matches.value_of("name") {
+                    svr.set_name(name);
+                }
+                info!("UDS client on node id {}", svr.who_am_i());
+                let mut client = uds::UdsClient::new(isotp::IsoTp::new(svr, isotp_options(matches)));
+                if let Err(e) = uds_command(&mut client, cmd, matches) {
+                    error!("UDS {} failed: {}", cmd, e);
+                    std::process::exit(1);
+                }
+            }
+            else if let Some(matches) = matches.subcommand_matches("j1939") {
+                //////////////////////// J1939 ////////////////////////////
+                if let Some(matches) = matches.subcommand_matches("log") {
+                    svr.connect(true);
+                    receive_filter(&mut svr, matches);
+                    info!("Logging on node id {}", svr.who_am_i());
+                    let mut reassembler = j1939::Reassembler::new();
+                    loop {
+                        let msg = match svr.get_packet().as_ref().and_then(j1939::J1939Message::from_data) {
+                            Some(msg) => msg,
+                            None => continue,
+                        };
+                        if matches.is_present("raw") && (msg.id.pgn == j1939::PGN_TP_CM || msg.id.pgn == j1939::PGN_TP_DT) {
+                            println!("{}", msg);
+                        }
+                        if let Some(msg) = reassembler.push(msg) {
+                            println!("{}", msg);
+                        }
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("send") {
+                    let mut node = j1939_node(svr, matches);
+                    let pgn = parse_id(matches.value_of("pgn").unwrap()) as u32;
+                    let destination = matches.value_of("destination").map(|x| parse_id(x) as u8).unwrap_or(j1939::ADDRESS_GLOBAL);
+                    let priority = matches.value_of("priority").map(|x| x.parse::<u8>().expect("Invalid priority")).unwrap_or(j1939::DEFAULT_PRIORITY);
+                    if let Err(e) = node.send(priority, pgn, destination, &parse_hex(matches.value_of("DATA").unwrap())) {
+                        error!("J1939 send failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("recv") {
+                    let mut node = j1939_node(svr, matches);
+                    let count = matches.value_of("count").map(|x| x.parse::<usize>().expect("Invalid count"));
+                    let mut received = 0;
+                    while count.map(|n| received < n).unwrap_or(true) {
+                        match node.recv(None) {
+                            Ok(msg) => { println!("{}", msg); received += 1; },
+                            Err(e) => warn!("J1939 reception failed: {}", e),
+                        }
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("claim") {
+                    if !matches.is_present("claim") {
+                        error!("A NAME must be given with --claim");
+                        std::process::exit(1);
+                    }
+                    let mut node = j1939_node(svr, matches);
+                    loop {
+                        if let Err(e) = node.recv(None) {
+                            warn!("J1939 reception failed: {}", e);
+                        }
+                    }
+                }
+            }
+            else if let Some(matches) = matches.subcommand_matches("canopen") {
+                //////////////////////// CANOPEN //////////////////////////
+                if let Some(matches) = matches.subcommand_matches("nmt") {
+                    canopen_connect(&mut svr, matches);
+                    let command = canopen::NmtCommand::from_name(matches.value_of("COMMAND").unwrap()).unwrap();
+                    let node = matches.value_of("NODE").map(canopen_node_id).unwrap_or(canopen::NODE_ALL);
+                    canopen::CanOpen::new(svr).nmt(command, node);
+                }
+                else if let Some(matches) = matches.subcommand_matches("sdo") {
+                    let (sub_matches, write) = match matches.subcommand() {
+                        ("read", Some(m)) => (m, false),
+                        ("write", Some(m)) => (m, true),
+                        _ => { println!("{}", matches.usage()); std::process::exit(1) },
+                    };
+                    canopen_connect(&mut svr, sub_matches);
+                    let node = canopen_node_id(sub_matches.value_of("NODE").unwrap());
+                    let index = parse_id(sub_matches.value_of("INDEX").unwrap()) as u16;
+                    let sub = parse_id(sub_matches.value_of("SUB").unwrap()) as u8;
+                    let mut master = canopen::CanOpen::new(svr);
+                    if let Some(timeout) = sub_matches.value_of("timeout") {
+                        master.sdo_timeout = Duration::from_millis(timeout.parse::<u64>().expect("Invalid timeout"));
+                    }
+                    let result = if write {
+                        master.sdo_download(node, index, sub, &parse_hex(sub_matches.value_of("DATA").unwrap()))
+                    } else {
+                        master.sdo_upload(node, index, sub).map(|dat| match dat.len() {
+                            1 | 2 | 4 | 8 => println!("{} ({})", hex_string(&dat), eds::format_value(eds::TYPE_UNSIGNED64, &dat)),
+                            _ => println!("{}", hex_string(&dat)),
+                        })
+                    };
+                    if let Err(e) = result {
+                        error!("SDO transfer failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("monitor") {
+                    canopen_connect(&mut svr, matches);
+                    let ms = |name: &str, default: u64| Duration::from_millis(matches.value_of(name).map(|x| x.parse::<u64>().expect("Invalid time")).unwrap_or(default));
+                    let guard_time = ms("guard_time", 1000);
+                    let factor = matches.value_of("life_time_factor").map(|x| x.parse::<u32>().expect("Invalid life time factor")).unwrap_or(3);
+                    let mut monitor = canopen::Monitor::new(ms("heartbeat_timeout", 3000), guard_time * factor);
+                    for node in matches.values_of("guard").into_iter().flatten() {
+                        monitor.guard(canopen_node_id(node));
+                    }
+                    let mut master = canopen::CanOpen::new(svr);
+                    let mut next_guard = std::time::Instant::now();
+                    loop {
+                        if std::time::Instant::now() >= next_guard {
+                            for node in monitor.request_guarding() {
+                                master.guard(node);
+                            }
+                            next_guard += guard_time;
+                        }
+                        match master.recv(Some(Duration::from_millis(50))) {
+                            Ok(data) => monitor.push(&data).iter().for_each(|e| println!("{}", e)),
+                            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => (),
+                            Err(e) => { error!("{}", e); std::process::exit(1); },
+                        }
+                        monitor.expire().iter().for_each(|e| println!("{}", e));
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("sync") {
+                    canopen_connect(&mut svr, matches);
+                    let period = Duration::from_millis(matches.value_of("period").map(|x| x.parse::<u64>().expect("Invalid period")).unwrap_or(100));
+                    let count = matches.value_of("count").map(|x| x.parse::<usize>().expect("Invalid count"));
+                    let rics_sync = matches.is_present("rics");
+                    let mut master = canopen::CanOpen::new(svr);
+                    let mut sent = 0;
+                    let mut next = std::time::Instant::now();
+                    while count.map(|n| sent < n).unwrap_or(true) {
+                        if rics_sync {
+                            master.sync_pulse();
+                        } else {
+                            master.sync();
+                        }
+                        sent += 1;
+                        next += period;
+                        thread::sleep(next.saturating_duration_since(std::time::Instant::now()));
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("slave") {
+                    let (od, node_id) = canopen_od(matches);
+                    canopen_connect(&mut svr, matches);
+                    let mut slave = canopen::Slave::new(svr, od, node_id);
+                    if let Err(e) = slave.run() {
+                        error!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+                else if let Some(matches) = matches.subcommand_matches("pdo") {
+                    let (od, node_id) = canopen_od(matches);
+                    canopen_connect(&mut svr, matches);
+                    let pdos: Vec<(String, eds::Pdo)> = od.tpdos().into_iter().map(|x| (format!("TPDO{}", x.number), x))
+                        .chain(od.rpdos().into_iter().map(|x| (format!("RPDO{}", x.number), x)))
+                        .filter(|(_, x)| x.valid()).collect();
+                    let mut master = canopen::CanOpen::new(svr);
+                    loop {
+                        let data = match master.recv(None) {
+                            Ok(data) => data,
+                            Err(e) => { error!("{}", e); std::process::exit(1); },
+                        };
+                        if data.get_field_type() != rics::RICS_Data_RICS_DataType::CAN || data.get_rtr() {
+                            continue;
+                        }
+                        for (name, pdo) in pdos.iter().filter(|(_, x)| x.can_id() == data.get_id()) {
+                            let values = od.pdo_values(pdo, data.get_data()).iter().map(|(index, sub, value)| match od.get(*index, *sub) {
+                                Some(entry) => format!("{}={}", entry.name, eds::format_value(entry.data_type, value)),
+                                None => format!("{:04X}sub{}={}", index, sub, hex_string(value)),
+                            }).collect::<Vec<String>>();
+                            println!("{} node {}: {}", name, node_id, values.join(", "));
+                        }
+                    }
+                }
+            }
+            else if let Some(matches) = matches.subcommand_matches("restbus") {
+                //////////////////////// RESTBUS //////////////////////////
+                let dbc = load_dbc(matches).unwrap();
+                let node = matches.value_of("node").unwrap();
+                svr.connect(true);
+                svr.set_name(matches.value_of("name").unwrap_or(node));
+                svr.drain();
+                let mut restbus = restbus::RestBus::new(svr, &dbc, node).unwrap_or_else(|e| { error!("{}", e); std::process::exit(1) });
+                for message in restbus.messages() {
+                    match message.cycle_time() {
+                        Some(period) => info!("{} every {:?}", message.name, period),
+                        None => info!("{} on change", message.name),
+                    }
+                }
+                if let Some(_path) = matches.value_of("lua") {
+                    #[cfg(feature="pluginlua")]
+                    {
+                    let lua = script::LuaRestbus::new(_path.to_string());
+                    restbus.set_update(move |message| lua.update(message));
+                    }
+                    if ! cfg!(feature="pluginlua") {
+                        error!("Lua hooks need the executable to be build with lua support");
+                        std::process::exit(1);
+                    }
+                }
+
+                for line in std::io::stdin().lock().lines() {
+                    let line = line.expect("Can't read stdin");
+                    let args: Vec<&str> = line.split_whitespace().collect();
+                    let result = match (args.get(0).cloned(), args.get(1).cloned()) {
+                        (Some("start"), Some(name)) => restbus.start(name),
+                        (Some("stop"), Some(name)) => restbus.stop(name),
+                        (Some("set"), Some(name)) => {
+                            let message = dbc.message_by_name(name).filter(|x| x.transmitter == node);
+                            let values: Result<Vec<(&str, f64)>, String> = args[2..].iter().map(|arg| {
+                                let message = message.ok_or_else(|| format!("{} isn't sent by the simulated node", name))?;
+                                let pos = arg.find('=').ok_or_else(|| format!("Invalid signal {}, expected NAME=VALUE", arg))?;
+                                let (name, value) = (&arg[..pos], &arg[pos+1..]);
+                                let signal = message.signal(name).ok_or_else(|| format!("Unknown signal {} in {}", name, message.name))?;
+                                signal.parse_value(value).map(|x| (name, x)).ok_or_else(|| format!("Invalid value {} for {}", value, name))
+                            }).collect();
+                            values.and_then(|values| restbus.set(name, &values))
+                        },
+                        (None, _) => Ok(()),
+                        _ => Err("Unknown command, use start MESSAGE, stop MESSAGE or set MESSAGE SIGNAL=VALUE...".to_string()),
+                    };
+                    if let Err(e) = result {
+                        error!("{}", e);
+                    }
+                }
+                // Keep sending once stdin is closed
+                loop {
+                    thread::park();
+                }
+            }
             else if let Some(matches) = matches.subcommand_matches("stream") {
                 //////////////////////// STREAM ///////////////////////////
                 svr.connect(true);
+                receive_filter(&mut svr, matches);
                 let node = svr.who_am_i();
-                println!("Sending on node id {}", node);
-
-                let svr_arc = Arc::new(Mutex::new(svr));
-                let svr_copy = svr_arc.clone();
+                if matches.is_present("reliable") {
+                    // Keep stdout for the stream data
+                    info!("Sending on node id {}", node);
+                } else {
+                    println!("Sending on node id {}", node);
+                }
                 let source_stream = matches.is_present("source_stream");
                 let sink_stream = matches.is_present("sink_stream");
 
-                // TODO windows compatibility
+                let mut settings = uart::LineSettings::default();
+                if let Some(baud) = matches.value_of("baud") {
+                    settings.baud = baud.parse().expect("Invalid baud rate");
+                }
+                if let Some(line) = matches.value_of("line") {
+                    settings = settings.with_format(line).unwrap_or_else(|| { error!("Invalid line settings {}, expected a format such as 8N1", line); std::process::exit(1) });
+                }
+                let packets = uart::PacketOptions {
+                    timeout: matches.value_of("timeout").map(|x| Duration::from_millis(x.parse().expect("Invalid timeout"))),
+                    delimiter: matches.value_of("delimiter").map(|x| parse_id(x) as u8),
+                    max_size: matches.value_of("max_size").map(|x| x.parse().expect("Invalid packet size")).unwrap_or(2048),
+                };
+
+                // The pseudo-terminal is kept open while the stream runs
                 #[cfg(target_family="unix")]
-                {
-                if cfg!(target_family="unix") {
-                    if !sink_stream {
-                        thread::spawn(move || {
-                            debug!("Starting stdin thread");
-                            let mut stdin_handle = unsafe { File::from_raw_fd(0) };
-                            loop {
-                                let mut buffer = [0u8;2048];
-                                let n = stdin_handle.read(&mut buffer[..]).expect("Can't access stdin");
-                                if n>0 {
-                                    trace!("Sending packet to server");
-                                    let mut svr = svr_copy.lock().unwrap();
-                                    svr.send_packet(server::stream_packet(buffer[..n].to_vec()));
-                                } else {
-                                    trace!("No bytes received by stdin read");
-                                }
+                let _pty = if matches.is_present("pty") {
+                    let pty = pty::Pty::open(&settings, matches.value_of("link").map(std::path::Path::new))
+                        .unwrap_or_else(|e| { error!("Can't open a pseudo-terminal: {}", e); std::process::exit(1) });
+                    println!("{}", pty.path().display());
+                    Some(pty)
+                } else {
+                    None
+                };
+                #[cfg(not(target_family="unix"))]
+                let _pty: Option<File> = if matches.is_present("pty") {
+                    error!("Pseudo-terminals are only available on unix");
+                    std::process::exit(1)
+                } else {
+                    None
+                };
+                let (mut input, mut output): (Box<dyn Read + Send>, Box<dyn Write + Send>) = match (&_pty, matches.value_of("serial")) {
+                    (Some(pty), _) => (Box::new(pty.try_clone().expect("Can't access the pseudo-terminal")),
+                                       Box::new(pty.try_clone().expect("Can't access the pseudo-terminal"))),
+                    (None, Some(path)) => {
+                        let port = uart::open(path, &settings, packets.timeout.unwrap_or(Duration::from_secs(1)))
+                            .unwrap_or_else(|e| { error!("Can't open {}: {}", path, e); std::process::exit(1) });
+                        (Box::new(port.try_clone().expect("Can't access the serial port")), Box::new(port))
+                    },
+                    (None, None) => (Box::new(std::io::stdin()), Box::new(stdout())),
+                };
+
+                if matches.is_present("reliable") {
+                    let peer = matches.value_of("target").map(|x| svr.node_from_string(x).unwrap_or_else(|| {
+                        error!("Unknown node {}", x);
+                        std::process::exit(1)
+                    }));
+                    let stream = Arc::new(reliable::ReliableStream::new(svr, peer, reliable::ReliableOptions::default()));
+                    let stream_copy = stream.clone();
+                    let writer = thread::spawn(move || {
+                        if sink_stream {
+                            return;
+                        }
+                        let result = std::io::copy(&mut input, &mut &*stream_copy).and_then(|_| stream_copy.close());
+                        if let Err(e) = result {
+                            error!("Reliable stream failed: {}", e);
+                            std::process::exit(1);
+                        }
+                    });
+                    if !source_stream {
+                        let mut buffer = [0u8;2048];
+                        loop {
+                            match (&*stream).read(&mut buffer) {
+                                Ok(0) => break,
+                                Ok(n) => {
+                                    output.write_all(&buffer[..n]).expect("Can't write the stream output");
+                                    output.flush().expect("Can't flush the stream output");
+                                },
+                                Err(e) => {
+                                    error!("Reliable stream failed: {}", e);
+                                    std::process::exit(1);
+                                },
                             }
-                        });
+                        }
                     }
+                    writer.join().ok();
+                    return;
+                }
+
+                // Packets are received on their own thread, sending doesn't wait for them
+                let responses = svr.listen_response();
+                if !sink_stream {
+                    thread::spawn(move || {
+                        debug!("Starting input thread");
+                        let mut buffer = [0u8;2048];
+                        let mut packetizer = uart::Packetizer::new(packets);
+                        loop {
+                            let (packets, end) = match input.read(&mut buffer[..]) {
+                                Ok(0) => (packetizer.flush().into_iter().collect(), true),
+                                Ok(n) => (packetizer.push(&buffer[..n]), false),
+                                // Gap on the serial line
+                                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => (packetizer.flush().into_iter().collect(), false),
+                                Err(e) => { error!("Can't read the stream input: {}", e); std::process::exit(1) },
+                            };
+                            for packet in packets {
+                                trace!("Sending packet to server");
+                                svr.send_packet(server::stream_packet(packet));
+                            }
+                            if end {
+                                trace!("End of the stream input");
+                                break;
+                            }
+                        }
+                    });
                 }
+
+                if !source_stream {
+                    debug!("Starting output loop");
+                    for resp in responses.iter() {
+                        debug!("{:?}", resp);
+                        if resp.has_data() && resp.get_data().get_field_type() == rics::RICS_Data_RICS_DataType::STREAM {
+                            trace!("Writing packet to the stream output");
+                            output.write_all(resp.get_data().get_data()).expect("Can't write the stream output");
+                            output.flush().expect("Can't flush the stream output");
+                        }
+                    }
                 }
 
-                if !source_stream { 
-                    debug!("Starting stdout loop");
+                loop {
+                    thread::park();
+                }
+
+            }
+            else if let Some(matches) = matches.subcommand_matches("bridge") {
+                //////////////////////// BRIDGE ///////////////////////////
+                let (mode, sub_matches) = matches.subcommand();
+                let matches = match sub_matches {
+                    Some(matches) => matches,
+                    None => { println!("{}", matches.usage()); return; },
+                };
+                svr.connect(true);
+                if let Some(name) = matches.value_of("name") {
+                    svr.set_name(name);
+                }
+                receive_filter(&mut svr, matches);
+                println!("Bridging on node id {}", svr.who_am_i());
+                let address = matches.value_of("ADDRESS").unwrap();
+                let result = match mode {
+                    "tcp-listen" => bridge::tcp_listen(svr, address),
+                    "tcp-connect" => bridge::tcp_connect(svr, address),
+                    "udp" => {
+                        let port = matches.value_of("port").map(parse_id).unwrap_or(0);
+                        bridge::udp(svr, matches.value_of("bind").unwrap_or("0.0.0.0:0"), address, port)
+                    },
+                    _ => unreachable!(),
+                };
+                if let Err(e) = result {
+                    error!("Can't bridge {}: {}", address, e);
+                    std::process::exit(1);
+                }
+            }
+            else if let Some(matches) = matches.subcommand_matches("datagram") {
+                /////////////////////// DATAGRAM //////////////////////////
+                let (cmd, sub_matches) = matches.subcommand();
+                let matches = match sub_matches {
+                    Some(matches) => matches,
+                    None => { println!("{}", matches.usage()); return; },
+                };
+                svr.connect(true);
+                if let Some(name) = matches.value_of("name") {
+                    svr.set_name(name);
+                }
+                if cmd == "send" {
+                    let mut packet = server::datagram_packet(parse_id(matches.value_of("PORT").unwrap()), parse_hex(matches.value_of("DATA").unwrap()));
+                    if let Some(topic) = matches.value_of("topic") {
+                        packet.set_topic(topic.to_string());
+                    }
+                    match matches.value_of("target") {
+                        Some(target) => {
+                            let target = svr.node_from_string(target).unwrap_or_else(|| { error!("Unknown node {}", target); std::process::exit(1) });
+                            svr.send_packet_to(packet, target);
+                        },
+                        None => svr.send_packet(packet),
+                    }
+                } else {
+                    receive_filter(&mut svr, matches);
+                    println!("Listening on node id {}", svr.who_am_i());
+                    let port = matches.value_of("PORT").map(parse_id);
                     loop {
-                        let packet = svr_arc.lock().unwrap().get_packet();
-                        let mut stdout_handle = stdout();
-                        debug!("{:?}", packet);
-                        if let Some(p) = packet {
-                            if p.get_field_type() == rics::RICS_Data_RICS_DataType::STREAM {
-                                trace!("Sending packet to stdout");
-                                stdout_handle.write_all(p.get_data()).expect("Can't write to stdout");
-                                stdout_handle.flush().expect("Can't flust stdout");
+                        let data = match port {
+                            Some(port) => svr.recv_datagram(port),
+                            None => match svr.get_packet() {
+                                Some(data) if data.get_field_type() == rics::RICS_Data_RICS_DataType::DATAGRAM => data,
+                                _ => continue,
+                            },
+                        };
+                        println!("{} {} {}", data.get_source(), data.get_id(), hex_string(data.get_data()));
+                    }
+                }
+            }
+            else if let Some(matches) = matches.subcommand_matches("rpc") {
+                ///////////////////////// RPC /////////////////////////////
+                svr.connect(true);
+                if let Some(name) = matches.value_of("name") {
+                    svr.set_name(name);
+                }
+                let target = matches.value_of("TARGET").unwrap();
+                let target = svr.node_from_string(target).unwrap_or_else(|| { error!("Unknown node {}", target); std::process::exit(1) });
+                let payload = matches.value_of("DATA").map(parse_hex).unwrap_or_default();
+                let timeout = Duration::from_millis(matches.value_of("timeout").map(|x| x.parse::<u64>().expect("Invalid timeout")).unwrap_or(1000));
+                match svr.call(target, matches.value_of("METHOD").unwrap(), &payload, timeout) {
+                    Ok(response) => println!("{}", hex_string(&response)),
+                    Err(e) => {
+                        error!("{}", e);
+                        std::process::exit(1);
+                    },
+                }
+            }
+            else if let Some(matches) = matches.subcommand_matches("record") {
+                //////////////////////// RECORD ///////////////////////////
+                svr.connect(true);
+                subscribe(&mut svr, matches);
+                receive_filter(&mut svr, matches);
+                let node = svr.who_am_i();
+                info!("Recording on node id {}", node);
+
+                // Node names are looked up on a separate connection, once per source
+                let mut names = server::RICSServer::open(conn_copy);
+                names.connect(false);
+                names.list_nodes();
+                let mut sources: std::collections::HashMap<i32, String> = std::collections::HashMap::new();
+                // Last sequence number seen from each source, filters and routes also leave gaps
+                let mut sequences: std::collections::HashMap<i32, u32> = std::collections::HashMap::new();
+
+                let format = matches.value_of("format").and_then(record::LogFormat::from_name)
+                    .unwrap_or(matches.value_of("FILE").map(record::LogFormat::from_path).unwrap_or(record::LogFormat::Csv));
+                let mut writer = match matches.value_of("FILE") {
+                    Some(path) => record::create_record_file(path, format).expect("Can't create record file"),
+                    None => record::record_writer(format, Box::new(stdout())).expect("Can't write record"),
+                };
+                loop {
+                    if let Some(p) = svr.get_packet() {
+                        if let Some(sequence) = server::data_sequence(&p) {
+                            if let Some(last) = sequences.insert(p.get_source(), sequence) {
+                                let missed = sequence.wrapping_sub(last).wrapping_sub(1);
+                                if missed != 0 {
+                                    debug!("{} packets from node {} were not received", missed, p.get_source());
+                                }
                             }
                         }
+                        if p.get_field_type() == rics::RICS_Data_RICS_DataType::CAN {
+                            let source = sources.entry(p.get_source()).or_insert_with(|| {
+                                if !names.list_nodes_cached().contains_key(&p.get_source()) {
+                                    names.list_nodes();
+                                }
+                                names.list_nodes_cached().get(&p.get_source()).cloned().unwrap_or(p.get_source().to_string())
+                            }).clone();
+                            let rec = record::Record { timestamp: record::data_timestamp(&p), source: Some(source), channel: None, data: p };
+                            writer.write_record(&rec).expect("Can't write record");
+                        }
                     }
                 }
+            }
+            else if let Some(matches) = matches.subcommand_matches("replay") {
+                //////////////////////// REPLAY ///////////////////////////
+                let opts = record::ReplayOptions {
+                    speed: matches.value_of("speed").map(|x| x.parse::<f64>().expect("Invalid speed factor")).unwrap_or(1.0),
+                    looping: matches.is_present("loop"),
+                    start: matches.value_of("start").map(|x| x.parse::<f64>().expect("Invalid start time")),
+                    end: matches.value_of("end").map(|x| x.parse::<f64>().expect("Invalid end time")),
+                    ids: matches.values_of("id").map(|x| x.map(parse_id).collect()).unwrap_or_default(),
+                    sources: matches.values_of("source").map(|x| x.map(|s| s.to_string()).collect()).unwrap_or_default(),
+                    as_source: matches.is_present("as_source"),
+                    target: matches.value_of("target").map(|x| x.parse::<i32>().expect("Invalid target number")),
+                };
+                if opts.speed <= 0.0 {
+                    error!("The speed factor must be positive");
+                    std::process::exit(1);
+                }
 
-                loop {}
+                let records = record::read_log(matches.value_of("FILE").unwrap()).expect("Can't read replay file");
+                let records = record::filter_records(records, &opts);
+                info!("Replaying {} packets", records.len());
 
+                if !opts.as_source {
+                    svr.connect(true);
+                    if let Some(name) = matches.value_of("name") {
+                        svr.set_name(name);
+                    }
+                    let node = svr.who_am_i();
+                    println!("Replaying on node id {}", node);
+                }
+                record::replay(svr, conn_copy, records, &opts);
             }
-            else if let Some(_matches) = matches.subcommand_matches("log") {
+            else if let Some(matches) = matches.subcommand_matches("log") {
+                let dbc = load_dbc(matches);
                 svr.connect(true);
+                subscribe(&mut svr, matches);
+                receive_filter(&mut svr, matches);
                 let node = svr.who_am_i();
                 println!("Logging on node id {}", node);
                 loop {
                     let packet = svr.get_response();
                     debug!("{:?}", packet);
                     if let Some(p) = packet {
-                        println!("{}", server::response_to_string(&p));
+                        match dbc.as_ref().filter(|_| p.has_data()).and_then(|x| x.decode(p.get_data())) {
+                            Some(msg) => println!("{} {}", server::response_to_string(&p), msg),
+                            None => println!("{}", server::response_to_string(&p)),
+                        }
                     }
                 }
             }
@@ -0,0 +1,247 @@
+//! pcapng capture export
+//!
+//! CAN packets are written with the LINKTYPE_CAN_SOCKETCAN encapsulation so that
+//! the captures can be opened directly in Wireshark. Every source node gets its
+//! own interface named after the node, and the node name is also stored as a
+//! comment on each packet.
+
+use std::io::{Result, Write};
+use std::collections::HashMap;
+
+use super::rics;
+
+/// LINKTYPE_CAN_SOCKETCAN
+const LINKTYPE_CAN_SOCKETCAN: u16 = 227;
+
+const BLOCK_SHB: u32 = 0x0A0D0D0A;
+const BLOCK_IDB: u32 = 0x00000001;
+const BLOCK_EPB: u32 = 0x00000006;
+
+const OPT_ENDOFOPT: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_NAME: u16 = 2;
+const OPT_IF_DESCRIPTION: u16 = 3;
+const OPT_IF_TSRESOL: u16 = 9;
+
+/// SocketCAN extended frame flag
+pub const CAN_EFF_FLAG: u32 = 0x80000000;
+/// SocketCAN remote frame flag
+pub const CAN_RTR_FLAG: u32 = 0x40000000;
+/// SocketCAN error frame flag
+pub const CAN_ERR_FLAG: u32 = 0x20000000;
+/// SocketCAN FD frame flags
+const CANFD_BRS: u8 = 0x01;
+const CANFD_ESI: u8 = 0x02;
+const CANFD_FDF: u8 = 0x04;
+
+/// Append a pcapng option, padded to 32 bits
+fn push_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    buf.extend_from_slice(value);
+    while buf.len() % 4 != 0 {
+        buf.push(0);
+    }
+}
+
+/// Encode a CAN packet in the SocketCAN pseudo header format.
+/// CAN FD packets use the 72 bytes `canfd_frame` layout.
+pub fn socketcan_frame(data: &rics::RICS_Data) -> Vec<u8> {
+    let mut id = data.get_id() as u32;
+    if data.get_extended() {
+        id |= CAN_EFF_FLAG;
+    }
+    if data.get_rtr() {
+        id |= CAN_RTR_FLAG;
+    }
+    if data.get_error() {
+        id |= CAN_ERR_FLAG;
+    }
+    let dat = data.get_data();
+    let (len, mtu) = if data.get_fd() { (dat.len().min(64), 72) } else { (dat.len().min(8), 16) };
+
+    let mut flags = 0;
+    if data.get_fd() {
+        flags |= CANFD_FDF;
+        if data.get_brs() { flags |= CANFD_BRS; }
+        if data.get_esi() { flags |= CANFD_ESI; }
+    }
+
+    let mut frame = Vec::with_capacity(mtu);
+    // The id is in network byte order
+    frame.extend_from_slice(&id.to_be_bytes());
+    frame.push(len as u8);
+    frame.extend_from_slice(&[flags, 0, 0]);
+    if !data.get_rtr() {
+        frame.extend_from_slice(&dat[..len]);
+    }
+    frame.resize(mtu, 0);
+    frame
+}
+
+/// pcapng file writer
+pub struct PcapngWriter<W: Write> {
+    out: W,
+    /// Interface number of each source node
+    interfaces: HashMap<String, u32>,
+}
+
+impl<W: Write> PcapngWriter<W> {
+
+    /// Create a writer, the section header is written immediately
+    pub fn new(out: W) -> Result<PcapngWriter<W>> {
+        let mut writer = PcapngWriter {
+            out: out,
+            interfaces: HashMap::new(),
+        };
+
+        let mut body = vec![];
+        body.extend_from_slice(&0x1A2B3C4Du32.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // Unknown section length
+        body.extend_from_slice(&(-1i64).to_le_bytes());
+        push_option(&mut body, OPT_SHB_USERAPPL, b"ricsctl");
+        push_option(&mut body, OPT_ENDOFOPT, &[]);
+        writer.write_block(BLOCK_SHB, &body)?;
+        Ok(writer)
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> Result<()> {
+        let len = (12 + body.len()) as u32;
+        self.out.write_all(&block_type.to_le_bytes())?;
+        self.out.write_all(&len.to_le_bytes())?;
+        self.out.write_all(body)?;
+        self.out.write_all(&len.to_le_bytes())
+    }
+
+    /// Get the interface of a source node, creating it if needed
+    fn interface(&mut self, source: &str) -> Result<u32> {
+        if let Some(n) = self.interfaces.get(source) {
+            return Ok(*n);
+        }
+
+        let n = self.interfaces.len() as u32;
+        let mut body = vec![];
+        body.extend_from_slice(&LINKTYPE_CAN_SOCKETCAN.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // Snap length
+        body.extend_from_slice(&0u32.to_le_bytes());
+        push_option(&mut body, OPT_IF_NAME, source.as_bytes());
+        push_option(&mut body, OPT_IF_DESCRIPTION, format!("RICS node {}", source).as_bytes());
+        // Nanosecond timestamps
+        push_option(&mut body, OPT_IF_TSRESOL, &[9]);
+        push_option(&mut body, OPT_ENDOFOPT, &[]);
+        self.write_block(BLOCK_IDB, &body)?;
+
+        self.interfaces.insert(source.to_string(), n);
+        Ok(n)
+    }
+
+    /// Write a CAN packet received at `timestamp` (seconds since the unix epoch) from `source`
+    pub fn write_packet(&mut self, timestamp: f64, source: &str, data: &rics::RICS_Data) -> Result<()> {
+        let iface = self.interface(source)?;
+        let frame = socketcan_frame(data);
+        let ts = (timestamp * 1e9) as u64;
+
+        let mut body = vec![];
+        body.extend_from_slice(&iface.to_le_bytes());
+        body.extend_from_slice(&((ts >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(ts as u32).to_le_bytes());
+        body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        body.extend_from_slice(&frame);
+        while body.len() % 4 != 0 {
+            body.push(0);
+        }
+        push_option(&mut body, OPT_COMMENT, format!("source: {}", source).as_bytes());
+        push_option(&mut body, OPT_ENDOFOPT, &[]);
+        self.write_block(BLOCK_EPB, &body)
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::server;
+
+    fn u32_at(buf: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes([buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]])
+    }
+
+    /// Split a capture in (block type, body), checking both block lengths
+    fn blocks(buf: &[u8]) -> Vec<(u32, &[u8])> {
+        let mut blocks = vec![];
+        let mut pos = 0;
+        while pos < buf.len() {
+            let len = u32_at(buf, pos + 4) as usize;
+            assert_eq!(len % 4, 0);
+            assert_eq!(u32_at(buf, pos + len - 4) as usize, len);
+            blocks.push((u32_at(buf, pos), &buf[pos + 8..pos + len - 4]));
+            pos += len;
+        }
+        assert_eq!(pos, buf.len());
+        blocks
+    }
+
+    #[test]
+    fn socketcan_frames() {
+        let frame = socketcan_frame(&server::can_packet(0x123, vec![1, 2, 3]));
+        assert_eq!(frame, vec![0, 0, 0x01, 0x23, 3, 0, 0, 0, 1, 2, 3, 0, 0, 0, 0, 0]);
+
+        let flags = server::CanFlags { extended: true, rtr: true, ..Default::default() };
+        let frame = socketcan_frame(&server::can_frame(0x1ABCDEF, vec![0; 4], flags));
+        assert_eq!(frame[..8], [0xC1, 0xAB, 0xCD, 0xEF, 4, 0, 0, 0]);
+        assert_eq!(frame.len(), 16);
+
+        let flags = server::CanFlags { fd: true, brs: true, ..Default::default() };
+        let frame = socketcan_frame(&server::can_frame(0x42, (0..12).collect(), flags));
+        assert_eq!(frame.len(), 72);
+        assert_eq!(frame[4..8], [12, CANFD_FDF | CANFD_BRS, 0, 0]);
+        assert_eq!(frame[8..20], (0..12).collect::<Vec<u8>>()[..]);
+    }
+
+    #[test]
+    fn block_layout() {
+        let mut writer = PcapngWriter::new(vec![]).unwrap();
+        writer.write_packet(1.5, "node1", &server::can_packet(0x10, vec![0xAA])).unwrap();
+        writer.write_packet(2.0, "node2", &server::can_packet(0x20, vec![])).unwrap();
+        writer.write_packet(4.294967296, "node1", &server::can_packet(0x30, vec![])).unwrap();
+
+        let blocks = blocks(&writer.out);
+        let kinds: Vec<u32> = blocks.iter().map(|x| x.0).collect();
+        assert_eq!(kinds, vec![BLOCK_SHB, BLOCK_IDB, BLOCK_EPB, BLOCK_IDB, BLOCK_EPB, BLOCK_EPB]);
+
+        // Byte order magic, version 1.0, unknown section length
+        let shb = blocks[0].1;
+        assert_eq!(u32_at(shb, 0), 0x1A2B3C4D);
+        assert_eq!(shb[4..8], [1, 0, 0, 0]);
+        assert_eq!(shb[8..16], [0xFF; 8]);
+
+        let idb = blocks[1].1;
+        assert_eq!(idb[..8], [227, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(idb[8..20], [OPT_IF_NAME as u8, 0, 5, 0, b'n', b'o', b'd', b'e', b'1', 0, 0, 0]);
+
+        // Interface, nanosecond timestamp, captured and original lengths, frame
+        let epb = blocks[2].1;
+        assert_eq!(u32_at(epb, 0), 0);
+        assert_eq!((u64::from(u32_at(epb, 4)) << 32) | u64::from(u32_at(epb, 8)), 1_500_000_000);
+        assert_eq!(u32_at(epb, 12), 16);
+        assert_eq!(u32_at(epb, 16), 16);
+        assert_eq!(epb[20..36], socketcan_frame(&server::can_packet(0x10, vec![0xAA]))[..]);
+        assert_eq!(epb[36..40], [OPT_COMMENT as u8, 0, 13, 0]);
+        assert_eq!(&epb[40..53], b"source: node1");
+        assert_eq!(epb[56..], [0, 0, 0, 0]);
+
+        assert_eq!(u32_at(blocks[4].1, 0), 1);
+        let epb = blocks[5].1;
+        assert_eq!(u32_at(epb, 0), 0);
+        assert_eq!(u32_at(epb, 4), 1);
+    }
+}
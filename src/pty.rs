@@ -0,0 +1,150 @@
+//! Pseudo-terminals for tools that only open serial devices
+//!
+//! The slave side of the pair is the device opened by the tools, configured
+//! in raw mode with the given line settings. The bridge keeps the slave open
+//! as well, so that the master side doesn't fail while no tool has opened it.
+//! Linux pseudo-terminals always report 8 data bits without parity, only the
+//! baud rate and stop bits are kept there.
+
+use std::ffi::CStr;
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+
+use super::uart::{LineSettings, Parity};
+
+fn baud_constant(baud: u32) -> Option<libc::speed_t> {
+    Some(match baud {
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        #[cfg(target_os="linux")]
+        460800 => libc::B460800,
+        #[cfg(target_os="linux")]
+        921600 => libc::B921600,
+        _ => return None,
+    })
+}
+
+fn check(ret: libc::c_int) -> Result<libc::c_int> {
+    if ret < 0 { Err(Error::last_os_error()) } else { Ok(ret) }
+}
+
+/// Configure a terminal in raw mode with the line settings
+fn set_line(tty: &File, settings: &LineSettings) -> Result<()> {
+    let speed = baud_constant(settings.baud)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unsupported baud rate {}", settings.baud)))?;
+    unsafe {
+        let mut tio: libc::termios = std::mem::zeroed();
+        check(libc::tcgetattr(tty.as_raw_fd(), &mut tio))?;
+        libc::cfmakeraw(&mut tio);
+        tio.c_cflag &= !(libc::CSIZE | libc::PARENB | libc::PARODD | libc::CSTOPB);
+        tio.c_cflag |= libc::CLOCAL | libc::CREAD | match settings.data_bits {
+            5 => libc::CS5,
+            6 => libc::CS6,
+            7 => libc::CS7,
+            _ => libc::CS8,
+        };
+        match settings.parity {
+            Parity::None => (),
+            Parity::Even => tio.c_cflag |= libc::PARENB,
+            Parity::Odd => tio.c_cflag |= libc::PARENB | libc::PARODD,
+        }
+        if settings.stop_bits == 2 {
+            tio.c_cflag |= libc::CSTOPB;
+        }
+        check(libc::cfsetispeed(&mut tio, speed))?;
+        check(libc::cfsetospeed(&mut tio, speed))?;
+        check(libc::tcsetattr(tty.as_raw_fd(), libc::TCSANOW, &tio))?;
+    }
+    Ok(())
+}
+
+/// Point `link` to `target`, replacing the link left by a previous run
+fn replace_link(target: &Path, link: &Path) -> Result<()> {
+    if let Ok(meta) = std::fs::symlink_metadata(link) {
+        if !meta.file_type().is_symlink() {
+            return Err(Error::new(ErrorKind::AlreadyExists, format!("{} exists and isn't a symbolic link", link.display())));
+        }
+    }
+    let name = link.file_name().ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid link path"))?;
+    let tmp = link.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    std::fs::remove_file(&tmp).ok();
+    std::os::unix::fs::symlink(target, &tmp)?;
+    std::fs::rename(&tmp, link)
+}
+
+/// Pseudo-terminal pair, read and written from the master side
+pub struct Pty {
+    master: File,
+    _slave: File,
+    path: PathBuf,
+    link: Option<PathBuf>,
+}
+
+impl Pty {
+    /// Open a pair, optionally linked from a fixed path
+    pub fn open(settings: &LineSettings, link: Option<&Path>) -> Result<Pty> {
+        let master = unsafe { File::from_raw_fd(check(libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY))?) };
+        let name = unsafe {
+            check(libc::grantpt(master.as_raw_fd()))?;
+            check(libc::unlockpt(master.as_raw_fd()))?;
+            let name = libc::ptsname(master.as_raw_fd());
+            if name.is_null() {
+                return Err(Error::last_os_error());
+            }
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        };
+        let path = PathBuf::from(name);
+        let slave = OpenOptions::new().read(true).write(true).custom_flags(libc::O_NOCTTY).open(&path)?;
+        set_line(&slave, settings)?;
+        if let Some(link) = link {
+            replace_link(&path, link)?;
+        }
+        Ok(Pty { master: master, _slave: slave, path: path, link: link.map(|x| x.to_path_buf()) })
+    }
+
+    /// Path of the slave device
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Master side handle, to read and write from different threads
+    pub fn try_clone(&self) -> Result<File> {
+        self.master.try_clone()
+    }
+}
+
+impl Read for Pty {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.master.read(buf)
+    }
+}
+
+impl Write for Pty {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.master.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.master.flush()
+    }
+}
+
+impl Drop for Pty {
+    fn drop(&mut self) {
+        if let Some(link) = &self.link {
+            if std::fs::read_link(link).map(|x| x == self.path).unwrap_or(false) {
+                std::fs::remove_file(link).ok();
+            }
+        }
+    }
+}
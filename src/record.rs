@@ -0,0 +1,396 @@
+//! Traffic recording and replay
+
+use std::io::{BufRead, BufReader, Write};
+use std::fs::File;
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::TimeZone;
+
+use super::rics;
+use super::server::{self, RICSServer, ConnectTo};
+use super::pcap::PcapngWriter;
+use super::candump::{self, CandumpWriter};
+use super::asc::{self, AscWriter};
+use super::blf::{self, BlfWriter};
+
+/// A recorded packet
+#[derive(Clone, Debug)]
+pub struct Record {
+    /// Reception time, in seconds since the unix epoch
+    pub timestamp: f64,
+    /// Name of the node that sent the packet, if known
+    pub source: Option<String>,
+    /// Bus channel number, for trace formats that have one
+    pub channel: Option<u32>,
+    /// Packet content
+    pub data: rics::RICS_Data,
+}
+
+/// Supported log file formats
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LogFormat {
+    /// `can log` text format, with the source node name appended
+    Csv,
+    /// pcapng capture with the SocketCAN link type
+    Pcapng,
+    /// can-utils candump log
+    Candump,
+    /// Vector ASC text trace
+    Asc,
+    /// Vector BLF binary trace
+    Blf,
+}
+
+impl LogFormat {
+    /// Parse a format name as given on the command line
+    pub fn from_name(name: &str) -> Option<LogFormat> {
+        match name {
+            "csv" => Some(LogFormat::Csv),
+            "pcapng" => Some(LogFormat::Pcapng),
+            "candump" => Some(LogFormat::Candump),
+            "asc" => Some(LogFormat::Asc),
+            "blf" => Some(LogFormat::Blf),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from a file extension, defaults to csv
+    pub fn from_path(path: &str) -> LogFormat {
+        let ext = std::path::Path::new(path).extension().and_then(|x| x.to_str()).unwrap_or("").to_lowercase();
+        match ext.as_str() {
+            "pcapng" | "pcap" => LogFormat::Pcapng,
+            "log" => LogFormat::Candump,
+            "asc" => LogFormat::Asc,
+            "blf" => LogFormat::Blf,
+            _ => LogFormat::Csv,
+        }
+    }
+}
+
+/// Destination of recorded packets
+pub trait RecordWriter {
+    fn write_record(&mut self, rec: &Record) -> std::io::Result<()>;
+}
+
+/// Writes records in the `can log` text format
+pub struct CsvWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(out: W) -> CsvWriter<W> {
+        CsvWriter { out: out }
+    }
+}
+
+impl<W: Write> RecordWriter for CsvWriter<W> {
+    fn write_record(&mut self, rec: &Record) -> std::io::Result<()> {
+        writeln!(self.out, "{}", record_to_string(rec))?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> RecordWriter for PcapngWriter<W> {
+    fn write_record(&mut self, rec: &Record) -> std::io::Result<()> {
+        self.write_packet(rec.timestamp, rec.source.as_ref().map(|x| x.as_str()).unwrap_or("rics"), &rec.data)?;
+        self.flush()
+    }
+}
+
+/// Create a record writer of the given format.
+/// BLF traces can only be written to files, see `create_record_file`.
+pub fn record_writer(format: LogFormat, out: Box<dyn Write>) -> std::io::Result<Box<dyn RecordWriter>> {
+    Ok(match format {
+        LogFormat::Csv => Box::new(CsvWriter::new(out)),
+        LogFormat::Pcapng => Box::new(PcapngWriter::new(out)?),
+        LogFormat::Candump => Box::new(CandumpWriter::new(out)),
+        LogFormat::Asc => Box::new(AscWriter::new(out)),
+        LogFormat::Blf => return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "BLF traces can only be written to a file")),
+    })
+}
+
+/// Create a file and a record writer of the given format for it
+pub fn create_record_file(path: &str, format: LogFormat) -> std::io::Result<Box<dyn RecordWriter>> {
+    let file = File::create(path)?;
+    match format {
+        LogFormat::Blf => Ok(Box::new(BlfWriter::new(file)?)),
+        _ => record_writer(format, Box::new(file)),
+    }
+}
+
+/// Assigns bus channel numbers to source node names, starting at 1
+pub struct ChannelMap {
+    channels: HashMap<String, u32>,
+}
+
+impl ChannelMap {
+    pub fn new() -> ChannelMap {
+        ChannelMap { channels: HashMap::new() }
+    }
+
+    /// Channel of a record, records that already have one keep it
+    pub fn channel(&mut self, rec: &Record) -> u32 {
+        if let Some(channel) = rec.channel {
+            return channel;
+        }
+        let n = self.channels.len() as u32 + 1;
+        *self.channels.entry(rec.source.clone().unwrap_or_default()).or_insert(n)
+    }
+}
+
+/// Host reception time of a packet, in seconds since the unix epoch
+pub fn data_timestamp(data: &rics::RICS_Data) -> f64 {
+    server::data_time(data).duration_since(std::time::UNIX_EPOCH).expect("Invalid packet time").as_secs_f64()
+}
+
+/// Format a timestamp the same way as `data_to_loggable_string`
+pub fn format_time(timestamp: f64) -> String {
+    let millis = (timestamp * 1e3).round() as i64;
+    let datetime = chrono::Local.timestamp_opt(millis.div_euclid(1000), (millis.rem_euclid(1000) * 1_000_000) as u32).unwrap();
+    format!("{}", datetime.format("%Y-%m-%d %T%.3f"))
+}
+
+/// Parse a local time formatted by `data_to_loggable_string`
+pub fn parse_time(s: &str) -> Option<f64> {
+    let time = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %T%.f").ok()?;
+    let time = chrono::Local.from_local_datetime(&time).earliest()?;
+    Some(time.timestamp() as f64 + time.timestamp_subsec_nanos() as f64 * 1e-9)
+}
+
+/// Pause between two iterations of a looping replay, so that a trace with a
+/// single timestamp does not flood the bus
+pub const LOOP_GAP: Duration = Duration::from_millis(100);
+
+/// Replay configuration
+pub struct ReplayOptions {
+    /// Time scaling factor, 2.0 replays twice as fast
+    pub speed: f64,
+    /// Restart from the beginning once the end is reached
+    pub looping: bool,
+    /// Start of the replay window, in seconds after the first record
+    pub start: Option<f64>,
+    /// End of the replay window, in seconds after the first record
+    pub end: Option<f64>,
+    /// Only replay these ids (all ids if empty)
+    pub ids: Vec<i32>,
+    /// Only replay packets from these source nodes (all sources if empty)
+    pub sources: Vec<String>,
+    /// Inject every packet as a node named after its original source
+    pub as_source: bool,
+    /// Send every packet to this node instead of the default route
+    pub target: Option<i32>,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> ReplayOptions {
+        ReplayOptions {
+            speed: 1.0,
+            looping: false,
+            start: None,
+            end: None,
+            ids: vec![],
+            sources: vec![],
+            as_source: false,
+            target: None,
+        }
+    }
+}
+
+/// Format a record as a recording line.
+/// The format is the one of `data_to_loggable_string` followed by the source node name,
+/// and by the frame flags if there are any.
+pub fn record_to_string(rec: &Record) -> String {
+    let data = &rec.data;
+    let flags = server::data_flags(data);
+    let line = format!("{},{:x},{},{},{}", format_time(rec.timestamp), data.get_id(), data.get_data().len(), data.get_data().iter()
+            .map(|x|format!("{:x}",x)).collect::<Vec<String>>().join(","), rec.source.as_ref().map(|x| x.as_str()).unwrap_or(""));
+    if flags.is_empty() { line } else { format!("{},{}", line, flags) }
+}
+
+/// Parse a line of a CAN log or recording.
+///
+/// Accepts `time,id,len,data...` as written by `can log`, with an optional
+/// source node name and frame flags after the data bytes as written by `record`.
+pub fn parse_log_line(line: &str) -> Option<Record> {
+    let fields: Vec<&str> = line.trim().split(',').collect();
+    if fields.len() < 3 {
+        return None;
+    }
+
+    let timestamp = parse_time(fields[0])?;
+    let id = u32::from_str_radix(fields[1], 16).ok()? as i32;
+    let len = fields[2].parse::<usize>().ok()?;
+    if len > server::CANFD_MAX_DLEN || fields.len() < 3 + len {
+        return None;
+    }
+    let dat = fields[3..3+len].iter().map(|x| u8::from_str_radix(x, 16).ok()).collect::<Option<Vec<u8>>>()?;
+
+    // An empty packet still leaves an empty data column
+    let mut rest = &fields[3+len..];
+    if len == 0 && rest.first() == Some(&"") {
+        rest = &rest[1..];
+    }
+    let source = rest.first().filter(|x| !x.is_empty()).map(|x| x.to_string());
+
+    let mut data = server::can_packet(id, dat);
+    if let Some(flags) = rest.get(1) {
+        server::set_data_flags(&mut data, flags);
+    }
+
+    Some(Record {
+        timestamp: timestamp,
+        source: source,
+        channel: None,
+        data: data,
+    })
+}
+
+/// Parse a line of any of the supported text log formats
+pub fn parse_any_line(line: &str) -> Option<Record> {
+    if line.trim_start().starts_with('(') {
+        candump::parse_candump_line(line)
+    } else {
+        parse_log_line(line)
+    }
+}
+
+/// Read all the packets of a CAN log, recording, candump, ASC or BLF file
+pub fn read_log(path: &str) -> std::io::Result<Vec<Record>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    if blf::is_blf(reader.fill_buf()?) {
+        return blf::read_blf(reader);
+    }
+    if LogFormat::from_path(path) == LogFormat::Asc || reader.fill_buf()?.starts_with(b"date ") {
+        return asc::read_asc(reader);
+    }
+
+    let mut records = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        match parse_any_line(&line) {
+            Some(rec) => records.push(rec),
+            None => if !line.trim().is_empty() { warn!("Skipping invalid log line: {}", line) },
+        }
+    }
+    Ok(records)
+}
+
+/// Keep the records inside the replay window that match the filters
+pub fn filter_records(records: Vec<Record>, opts: &ReplayOptions) -> Vec<Record> {
+    let t0 = match records.first() {
+        Some(rec) => rec.timestamp,
+        None => return records,
+    };
+
+    records.into_iter().filter(|rec| {
+        let t = rec.timestamp - t0;
+        opts.start.map(|s| t >= s).unwrap_or(true)
+            && opts.end.map(|e| t <= e).unwrap_or(true)
+            && (opts.ids.is_empty() || opts.ids.contains(&rec.data.get_id()))
+            && (opts.sources.is_empty() || rec.source.as_ref().map(|s| opts.sources.contains(s)).unwrap_or(false))
+    }).collect()
+}
+
+/// Send the records with their original timing.
+///
+/// The packets are sent by `svr` unless `as_source` is set, in which case
+/// a node is connected through `ct` for every source name in the records.
+pub fn replay(mut svr: RICSServer, ct: ConnectTo, records: Vec<Record>, opts: &ReplayOptions) {
+    let mut nodes: HashMap<String, RICSServer> = HashMap::new();
+    if opts.as_source {
+        for rec in records.iter() {
+            let name = rec.source.clone().unwrap_or_else(|| "replay".to_string());
+            if !nodes.contains_key(&name) {
+                let mut node = RICSServer::open(ct.clone());
+                node.connect(true);
+                node.set_name(name.clone());
+                info!("Replaying {} on node id {}", name, node.who_am_i());
+                node.drain();
+                nodes.insert(name, node);
+            }
+        }
+    } else {
+        svr.drain();
+    }
+
+    let t0 = match records.first() {
+        Some(rec) => rec.timestamp,
+        None => { warn!("Nothing to replay"); return; },
+    };
+
+    loop {
+        let start = Instant::now();
+        for rec in records.iter() {
+            let due = Duration::from_secs_f64(((rec.timestamp - t0) / opts.speed).max(0.0));
+            let elapsed = start.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+
+            let node = if opts.as_source {
+                nodes.get_mut(rec.source.as_ref().map(|x| x.as_str()).unwrap_or("replay")).unwrap()
+            } else {
+                &mut svr
+            };
+            let mut data = rec.data.clone();
+            data.clear_source();
+            data.clear_timestamp();
+            data.clear_sequence();
+            trace!("Replaying {}", server::data_to_string(&data));
+            match opts.target {
+                Some(t) => node.send_packet_to(data, t),
+                None => node.send_packet(data),
+            }
+        }
+
+        if !opts.looping {
+            break;
+        }
+        thread::sleep(LOOP_GAP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Id, data and flags of a packet
+    fn frame(data: &rics::RICS_Data) -> (i32, Vec<u8>, String) {
+        (data.get_id(), data.get_data().to_vec(), server::data_flags(data))
+    }
+
+    /// Frame of a `can log` line read back as a recording
+    fn log_round_trip(data: &rics::RICS_Data) -> (i32, Vec<u8>, String) {
+        frame(&parse_log_line(&server::data_to_loggable_string(data)).unwrap().data)
+    }
+
+    #[test]
+    fn fd_log_lines() {
+        let data = server::can_packet(0x42, vec![1, 2]);
+        assert!(server::data_to_loggable_string(&data).ends_with(",42,2,1,2"));
+        assert_eq!(log_round_trip(&data), frame(&data));
+
+        let flags = server::CanFlags { fd: true, brs: true, esi: true, ..Default::default() };
+        let data = server::can_frame(0x42, (0..12).collect(), flags);
+        assert!(server::data_to_loggable_string(&data).ends_with(",9,a,b,,FD BRS ESI"));
+        assert_eq!(log_round_trip(&data), frame(&data));
+        // CAN FD frames of 8 bytes or less are only told apart by their flag
+        let data = server::can_frame(0x42, vec![1], server::CanFlags { fd: true, ..Default::default() });
+        assert_eq!(log_round_trip(&data), frame(&data));
+    }
+    #[test]
+    fn error_and_remote_log_lines() {
+        let data = server::can_frame(0x4, vec![0, 0, 0x80, 0, 0, 0, 0, 0], server::CanFlags { error: true, ..Default::default() });
+        assert!(server::data_to_loggable_string(&data).ends_with(",4,8,0,0,80,0,0,0,0,0,,ERR"));
+        assert_eq!(log_round_trip(&data), frame(&data));
+
+        let flags = server::CanFlags { extended: true, rtr: true, ..Default::default() };
+        let data = server::can_frame(0x18DAF110, vec![0; 3], flags);
+        assert!(server::data_to_loggable_string(&data).ends_with(",,EXT RTR"));
+        assert_eq!(log_round_trip(&data), frame(&data));
+        let data = server::can_frame(0x123, vec![], server::CanFlags { rtr: true, ..Default::default() });
+        assert!(server::data_to_loggable_string(&data).ends_with(",123,0,,,RTR"));
+        assert_eq!(log_round_trip(&data), frame(&data));
+    }
+}
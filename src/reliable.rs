@@ -0,0 +1,580 @@
+//! Reliable, ordered byte streams over STREAM packets
+//!
+//! Each direction of the stream is a sequence of segments, opened by a SYN
+//! segment carrying a random initial sequence number and closed by a FIN
+//! segment. Every segment received is acknowledged with the next sequence
+//! number expected and the number of segments the receiver can still
+//! buffer. Segments that aren't acknowledged in time are sent again, and
+//! segments received out of order are kept until the missing ones arrive.
+//!
+//! Segment header: kind (1 byte), sequence number (4 bytes, big endian),
+//! then the payload, or the receive window (2 bytes, big endian) for
+//! acknowledgements.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::rics;
+use super::server::{self, RICSServer};
+
+const SEG_SYN: u8 = 1;
+const SEG_DATA: u8 = 2;
+const SEG_ACK: u8 = 3;
+const SEG_FIN: u8 = 4;
+
+const HEADER_LEN: usize = 5;
+
+/// Sequence numbers wrap around, `a` is before `b` when less than half the
+/// sequence space separates them
+fn seq_before(a: u32, b: u32) -> bool {
+    b.wrapping_sub(a) as i32 > 0
+}
+
+#[derive(Clone, Debug)]
+pub struct ReliableOptions {
+    /// Largest payload of a segment
+    pub segment_size: usize,
+    /// Segments sent and not acknowledged yet, and segments buffered by the receiver
+    pub window: usize,
+    /// Time after which a segment that isn't acknowledged is sent again
+    pub timeout: Duration,
+    /// Retransmissions of a segment before the peer is considered lost.
+    /// The SYN segment is retransmitted until the peer answers.
+    pub retries: u32,
+}
+
+impl Default for ReliableOptions {
+    fn default() -> ReliableOptions {
+        ReliableOptions {
+            segment_size: 1024,
+            window: 16,
+            timeout: Duration::from_millis(200),
+            retries: 10,
+        }
+    }
+}
+
+/// State shared by the stream and its thread
+#[derive(Default)]
+struct State {
+    /// Bytes received in order and not read yet
+    incoming: VecDeque<u8>,
+    /// The peer closed its direction
+    eof: bool,
+    /// Segments queued or in flight
+    pending: usize,
+    error: Option<String>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn update(&self, f: impl FnOnce(&mut State)) {
+        f(&mut self.state.lock().unwrap());
+        self.changed.notify_all();
+    }
+}
+
+enum Event {
+    Packet(rics::RICS_Data),
+    Write(Vec<u8>),
+    /// The application read bytes, the receive window may have grown
+    Read,
+    Close,
+    Shutdown,
+}
+
+/// Reliable stream with a peer node, or over the default route.
+/// Like `TcpStream`, `&ReliableStream` is also `Read` and `Write`, so that
+/// both directions can be used from different threads. Dropping the stream
+/// stops it at once, flush or close it first.
+pub struct ReliableStream {
+    shared: Arc<Shared>,
+    events: Mutex<Sender<Event>>,
+    options: ReliableOptions,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReliableStream {
+    /// The server must be connected as a node. Only the STREAM packets of
+    /// `peer` are accepted when it is given, and segments are sent to it.
+    /// Otherwise segments follow the default route until a SYN is received,
+    /// its source becoming the peer.
+    pub fn new(mut server: RICSServer, peer: Option<i32>, options: ReliableOptions) -> ReliableStream {
+        let shared = Arc::new(Shared { state: Mutex::new(State::default()), changed: Condvar::new() });
+        let (events, rx) = channel();
+        let responses = server.listen_response();
+        let packets = events.clone();
+        thread::spawn(move || {
+            for resp in responses.iter() {
+                let data = resp.get_data();
+                if resp.has_data() && data.get_field_type() == rics::RICS_Data_RICS_DataType::STREAM
+                    && peer.map(|x| x == data.get_source()).unwrap_or(true)
+                    && packets.send(Event::Packet(data.clone())).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut connection = Connection::new(server, peer, options.clone(), shared.clone());
+        let thread = thread::spawn(move || connection.run(rx));
+        ReliableStream { shared: shared, events: Mutex::new(events), options: options, thread: Some(thread) }
+    }
+
+    fn send(&self, event: Event) -> Result<()> {
+        self.events.lock().unwrap().send(event).map_err(|_| Error::new(ErrorKind::BrokenPipe, "Stream thread stopped"))
+    }
+
+    fn error(state: &State) -> Result<()> {
+        match &state.error {
+            Some(e) => Err(Error::new(ErrorKind::TimedOut, e.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Close the sending direction once every byte written is acknowledged.
+    /// Reading goes on until the peer closes its own direction.
+    pub fn close(&self) -> Result<()> {
+        self.shared.update(|state| state.pending += 1);
+        self.send(Event::Close)?;
+        self.wait_acknowledged()
+    }
+
+    fn receive(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut state = self.shared.state.lock().unwrap();
+        while state.incoming.is_empty() && !state.eof && state.error.is_none() {
+            state = self.shared.changed.wait(state).unwrap();
+        }
+        if state.incoming.is_empty() {
+            return ReliableStream::error(&state).map(|_| 0);
+        }
+        let n = buf.len().min(state.incoming.len());
+        for (dst, src) in buf.iter_mut().zip(state.incoming.drain(..n)) {
+            *dst = src;
+        }
+        drop(state);
+        self.send(Event::Read).ok();
+        Ok(n)
+    }
+
+    /// Queue up to one segment, blocking while the window is full
+    fn queue(&self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.shared.state.lock().unwrap();
+        while state.pending >= self.options.window && state.error.is_none() {
+            state = self.shared.changed.wait(state).unwrap();
+        }
+        ReliableStream::error(&state)?;
+        state.pending += 1;
+        drop(state);
+        let n = buf.len().min(self.options.segment_size);
+        self.send(Event::Write(buf[..n].to_vec()))?;
+        Ok(n)
+    }
+
+    /// Block until every byte written is acknowledged
+    fn wait_acknowledged(&self) -> Result<()> {
+        let mut state = self.shared.state.lock().unwrap();
+        while state.pending > 0 && state.error.is_none() {
+            state = self.shared.changed.wait(state).unwrap();
+        }
+        ReliableStream::error(&state)
+    }
+}
+
+impl Read for ReliableStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.receive(buf)
+    }
+}
+
+impl Read for &ReliableStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.receive(buf)
+    }
+}
+
+impl Write for ReliableStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.queue(buf)
+    }
+
+    /// Block until every byte written is acknowledged
+    fn flush(&mut self) -> Result<()> {
+        self.wait_acknowledged()
+    }
+}
+
+impl Write for &ReliableStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.queue(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.wait_acknowledged()
+    }
+}
+
+impl Drop for ReliableStream {
+    fn drop(&mut self) {
+        self.send(Event::Shutdown).ok();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// Segment sent and not acknowledged yet
+struct Unacked {
+    segment: Vec<u8>,
+    sent: Instant,
+    retries: u32,
+}
+
+struct Connection {
+    server: RICSServer,
+    peer: Option<i32>,
+    options: ReliableOptions,
+    shared: Arc<Shared>,
+
+    /// Segments written and not sent yet
+    queue: VecDeque<(u8, Vec<u8>)>,
+    unacked: BTreeMap<u32, Unacked>,
+    next_seq: u32,
+    /// The peer acknowledged our SYN
+    opened: bool,
+    peer_window: usize,
+    last_ack: Instant,
+
+    /// Initial sequence number of the peer, once its SYN is received
+    peer_syn: Option<u32>,
+    expected: u32,
+    out_of_order: BTreeMap<u32, (u8, Vec<u8>)>,
+    /// Last window advertised to the peer
+    advertised: usize,
+}
+
+impl Connection {
+    fn new(server: RICSServer, peer: Option<i32>, options: ReliableOptions, shared: Arc<Shared>) -> Connection {
+        let isn = rand::random::<u32>();
+        let mut queue = VecDeque::new();
+        queue.push_back((SEG_SYN, vec![]));
+        Connection {
+            server: server,
+            peer: peer,
+            peer_window: 1,
+            last_ack: Instant::now(),
+            advertised: options.window,
+            options: options,
+            shared: shared,
+            queue: queue,
+            unacked: BTreeMap::new(),
+            next_seq: isn,
+            opened: false,
+            peer_syn: None,
+            expected: 0,
+            out_of_order: BTreeMap::new(),
+        }
+    }
+
+    fn send(&mut self, segment: Vec<u8>) {
+        let data = server::stream_packet(segment);
+        match self.peer {
+            Some(peer) => self.server.send_packet_to(data, peer),
+            None => self.server.send_packet(data),
+        }
+    }
+
+    /// Segments the read buffer can still take
+    fn buffer_space(&self) -> usize {
+        let buffered = self.shared.state.lock().unwrap().incoming.len();
+        (self.options.window * self.options.segment_size).saturating_sub(buffered) / self.options.segment_size
+    }
+
+    /// Segments the peer may send beyond the next one expected
+    fn window(&self) -> usize {
+        self.buffer_space().saturating_sub(self.out_of_order.len())
+    }
+
+    fn ack(&mut self) {
+        self.advertised = self.window();
+        let mut segment = vec![SEG_ACK];
+        segment.extend_from_slice(&self.expected.to_be_bytes());
+        segment.extend_from_slice(&(self.advertised.min(0xFFFF) as u16).to_be_bytes());
+        self.send(segment);
+    }
+
+    fn run(&mut self, events: Receiver<Event>) {
+        loop {
+            let deadline = self.unacked.values().map(|x| x.sent + self.options.timeout).chain(self.probe_time()).min();
+            let event = match deadline {
+                Some(deadline) => events.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => events.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match event {
+                Ok(Event::Packet(data)) => self.receive(data.get_source(), data.get_data()),
+                Ok(Event::Write(payload)) => self.queue.push_back((SEG_DATA, payload)),
+                Ok(Event::Close) => self.queue.push_back((SEG_FIN, vec![])),
+                Ok(Event::Read) => {
+                    // Reopen a closing window without waiting for the next segment
+                    if self.peer_syn.is_some() && self.advertised < self.options.window / 2 && self.window() > self.advertised {
+                        self.ack();
+                    }
+                },
+                Ok(Event::Shutdown) | Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => (),
+            }
+            if !self.retransmit() {
+                return;
+            }
+            self.transmit();
+        }
+    }
+
+    /// Time at which a segment is sent to probe a full peer window, in case
+    /// the acknowledgement reopening it was lost
+    fn probe_time(&self) -> Option<Instant> {
+        if self.opened && self.peer_window == 0 && self.unacked.is_empty() && !self.queue.is_empty() {
+            Some(self.last_ack + self.options.timeout)
+        } else {
+            None
+        }
+    }
+
+    /// Send queued segments while the peer window allows it
+    fn transmit(&mut self) {
+        while let Some((kind, _)) = self.queue.front() {
+            let probe = self.probe_time().map(|x| x <= Instant::now()).unwrap_or(false);
+            let window = if !self.opened || probe {
+                1
+            } else {
+                self.peer_window.min(self.options.window)
+            };
+            if self.unacked.len() >= window || (*kind != SEG_SYN && !self.opened) {
+                break;
+            }
+            let (kind, payload) = self.queue.pop_front().unwrap();
+            let mut segment = vec![kind];
+            segment.extend_from_slice(&self.next_seq.to_be_bytes());
+            segment.extend_from_slice(&payload);
+            self.unacked.insert(self.next_seq, Unacked { segment: segment.clone(), sent: Instant::now(), retries: 0 });
+            self.next_seq = self.next_seq.wrapping_add(1);
+            self.send(segment);
+        }
+    }
+
+    /// Send the segments not acknowledged in time again, false once the peer is lost
+    fn retransmit(&mut self) -> bool {
+        let now = Instant::now();
+        let mut expired = vec![];
+        for (seq, unacked) in self.unacked.iter_mut() {
+            if unacked.sent + self.options.timeout > now {
+                continue;
+            }
+            if self.opened && unacked.retries >= self.options.retries {
+                warn!("Reliable stream segment {} not acknowledged after {} retries", seq, unacked.retries);
+                self.shared.update(|state| state.error = Some("The peer doesn't acknowledge the stream".to_string()));
+                return false;
+            }
+            unacked.retries += 1;
+            unacked.sent = now;
+            expired.push(unacked.segment.clone());
+        }
+        for segment in expired {
+            debug!("Retransmitting reliable stream segment");
+            self.send(segment);
+        }
+        true
+    }
+
+    fn receive(&mut self, source: i32, dat: &[u8]) {
+        if self.peer.map(|x| x != source).unwrap_or(false) {
+            return;
+        }
+        if dat.len() < HEADER_LEN {
+            warn!("Invalid reliable stream segment {:?}", dat);
+            return;
+        }
+        let kind = dat[0];
+        let seq = u32::from_be_bytes([dat[1], dat[2], dat[3], dat[4]]);
+        let payload = &dat[HEADER_LEN..];
+        match kind {
+            SEG_ACK => {
+                let window = payload.get(0..2).map(|x| u16::from_be_bytes([x[0], x[1]]) as usize).unwrap_or(0);
+                let acked: Vec<u32> = self.unacked.keys().cloned().filter(|x| seq_before(*x, seq)).collect();
+                let mut done = 0;
+                for x in acked {
+                    match self.unacked.remove(&x) {
+                        Some(ref unacked) if unacked.segment[0] == SEG_SYN => self.opened = true,
+                        _ => done += 1,
+                    }
+                }
+                // An acknowledgement shows the peer is alive, even with a full window
+                for unacked in self.unacked.values_mut() {
+                    unacked.retries = 0;
+                }
+                self.peer_window = window;
+                self.last_ack = Instant::now();
+                self.shared.update(|state| state.pending = state.pending.saturating_sub(done));
+            },
+            SEG_SYN => {
+                if self.peer.is_none() {
+                    debug!("Reliable stream peer is node {}", source);
+                    self.peer = Some(source);
+                }
+                if self.peer_syn != Some(seq) {
+                    debug!("Reliable stream opened by the peer");
+                    self.peer_syn = Some(seq);
+                    self.expected = seq.wrapping_add(1);
+                    self.out_of_order.clear();
+                    self.shared.update(|state| state.eof = false);
+                }
+                self.ack();
+            },
+            SEG_DATA | SEG_FIN if self.peer_syn.is_some() => {
+                let offset = seq.wrapping_sub(self.expected) as usize;
+                if !seq_before(seq, self.expected) && offset < self.buffer_space() {
+                    self.out_of_order.entry(seq).or_insert_with(|| (kind, payload.to_vec()));
+                }
+                self.deliver();
+                self.ack();
+            },
+            SEG_DATA | SEG_FIN => debug!("Reliable stream segment before the peer SYN"),
+            _ => warn!("Unknown reliable stream segment kind {}", kind),
+        }
+    }
+
+    /// Move the segments received in order to the read buffer
+    fn deliver(&mut self) {
+        while let Some((kind, payload)) = self.out_of_order.remove(&self.expected) {
+            self.expected = self.expected.wrapping_add(1);
+            self.shared.update(|state| {
+                if kind == SEG_FIN {
+                    state.eof = true;
+                } else {
+                    state.incoming.extend(payload);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testutil::Output;
+
+    /// Segments sent since the last call
+    fn segments(output: &Output) -> Vec<Vec<u8>> {
+        output.requests().iter().map(|request| {
+            assert_eq!(request.get_data().get_target(), 1);
+            request.get_data().get_data().to_vec()
+        }).collect()
+    }
+
+    fn connection(options: ReliableOptions) -> (Connection, Output) {
+        let output = Output::default();
+        let shared = Arc::new(Shared { state: Mutex::new(State::default()), changed: Condvar::new() });
+        let server = RICSServer::new_from(std::io::empty(), output.clone());
+        (Connection::new(server, Some(1), options, shared), output)
+    }
+
+    fn segment(kind: u8, seq: u32, payload: &[u8]) -> Vec<u8> {
+        let mut segment = vec![kind];
+        segment.extend_from_slice(&seq.to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    fn ack(seq: u32, window: u16) -> Vec<u8> {
+        segment(SEG_ACK, seq, &window.to_be_bytes())
+    }
+
+    #[test]
+    fn sequence_numbers() {
+        assert!(seq_before(1, 2));
+        assert!(!seq_before(2, 2));
+        assert!(!seq_before(3, 2));
+        assert!(seq_before(0xFFFF_FFFF, 0));
+        assert!(!seq_before(0, 0xFFFF_FFFF));
+    }
+
+    #[test]
+    fn reordering() {
+        let (mut conn, output) = connection(ReliableOptions { window: 4, ..Default::default() });
+        let isn = 0xFFFF_FFFE;
+        conn.receive(1, &segment(SEG_SYN, isn, &[]));
+        assert_eq!(segments(&output), vec![ack(isn.wrapping_add(1), 4)]);
+
+        // Segments after a missing one are kept and still acknowledge the missing one
+        conn.receive(1, &segment(SEG_DATA, isn.wrapping_add(2), b"cd"));
+        conn.receive(1, &segment(SEG_FIN, isn.wrapping_add(3), &[]));
+        assert_eq!(segments(&output), vec![ack(isn.wrapping_add(1), 3), ack(isn.wrapping_add(1), 2)]);
+        assert!(conn.shared.state.lock().unwrap().incoming.is_empty());
+
+        conn.receive(1, &segment(SEG_DATA, isn.wrapping_add(1), b"ab"));
+        assert_eq!(segments(&output), vec![ack(isn.wrapping_add(4), 3)]);
+        let state = conn.shared.state.lock().unwrap();
+        assert_eq!(state.incoming.iter().cloned().collect::<Vec<u8>>(), b"abcd");
+        assert!(state.eof);
+        drop(state);
+
+        // Duplicates are acknowledged again but not delivered twice
+        conn.receive(1, &segment(SEG_DATA, isn.wrapping_add(2), b"cd"));
+        assert_eq!(segments(&output), vec![ack(isn.wrapping_add(4), 3)]);
+        assert_eq!(conn.shared.state.lock().unwrap().incoming.len(), 4);
+
+        // Other nodes and segments beyond the window are ignored
+        conn.receive(2, &segment(SEG_DATA, isn.wrapping_add(4), b"ef"));
+        assert!(segments(&output).is_empty());
+        conn.receive(1, &segment(SEG_DATA, isn.wrapping_add(8), b"ij"));
+        conn.receive(1, &segment(SEG_DATA, isn.wrapping_add(4), b"ef"));
+        assert_eq!(conn.shared.state.lock().unwrap().incoming.len(), 6);
+        assert!(conn.out_of_order.is_empty());
+    }
+
+    #[test]
+    fn retransmission() {
+        let (mut conn, output) = connection(ReliableOptions { timeout: Duration::from_millis(0), retries: 2, ..Default::default() });
+        let isn = conn.next_seq;
+        conn.transmit();
+        assert_eq!(segments(&output), vec![segment(SEG_SYN, isn, &[])]);
+
+        // Data waits for the SYN to be acknowledged, which is retransmitted until then
+        conn.queue.push_back((SEG_DATA, b"ab".to_vec()));
+        conn.queue.push_back((SEG_DATA, b"cd".to_vec()));
+        conn.shared.update(|state| state.pending = 2);
+        for _ in 0..5 {
+            assert!(conn.retransmit());
+            conn.transmit();
+        }
+        assert_eq!(segments(&output), vec![segment(SEG_SYN, isn, &[]); 5]);
+
+        // The peer window only lets one segment through
+        conn.receive(1, &ack(isn.wrapping_add(1), 1));
+        conn.transmit();
+        assert_eq!(segments(&output), vec![segment(SEG_DATA, isn.wrapping_add(1), b"ab")]);
+        assert!(conn.retransmit());
+        assert_eq!(segments(&output), vec![segment(SEG_DATA, isn.wrapping_add(1), b"ab")]);
+
+        conn.receive(1, &ack(isn.wrapping_add(2), 1));
+        assert_eq!(conn.shared.state.lock().unwrap().pending, 1);
+        conn.transmit();
+        assert_eq!(segments(&output), vec![segment(SEG_DATA, isn.wrapping_add(2), b"cd")]);
+
+        // The peer is lost once the retries are exhausted
+        assert!(conn.retransmit());
+        assert!(conn.retransmit());
+        assert!(!conn.retransmit());
+        assert_eq!(segments(&output).len(), 2);
+        assert!(conn.shared.state.lock().unwrap().error.is_some());
+    }
+}
@@ -0,0 +1,105 @@
+//! Rest-bus simulation
+//!
+//! Stands in for an ECU missing from the bus by sending every message its
+//! DBC node transmits. Messages with a `GenMsgCycleTime` are sent at that
+//! period, after their `GenMsgStartDelayTime`, the others once at start and
+//! whenever their signals change. Signals start at their `GenSigStartValue`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::dbc::{Dbc, Message};
+use super::scheduler::{CyclicMessage, Scheduler};
+use super::server::RICSServer;
+
+/// Simulated DBC node
+pub struct RestBus {
+    scheduler: Scheduler,
+    /// Messages of the node and their current data, by name
+    messages: HashMap<String, (Message, Vec<u8>)>,
+}
+
+impl RestBus {
+    /// The server must be connected, it is only used to send packets from now on
+    pub fn new(server: RICSServer, dbc: &Dbc, node: &str) -> Result<RestBus, String> {
+        let messages: HashMap<String, (Message, Vec<u8>)> = dbc.messages.values()
+            .filter(|x| x.transmitter == node)
+            .map(|x| (x.name.clone(), (x.clone(), x.initial_data())))
+            .collect();
+        if messages.is_empty() {
+            return Err(format!("Node {} doesn't send any message", node));
+        }
+        let restbus = RestBus { scheduler: Scheduler::new(server), messages: messages };
+        for (message, dat) in restbus.messages.values() {
+            restbus.schedule(message, dat.clone(), message.start_delay());
+        }
+        Ok(restbus)
+    }
+
+    fn schedule(&self, message: &Message, dat: Vec<u8>, offset: Duration) {
+        let mut cyclic = CyclicMessage::new(message.frame(dat), message.cycle_time().unwrap_or(Duration::from_secs(1)));
+        cyclic.offset = offset;
+        if message.cycle_time().is_none() {
+            cyclic.count = Some(1);
+        }
+        self.scheduler.add(message.name.clone(), cyclic);
+    }
+
+    /// Simulated messages, sorted by name
+    pub fn messages(&self) -> Vec<&Message> {
+        let mut messages: Vec<&Message> = self.messages.values().map(|(x, _)| x).collect();
+        messages.sort_by(|a, b| a.name.cmp(&b.name));
+        messages
+    }
+
+    fn message(&self, name: &str) -> Result<&Message, String> {
+        self.messages.get(name).map(|(x, _)| x).ok_or_else(|| format!("{} isn't sent by the simulated node", name))
+    }
+
+    /// Change the physical values of signals of a message. Cyclic messages
+    /// carry them from their next transmission, the others are sent at once.
+    pub fn set(&mut self, name: &str, values: &[(&str, f64)]) -> Result<(), String> {
+        let (message, dat) = self.messages.get_mut(name).ok_or_else(|| format!("{} isn't sent by the simulated node", name))?;
+        let mut new_dat = dat.clone();
+        for (signal, physical) in values {
+            message.set_signal(&mut new_dat, signal, *physical)?;
+        }
+        *dat = new_dat.clone();
+        let message = message.clone();
+        if message.cycle_time().is_some() {
+            self.scheduler.set_data(name, new_dat);
+        } else {
+            self.schedule(&message, new_dat, Duration::from_secs(0));
+        }
+        Ok(())
+    }
+
+    /// Restart the transmission of a message
+    pub fn start(&self, name: &str) -> Result<(), String> {
+        self.message(name)?;
+        self.scheduler.start(name);
+        Ok(())
+    }
+
+    /// Stop the transmission of a cyclic message
+    pub fn stop(&self, name: &str) -> Result<(), String> {
+        self.message(name)?;
+        self.scheduler.stop(name);
+        Ok(())
+    }
+
+    /// Call `update` with the message name before each transmission, the
+    /// signal values it returns only apply to that transmission
+    pub fn set_update(&self, mut update: impl FnMut(&str) -> Vec<(String, f64)> + Send + 'static) {
+        let messages: HashMap<String, Message> = self.messages.iter().map(|(name, (x, _))| (name.clone(), x.clone())).collect();
+        self.scheduler.set_update(move |name, dat| {
+            if let Some(message) = messages.get(name) {
+                for (signal, physical) in update(name) {
+                    if let Err(e) = message.set_signal(dat, &signal, physical) {
+                        warn!("{}", e);
+                    }
+                }
+            }
+        });
+    }
+}
@@ -184,9 +184,18 @@ pub struct RICS_Data {
     // message fields
     source: ::std::option::Option<i32>,
     target: ::std::option::Option<i32>,
+    timestamp: ::std::option::Option<u64>,
+    sequence: ::std::option::Option<u32>,
     data: ::protobuf::SingularField<::std::vec::Vec<u8>>,
     id: ::std::option::Option<i32>,
     field_type: ::std::option::Option<RICS_Data_RICS_DataType>,
+    fd: ::std::option::Option<bool>,
+    brs: ::std::option::Option<bool>,
+    esi: ::std::option::Option<bool>,
+    extended: ::std::option::Option<bool>,
+    rtr: ::std::option::Option<bool>,
+    error: ::std::option::Option<bool>,
+    topic: ::protobuf::SingularField<::std::string::String>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -241,6 +250,44 @@ impl RICS_Data {
         self.target = ::std::option::Option::Some(v);
     }
 
+    // optional uint64 timestamp = 102;
+
+
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp.unwrap_or(0)
+    }
+    pub fn clear_timestamp(&mut self) {
+        self.timestamp = ::std::option::Option::None;
+    }
+
+    pub fn has_timestamp(&self) -> bool {
+        self.timestamp.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_timestamp(&mut self, v: u64) {
+        self.timestamp = ::std::option::Option::Some(v);
+    }
+
+    // optional uint32 sequence = 103;
+
+
+    pub fn get_sequence(&self) -> u32 {
+        self.sequence.unwrap_or(0)
+    }
+    pub fn clear_sequence(&mut self) {
+        self.sequence = ::std::option::Option::None;
+    }
+
+    pub fn has_sequence(&self) -> bool {
+        self.sequence.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sequence(&mut self, v: u32) {
+        self.sequence = ::std::option::Option::Some(v);
+    }
+
     // optional bytes data = 1;
 
 
@@ -314,6 +361,156 @@ impl RICS_Data {
     pub fn set_field_type(&mut self, v: RICS_Data_RICS_DataType) {
         self.field_type = ::std::option::Option::Some(v);
     }
+
+    // optional bool fd = 4;
+
+
+    pub fn get_fd(&self) -> bool {
+        self.fd.unwrap_or(false)
+    }
+    pub fn clear_fd(&mut self) {
+        self.fd = ::std::option::Option::None;
+    }
+
+    pub fn has_fd(&self) -> bool {
+        self.fd.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_fd(&mut self, v: bool) {
+        self.fd = ::std::option::Option::Some(v);
+    }
+
+    // optional bool brs = 5;
+
+
+    pub fn get_brs(&self) -> bool {
+        self.brs.unwrap_or(false)
+    }
+    pub fn clear_brs(&mut self) {
+        self.brs = ::std::option::Option::None;
+    }
+
+    pub fn has_brs(&self) -> bool {
+        self.brs.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_brs(&mut self, v: bool) {
+        self.brs = ::std::option::Option::Some(v);
+    }
+
+    // optional bool esi = 6;
+
+
+    pub fn get_esi(&self) -> bool {
+        self.esi.unwrap_or(false)
+    }
+    pub fn clear_esi(&mut self) {
+        self.esi = ::std::option::Option::None;
+    }
+
+    pub fn has_esi(&self) -> bool {
+        self.esi.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_esi(&mut self, v: bool) {
+        self.esi = ::std::option::Option::Some(v);
+    }
+
+    // optional bool extended = 7;
+
+
+    pub fn get_extended(&self) -> bool {
+        self.extended.unwrap_or(false)
+    }
+    pub fn clear_extended(&mut self) {
+        self.extended = ::std::option::Option::None;
+    }
+
+    pub fn has_extended(&self) -> bool {
+        self.extended.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_extended(&mut self, v: bool) {
+        self.extended = ::std::option::Option::Some(v);
+    }
+
+    // optional bool rtr = 8;
+
+
+    pub fn get_rtr(&self) -> bool {
+        self.rtr.unwrap_or(false)
+    }
+    pub fn clear_rtr(&mut self) {
+        self.rtr = ::std::option::Option::None;
+    }
+
+    pub fn has_rtr(&self) -> bool {
+        self.rtr.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_rtr(&mut self, v: bool) {
+        self.rtr = ::std::option::Option::Some(v);
+    }
+
+    // optional bool error = 9;
+
+
+    pub fn get_error(&self) -> bool {
+        self.error.unwrap_or(false)
+    }
+    pub fn clear_error(&mut self) {
+        self.error = ::std::option::Option::None;
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_error(&mut self, v: bool) {
+        self.error = ::std::option::Option::Some(v);
+    }
+
+    // optional string topic = 10;
+
+
+    pub fn get_topic(&self) -> &str {
+        match self.topic.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_topic(&mut self) {
+        self.topic.clear();
+    }
+
+    pub fn has_topic(&self) -> bool {
+        self.topic.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_topic(&mut self, v: ::std::string::String) {
+        self.topic = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_topic(&mut self) -> &mut ::std::string::String {
+        if self.topic.is_none() {
+            self.topic.set_default();
+        }
+        self.topic.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_topic(&mut self) -> ::std::string::String {
+        self.topic.take().unwrap_or_else(|| ::std::string::String::new())
+    }
 }
 
 impl ::protobuf::Message for RICS_Data {
@@ -339,6 +536,20 @@ impl ::protobuf::Message for RICS_Data {
                     let tmp = is.read_int32()?;
                     self.target = ::std::option::Option::Some(tmp);
                 },
+                102 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.timestamp = ::std::option::Option::Some(tmp);
+                },
+                103 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.sequence = ::std::option::Option::Some(tmp);
+                },
                 1 => {
                     ::protobuf::rt::read_singular_bytes_into(wire_type, is, &mut self.data)?;
                 },
@@ -352,6 +563,51 @@ impl ::protobuf::Message for RICS_Data {
                 3 => {
                     ::protobuf::rt::read_proto2_enum_with_unknown_fields_into(wire_type, is, &mut self.field_type, 3, &mut self.unknown_fields)?
                 },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.fd = ::std::option::Option::Some(tmp);
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.brs = ::std::option::Option::Some(tmp);
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.esi = ::std::option::Option::Some(tmp);
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.extended = ::std::option::Option::Some(tmp);
+                },
+                8 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.rtr = ::std::option::Option::Some(tmp);
+                },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.error = ::std::option::Option::Some(tmp);
+                },
+                10 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.topic)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -370,6 +626,12 @@ impl ::protobuf::Message for RICS_Data {
         if let Some(v) = self.target {
             my_size += ::protobuf::rt::value_size(101, v, ::protobuf::wire_format::WireTypeVarint);
         }
+        if let Some(v) = self.timestamp {
+            my_size += ::protobuf::rt::value_size(102, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.sequence {
+            my_size += ::protobuf::rt::value_size(103, v, ::protobuf::wire_format::WireTypeVarint);
+        }
         if let Some(ref v) = self.data.as_ref() {
             my_size += ::protobuf::rt::bytes_size(1, &v);
         }
@@ -379,6 +641,27 @@ impl ::protobuf::Message for RICS_Data {
         if let Some(v) = self.field_type {
             my_size += ::protobuf::rt::enum_size(3, v);
         }
+        if let Some(v) = self.fd {
+            my_size += 2;
+        }
+        if let Some(v) = self.brs {
+            my_size += 2;
+        }
+        if let Some(v) = self.esi {
+            my_size += 2;
+        }
+        if let Some(v) = self.extended {
+            my_size += 2;
+        }
+        if let Some(v) = self.rtr {
+            my_size += 2;
+        }
+        if let Some(v) = self.error {
+            my_size += 2;
+        }
+        if let Some(ref v) = self.topic.as_ref() {
+            my_size += ::protobuf::rt::string_size(10, &v);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -391,6 +674,12 @@ impl ::protobuf::Message for RICS_Data {
         if let Some(v) = self.target {
             os.write_int32(101, v)?;
         }
+        if let Some(v) = self.timestamp {
+            os.write_uint64(102, v)?;
+        }
+        if let Some(v) = self.sequence {
+            os.write_uint32(103, v)?;
+        }
         if let Some(ref v) = self.data.as_ref() {
             os.write_bytes(1, &v)?;
         }
@@ -400,6 +689,27 @@ impl ::protobuf::Message for RICS_Data {
         if let Some(v) = self.field_type {
             os.write_enum(3, ::protobuf::ProtobufEnum::value(&v))?;
         }
+        if let Some(v) = self.fd {
+            os.write_bool(4, v)?;
+        }
+        if let Some(v) = self.brs {
+            os.write_bool(5, v)?;
+        }
+        if let Some(v) = self.esi {
+            os.write_bool(6, v)?;
+        }
+        if let Some(v) = self.extended {
+            os.write_bool(7, v)?;
+        }
+        if let Some(v) = self.rtr {
+            os.write_bool(8, v)?;
+        }
+        if let Some(v) = self.error {
+            os.write_bool(9, v)?;
+        }
+        if let Some(ref v) = self.topic.as_ref() {
+            os.write_string(10, &v)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -448,6 +758,16 @@ impl ::protobuf::Message for RICS_Data {
                 |m: &RICS_Data| { &m.target },
                 |m: &mut RICS_Data| { &mut m.target },
             ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "timestamp",
+                |m: &RICS_Data| { &m.timestamp },
+                |m: &mut RICS_Data| { &mut m.timestamp },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "sequence",
+                |m: &RICS_Data| { &m.sequence },
+                |m: &mut RICS_Data| { &mut m.sequence },
+            ));
             fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
                 "data",
                 |m: &RICS_Data| { &m.data },
@@ -463,6 +783,41 @@ impl ::protobuf::Message for RICS_Data {
                 |m: &RICS_Data| { &m.field_type },
                 |m: &mut RICS_Data| { &mut m.field_type },
             ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "fd",
+                |m: &RICS_Data| { &m.fd },
+                |m: &mut RICS_Data| { &mut m.fd },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "brs",
+                |m: &RICS_Data| { &m.brs },
+                |m: &mut RICS_Data| { &mut m.brs },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "esi",
+                |m: &RICS_Data| { &m.esi },
+                |m: &mut RICS_Data| { &mut m.esi },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "extended",
+                |m: &RICS_Data| { &m.extended },
+                |m: &mut RICS_Data| { &mut m.extended },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "rtr",
+                |m: &RICS_Data| { &m.rtr },
+                |m: &mut RICS_Data| { &mut m.rtr },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "error",
+                |m: &RICS_Data| { &m.error },
+                |m: &mut RICS_Data| { &mut m.error },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "topic",
+                |m: &RICS_Data| { &m.topic },
+                |m: &mut RICS_Data| { &mut m.topic },
+            ));
             ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Data>(
                 "RICS_Data",
                 fields,
@@ -481,9 +836,18 @@ impl ::protobuf::Clear for RICS_Data {
     fn clear(&mut self) {
         self.source = ::std::option::Option::None;
         self.target = ::std::option::Option::None;
+        self.timestamp = ::std::option::Option::None;
+        self.sequence = ::std::option::Option::None;
         self.data.clear();
         self.id = ::std::option::Option::None;
         self.field_type = ::std::option::Option::None;
+        self.fd = ::std::option::Option::None;
+        self.brs = ::std::option::Option::None;
+        self.esi = ::std::option::Option::None;
+        self.extended = ::std::option::Option::None;
+        self.rtr = ::std::option::Option::None;
+        self.error = ::std::option::Option::None;
+        self.topic.clear();
         self.unknown_fields.clear();
     }
 }
@@ -560,86 +924,106 @@ impl ::protobuf::reflect::ProtobufValue for RICS_Data_RICS_DataType {
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct RICS_Route {
+pub struct RICS_CanFault {
     // message fields
-    from: ::std::option::Option<i32>,
-    to: ::std::option::Option<i32>,
-    bidirectional: ::std::option::Option<bool>,
+    node: ::std::option::Option<i32>,
+    tx_errors: ::std::option::Option<u32>,
+    rx_errors: ::std::option::Option<u32>,
+    recover: ::std::option::Option<bool>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a RICS_Route {
-    fn default() -> &'a RICS_Route {
-        <RICS_Route as ::protobuf::Message>::default_instance()
+impl<'a> ::std::default::Default for &'a RICS_CanFault {
+    fn default() -> &'a RICS_CanFault {
+        <RICS_CanFault as ::protobuf::Message>::default_instance()
     }
 }
 
-impl RICS_Route {
-    pub fn new() -> RICS_Route {
+impl RICS_CanFault {
+    pub fn new() -> RICS_CanFault {
         ::std::default::Default::default()
     }
 
-    // optional int32 from = 1;
+    // optional int32 node = 1;
 
 
-    pub fn get_from(&self) -> i32 {
-        self.from.unwrap_or(0)
+    pub fn get_node(&self) -> i32 {
+        self.node.unwrap_or(0)
     }
-    pub fn clear_from(&mut self) {
-        self.from = ::std::option::Option::None;
+    pub fn clear_node(&mut self) {
+        self.node = ::std::option::Option::None;
     }
 
-    pub fn has_from(&self) -> bool {
-        self.from.is_some()
+    pub fn has_node(&self) -> bool {
+        self.node.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_from(&mut self, v: i32) {
-        self.from = ::std::option::Option::Some(v);
+    pub fn set_node(&mut self, v: i32) {
+        self.node = ::std::option::Option::Some(v);
     }
 
-    // optional int32 to = 2;
+    // optional uint32 tx_errors = 2;
 
 
-    pub fn get_to(&self) -> i32 {
-        self.to.unwrap_or(0)
+    pub fn get_tx_errors(&self) -> u32 {
+        self.tx_errors.unwrap_or(0)
     }
-    pub fn clear_to(&mut self) {
-        self.to = ::std::option::Option::None;
+    pub fn clear_tx_errors(&mut self) {
+        self.tx_errors = ::std::option::Option::None;
     }
 
-    pub fn has_to(&self) -> bool {
-        self.to.is_some()
+    pub fn has_tx_errors(&self) -> bool {
+        self.tx_errors.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_to(&mut self, v: i32) {
-        self.to = ::std::option::Option::Some(v);
+    pub fn set_tx_errors(&mut self, v: u32) {
+        self.tx_errors = ::std::option::Option::Some(v);
     }
 
-    // optional bool bidirectional = 3;
+    // optional uint32 rx_errors = 3;
 
 
-    pub fn get_bidirectional(&self) -> bool {
-        self.bidirectional.unwrap_or(false)
+    pub fn get_rx_errors(&self) -> u32 {
+        self.rx_errors.unwrap_or(0)
     }
-    pub fn clear_bidirectional(&mut self) {
-        self.bidirectional = ::std::option::Option::None;
+    pub fn clear_rx_errors(&mut self) {
+        self.rx_errors = ::std::option::Option::None;
     }
 
-    pub fn has_bidirectional(&self) -> bool {
-        self.bidirectional.is_some()
+    pub fn has_rx_errors(&self) -> bool {
+        self.rx_errors.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_bidirectional(&mut self, v: bool) {
-        self.bidirectional = ::std::option::Option::Some(v);
+    pub fn set_rx_errors(&mut self, v: u32) {
+        self.rx_errors = ::std::option::Option::Some(v);
+    }
+
+    // optional bool recover = 4;
+
+
+    pub fn get_recover(&self) -> bool {
+        self.recover.unwrap_or(false)
+    }
+    pub fn clear_recover(&mut self) {
+        self.recover = ::std::option::Option::None;
+    }
+
+    pub fn has_recover(&self) -> bool {
+        self.recover.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_recover(&mut self, v: bool) {
+        self.recover = ::std::option::Option::Some(v);
     }
 }
 
-impl ::protobuf::Message for RICS_Route {
+impl ::protobuf::Message for RICS_CanFault {
     fn is_initialized(&self) -> bool {
         true
     }
@@ -653,21 +1037,28 @@ impl ::protobuf::Message for RICS_Route {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
                     let tmp = is.read_int32()?;
-                    self.from = ::std::option::Option::Some(tmp);
+                    self.node = ::std::option::Option::Some(tmp);
                 },
                 2 => {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
-                    let tmp = is.read_int32()?;
-                    self.to = ::std::option::Option::Some(tmp);
+                    let tmp = is.read_uint32()?;
+                    self.tx_errors = ::std::option::Option::Some(tmp);
                 },
                 3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.rx_errors = ::std::option::Option::Some(tmp);
+                },
+                4 => {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
                     let tmp = is.read_bool()?;
-                    self.bidirectional = ::std::option::Option::Some(tmp);
+                    self.recover = ::std::option::Option::Some(tmp);
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -681,13 +1072,16 @@ impl ::protobuf::Message for RICS_Route {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        if let Some(v) = self.from {
+        if let Some(v) = self.node {
             my_size += ::protobuf::rt::value_size(1, v, ::protobuf::wire_format::WireTypeVarint);
         }
-        if let Some(v) = self.to {
+        if let Some(v) = self.tx_errors {
             my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
         }
-        if let Some(v) = self.bidirectional {
+        if let Some(v) = self.rx_errors {
+            my_size += ::protobuf::rt::value_size(3, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.recover {
             my_size += 2;
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
@@ -696,14 +1090,17 @@ impl ::protobuf::Message for RICS_Route {
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
-        if let Some(v) = self.from {
+        if let Some(v) = self.node {
             os.write_int32(1, v)?;
         }
-        if let Some(v) = self.to {
-            os.write_int32(2, v)?;
+        if let Some(v) = self.tx_errors {
+            os.write_uint32(2, v)?;
         }
-        if let Some(v) = self.bidirectional {
-            os.write_bool(3, v)?;
+        if let Some(v) = self.rx_errors {
+            os.write_uint32(3, v)?;
+        }
+        if let Some(v) = self.recover {
+            os.write_bool(4, v)?;
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
@@ -735,8 +1132,8 @@ impl ::protobuf::Message for RICS_Route {
         Self::descriptor_static()
     }
 
-    fn new() -> RICS_Route {
-        RICS_Route::new()
+    fn new() -> RICS_CanFault {
+        RICS_CanFault::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
@@ -744,349 +1141,501 @@ impl ::protobuf::Message for RICS_Route {
         descriptor.get(|| {
             let mut fields = ::std::vec::Vec::new();
             fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
-                "from",
-                |m: &RICS_Route| { &m.from },
-                |m: &mut RICS_Route| { &mut m.from },
+                "node",
+                |m: &RICS_CanFault| { &m.node },
+                |m: &mut RICS_CanFault| { &mut m.node },
             ));
-            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
-                "to",
-                |m: &RICS_Route| { &m.to },
-                |m: &mut RICS_Route| { &mut m.to },
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tx_errors",
+                |m: &RICS_CanFault| { &m.tx_errors },
+                |m: &mut RICS_CanFault| { &mut m.tx_errors },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "rx_errors",
+                |m: &RICS_CanFault| { &m.rx_errors },
+                |m: &mut RICS_CanFault| { &mut m.rx_errors },
             ));
             fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
-                "bidirectional",
-                |m: &RICS_Route| { &m.bidirectional },
-                |m: &mut RICS_Route| { &mut m.bidirectional },
+                "recover",
+                |m: &RICS_CanFault| { &m.recover },
+                |m: &mut RICS_CanFault| { &mut m.recover },
             ));
-            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Route>(
-                "RICS_Route",
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_CanFault>(
+                "RICS_CanFault",
                 fields,
                 file_descriptor_proto()
             )
         })
     }
 
-    fn default_instance() -> &'static RICS_Route {
-        static instance: ::protobuf::rt::LazyV2<RICS_Route> = ::protobuf::rt::LazyV2::INIT;
-        instance.get(RICS_Route::new)
+    fn default_instance() -> &'static RICS_CanFault {
+        static instance: ::protobuf::rt::LazyV2<RICS_CanFault> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_CanFault::new)
     }
 }
 
-impl ::protobuf::Clear for RICS_Route {
+impl ::protobuf::Clear for RICS_CanFault {
     fn clear(&mut self) {
-        self.from = ::std::option::Option::None;
-        self.to = ::std::option::Option::None;
-        self.bidirectional = ::std::option::Option::None;
+        self.node = ::std::option::Option::None;
+        self.tx_errors = ::std::option::Option::None;
+        self.rx_errors = ::std::option::Option::None;
+        self.recover = ::std::option::Option::None;
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for RICS_Route {
+impl ::std::fmt::Debug for RICS_CanFault {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for RICS_Route {
+impl ::protobuf::reflect::ProtobufValue for RICS_CanFault {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct RICS_Request {
-    // message oneof groups
-    pub content: ::std::option::Option<RICS_Request_oneof_content>,
+pub struct RICS_Subscription {
+    // message fields
+    field_type: ::std::option::Option<RICS_Subscription_RICS_SubscriptionType>,
+    id_min: ::std::option::Option<i32>,
+    id_max: ::std::option::Option<i32>,
+    topic: ::protobuf::SingularField<::std::string::String>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a RICS_Request {
-    fn default() -> &'a RICS_Request {
-        <RICS_Request as ::protobuf::Message>::default_instance()
+impl<'a> ::std::default::Default for &'a RICS_Subscription {
+    fn default() -> &'a RICS_Subscription {
+        <RICS_Subscription as ::protobuf::Message>::default_instance()
     }
 }
 
-#[derive(Clone,PartialEq,Debug)]
-pub enum RICS_Request_oneof_content {
-    set_name(::std::string::String),
-    query(RICS_Request_RICS_Query),
-    data(RICS_Data),
-    add_route(RICS_Route),
-    del_route(RICS_Route),
-    can_drop_chance(f32),
-}
-
-impl RICS_Request {
-    pub fn new() -> RICS_Request {
+impl RICS_Subscription {
+    pub fn new() -> RICS_Subscription {
         ::std::default::Default::default()
     }
 
-    // optional string set_name = 2;
+    // optional .RICS_Subscription.RICS_SubscriptionType type = 1;
 
 
-    pub fn get_set_name(&self) -> &str {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::set_name(ref v)) => v,
-            _ => "",
-        }
+    pub fn get_field_type(&self) -> RICS_Subscription_RICS_SubscriptionType {
+        self.field_type.unwrap_or(RICS_Subscription_RICS_SubscriptionType::CAN)
     }
-    pub fn clear_set_name(&mut self) {
-        self.content = ::std::option::Option::None;
+    pub fn clear_field_type(&mut self) {
+        self.field_type = ::std::option::Option::None;
     }
 
-    pub fn has_set_name(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::set_name(..)) => true,
-            _ => false,
-        }
+    pub fn has_field_type(&self) -> bool {
+        self.field_type.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_set_name(&mut self, v: ::std::string::String) {
-        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::set_name(v))
+    pub fn set_field_type(&mut self, v: RICS_Subscription_RICS_SubscriptionType) {
+        self.field_type = ::std::option::Option::Some(v);
     }
 
-    // Mutable pointer to the field.
-    pub fn mut_set_name(&mut self) -> &mut ::std::string::String {
-        if let ::std::option::Option::Some(RICS_Request_oneof_content::set_name(_)) = self.content {
-        } else {
-            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::set_name(::std::string::String::new()));
-        }
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::set_name(ref mut v)) => v,
-            _ => panic!(),
-        }
+    // optional int32 id_min = 2;
+
+
+    pub fn get_id_min(&self) -> i32 {
+        self.id_min.unwrap_or(0)
+    }
+    pub fn clear_id_min(&mut self) {
+        self.id_min = ::std::option::Option::None;
     }
 
-    // Take field
-    pub fn take_set_name(&mut self) -> ::std::string::String {
-        if self.has_set_name() {
-            match self.content.take() {
-                ::std::option::Option::Some(RICS_Request_oneof_content::set_name(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            ::std::string::String::new()
-        }
+    pub fn has_id_min(&self) -> bool {
+        self.id_min.is_some()
     }
 
-    // optional .RICS_Request.RICS_Query query = 3;
+    // Param is passed by value, moved
+    pub fn set_id_min(&mut self, v: i32) {
+        self.id_min = ::std::option::Option::Some(v);
+    }
 
+    // optional int32 id_max = 3;
 
-    pub fn get_query(&self) -> RICS_Request_RICS_Query {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::query(v)) => v,
-            _ => RICS_Request_RICS_Query::NULL,
-        }
+
+    pub fn get_id_max(&self) -> i32 {
+        self.id_max.unwrap_or(0)
     }
-    pub fn clear_query(&mut self) {
-        self.content = ::std::option::Option::None;
+    pub fn clear_id_max(&mut self) {
+        self.id_max = ::std::option::Option::None;
     }
 
-    pub fn has_query(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::query(..)) => true,
-            _ => false,
-        }
+    pub fn has_id_max(&self) -> bool {
+        self.id_max.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_query(&mut self, v: RICS_Request_RICS_Query) {
-        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::query(v))
+    pub fn set_id_max(&mut self, v: i32) {
+        self.id_max = ::std::option::Option::Some(v);
     }
 
-    // optional .RICS_Data data = 4;
+    // optional string topic = 4;
 
 
-    pub fn get_data(&self) -> &RICS_Data {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::data(ref v)) => v,
-            _ => <RICS_Data as ::protobuf::Message>::default_instance(),
+    pub fn get_topic(&self) -> &str {
+        match self.topic.as_ref() {
+            Some(v) => &v,
+            None => "",
         }
     }
-    pub fn clear_data(&mut self) {
-        self.content = ::std::option::Option::None;
+    pub fn clear_topic(&mut self) {
+        self.topic.clear();
     }
 
-    pub fn has_data(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::data(..)) => true,
-            _ => false,
-        }
+    pub fn has_topic(&self) -> bool {
+        self.topic.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_data(&mut self, v: RICS_Data) {
-        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::data(v))
+    pub fn set_topic(&mut self, v: ::std::string::String) {
+        self.topic = ::protobuf::SingularField::some(v);
     }
 
     // Mutable pointer to the field.
-    pub fn mut_data(&mut self) -> &mut RICS_Data {
-        if let ::std::option::Option::Some(RICS_Request_oneof_content::data(_)) = self.content {
-        } else {
-            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::data(RICS_Data::new()));
-        }
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::data(ref mut v)) => v,
-            _ => panic!(),
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_topic(&mut self) -> &mut ::std::string::String {
+        if self.topic.is_none() {
+            self.topic.set_default();
         }
+        self.topic.as_mut().unwrap()
     }
 
     // Take field
-    pub fn take_data(&mut self) -> RICS_Data {
-        if self.has_data() {
-            match self.content.take() {
-                ::std::option::Option::Some(RICS_Request_oneof_content::data(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            RICS_Data::new()
-        }
+    pub fn take_topic(&mut self) -> ::std::string::String {
+        self.topic.take().unwrap_or_else(|| ::std::string::String::new())
     }
+}
 
-    // optional .RICS_Route add_route = 5;
-
-
-    pub fn get_add_route(&self) -> &RICS_Route {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::add_route(ref v)) => v,
-            _ => <RICS_Route as ::protobuf::Message>::default_instance(),
-        }
-    }
-    pub fn clear_add_route(&mut self) {
-        self.content = ::std::option::Option::None;
+impl ::protobuf::Message for RICS_Subscription {
+    fn is_initialized(&self) -> bool {
+        true
     }
 
-    pub fn has_add_route(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::add_route(..)) => true,
-            _ => false,
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_proto2_enum_with_unknown_fields_into(wire_type, is, &mut self.field_type, 1, &mut self.unknown_fields)?
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int32()?;
+                    self.id_min = ::std::option::Option::Some(tmp);
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int32()?;
+                    self.id_max = ::std::option::Option::Some(tmp);
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.topic)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
         }
+        ::std::result::Result::Ok(())
     }
 
-    // Param is passed by value, moved
-    pub fn set_add_route(&mut self, v: RICS_Route) {
-        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::add_route(v))
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.field_type {
+            my_size += ::protobuf::rt::enum_size(1, v);
+        }
+        if let Some(v) = self.id_min {
+            my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.id_max {
+            my_size += ::protobuf::rt::value_size(3, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.topic.as_ref() {
+            my_size += ::protobuf::rt::string_size(4, &v);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
     }
 
-    // Mutable pointer to the field.
-    pub fn mut_add_route(&mut self) -> &mut RICS_Route {
-        if let ::std::option::Option::Some(RICS_Request_oneof_content::add_route(_)) = self.content {
-        } else {
-            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::add_route(RICS_Route::new()));
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.field_type {
+            os.write_enum(1, ::protobuf::ProtobufEnum::value(&v))?;
         }
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::add_route(ref mut v)) => v,
-            _ => panic!(),
+        if let Some(v) = self.id_min {
+            os.write_int32(2, v)?;
+        }
+        if let Some(v) = self.id_max {
+            os.write_int32(3, v)?;
         }
+        if let Some(ref v) = self.topic.as_ref() {
+            os.write_string(4, &v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
     }
 
-    // Take field
-    pub fn take_add_route(&mut self) -> RICS_Route {
-        if self.has_add_route() {
-            match self.content.take() {
-                ::std::option::Option::Some(RICS_Request_oneof_content::add_route(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            RICS_Route::new()
-        }
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
     }
 
-    // optional .RICS_Route del_route = 6;
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
 
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
 
-    pub fn get_del_route(&self) -> &RICS_Route {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::del_route(ref v)) => v,
-            _ => <RICS_Route as ::protobuf::Message>::default_instance(),
-        }
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
     }
-    pub fn clear_del_route(&mut self) {
-        self.content = ::std::option::Option::None;
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
     }
 
-    pub fn has_del_route(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::del_route(..)) => true,
-            _ => false,
-        }
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
     }
 
-    // Param is passed by value, moved
-    pub fn set_del_route(&mut self, v: RICS_Route) {
-        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::del_route(v))
+    fn new() -> RICS_Subscription {
+        RICS_Subscription::new()
     }
 
-    // Mutable pointer to the field.
-    pub fn mut_del_route(&mut self) -> &mut RICS_Route {
-        if let ::std::option::Option::Some(RICS_Request_oneof_content::del_route(_)) = self.content {
-        } else {
-            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::del_route(RICS_Route::new()));
-        }
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::del_route(ref mut v)) => v,
-            _ => panic!(),
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeEnum<RICS_Subscription_RICS_SubscriptionType>>(
+                "type",
+                |m: &RICS_Subscription| { &m.field_type },
+                |m: &mut RICS_Subscription| { &mut m.field_type },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "id_min",
+                |m: &RICS_Subscription| { &m.id_min },
+                |m: &mut RICS_Subscription| { &mut m.id_min },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "id_max",
+                |m: &RICS_Subscription| { &m.id_max },
+                |m: &mut RICS_Subscription| { &mut m.id_max },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "topic",
+                |m: &RICS_Subscription| { &m.topic },
+                |m: &mut RICS_Subscription| { &mut m.topic },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Subscription>(
+                "RICS_Subscription",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RICS_Subscription {
+        static instance: ::protobuf::rt::LazyV2<RICS_Subscription> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Subscription::new)
+    }
+}
+
+impl ::protobuf::Clear for RICS_Subscription {
+    fn clear(&mut self) {
+        self.field_type = ::std::option::Option::None;
+        self.id_min = ::std::option::Option::None;
+        self.id_max = ::std::option::Option::None;
+        self.topic.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RICS_Subscription {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Subscription {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum RICS_Subscription_RICS_SubscriptionType {
+    CAN = 0,
+    DATAGRAM = 1,
+    TOPIC = 2,
+}
+
+impl ::protobuf::ProtobufEnum for RICS_Subscription_RICS_SubscriptionType {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<RICS_Subscription_RICS_SubscriptionType> {
+        match value {
+            0 => ::std::option::Option::Some(RICS_Subscription_RICS_SubscriptionType::CAN),
+            1 => ::std::option::Option::Some(RICS_Subscription_RICS_SubscriptionType::DATAGRAM),
+            2 => ::std::option::Option::Some(RICS_Subscription_RICS_SubscriptionType::TOPIC),
+            _ => ::std::option::Option::None
         }
     }
 
+    fn values() -> &'static [Self] {
+        static values: &'static [RICS_Subscription_RICS_SubscriptionType] = &[
+            RICS_Subscription_RICS_SubscriptionType::CAN,
+            RICS_Subscription_RICS_SubscriptionType::DATAGRAM,
+            RICS_Subscription_RICS_SubscriptionType::TOPIC,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<RICS_Subscription_RICS_SubscriptionType>("RICS_Subscription.RICS_SubscriptionType", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for RICS_Subscription_RICS_SubscriptionType {
+}
+
+impl ::std::default::Default for RICS_Subscription_RICS_SubscriptionType {
+    fn default() -> Self {
+        RICS_Subscription_RICS_SubscriptionType::CAN
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Subscription_RICS_SubscriptionType {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RICS_ReceiveFilter {
+    // message fields
+    pub ids: ::protobuf::RepeatedField<RICS_ReceiveFilter_RICS_IdFilter>,
+    pub types: ::std::vec::Vec<RICS_Data_RICS_DataType>,
+    pub sources: ::std::vec::Vec<i32>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RICS_ReceiveFilter {
+    fn default() -> &'a RICS_ReceiveFilter {
+        <RICS_ReceiveFilter as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RICS_ReceiveFilter {
+    pub fn new() -> RICS_ReceiveFilter {
+        ::std::default::Default::default()
+    }
+
+    // repeated .RICS_ReceiveFilter.RICS_IdFilter ids = 1;
+
+
+    pub fn get_ids(&self) -> &[RICS_ReceiveFilter_RICS_IdFilter] {
+        &self.ids
+    }
+    pub fn clear_ids(&mut self) {
+        self.ids.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_ids(&mut self, v: ::protobuf::RepeatedField<RICS_ReceiveFilter_RICS_IdFilter>) {
+        self.ids = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_ids(&mut self) -> &mut ::protobuf::RepeatedField<RICS_ReceiveFilter_RICS_IdFilter> {
+        &mut self.ids
+    }
+
     // Take field
-    pub fn take_del_route(&mut self) -> RICS_Route {
-        if self.has_del_route() {
-            match self.content.take() {
-                ::std::option::Option::Some(RICS_Request_oneof_content::del_route(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            RICS_Route::new()
-        }
+    pub fn take_ids(&mut self) -> ::protobuf::RepeatedField<RICS_ReceiveFilter_RICS_IdFilter> {
+        ::std::mem::replace(&mut self.ids, ::protobuf::RepeatedField::new())
     }
 
-    // optional float can_drop_chance = 7;
+    // repeated .RICS_Data.RICS_DataType types = 2;
 
 
-    pub fn get_can_drop_chance(&self) -> f32 {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(v)) => v,
-            _ => 0.,
-        }
+    pub fn get_types(&self) -> &[RICS_Data_RICS_DataType] {
+        &self.types
     }
-    pub fn clear_can_drop_chance(&mut self) {
-        self.content = ::std::option::Option::None;
+    pub fn clear_types(&mut self) {
+        self.types.clear();
     }
 
-    pub fn has_can_drop_chance(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(..)) => true,
-            _ => false,
-        }
+    // Param is passed by value, moved
+    pub fn set_types(&mut self, v: ::std::vec::Vec<RICS_Data_RICS_DataType>) {
+        self.types = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_types(&mut self) -> &mut ::std::vec::Vec<RICS_Data_RICS_DataType> {
+        &mut self.types
+    }
+
+    // Take field
+    pub fn take_types(&mut self) -> ::std::vec::Vec<RICS_Data_RICS_DataType> {
+        ::std::mem::replace(&mut self.types, ::std::vec::Vec::new())
+    }
+
+    // repeated int32 sources = 3;
+
+
+    pub fn get_sources(&self) -> &[i32] {
+        &self.sources
+    }
+    pub fn clear_sources(&mut self) {
+        self.sources.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_can_drop_chance(&mut self, v: f32) {
-        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(v))
+    pub fn set_sources(&mut self, v: ::std::vec::Vec<i32>) {
+        self.sources = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_sources(&mut self) -> &mut ::std::vec::Vec<i32> {
+        &mut self.sources
+    }
+
+    // Take field
+    pub fn take_sources(&mut self) -> ::std::vec::Vec<i32> {
+        ::std::mem::replace(&mut self.sources, ::std::vec::Vec::new())
     }
 }
 
-impl ::protobuf::Message for RICS_Request {
+impl ::protobuf::Message for RICS_ReceiveFilter {
     fn is_initialized(&self) -> bool {
-        if let Some(RICS_Request_oneof_content::data(ref v)) = self.content {
-            if !v.is_initialized() {
-                return false;
-            }
-        }
-        if let Some(RICS_Request_oneof_content::add_route(ref v)) = self.content {
-            if !v.is_initialized() {
-                return false;
-            }
-        }
-        if let Some(RICS_Request_oneof_content::del_route(ref v)) = self.content {
+        for v in &self.ids {
             if !v.is_initialized() {
                 return false;
             }
-        }
+        };
         true
     }
 
@@ -1094,41 +1643,14 @@ impl ::protobuf::Message for RICS_Request {
         while !is.eof()? {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.ids)?;
+                },
                 2 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    }
-                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::set_name(is.read_string()?));
+                    ::protobuf::rt::read_repeated_enum_with_unknown_fields_into(wire_type, is, &mut self.types, 2, &mut self.unknown_fields)?
                 },
                 3 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    }
-                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::query(is.read_enum()?));
-                },
-                4 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    }
-                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::data(is.read_message()?));
-                },
-                5 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    }
-                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::add_route(is.read_message()?));
-                },
-                6 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    }
-                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::del_route(is.read_message()?));
-                },
-                7 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeFixed32 {
-                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
-                    }
-                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(is.read_float()?));
+                    ::protobuf::rt::read_repeated_int32_into(wire_type, is, &mut self.sources)?;
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -1142,65 +1664,33 @@ impl ::protobuf::Message for RICS_Request {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        if let ::std::option::Option::Some(ref v) = self.content {
-            match v {
-                &RICS_Request_oneof_content::set_name(ref v) => {
-                    my_size += ::protobuf::rt::string_size(2, &v);
-                },
-                &RICS_Request_oneof_content::query(v) => {
-                    my_size += ::protobuf::rt::enum_size(3, v);
-                },
-                &RICS_Request_oneof_content::data(ref v) => {
-                    let len = v.compute_size();
-                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
-                },
-                &RICS_Request_oneof_content::add_route(ref v) => {
-                    let len = v.compute_size();
-                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
-                },
-                &RICS_Request_oneof_content::del_route(ref v) => {
-                    let len = v.compute_size();
-                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
-                },
-                &RICS_Request_oneof_content::can_drop_chance(v) => {
-                    my_size += 5;
-                },
-            };
-        }
+        for value in &self.ids {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.types {
+            my_size += ::protobuf::rt::enum_size(2, *value);
+        };
+        for value in &self.sources {
+            my_size += ::protobuf::rt::value_size(3, *value, ::protobuf::wire_format::WireTypeVarint);
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
-        if let ::std::option::Option::Some(ref v) = self.content {
-            match v {
-                &RICS_Request_oneof_content::set_name(ref v) => {
-                    os.write_string(2, v)?;
-                },
-                &RICS_Request_oneof_content::query(v) => {
-                    os.write_enum(3, ::protobuf::ProtobufEnum::value(&v))?;
-                },
-                &RICS_Request_oneof_content::data(ref v) => {
-                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &RICS_Request_oneof_content::add_route(ref v) => {
-                    os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &RICS_Request_oneof_content::del_route(ref v) => {
-                    os.write_tag(6, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &RICS_Request_oneof_content::can_drop_chance(v) => {
-                    os.write_float(7, v)?;
-                },
-            };
-        }
+        for v in &self.ids {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.types {
+            os.write_enum(2, ::protobuf::ProtobufEnum::value(v))?;
+        };
+        for v in &self.sources {
+            os.write_int32(3, *v)?;
+        };
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -1231,307 +1721,126 @@ impl ::protobuf::Message for RICS_Request {
         Self::descriptor_static()
     }
 
-    fn new() -> RICS_Request {
-        RICS_Request::new()
+    fn new() -> RICS_ReceiveFilter {
+        RICS_ReceiveFilter::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
         static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
         descriptor.get(|| {
             let mut fields = ::std::vec::Vec::new();
-            fields.push(::protobuf::reflect::accessor::make_singular_string_accessor::<_>(
-                "set_name",
-                RICS_Request::has_set_name,
-                RICS_Request::get_set_name,
-            ));
-            fields.push(::protobuf::reflect::accessor::make_singular_enum_accessor::<_, RICS_Request_RICS_Query>(
-                "query",
-                RICS_Request::has_query,
-                RICS_Request::get_query,
-            ));
-            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Data>(
-                "data",
-                RICS_Request::has_data,
-                RICS_Request::get_data,
-            ));
-            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Route>(
-                "add_route",
-                RICS_Request::has_add_route,
-                RICS_Request::get_add_route,
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<RICS_ReceiveFilter_RICS_IdFilter>>(
+                "ids",
+                |m: &RICS_ReceiveFilter| { &m.ids },
+                |m: &mut RICS_ReceiveFilter| { &mut m.ids },
             ));
-            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Route>(
-                "del_route",
-                RICS_Request::has_del_route,
-                RICS_Request::get_del_route,
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeEnum<RICS_Data_RICS_DataType>>(
+                "types",
+                |m: &RICS_ReceiveFilter| { &m.types },
+                |m: &mut RICS_ReceiveFilter| { &mut m.types },
             ));
-            fields.push(::protobuf::reflect::accessor::make_singular_f32_accessor::<_>(
-                "can_drop_chance",
-                RICS_Request::has_can_drop_chance,
-                RICS_Request::get_can_drop_chance,
+            fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "sources",
+                |m: &RICS_ReceiveFilter| { &m.sources },
+                |m: &mut RICS_ReceiveFilter| { &mut m.sources },
             ));
-            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Request>(
-                "RICS_Request",
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_ReceiveFilter>(
+                "RICS_ReceiveFilter",
                 fields,
                 file_descriptor_proto()
             )
         })
     }
 
-    fn default_instance() -> &'static RICS_Request {
-        static instance: ::protobuf::rt::LazyV2<RICS_Request> = ::protobuf::rt::LazyV2::INIT;
-        instance.get(RICS_Request::new)
+    fn default_instance() -> &'static RICS_ReceiveFilter {
+        static instance: ::protobuf::rt::LazyV2<RICS_ReceiveFilter> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_ReceiveFilter::new)
     }
 }
 
-impl ::protobuf::Clear for RICS_Request {
+impl ::protobuf::Clear for RICS_ReceiveFilter {
     fn clear(&mut self) {
-        self.content = ::std::option::Option::None;
-        self.content = ::std::option::Option::None;
-        self.content = ::std::option::Option::None;
-        self.content = ::std::option::Option::None;
-        self.content = ::std::option::Option::None;
-        self.content = ::std::option::Option::None;
+        self.ids.clear();
+        self.types.clear();
+        self.sources.clear();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for RICS_Request {
+impl ::std::fmt::Debug for RICS_ReceiveFilter {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for RICS_Request {
+impl ::protobuf::reflect::ProtobufValue for RICS_ReceiveFilter {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
-#[derive(Clone,PartialEq,Eq,Debug,Hash)]
-pub enum RICS_Request_RICS_Query {
-    NULL = 0,
-    LIST_SINK = 1,
-    WHO_AM_I = 2,
-    SET_FLAG_CAN_BROADCAST = 3,
-    CLEAR_FLAG_CAN_BROADCAST = 4,
-    DAEMON_QUIT = 255,
-}
-
-impl ::protobuf::ProtobufEnum for RICS_Request_RICS_Query {
-    fn value(&self) -> i32 {
-        *self as i32
-    }
-
-    fn from_i32(value: i32) -> ::std::option::Option<RICS_Request_RICS_Query> {
-        match value {
-            0 => ::std::option::Option::Some(RICS_Request_RICS_Query::NULL),
-            1 => ::std::option::Option::Some(RICS_Request_RICS_Query::LIST_SINK),
-            2 => ::std::option::Option::Some(RICS_Request_RICS_Query::WHO_AM_I),
-            3 => ::std::option::Option::Some(RICS_Request_RICS_Query::SET_FLAG_CAN_BROADCAST),
-            4 => ::std::option::Option::Some(RICS_Request_RICS_Query::CLEAR_FLAG_CAN_BROADCAST),
-            255 => ::std::option::Option::Some(RICS_Request_RICS_Query::DAEMON_QUIT),
-            _ => ::std::option::Option::None
-        }
-    }
-
-    fn values() -> &'static [Self] {
-        static values: &'static [RICS_Request_RICS_Query] = &[
-            RICS_Request_RICS_Query::NULL,
-            RICS_Request_RICS_Query::LIST_SINK,
-            RICS_Request_RICS_Query::WHO_AM_I,
-            RICS_Request_RICS_Query::SET_FLAG_CAN_BROADCAST,
-            RICS_Request_RICS_Query::CLEAR_FLAG_CAN_BROADCAST,
-            RICS_Request_RICS_Query::DAEMON_QUIT,
-        ];
-        values
-    }
-
-    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
-        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
-        descriptor.get(|| {
-            ::protobuf::reflect::EnumDescriptor::new_pb_name::<RICS_Request_RICS_Query>("RICS_Request.RICS_Query", file_descriptor_proto())
-        })
-    }
-}
-
-impl ::std::marker::Copy for RICS_Request_RICS_Query {
-}
-
-impl ::std::default::Default for RICS_Request_RICS_Query {
-    fn default() -> Self {
-        RICS_Request_RICS_Query::NULL
-    }
-}
-
-impl ::protobuf::reflect::ProtobufValue for RICS_Request_RICS_Query {
-    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
-        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
-    }
-}
-
 #[derive(PartialEq,Clone,Default)]
-pub struct RICS_Response {
-    // message oneof groups
-    pub content: ::std::option::Option<RICS_Response_oneof_content>,
+pub struct RICS_ReceiveFilter_RICS_IdFilter {
+    // message fields
+    id: ::std::option::Option<i32>,
+    mask: ::std::option::Option<i32>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a RICS_Response {
-    fn default() -> &'a RICS_Response {
-        <RICS_Response as ::protobuf::Message>::default_instance()
+impl<'a> ::std::default::Default for &'a RICS_ReceiveFilter_RICS_IdFilter {
+    fn default() -> &'a RICS_ReceiveFilter_RICS_IdFilter {
+        <RICS_ReceiveFilter_RICS_IdFilter as ::protobuf::Message>::default_instance()
     }
 }
 
-#[derive(Clone,PartialEq,Debug)]
-pub enum RICS_Response_oneof_content {
-    idlist(RICS_Response_RICS_IdList),
-    node(i32),
-    data(RICS_Data),
-}
-
-impl RICS_Response {
-    pub fn new() -> RICS_Response {
+impl RICS_ReceiveFilter_RICS_IdFilter {
+    pub fn new() -> RICS_ReceiveFilter_RICS_IdFilter {
         ::std::default::Default::default()
     }
 
-    // optional .RICS_Response.RICS_IdList idlist = 1;
-
-
-    pub fn get_idlist(&self) -> &RICS_Response_RICS_IdList {
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::idlist(ref v)) => v,
-            _ => <RICS_Response_RICS_IdList as ::protobuf::Message>::default_instance(),
-        }
-    }
-    pub fn clear_idlist(&mut self) {
-        self.content = ::std::option::Option::None;
-    }
-
-    pub fn has_idlist(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::idlist(..)) => true,
-            _ => false,
-        }
-    }
-
-    // Param is passed by value, moved
-    pub fn set_idlist(&mut self, v: RICS_Response_RICS_IdList) {
-        self.content = ::std::option::Option::Some(RICS_Response_oneof_content::idlist(v))
-    }
-
-    // Mutable pointer to the field.
-    pub fn mut_idlist(&mut self) -> &mut RICS_Response_RICS_IdList {
-        if let ::std::option::Option::Some(RICS_Response_oneof_content::idlist(_)) = self.content {
-        } else {
-            self.content = ::std::option::Option::Some(RICS_Response_oneof_content::idlist(RICS_Response_RICS_IdList::new()));
-        }
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::idlist(ref mut v)) => v,
-            _ => panic!(),
-        }
-    }
-
-    // Take field
-    pub fn take_idlist(&mut self) -> RICS_Response_RICS_IdList {
-        if self.has_idlist() {
-            match self.content.take() {
-                ::std::option::Option::Some(RICS_Response_oneof_content::idlist(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            RICS_Response_RICS_IdList::new()
-        }
-    }
-
-    // optional int32 node = 2;
+    // optional int32 id = 1;
 
 
-    pub fn get_node(&self) -> i32 {
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::node(v)) => v,
-            _ => 0,
-        }
+    pub fn get_id(&self) -> i32 {
+        self.id.unwrap_or(0)
     }
-    pub fn clear_node(&mut self) {
-        self.content = ::std::option::Option::None;
+    pub fn clear_id(&mut self) {
+        self.id = ::std::option::Option::None;
     }
 
-    pub fn has_node(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::node(..)) => true,
-            _ => false,
-        }
+    pub fn has_id(&self) -> bool {
+        self.id.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_node(&mut self, v: i32) {
-        self.content = ::std::option::Option::Some(RICS_Response_oneof_content::node(v))
+    pub fn set_id(&mut self, v: i32) {
+        self.id = ::std::option::Option::Some(v);
     }
 
-    // optional .RICS_Data data = 4;
+    // optional int32 mask = 2;
 
 
-    pub fn get_data(&self) -> &RICS_Data {
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::data(ref v)) => v,
-            _ => <RICS_Data as ::protobuf::Message>::default_instance(),
-        }
+    pub fn get_mask(&self) -> i32 {
+        self.mask.unwrap_or(0)
     }
-    pub fn clear_data(&mut self) {
-        self.content = ::std::option::Option::None;
+    pub fn clear_mask(&mut self) {
+        self.mask = ::std::option::Option::None;
     }
 
-    pub fn has_data(&self) -> bool {
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::data(..)) => true,
-            _ => false,
-        }
+    pub fn has_mask(&self) -> bool {
+        self.mask.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_data(&mut self, v: RICS_Data) {
-        self.content = ::std::option::Option::Some(RICS_Response_oneof_content::data(v))
-    }
-
-    // Mutable pointer to the field.
-    pub fn mut_data(&mut self) -> &mut RICS_Data {
-        if let ::std::option::Option::Some(RICS_Response_oneof_content::data(_)) = self.content {
-        } else {
-            self.content = ::std::option::Option::Some(RICS_Response_oneof_content::data(RICS_Data::new()));
-        }
-        match self.content {
-            ::std::option::Option::Some(RICS_Response_oneof_content::data(ref mut v)) => v,
-            _ => panic!(),
-        }
-    }
-
-    // Take field
-    pub fn take_data(&mut self) -> RICS_Data {
-        if self.has_data() {
-            match self.content.take() {
-                ::std::option::Option::Some(RICS_Response_oneof_content::data(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            RICS_Data::new()
-        }
+    pub fn set_mask(&mut self, v: i32) {
+        self.mask = ::std::option::Option::Some(v);
     }
 }
 
-impl ::protobuf::Message for RICS_Response {
+impl ::protobuf::Message for RICS_ReceiveFilter_RICS_IdFilter {
     fn is_initialized(&self) -> bool {
-        if let Some(RICS_Response_oneof_content::idlist(ref v)) = self.content {
-            if !v.is_initialized() {
-                return false;
-            }
-        }
-        if let Some(RICS_Response_oneof_content::data(ref v)) = self.content {
-            if !v.is_initialized() {
-                return false;
-            }
-        }
         true
     }
 
@@ -1540,22 +1849,1973 @@ impl ::protobuf::Message for RICS_Response {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
                 1 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
-                    self.content = ::std::option::Option::Some(RICS_Response_oneof_content::idlist(is.read_message()?));
+                    let tmp = is.read_int32()?;
+                    self.id = ::std::option::Option::Some(tmp);
                 },
                 2 => {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
-                    self.content = ::std::option::Option::Some(RICS_Response_oneof_content::node(is.read_int32()?));
+                    let tmp = is.read_int32()?;
+                    self.mask = ::std::option::Option::Some(tmp);
                 },
-                4 => {
-                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.id {
+            my_size += ::protobuf::rt::value_size(1, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.mask {
+            my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.id {
+            os.write_int32(1, v)?;
+        }
+        if let Some(v) = self.mask {
+            os.write_int32(2, v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RICS_ReceiveFilter_RICS_IdFilter {
+        RICS_ReceiveFilter_RICS_IdFilter::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "id",
+                |m: &RICS_ReceiveFilter_RICS_IdFilter| { &m.id },
+                |m: &mut RICS_ReceiveFilter_RICS_IdFilter| { &mut m.id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "mask",
+                |m: &RICS_ReceiveFilter_RICS_IdFilter| { &m.mask },
+                |m: &mut RICS_ReceiveFilter_RICS_IdFilter| { &mut m.mask },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_ReceiveFilter_RICS_IdFilter>(
+                "RICS_ReceiveFilter.RICS_IdFilter",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RICS_ReceiveFilter_RICS_IdFilter {
+        static instance: ::protobuf::rt::LazyV2<RICS_ReceiveFilter_RICS_IdFilter> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_ReceiveFilter_RICS_IdFilter::new)
+    }
+}
+
+impl ::protobuf::Clear for RICS_ReceiveFilter_RICS_IdFilter {
+    fn clear(&mut self) {
+        self.id = ::std::option::Option::None;
+        self.mask = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RICS_ReceiveFilter_RICS_IdFilter {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_ReceiveFilter_RICS_IdFilter {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RICS_Route {
+    // message fields
+    from: ::std::option::Option<i32>,
+    to: ::std::option::Option<i32>,
+    bidirectional: ::std::option::Option<bool>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RICS_Route {
+    fn default() -> &'a RICS_Route {
+        <RICS_Route as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RICS_Route {
+    pub fn new() -> RICS_Route {
+        ::std::default::Default::default()
+    }
+
+    // optional int32 from = 1;
+
+
+    pub fn get_from(&self) -> i32 {
+        self.from.unwrap_or(0)
+    }
+    pub fn clear_from(&mut self) {
+        self.from = ::std::option::Option::None;
+    }
+
+    pub fn has_from(&self) -> bool {
+        self.from.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_from(&mut self, v: i32) {
+        self.from = ::std::option::Option::Some(v);
+    }
+
+    // optional int32 to = 2;
+
+
+    pub fn get_to(&self) -> i32 {
+        self.to.unwrap_or(0)
+    }
+    pub fn clear_to(&mut self) {
+        self.to = ::std::option::Option::None;
+    }
+
+    pub fn has_to(&self) -> bool {
+        self.to.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_to(&mut self, v: i32) {
+        self.to = ::std::option::Option::Some(v);
+    }
+
+    // optional bool bidirectional = 3;
+
+
+    pub fn get_bidirectional(&self) -> bool {
+        self.bidirectional.unwrap_or(false)
+    }
+    pub fn clear_bidirectional(&mut self) {
+        self.bidirectional = ::std::option::Option::None;
+    }
+
+    pub fn has_bidirectional(&self) -> bool {
+        self.bidirectional.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_bidirectional(&mut self, v: bool) {
+        self.bidirectional = ::std::option::Option::Some(v);
+    }
+}
+
+impl ::protobuf::Message for RICS_Route {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
-                    self.content = ::std::option::Option::Some(RICS_Response_oneof_content::data(is.read_message()?));
+                    let tmp = is.read_int32()?;
+                    self.from = ::std::option::Option::Some(tmp);
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int32()?;
+                    self.to = ::std::option::Option::Some(tmp);
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.bidirectional = ::std::option::Option::Some(tmp);
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.from {
+            my_size += ::protobuf::rt::value_size(1, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.to {
+            my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.bidirectional {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.from {
+            os.write_int32(1, v)?;
+        }
+        if let Some(v) = self.to {
+            os.write_int32(2, v)?;
+        }
+        if let Some(v) = self.bidirectional {
+            os.write_bool(3, v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RICS_Route {
+        RICS_Route::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "from",
+                |m: &RICS_Route| { &m.from },
+                |m: &mut RICS_Route| { &mut m.from },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "to",
+                |m: &RICS_Route| { &m.to },
+                |m: &mut RICS_Route| { &mut m.to },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "bidirectional",
+                |m: &RICS_Route| { &m.bidirectional },
+                |m: &mut RICS_Route| { &mut m.bidirectional },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Route>(
+                "RICS_Route",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RICS_Route {
+        static instance: ::protobuf::rt::LazyV2<RICS_Route> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Route::new)
+    }
+}
+
+impl ::protobuf::Clear for RICS_Route {
+    fn clear(&mut self) {
+        self.from = ::std::option::Option::None;
+        self.to = ::std::option::Option::None;
+        self.bidirectional = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RICS_Route {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Route {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RICS_Request {
+    // message oneof groups
+    pub content: ::std::option::Option<RICS_Request_oneof_content>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RICS_Request {
+    fn default() -> &'a RICS_Request {
+        <RICS_Request as ::protobuf::Message>::default_instance()
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub enum RICS_Request_oneof_content {
+    set_name(::std::string::String),
+    query(RICS_Request_RICS_Query),
+    data(RICS_Data),
+    add_route(RICS_Route),
+    del_route(RICS_Route),
+    can_drop_chance(f32),
+    can_fault(RICS_CanFault),
+    can_restart_ms(u32),
+    subscribe(RICS_Subscription),
+    unsubscribe(RICS_Subscription),
+    receive_filter(RICS_ReceiveFilter),
+}
+
+impl RICS_Request {
+    pub fn new() -> RICS_Request {
+        ::std::default::Default::default()
+    }
+
+    // optional string set_name = 2;
+
+
+    pub fn get_set_name(&self) -> &str {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::set_name(ref v)) => v,
+            _ => "",
+        }
+    }
+    pub fn clear_set_name(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_set_name(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::set_name(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_set_name(&mut self, v: ::std::string::String) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::set_name(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_set_name(&mut self) -> &mut ::std::string::String {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::set_name(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::set_name(::std::string::String::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::set_name(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_set_name(&mut self) -> ::std::string::String {
+        if self.has_set_name() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::set_name(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            ::std::string::String::new()
+        }
+    }
+
+    // optional .RICS_Request.RICS_Query query = 3;
+
+
+    pub fn get_query(&self) -> RICS_Request_RICS_Query {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::query(v)) => v,
+            _ => RICS_Request_RICS_Query::NULL,
+        }
+    }
+    pub fn clear_query(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_query(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::query(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_query(&mut self, v: RICS_Request_RICS_Query) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::query(v))
+    }
+
+    // optional .RICS_Data data = 4;
+
+
+    pub fn get_data(&self) -> &RICS_Data {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::data(ref v)) => v,
+            _ => <RICS_Data as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_data(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_data(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::data(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: RICS_Data) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::data(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_data(&mut self) -> &mut RICS_Data {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::data(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::data(RICS_Data::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::data(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> RICS_Data {
+        if self.has_data() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::data(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Data::new()
+        }
+    }
+
+    // optional .RICS_Route add_route = 5;
+
+
+    pub fn get_add_route(&self) -> &RICS_Route {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::add_route(ref v)) => v,
+            _ => <RICS_Route as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_add_route(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_add_route(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::add_route(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_add_route(&mut self, v: RICS_Route) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::add_route(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_add_route(&mut self) -> &mut RICS_Route {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::add_route(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::add_route(RICS_Route::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::add_route(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_add_route(&mut self) -> RICS_Route {
+        if self.has_add_route() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::add_route(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Route::new()
+        }
+    }
+
+    // optional .RICS_Route del_route = 6;
+
+
+    pub fn get_del_route(&self) -> &RICS_Route {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::del_route(ref v)) => v,
+            _ => <RICS_Route as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_del_route(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_del_route(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::del_route(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_del_route(&mut self, v: RICS_Route) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::del_route(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_del_route(&mut self) -> &mut RICS_Route {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::del_route(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::del_route(RICS_Route::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::del_route(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_del_route(&mut self) -> RICS_Route {
+        if self.has_del_route() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::del_route(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Route::new()
+        }
+    }
+
+    // optional float can_drop_chance = 7;
+
+
+    pub fn get_can_drop_chance(&self) -> f32 {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(v)) => v,
+            _ => 0.,
+        }
+    }
+    pub fn clear_can_drop_chance(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_can_drop_chance(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_can_drop_chance(&mut self, v: f32) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(v))
+    }
+
+    // optional .RICS_CanFault can_fault = 8;
+
+
+    pub fn get_can_fault(&self) -> &RICS_CanFault {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(ref v)) => v,
+            _ => <RICS_CanFault as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_can_fault(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_can_fault(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_can_fault(&mut self, v: RICS_CanFault) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_can_fault(&mut self) -> &mut RICS_CanFault {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(RICS_CanFault::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_can_fault(&mut self) -> RICS_CanFault {
+        if self.has_can_fault() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_CanFault::new()
+        }
+    }
+
+    // optional uint32 can_restart_ms = 9;
+
+
+    pub fn get_can_restart_ms(&self) -> u32 {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::can_restart_ms(v)) => v,
+            _ => 0,
+        }
+    }
+    pub fn clear_can_restart_ms(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_can_restart_ms(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::can_restart_ms(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_can_restart_ms(&mut self, v: u32) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_restart_ms(v))
+    }
+
+    // optional .RICS_Subscription subscribe = 10;
+
+
+    pub fn get_subscribe(&self) -> &RICS_Subscription {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(ref v)) => v,
+            _ => <RICS_Subscription as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_subscribe(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_subscribe(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subscribe(&mut self, v: RICS_Subscription) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_subscribe(&mut self) -> &mut RICS_Subscription {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(RICS_Subscription::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_subscribe(&mut self) -> RICS_Subscription {
+        if self.has_subscribe() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Subscription::new()
+        }
+    }
+
+    // optional .RICS_Subscription unsubscribe = 11;
+
+
+    pub fn get_unsubscribe(&self) -> &RICS_Subscription {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(ref v)) => v,
+            _ => <RICS_Subscription as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_unsubscribe(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_unsubscribe(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_unsubscribe(&mut self, v: RICS_Subscription) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_unsubscribe(&mut self) -> &mut RICS_Subscription {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(RICS_Subscription::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_unsubscribe(&mut self) -> RICS_Subscription {
+        if self.has_unsubscribe() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Subscription::new()
+        }
+    }
+
+    // optional .RICS_ReceiveFilter receive_filter = 12;
+
+
+    pub fn get_receive_filter(&self) -> &RICS_ReceiveFilter {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(ref v)) => v,
+            _ => <RICS_ReceiveFilter as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_receive_filter(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_receive_filter(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_receive_filter(&mut self, v: RICS_ReceiveFilter) {
+        self.content = ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_receive_filter(&mut self) -> &mut RICS_ReceiveFilter {
+        if let ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(RICS_ReceiveFilter::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_receive_filter(&mut self) -> RICS_ReceiveFilter {
+        if self.has_receive_filter() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_ReceiveFilter::new()
+        }
+    }
+}
+
+impl ::protobuf::Message for RICS_Request {
+    fn is_initialized(&self) -> bool {
+        if let Some(RICS_Request_oneof_content::data(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Request_oneof_content::add_route(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Request_oneof_content::del_route(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Request_oneof_content::can_fault(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Request_oneof_content::subscribe(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Request_oneof_content::unsubscribe(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Request_oneof_content::receive_filter(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::set_name(is.read_string()?));
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::query(is.read_enum()?));
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::data(is.read_message()?));
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::add_route(is.read_message()?));
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::del_route(is.read_message()?));
+                },
+                7 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeFixed32 {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_drop_chance(is.read_float()?));
+                },
+                8 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_fault(is.read_message()?));
+                },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::can_restart_ms(is.read_uint32()?));
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::subscribe(is.read_message()?));
+                },
+                11 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::unsubscribe(is.read_message()?));
+                },
+                12 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Request_oneof_content::receive_filter(is.read_message()?));
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let ::std::option::Option::Some(ref v) = self.content {
+            match v {
+                &RICS_Request_oneof_content::set_name(ref v) => {
+                    my_size += ::protobuf::rt::string_size(2, &v);
+                },
+                &RICS_Request_oneof_content::query(v) => {
+                    my_size += ::protobuf::rt::enum_size(3, v);
+                },
+                &RICS_Request_oneof_content::data(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Request_oneof_content::add_route(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Request_oneof_content::del_route(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Request_oneof_content::can_drop_chance(v) => {
+                    my_size += 5;
+                },
+                &RICS_Request_oneof_content::can_fault(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Request_oneof_content::can_restart_ms(v) => {
+                    my_size += ::protobuf::rt::value_size(9, v, ::protobuf::wire_format::WireTypeVarint);
+                },
+                &RICS_Request_oneof_content::subscribe(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Request_oneof_content::unsubscribe(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Request_oneof_content::receive_filter(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let ::std::option::Option::Some(ref v) = self.content {
+            match v {
+                &RICS_Request_oneof_content::set_name(ref v) => {
+                    os.write_string(2, v)?;
+                },
+                &RICS_Request_oneof_content::query(v) => {
+                    os.write_enum(3, ::protobuf::ProtobufEnum::value(&v))?;
+                },
+                &RICS_Request_oneof_content::data(ref v) => {
+                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Request_oneof_content::add_route(ref v) => {
+                    os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Request_oneof_content::del_route(ref v) => {
+                    os.write_tag(6, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Request_oneof_content::can_drop_chance(v) => {
+                    os.write_float(7, v)?;
+                },
+                &RICS_Request_oneof_content::can_fault(ref v) => {
+                    os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Request_oneof_content::can_restart_ms(v) => {
+                    os.write_uint32(9, v)?;
+                },
+                &RICS_Request_oneof_content::subscribe(ref v) => {
+                    os.write_tag(10, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Request_oneof_content::unsubscribe(ref v) => {
+                    os.write_tag(11, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Request_oneof_content::receive_filter(ref v) => {
+                    os.write_tag(12, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RICS_Request {
+        RICS_Request::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_string_accessor::<_>(
+                "set_name",
+                RICS_Request::has_set_name,
+                RICS_Request::get_set_name,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_enum_accessor::<_, RICS_Request_RICS_Query>(
+                "query",
+                RICS_Request::has_query,
+                RICS_Request::get_query,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Data>(
+                "data",
+                RICS_Request::has_data,
+                RICS_Request::get_data,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Route>(
+                "add_route",
+                RICS_Request::has_add_route,
+                RICS_Request::get_add_route,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Route>(
+                "del_route",
+                RICS_Request::has_del_route,
+                RICS_Request::get_del_route,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_f32_accessor::<_>(
+                "can_drop_chance",
+                RICS_Request::has_can_drop_chance,
+                RICS_Request::get_can_drop_chance,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_CanFault>(
+                "can_fault",
+                RICS_Request::has_can_fault,
+                RICS_Request::get_can_fault,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_u32_accessor::<_>(
+                "can_restart_ms",
+                RICS_Request::has_can_restart_ms,
+                RICS_Request::get_can_restart_ms,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Subscription>(
+                "subscribe",
+                RICS_Request::has_subscribe,
+                RICS_Request::get_subscribe,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Subscription>(
+                "unsubscribe",
+                RICS_Request::has_unsubscribe,
+                RICS_Request::get_unsubscribe,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_ReceiveFilter>(
+                "receive_filter",
+                RICS_Request::has_receive_filter,
+                RICS_Request::get_receive_filter,
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Request>(
+                "RICS_Request",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RICS_Request {
+        static instance: ::protobuf::rt::LazyV2<RICS_Request> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Request::new)
+    }
+}
+
+impl ::protobuf::Clear for RICS_Request {
+    fn clear(&mut self) {
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RICS_Request {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Request {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum RICS_Request_RICS_Query {
+    NULL = 0,
+    LIST_SINK = 1,
+    WHO_AM_I = 2,
+    SET_FLAG_CAN_BROADCAST = 3,
+    CLEAR_FLAG_CAN_BROADCAST = 4,
+    SET_FLAG_CAN_ERRORS = 5,
+    CLEAR_FLAG_CAN_ERRORS = 6,
+    LIST_CAN_STATE = 7,
+    DAEMON_QUIT = 255,
+}
+
+impl ::protobuf::ProtobufEnum for RICS_Request_RICS_Query {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<RICS_Request_RICS_Query> {
+        match value {
+            0 => ::std::option::Option::Some(RICS_Request_RICS_Query::NULL),
+            1 => ::std::option::Option::Some(RICS_Request_RICS_Query::LIST_SINK),
+            2 => ::std::option::Option::Some(RICS_Request_RICS_Query::WHO_AM_I),
+            3 => ::std::option::Option::Some(RICS_Request_RICS_Query::SET_FLAG_CAN_BROADCAST),
+            4 => ::std::option::Option::Some(RICS_Request_RICS_Query::CLEAR_FLAG_CAN_BROADCAST),
+            5 => ::std::option::Option::Some(RICS_Request_RICS_Query::SET_FLAG_CAN_ERRORS),
+            6 => ::std::option::Option::Some(RICS_Request_RICS_Query::CLEAR_FLAG_CAN_ERRORS),
+            7 => ::std::option::Option::Some(RICS_Request_RICS_Query::LIST_CAN_STATE),
+            255 => ::std::option::Option::Some(RICS_Request_RICS_Query::DAEMON_QUIT),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [RICS_Request_RICS_Query] = &[
+            RICS_Request_RICS_Query::NULL,
+            RICS_Request_RICS_Query::LIST_SINK,
+            RICS_Request_RICS_Query::WHO_AM_I,
+            RICS_Request_RICS_Query::SET_FLAG_CAN_BROADCAST,
+            RICS_Request_RICS_Query::CLEAR_FLAG_CAN_BROADCAST,
+            RICS_Request_RICS_Query::SET_FLAG_CAN_ERRORS,
+            RICS_Request_RICS_Query::CLEAR_FLAG_CAN_ERRORS,
+            RICS_Request_RICS_Query::LIST_CAN_STATE,
+            RICS_Request_RICS_Query::DAEMON_QUIT,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<RICS_Request_RICS_Query>("RICS_Request.RICS_Query", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for RICS_Request_RICS_Query {
+}
+
+impl ::std::default::Default for RICS_Request_RICS_Query {
+    fn default() -> Self {
+        RICS_Request_RICS_Query::NULL
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Request_RICS_Query {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RICS_Response {
+    // message oneof groups
+    pub content: ::std::option::Option<RICS_Response_oneof_content>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RICS_Response {
+    fn default() -> &'a RICS_Response {
+        <RICS_Response as ::protobuf::Message>::default_instance()
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub enum RICS_Response_oneof_content {
+    idlist(RICS_Response_RICS_IdList),
+    node(i32),
+    data(RICS_Data),
+    can_states(RICS_Response_RICS_CanStateList),
+}
+
+impl RICS_Response {
+    pub fn new() -> RICS_Response {
+        ::std::default::Default::default()
+    }
+
+    // optional .RICS_Response.RICS_IdList idlist = 1;
+
+
+    pub fn get_idlist(&self) -> &RICS_Response_RICS_IdList {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::idlist(ref v)) => v,
+            _ => <RICS_Response_RICS_IdList as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_idlist(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_idlist(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::idlist(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_idlist(&mut self, v: RICS_Response_RICS_IdList) {
+        self.content = ::std::option::Option::Some(RICS_Response_oneof_content::idlist(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_idlist(&mut self) -> &mut RICS_Response_RICS_IdList {
+        if let ::std::option::Option::Some(RICS_Response_oneof_content::idlist(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Response_oneof_content::idlist(RICS_Response_RICS_IdList::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::idlist(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_idlist(&mut self) -> RICS_Response_RICS_IdList {
+        if self.has_idlist() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Response_oneof_content::idlist(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Response_RICS_IdList::new()
+        }
+    }
+
+    // optional int32 node = 2;
+
+
+    pub fn get_node(&self) -> i32 {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::node(v)) => v,
+            _ => 0,
+        }
+    }
+    pub fn clear_node(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_node(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::node(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_node(&mut self, v: i32) {
+        self.content = ::std::option::Option::Some(RICS_Response_oneof_content::node(v))
+    }
+
+    // optional .RICS_Data data = 4;
+
+
+    pub fn get_data(&self) -> &RICS_Data {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::data(ref v)) => v,
+            _ => <RICS_Data as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_data(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_data(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::data(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: RICS_Data) {
+        self.content = ::std::option::Option::Some(RICS_Response_oneof_content::data(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_data(&mut self) -> &mut RICS_Data {
+        if let ::std::option::Option::Some(RICS_Response_oneof_content::data(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Response_oneof_content::data(RICS_Data::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::data(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> RICS_Data {
+        if self.has_data() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Response_oneof_content::data(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Data::new()
+        }
+    }
+
+    // optional .RICS_Response.RICS_CanStateList can_states = 5;
+
+
+    pub fn get_can_states(&self) -> &RICS_Response_RICS_CanStateList {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::can_states(ref v)) => v,
+            _ => <RICS_Response_RICS_CanStateList as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_can_states(&mut self) {
+        self.content = ::std::option::Option::None;
+    }
+
+    pub fn has_can_states(&self) -> bool {
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::can_states(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_can_states(&mut self, v: RICS_Response_RICS_CanStateList) {
+        self.content = ::std::option::Option::Some(RICS_Response_oneof_content::can_states(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_can_states(&mut self) -> &mut RICS_Response_RICS_CanStateList {
+        if let ::std::option::Option::Some(RICS_Response_oneof_content::can_states(_)) = self.content {
+        } else {
+            self.content = ::std::option::Option::Some(RICS_Response_oneof_content::can_states(RICS_Response_RICS_CanStateList::new()));
+        }
+        match self.content {
+            ::std::option::Option::Some(RICS_Response_oneof_content::can_states(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_can_states(&mut self) -> RICS_Response_RICS_CanStateList {
+        if self.has_can_states() {
+            match self.content.take() {
+                ::std::option::Option::Some(RICS_Response_oneof_content::can_states(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            RICS_Response_RICS_CanStateList::new()
+        }
+    }
+}
+
+impl ::protobuf::Message for RICS_Response {
+    fn is_initialized(&self) -> bool {
+        if let Some(RICS_Response_oneof_content::idlist(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Response_oneof_content::data(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(RICS_Response_oneof_content::can_states(ref v)) = self.content {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Response_oneof_content::idlist(is.read_message()?));
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Response_oneof_content::node(is.read_int32()?));
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Response_oneof_content::data(is.read_message()?));
+                },
+                5 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.content = ::std::option::Option::Some(RICS_Response_oneof_content::can_states(is.read_message()?));
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let ::std::option::Option::Some(ref v) = self.content {
+            match v {
+                &RICS_Response_oneof_content::idlist(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Response_oneof_content::node(v) => {
+                    my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
+                },
+                &RICS_Response_oneof_content::data(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &RICS_Response_oneof_content::can_states(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+            };
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let ::std::option::Option::Some(ref v) = self.content {
+            match v {
+                &RICS_Response_oneof_content::idlist(ref v) => {
+                    os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Response_oneof_content::node(v) => {
+                    os.write_int32(2, v)?;
+                },
+                &RICS_Response_oneof_content::data(ref v) => {
+                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &RICS_Response_oneof_content::can_states(ref v) => {
+                    os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+            };
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RICS_Response {
+        RICS_Response::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Response_RICS_IdList>(
+                "idlist",
+                RICS_Response::has_idlist,
+                RICS_Response::get_idlist,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_i32_accessor::<_>(
+                "node",
+                RICS_Response::has_node,
+                RICS_Response::get_node,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Data>(
+                "data",
+                RICS_Response::has_data,
+                RICS_Response::get_data,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Response_RICS_CanStateList>(
+                "can_states",
+                RICS_Response::has_can_states,
+                RICS_Response::get_can_states,
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response>(
+                "RICS_Response",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RICS_Response {
+        static instance: ::protobuf::rt::LazyV2<RICS_Response> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Response::new)
+    }
+}
+
+impl ::protobuf::Clear for RICS_Response {
+    fn clear(&mut self) {
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.content = ::std::option::Option::None;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RICS_Response {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Response {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RICS_Response_RICS_Id {
+    // message fields
+    id: ::std::option::Option<i32>,
+    name: ::protobuf::SingularField<::std::string::String>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RICS_Response_RICS_Id {
+    fn default() -> &'a RICS_Response_RICS_Id {
+        <RICS_Response_RICS_Id as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RICS_Response_RICS_Id {
+    pub fn new() -> RICS_Response_RICS_Id {
+        ::std::default::Default::default()
+    }
+
+    // required int32 id = 1;
+
+
+    pub fn get_id(&self) -> i32 {
+        self.id.unwrap_or(0)
+    }
+    pub fn clear_id(&mut self) {
+        self.id = ::std::option::Option::None;
+    }
+
+    pub fn has_id(&self) -> bool {
+        self.id.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_id(&mut self, v: i32) {
+        self.id = ::std::option::Option::Some(v);
+    }
+
+    // optional string name = 2;
+
+
+    pub fn get_name(&self) -> &str {
+        match self.name.as_ref() {
+            Some(v) => &v,
+            None => "",
+        }
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    pub fn has_name(&self) -> bool {
+        self.name.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = ::protobuf::SingularField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        if self.name.is_none() {
+            self.name.set_default();
+        }
+        self.name.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        self.name.take().unwrap_or_else(|| ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for RICS_Response_RICS_Id {
+    fn is_initialized(&self) -> bool {
+        if self.id.is_none() {
+            return false;
+        }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_int32()?;
+                    self.id = ::std::option::Option::Some(tmp);
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if let Some(v) = self.id {
+            my_size += ::protobuf::rt::value_size(1, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(ref v) = self.name.as_ref() {
+            my_size += ::protobuf::rt::string_size(2, &v);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if let Some(v) = self.id {
+            os.write_int32(1, v)?;
+        }
+        if let Some(ref v) = self.name.as_ref() {
+            os.write_string(2, &v)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> RICS_Response_RICS_Id {
+        RICS_Response_RICS_Id::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "id",
+                |m: &RICS_Response_RICS_Id| { &m.id },
+                |m: &mut RICS_Response_RICS_Id| { &mut m.id },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &RICS_Response_RICS_Id| { &m.name },
+                |m: &mut RICS_Response_RICS_Id| { &mut m.name },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response_RICS_Id>(
+                "RICS_Response.RICS_Id",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static RICS_Response_RICS_Id {
+        static instance: ::protobuf::rt::LazyV2<RICS_Response_RICS_Id> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Response_RICS_Id::new)
+    }
+}
+
+impl ::protobuf::Clear for RICS_Response_RICS_Id {
+    fn clear(&mut self) {
+        self.id = ::std::option::Option::None;
+        self.name.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for RICS_Response_RICS_Id {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_Id {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct RICS_Response_RICS_IdList {
+    // message fields
+    pub ids: ::protobuf::RepeatedField<RICS_Response_RICS_Id>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a RICS_Response_RICS_IdList {
+    fn default() -> &'a RICS_Response_RICS_IdList {
+        <RICS_Response_RICS_IdList as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl RICS_Response_RICS_IdList {
+    pub fn new() -> RICS_Response_RICS_IdList {
+        ::std::default::Default::default()
+    }
+
+    // repeated .RICS_Response.RICS_Id ids = 1;
+
+
+    pub fn get_ids(&self) -> &[RICS_Response_RICS_Id] {
+        &self.ids
+    }
+    pub fn clear_ids(&mut self) {
+        self.ids.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_ids(&mut self, v: ::protobuf::RepeatedField<RICS_Response_RICS_Id>) {
+        self.ids = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_ids(&mut self) -> &mut ::protobuf::RepeatedField<RICS_Response_RICS_Id> {
+        &mut self.ids
+    }
+
+    // Take field
+    pub fn take_ids(&mut self) -> ::protobuf::RepeatedField<RICS_Response_RICS_Id> {
+        ::std::mem::replace(&mut self.ids, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for RICS_Response_RICS_IdList {
+    fn is_initialized(&self) -> bool {
+        for v in &self.ids {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.ids)?;
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -1569,44 +3829,21 @@ impl ::protobuf::Message for RICS_Response {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        if let ::std::option::Option::Some(ref v) = self.content {
-            match v {
-                &RICS_Response_oneof_content::idlist(ref v) => {
-                    let len = v.compute_size();
-                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
-                },
-                &RICS_Response_oneof_content::node(v) => {
-                    my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
-                },
-                &RICS_Response_oneof_content::data(ref v) => {
-                    let len = v.compute_size();
-                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
-                },
-            };
-        }
+        for value in &self.ids {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
-        if let ::std::option::Option::Some(ref v) = self.content {
-            match v {
-                &RICS_Response_oneof_content::idlist(ref v) => {
-                    os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-                &RICS_Response_oneof_content::node(v) => {
-                    os.write_int32(2, v)?;
-                },
-                &RICS_Response_oneof_content::data(ref v) => {
-                    os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-                    os.write_raw_varint32(v.get_cached_size())?;
-                    v.write_to_with_cached_sizes(os)?;
-                },
-            };
-        }
+        for v in &self.ids {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -1637,82 +3874,72 @@ impl ::protobuf::Message for RICS_Response {
         Self::descriptor_static()
     }
 
-    fn new() -> RICS_Response {
-        RICS_Response::new()
+    fn new() -> RICS_Response_RICS_IdList {
+        RICS_Response_RICS_IdList::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
         static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
         descriptor.get(|| {
             let mut fields = ::std::vec::Vec::new();
-            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Response_RICS_IdList>(
-                "idlist",
-                RICS_Response::has_idlist,
-                RICS_Response::get_idlist,
-            ));
-            fields.push(::protobuf::reflect::accessor::make_singular_i32_accessor::<_>(
-                "node",
-                RICS_Response::has_node,
-                RICS_Response::get_node,
-            ));
-            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, RICS_Data>(
-                "data",
-                RICS_Response::has_data,
-                RICS_Response::get_data,
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<RICS_Response_RICS_Id>>(
+                "ids",
+                |m: &RICS_Response_RICS_IdList| { &m.ids },
+                |m: &mut RICS_Response_RICS_IdList| { &mut m.ids },
             ));
-            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response>(
-                "RICS_Response",
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response_RICS_IdList>(
+                "RICS_Response.RICS_IdList",
                 fields,
                 file_descriptor_proto()
             )
         })
     }
 
-    fn default_instance() -> &'static RICS_Response {
-        static instance: ::protobuf::rt::LazyV2<RICS_Response> = ::protobuf::rt::LazyV2::INIT;
-        instance.get(RICS_Response::new)
+    fn default_instance() -> &'static RICS_Response_RICS_IdList {
+        static instance: ::protobuf::rt::LazyV2<RICS_Response_RICS_IdList> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Response_RICS_IdList::new)
     }
 }
 
-impl ::protobuf::Clear for RICS_Response {
+impl ::protobuf::Clear for RICS_Response_RICS_IdList {
     fn clear(&mut self) {
-        self.content = ::std::option::Option::None;
-        self.content = ::std::option::Option::None;
-        self.content = ::std::option::Option::None;
+        self.ids.clear();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for RICS_Response {
+impl ::std::fmt::Debug for RICS_Response_RICS_IdList {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for RICS_Response {
+impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_IdList {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct RICS_Response_RICS_Id {
+pub struct RICS_Response_RICS_CanState {
     // message fields
     id: ::std::option::Option<i32>,
-    name: ::protobuf::SingularField<::std::string::String>,
+    tec: ::std::option::Option<u32>,
+    rec: ::std::option::Option<u32>,
+    state: ::std::option::Option<RICS_Response_RICS_CanState_RICS_ErrorState>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a RICS_Response_RICS_Id {
-    fn default() -> &'a RICS_Response_RICS_Id {
-        <RICS_Response_RICS_Id as ::protobuf::Message>::default_instance()
+impl<'a> ::std::default::Default for &'a RICS_Response_RICS_CanState {
+    fn default() -> &'a RICS_Response_RICS_CanState {
+        <RICS_Response_RICS_CanState as ::protobuf::Message>::default_instance()
     }
 }
 
-impl RICS_Response_RICS_Id {
-    pub fn new() -> RICS_Response_RICS_Id {
+impl RICS_Response_RICS_CanState {
+    pub fn new() -> RICS_Response_RICS_CanState {
         ::std::default::Default::default()
     }
 
@@ -1735,44 +3962,65 @@ impl RICS_Response_RICS_Id {
         self.id = ::std::option::Option::Some(v);
     }
 
-    // optional string name = 2;
+    // optional uint32 tec = 2;
 
 
-    pub fn get_name(&self) -> &str {
-        match self.name.as_ref() {
-            Some(v) => &v,
-            None => "",
-        }
+    pub fn get_tec(&self) -> u32 {
+        self.tec.unwrap_or(0)
     }
-    pub fn clear_name(&mut self) {
-        self.name.clear();
+    pub fn clear_tec(&mut self) {
+        self.tec = ::std::option::Option::None;
     }
 
-    pub fn has_name(&self) -> bool {
-        self.name.is_some()
+    pub fn has_tec(&self) -> bool {
+        self.tec.is_some()
     }
 
     // Param is passed by value, moved
-    pub fn set_name(&mut self, v: ::std::string::String) {
-        self.name = ::protobuf::SingularField::some(v);
+    pub fn set_tec(&mut self, v: u32) {
+        self.tec = ::std::option::Option::Some(v);
     }
 
-    // Mutable pointer to the field.
-    // If field is not initialized, it is initialized with default value first.
-    pub fn mut_name(&mut self) -> &mut ::std::string::String {
-        if self.name.is_none() {
-            self.name.set_default();
-        }
-        self.name.as_mut().unwrap()
+    // optional uint32 rec = 3;
+
+
+    pub fn get_rec(&self) -> u32 {
+        self.rec.unwrap_or(0)
+    }
+    pub fn clear_rec(&mut self) {
+        self.rec = ::std::option::Option::None;
     }
 
-    // Take field
-    pub fn take_name(&mut self) -> ::std::string::String {
-        self.name.take().unwrap_or_else(|| ::std::string::String::new())
+    pub fn has_rec(&self) -> bool {
+        self.rec.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_rec(&mut self, v: u32) {
+        self.rec = ::std::option::Option::Some(v);
+    }
+
+    // optional .RICS_Response.RICS_CanState.RICS_ErrorState state = 4;
+
+
+    pub fn get_state(&self) -> RICS_Response_RICS_CanState_RICS_ErrorState {
+        self.state.unwrap_or(RICS_Response_RICS_CanState_RICS_ErrorState::ERROR_ACTIVE)
+    }
+    pub fn clear_state(&mut self) {
+        self.state = ::std::option::Option::None;
+    }
+
+    pub fn has_state(&self) -> bool {
+        self.state.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_state(&mut self, v: RICS_Response_RICS_CanState_RICS_ErrorState) {
+        self.state = ::std::option::Option::Some(v);
     }
 }
 
-impl ::protobuf::Message for RICS_Response_RICS_Id {
+impl ::protobuf::Message for RICS_Response_RICS_CanState {
     fn is_initialized(&self) -> bool {
         if self.id.is_none() {
             return false;
@@ -1792,7 +4040,21 @@ impl ::protobuf::Message for RICS_Response_RICS_Id {
                     self.id = ::std::option::Option::Some(tmp);
                 },
                 2 => {
-                    ::protobuf::rt::read_singular_string_into(wire_type, is, &mut self.name)?;
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.tec = ::std::option::Option::Some(tmp);
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.rec = ::std::option::Option::Some(tmp);
+                },
+                4 => {
+                    ::protobuf::rt::read_proto2_enum_with_unknown_fields_into(wire_type, is, &mut self.state, 4, &mut self.unknown_fields)?
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -1809,8 +4071,14 @@ impl ::protobuf::Message for RICS_Response_RICS_Id {
         if let Some(v) = self.id {
             my_size += ::protobuf::rt::value_size(1, v, ::protobuf::wire_format::WireTypeVarint);
         }
-        if let Some(ref v) = self.name.as_ref() {
-            my_size += ::protobuf::rt::string_size(2, &v);
+        if let Some(v) = self.tec {
+            my_size += ::protobuf::rt::value_size(2, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.rec {
+            my_size += ::protobuf::rt::value_size(3, v, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if let Some(v) = self.state {
+            my_size += ::protobuf::rt::enum_size(4, v);
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
@@ -1821,8 +4089,14 @@ impl ::protobuf::Message for RICS_Response_RICS_Id {
         if let Some(v) = self.id {
             os.write_int32(1, v)?;
         }
-        if let Some(ref v) = self.name.as_ref() {
-            os.write_string(2, &v)?;
+        if let Some(v) = self.tec {
+            os.write_uint32(2, v)?;
+        }
+        if let Some(v) = self.rec {
+            os.write_uint32(3, v)?;
+        }
+        if let Some(v) = self.state {
+            os.write_enum(4, ::protobuf::ProtobufEnum::value(&v))?;
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
@@ -1854,8 +4128,8 @@ impl ::protobuf::Message for RICS_Response_RICS_Id {
         Self::descriptor_static()
     }
 
-    fn new() -> RICS_Response_RICS_Id {
-        RICS_Response_RICS_Id::new()
+    fn new() -> RICS_Response_RICS_CanState {
+        RICS_Response_RICS_CanState::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
@@ -1864,97 +4138,162 @@ impl ::protobuf::Message for RICS_Response_RICS_Id {
             let mut fields = ::std::vec::Vec::new();
             fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
                 "id",
-                |m: &RICS_Response_RICS_Id| { &m.id },
-                |m: &mut RICS_Response_RICS_Id| { &mut m.id },
+                |m: &RICS_Response_RICS_CanState| { &m.id },
+                |m: &mut RICS_Response_RICS_CanState| { &mut m.id },
             ));
-            fields.push(::protobuf::reflect::accessor::make_singular_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
-                "name",
-                |m: &RICS_Response_RICS_Id| { &m.name },
-                |m: &mut RICS_Response_RICS_Id| { &mut m.name },
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "tec",
+                |m: &RICS_Response_RICS_CanState| { &m.tec },
+                |m: &mut RICS_Response_RICS_CanState| { &mut m.tec },
             ));
-            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response_RICS_Id>(
-                "RICS_Response.RICS_Id",
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "rec",
+                |m: &RICS_Response_RICS_CanState| { &m.rec },
+                |m: &mut RICS_Response_RICS_CanState| { &mut m.rec },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeEnum<RICS_Response_RICS_CanState_RICS_ErrorState>>(
+                "state",
+                |m: &RICS_Response_RICS_CanState| { &m.state },
+                |m: &mut RICS_Response_RICS_CanState| { &mut m.state },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response_RICS_CanState>(
+                "RICS_Response.RICS_CanState",
                 fields,
                 file_descriptor_proto()
             )
         })
     }
 
-    fn default_instance() -> &'static RICS_Response_RICS_Id {
-        static instance: ::protobuf::rt::LazyV2<RICS_Response_RICS_Id> = ::protobuf::rt::LazyV2::INIT;
-        instance.get(RICS_Response_RICS_Id::new)
+    fn default_instance() -> &'static RICS_Response_RICS_CanState {
+        static instance: ::protobuf::rt::LazyV2<RICS_Response_RICS_CanState> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Response_RICS_CanState::new)
     }
 }
 
-impl ::protobuf::Clear for RICS_Response_RICS_Id {
+impl ::protobuf::Clear for RICS_Response_RICS_CanState {
     fn clear(&mut self) {
         self.id = ::std::option::Option::None;
-        self.name.clear();
+        self.tec = ::std::option::Option::None;
+        self.rec = ::std::option::Option::None;
+        self.state = ::std::option::Option::None;
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for RICS_Response_RICS_Id {
+impl ::std::fmt::Debug for RICS_Response_RICS_CanState {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_Id {
+impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_CanState {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum RICS_Response_RICS_CanState_RICS_ErrorState {
+    ERROR_ACTIVE = 0,
+    ERROR_PASSIVE = 1,
+    BUS_OFF = 2,
+}
+
+impl ::protobuf::ProtobufEnum for RICS_Response_RICS_CanState_RICS_ErrorState {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<RICS_Response_RICS_CanState_RICS_ErrorState> {
+        match value {
+            0 => ::std::option::Option::Some(RICS_Response_RICS_CanState_RICS_ErrorState::ERROR_ACTIVE),
+            1 => ::std::option::Option::Some(RICS_Response_RICS_CanState_RICS_ErrorState::ERROR_PASSIVE),
+            2 => ::std::option::Option::Some(RICS_Response_RICS_CanState_RICS_ErrorState::BUS_OFF),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [RICS_Response_RICS_CanState_RICS_ErrorState] = &[
+            RICS_Response_RICS_CanState_RICS_ErrorState::ERROR_ACTIVE,
+            RICS_Response_RICS_CanState_RICS_ErrorState::ERROR_PASSIVE,
+            RICS_Response_RICS_CanState_RICS_ErrorState::BUS_OFF,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<RICS_Response_RICS_CanState_RICS_ErrorState>("RICS_Response.RICS_CanState.RICS_ErrorState", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for RICS_Response_RICS_CanState_RICS_ErrorState {
+}
+
+impl ::std::default::Default for RICS_Response_RICS_CanState_RICS_ErrorState {
+    fn default() -> Self {
+        RICS_Response_RICS_CanState_RICS_ErrorState::ERROR_ACTIVE
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_CanState_RICS_ErrorState {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
 #[derive(PartialEq,Clone,Default)]
-pub struct RICS_Response_RICS_IdList {
+pub struct RICS_Response_RICS_CanStateList {
     // message fields
-    pub ids: ::protobuf::RepeatedField<RICS_Response_RICS_Id>,
+    pub states: ::protobuf::RepeatedField<RICS_Response_RICS_CanState>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a RICS_Response_RICS_IdList {
-    fn default() -> &'a RICS_Response_RICS_IdList {
-        <RICS_Response_RICS_IdList as ::protobuf::Message>::default_instance()
+impl<'a> ::std::default::Default for &'a RICS_Response_RICS_CanStateList {
+    fn default() -> &'a RICS_Response_RICS_CanStateList {
+        <RICS_Response_RICS_CanStateList as ::protobuf::Message>::default_instance()
     }
 }
 
-impl RICS_Response_RICS_IdList {
-    pub fn new() -> RICS_Response_RICS_IdList {
+impl RICS_Response_RICS_CanStateList {
+    pub fn new() -> RICS_Response_RICS_CanStateList {
         ::std::default::Default::default()
     }
 
-    // repeated .RICS_Response.RICS_Id ids = 1;
+    // repeated .RICS_Response.RICS_CanState states = 1;
 
 
-    pub fn get_ids(&self) -> &[RICS_Response_RICS_Id] {
-        &self.ids
+    pub fn get_states(&self) -> &[RICS_Response_RICS_CanState] {
+        &self.states
     }
-    pub fn clear_ids(&mut self) {
-        self.ids.clear();
+    pub fn clear_states(&mut self) {
+        self.states.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_ids(&mut self, v: ::protobuf::RepeatedField<RICS_Response_RICS_Id>) {
-        self.ids = v;
+    pub fn set_states(&mut self, v: ::protobuf::RepeatedField<RICS_Response_RICS_CanState>) {
+        self.states = v;
     }
 
     // Mutable pointer to the field.
-    pub fn mut_ids(&mut self) -> &mut ::protobuf::RepeatedField<RICS_Response_RICS_Id> {
-        &mut self.ids
+    pub fn mut_states(&mut self) -> &mut ::protobuf::RepeatedField<RICS_Response_RICS_CanState> {
+        &mut self.states
     }
 
     // Take field
-    pub fn take_ids(&mut self) -> ::protobuf::RepeatedField<RICS_Response_RICS_Id> {
-        ::std::mem::replace(&mut self.ids, ::protobuf::RepeatedField::new())
+    pub fn take_states(&mut self) -> ::protobuf::RepeatedField<RICS_Response_RICS_CanState> {
+        ::std::mem::replace(&mut self.states, ::protobuf::RepeatedField::new())
     }
 }
 
-impl ::protobuf::Message for RICS_Response_RICS_IdList {
+impl ::protobuf::Message for RICS_Response_RICS_CanStateList {
     fn is_initialized(&self) -> bool {
-        for v in &self.ids {
+        for v in &self.states {
             if !v.is_initialized() {
                 return false;
             }
@@ -1967,7 +4306,7 @@ impl ::protobuf::Message for RICS_Response_RICS_IdList {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
                 1 => {
-                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.ids)?;
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.states)?;
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -1981,7 +4320,7 @@ impl ::protobuf::Message for RICS_Response_RICS_IdList {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        for value in &self.ids {
+        for value in &self.states {
             let len = value.compute_size();
             my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
         };
@@ -1991,7 +4330,7 @@ impl ::protobuf::Message for RICS_Response_RICS_IdList {
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
-        for v in &self.ids {
+        for v in &self.states {
             os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
             os.write_raw_varint32(v.get_cached_size())?;
             v.write_to_with_cached_sizes(os)?;
@@ -2026,47 +4365,47 @@ impl ::protobuf::Message for RICS_Response_RICS_IdList {
         Self::descriptor_static()
     }
 
-    fn new() -> RICS_Response_RICS_IdList {
-        RICS_Response_RICS_IdList::new()
+    fn new() -> RICS_Response_RICS_CanStateList {
+        RICS_Response_RICS_CanStateList::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
         static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
         descriptor.get(|| {
             let mut fields = ::std::vec::Vec::new();
-            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<RICS_Response_RICS_Id>>(
-                "ids",
-                |m: &RICS_Response_RICS_IdList| { &m.ids },
-                |m: &mut RICS_Response_RICS_IdList| { &mut m.ids },
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<RICS_Response_RICS_CanState>>(
+                "states",
+                |m: &RICS_Response_RICS_CanStateList| { &m.states },
+                |m: &mut RICS_Response_RICS_CanStateList| { &mut m.states },
             ));
-            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response_RICS_IdList>(
-                "RICS_Response.RICS_IdList",
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<RICS_Response_RICS_CanStateList>(
+                "RICS_Response.RICS_CanStateList",
                 fields,
                 file_descriptor_proto()
             )
         })
     }
 
-    fn default_instance() -> &'static RICS_Response_RICS_IdList {
-        static instance: ::protobuf::rt::LazyV2<RICS_Response_RICS_IdList> = ::protobuf::rt::LazyV2::INIT;
-        instance.get(RICS_Response_RICS_IdList::new)
+    fn default_instance() -> &'static RICS_Response_RICS_CanStateList {
+        static instance: ::protobuf::rt::LazyV2<RICS_Response_RICS_CanStateList> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(RICS_Response_RICS_CanStateList::new)
     }
 }
 
-impl ::protobuf::Clear for RICS_Response_RICS_IdList {
+impl ::protobuf::Clear for RICS_Response_RICS_CanStateList {
     fn clear(&mut self) {
-        self.ids.clear();
+        self.states.clear();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for RICS_Response_RICS_IdList {
+impl ::std::fmt::Debug for RICS_Response_RICS_CanStateList {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_IdList {
+impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_CanStateList {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Message(self)
     }
@@ -2074,32 +4413,66 @@ impl ::protobuf::reflect::ProtobufValue for RICS_Response_RICS_IdList {
 
 static file_descriptor_proto_data: &'static [u8] = b"\
     \n\nrics.proto\"9\n\x0fRICS_Connection\x12&\n\x0fconnect_as_node\x18\x01\
-    \x20\x01(\x08R\rconnectAsNode\"\xdc\x01\n\tRICS_Data\x12\x16\n\x06source\
+    \x20\x01(\x08R\rconnectAsNode\"\xa4\x03\n\tRICS_Data\x12\x16\n\x06source\
     \x18d\x20\x01(\x05R\x06source\x12\x16\n\x06target\x18e\x20\x01(\x05R\x06\
-    target\x12\x12\n\x04data\x18\x01\x20\x01(\x0cR\x04data\x12\x0e\n\x02id\
-    \x18\x02\x20\x01(\x05R\x02id\x12,\n\x04type\x18\x03\x20\x01(\x0e2\x18.RI\
-    CS_Data.RICS_DataTypeR\x04type\"E\n\rRICS_DataType\x12\x07\n\x03RAW\x10\
-    \0\x12\n\n\x06STREAM\x10\x01\x12\x07\n\x03CAN\x10\x02\x12\x0c\n\x08DATAG\
-    RAM\x10\x03\x12\x08\n\x04SYNC\x10\x04*\x06\x08\xc8\x01\x10\xac\x02\"V\n\
-    \nRICS_Route\x12\x12\n\x04from\x18\x01\x20\x01(\x05R\x04from\x12\x0e\n\
-    \x02to\x18\x02\x20\x01(\x05R\x02to\x12$\n\rbidirectional\x18\x03\x20\x01\
-    (\x08R\rbidirectional\"\x8d\x03\n\x0cRICS_Request\x12\x1b\n\x08set_name\
-    \x18\x02\x20\x01(\tH\0R\x07setName\x120\n\x05query\x18\x03\x20\x01(\x0e2\
-    \x18.RICS_Request.RICS_QueryH\0R\x05query\x12\x20\n\x04data\x18\x04\x20\
-    \x01(\x0b2\n.RICS_DataH\0R\x04data\x12*\n\tadd_route\x18\x05\x20\x01(\
-    \x0b2\x0b.RICS_RouteH\0R\x08addRoute\x12*\n\tdel_route\x18\x06\x20\x01(\
-    \x0b2\x0b.RICS_RouteH\0R\x08delRoute\x12(\n\x0fcan_drop_chance\x18\x07\
-    \x20\x01(\x02H\0R\rcanDropChance\"\x7f\n\nRICS_Query\x12\x08\n\x04NULL\
-    \x10\0\x12\r\n\tLIST_SINK\x10\x01\x12\x0c\n\x08WHO_AM_I\x10\x02\x12\x1a\
-    \n\x16SET_FLAG_CAN_BROADCAST\x10\x03\x12\x1c\n\x18CLEAR_FLAG_CAN_BROADCA\
-    ST\x10\x04\x12\x10\n\x0bDAEMON_QUIT\x10\xff\x01B\t\n\x07content\"\xf0\
-    \x01\n\rRICS_Response\x124\n\x06idlist\x18\x01\x20\x01(\x0b2\x1a.RICS_Re\
-    sponse.RICS_IdListH\0R\x06idlist\x12\x14\n\x04node\x18\x02\x20\x01(\x05H\
-    \0R\x04node\x12\x20\n\x04data\x18\x04\x20\x01(\x0b2\n.RICS_DataH\0R\x04d\
-    ata\x1a-\n\x07RICS_Id\x12\x0e\n\x02id\x18\x01\x20\x02(\x05R\x02id\x12\
-    \x12\n\x04name\x18\x02\x20\x01(\tR\x04name\x1a7\n\x0bRICS_IdList\x12(\n\
-    \x03ids\x18\x01\x20\x03(\x0b2\x16.RICS_Response.RICS_IdR\x03idsB\t\n\x07\
-    content\
+    target\x12\x1c\n\ttimestamp\x18f\x20\x01(\x04R\ttimestamp\x12\x1a\n\x08s\
+    equence\x18g\x20\x01(\rR\x08sequence\x12\x12\n\x04data\x18\x01\x20\x01(\
+    \x0cR\x04data\x12\x0e\n\x02id\x18\x02\x20\x01(\x05R\x02id\x12,\n\x04type\
+    \x18\x03\x20\x01(\x0e2\x18.RICS_Data.RICS_DataTypeR\x04type\x12\x0e\n\
+    \x02fd\x18\x04\x20\x01(\x08R\x02fd\x12\x10\n\x03brs\x18\x05\x20\x01(\x08\
+    R\x03brs\x12\x10\n\x03esi\x18\x06\x20\x01(\x08R\x03esi\x12\x1a\n\x08exte\
+    nded\x18\x07\x20\x01(\x08R\x08extended\x12\x10\n\x03rtr\x18\x08\x20\x01(\
+    \x08R\x03rtr\x12\x14\n\x05error\x18\t\x20\x01(\x08R\x05error\x12\x14\n\
+    \x05topic\x18\n\x20\x01(\tR\x05topic\"E\n\rRICS_DataType\x12\x07\n\x03RA\
+    W\x10\0\x12\n\n\x06STREAM\x10\x01\x12\x07\n\x03CAN\x10\x02\x12\x0c\n\x08\
+    DATAGRAM\x10\x03\x12\x08\n\x04SYNC\x10\x04*\x06\x08\xc8\x01\x10\xac\x02\
+    \"w\n\rRICS_CanFault\x12\x12\n\x04node\x18\x01\x20\x01(\x05R\x04node\x12\
+    \x1b\n\ttx_errors\x18\x02\x20\x01(\rR\x08txErrors\x12\x1b\n\trx_errors\
+    \x18\x03\x20\x01(\rR\x08rxErrors\x12\x18\n\x07recover\x18\x04\x20\x01(\
+    \x08R\x07recover\"\xd0\x01\n\x11RICS_Subscription\x12<\n\x04type\x18\x01\
+    \x20\x01(\x0e2(.RICS_Subscription.RICS_SubscriptionTypeR\x04type\x12\x15\
+    \n\x06id_min\x18\x02\x20\x01(\x05R\x05idMin\x12\x15\n\x06id_max\x18\x03\
+    \x20\x01(\x05R\x05idMax\x12\x14\n\x05topic\x18\x04\x20\x01(\tR\x05topic\
+    \"9\n\x15RICS_SubscriptionType\x12\x07\n\x03CAN\x10\0\x12\x0c\n\x08DATAG\
+    RAM\x10\x01\x12\t\n\x05TOPIC\x10\x02\"\xc8\x01\n\x12RICS_ReceiveFilter\
+    \x123\n\x03ids\x18\x01\x20\x03(\x0b2!.RICS_ReceiveFilter.RICS_IdFilterR\
+    \x03ids\x12.\n\x05types\x18\x02\x20\x03(\x0e2\x18.RICS_Data.RICS_DataTyp\
+    eR\x05types\x12\x18\n\x07sources\x18\x03\x20\x03(\x05R\x07sources\x1a3\n\
+    \rRICS_IdFilter\x12\x0e\n\x02id\x18\x01\x20\x01(\x05R\x02id\x12\x12\n\
+    \x04mask\x18\x02\x20\x01(\x05R\x04mask\"V\n\nRICS_Route\x12\x12\n\x04fro\
+    m\x18\x01\x20\x01(\x05R\x04from\x12\x0e\n\x02to\x18\x02\x20\x01(\x05R\
+    \x02to\x12$\n\rbidirectional\x18\x03\x20\x01(\x08R\rbidirectional\"\xd7\
+    \x05\n\x0cRICS_Request\x12\x1b\n\x08set_name\x18\x02\x20\x01(\tH\0R\x07s\
+    etName\x120\n\x05query\x18\x03\x20\x01(\x0e2\x18.RICS_Request.RICS_Query\
+    H\0R\x05query\x12\x20\n\x04data\x18\x04\x20\x01(\x0b2\n.RICS_DataH\0R\
+    \x04data\x12*\n\tadd_route\x18\x05\x20\x01(\x0b2\x0b.RICS_RouteH\0R\x08a\
+    ddRoute\x12*\n\tdel_route\x18\x06\x20\x01(\x0b2\x0b.RICS_RouteH\0R\x08de\
+    lRoute\x12(\n\x0fcan_drop_chance\x18\x07\x20\x01(\x02H\0R\rcanDropChance\
+    \x12-\n\tcan_fault\x18\x08\x20\x01(\x0b2\x0e.RICS_CanFaultH\0R\x08canFau\
+    lt\x12&\n\x0ecan_restart_ms\x18\t\x20\x01(\rH\0R\x0ccanRestartMs\x122\n\
+    \tsubscribe\x18\n\x20\x01(\x0b2\x12.RICS_SubscriptionH\0R\tsubscribe\x12\
+    6\n\x0bunsubscribe\x18\x0b\x20\x01(\x0b2\x12.RICS_SubscriptionH\0R\x0bun\
+    subscribe\x12<\n\x0ereceive_filter\x18\x0c\x20\x01(\x0b2\x13.RICS_Receiv\
+    eFilterH\0R\rreceiveFilter\"\xc7\x01\n\nRICS_Query\x12\x08\n\x04NULL\x10\
+    \0\x12\r\n\tLIST_SINK\x10\x01\x12\x0c\n\x08WHO_AM_I\x10\x02\x12\x1a\n\
+    \x16SET_FLAG_CAN_BROADCAST\x10\x03\x12\x1c\n\x18CLEAR_FLAG_CAN_BROADCAST\
+    \x10\x04\x12\x17\n\x13SET_FLAG_CAN_ERRORS\x10\x05\x12\x19\n\x15CLEAR_FLA\
+    G_CAN_ERRORS\x10\x06\x12\x12\n\x0eLIST_CAN_STATE\x10\x07\x12\x10\n\x0bDA\
+    EMON_QUIT\x10\xff\x01B\t\n\x07content\"\xcd\x04\n\rRICS_Response\x124\n\
+    \x06idlist\x18\x01\x20\x01(\x0b2\x1a.RICS_Response.RICS_IdListH\0R\x06id\
+    list\x12\x14\n\x04node\x18\x02\x20\x01(\x05H\0R\x04node\x12\x20\n\x04dat\
+    a\x18\x04\x20\x01(\x0b2\n.RICS_DataH\0R\x04data\x12A\n\ncan_states\x18\
+    \x05\x20\x01(\x0b2\x20.RICS_Response.RICS_CanStateListH\0R\tcanStates\
+    \x1a-\n\x07RICS_Id\x12\x0e\n\x02id\x18\x01\x20\x02(\x05R\x02id\x12\x12\n\
+    \x04name\x18\x02\x20\x01(\tR\x04name\x1a7\n\x0bRICS_IdList\x12(\n\x03ids\
+    \x18\x01\x20\x03(\x0b2\x16.RICS_Response.RICS_IdR\x03ids\x1a\xcc\x01\n\r\
+    RICS_CanState\x12\x0e\n\x02id\x18\x01\x20\x02(\x05R\x02id\x12\x10\n\x03t\
+    ec\x18\x02\x20\x01(\rR\x03tec\x12\x10\n\x03rec\x18\x03\x20\x01(\rR\x03re\
+    c\x12B\n\x05state\x18\x04\x20\x01(\x0e2,.RICS_Response.RICS_CanState.RIC\
+    S_ErrorStateR\x05state\"C\n\x0fRICS_ErrorState\x12\x10\n\x0cERROR_ACTIVE\
+    \x10\0\x12\x11\n\rERROR_PASSIVE\x10\x01\x12\x0b\n\x07BUS_OFF\x10\x02\x1a\
+    I\n\x11RICS_CanStateList\x124\n\x06states\x18\x01\x20\x03(\x0b2\x1c.RICS\
+    _Response.RICS_CanStateR\x06statesB\t\n\x07content\
 ";
 
 static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
@@ -0,0 +1,248 @@
+//! Remote procedure calls over DATAGRAM packets
+//!
+//! Requests are sent to the `RPC_PORT` of a node, carrying a correlation id
+//! and the name of the method called, and are answered on the same port
+//! with either the response or an error message. Clients send a request
+//! again if its response is late, with the same correlation id, and nodes
+//! answer repeated requests with their previous response instead of calling
+//! the method again.
+//!
+//! Packet: kind (1 byte), correlation id (4 bytes, big endian), then for
+//! requests the method name length (1 byte), the method name and the
+//! payload, for responses the payload and for errors the error message.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use super::rics;
+use super::server::{self, RICSServer};
+
+/// DATAGRAM port of the RPC requests and responses
+pub const RPC_PORT: i32 = 111;
+/// Times a request is sent again before the call times out
+pub const RPC_RETRIES: u32 = 2;
+
+const KIND_REQUEST: u8 = 1;
+const KIND_RESPONSE: u8 = 2;
+const KIND_ERROR: u8 = 3;
+
+const HEADER_LEN: usize = 5;
+/// Responses kept to answer repeated requests
+const ANSWERED_MAX: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RpcError {
+    /// No response before the timeout
+    Timeout,
+    /// Method names are limited to 255 bytes
+    InvalidMethod,
+    /// Error returned by the called node
+    Remote(String),
+    /// The server connection was closed before the response
+    Disconnected,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcError::Timeout => write!(f, "No response"),
+            RpcError::InvalidMethod => write!(f, "Method name too long"),
+            RpcError::Remote(e) => write!(f, "{}", e),
+            RpcError::Disconnected => write!(f, "Server connection closed"),
+        }
+    }
+}
+
+/// Request received by a node
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub source: i32,
+    pub id: u32,
+    pub method: String,
+    pub payload: Vec<u8>,
+}
+
+impl Request {
+    /// Request carried by a packet, if it is one
+    pub fn parse(data: &rics::RICS_Data) -> Option<Request> {
+        let dat = data.get_data();
+        if data.get_field_type() != rics::RICS_Data_RICS_DataType::DATAGRAM || data.get_id() != RPC_PORT
+            || dat.len() <= HEADER_LEN || dat[0] != KIND_REQUEST {
+            return None;
+        }
+        let end = HEADER_LEN + 1 + dat[HEADER_LEN] as usize;
+        if dat.len() < end {
+            return None;
+        }
+        Some(Request {
+            source: data.get_source(),
+            id: u32::from_be_bytes([dat[1], dat[2], dat[3], dat[4]]),
+            method: String::from_utf8_lossy(&dat[HEADER_LEN + 1..end]).into_owned(),
+            payload: dat[end..].to_vec(),
+        })
+    }
+}
+
+fn packet(kind: u8, id: u32, body: &[u8]) -> rics::RICS_Data {
+    let mut dat = Vec::with_capacity(HEADER_LEN + body.len());
+    dat.push(kind);
+    dat.extend_from_slice(&id.to_be_bytes());
+    dat.extend_from_slice(body);
+    server::datagram_packet(RPC_PORT, dat)
+}
+
+/// Request packet, if the method name fits
+pub fn request_packet(id: u32, method: &str, payload: &[u8]) -> Option<rics::RICS_Data> {
+    if method.len() > 255 {
+        return None;
+    }
+    let mut body = vec![method.len() as u8];
+    body.extend_from_slice(method.as_bytes());
+    body.extend_from_slice(payload);
+    Some(packet(KIND_REQUEST, id, &body))
+}
+
+fn response_packet(id: u32, result: &Result<Vec<u8>, String>) -> rics::RICS_Data {
+    match result {
+        Ok(payload) => packet(KIND_RESPONSE, id, payload),
+        Err(e) => packet(KIND_ERROR, id, e.as_bytes()),
+    }
+}
+
+/// Result of the call `id`, if the packet answers it
+pub fn parse_response(data: &rics::RICS_Data, id: u32) -> Option<Result<Vec<u8>, RpcError>> {
+    let dat = data.get_data();
+    if data.get_field_type() != rics::RICS_Data_RICS_DataType::DATAGRAM || data.get_id() != RPC_PORT
+        || dat.len() < HEADER_LEN || u32::from_be_bytes([dat[1], dat[2], dat[3], dat[4]]) != id {
+        return None;
+    }
+    match dat[0] {
+        KIND_RESPONSE => Some(Ok(dat[HEADER_LEN..].to_vec())),
+        KIND_ERROR => Some(Err(RpcError::Remote(String::from_utf8_lossy(&dat[HEADER_LEN..]).into_owned()))),
+        _ => None,
+    }
+}
+
+/// Method handler, called with the source node and the payload of the request
+pub type Handler = Box<dyn FnMut(&mut RICSServer, i32, &[u8]) -> Result<Vec<u8>, String> + Send>;
+
+/// Methods of a node
+pub struct RpcServer {
+    handlers: HashMap<String, Handler>,
+    /// Last responses, by source node and correlation id
+    answered: VecDeque<((i32, u32), rics::RICS_Data)>,
+}
+
+impl RpcServer {
+    pub fn new() -> RpcServer {
+        RpcServer { handlers: HashMap::new(), answered: VecDeque::new() }
+    }
+
+    /// Add a method, replacing the handler of the same name
+    pub fn register(&mut self, method: impl Into<String>, handler: impl FnMut(&mut RICSServer, i32, &[u8]) -> Result<Vec<u8>, String> + Send + 'static) {
+        self.handlers.insert(method.into(), Box::new(handler));
+    }
+
+    /// Answer the request carried by a packet. Returns false if it isn't a request.
+    pub fn handle(&mut self, server: &mut RICSServer, data: &rics::RICS_Data) -> bool {
+        self.handle_with(server, data, |_, _, _, _| None)
+    }
+
+    /// Answer the request carried by a packet, calling `fallback` for the
+    /// methods without handler, which returns None if it doesn't know them either.
+    /// Returns false if the packet isn't a request.
+    pub fn handle_with(&mut self, server: &mut RICSServer, data: &rics::RICS_Data,
+                       fallback: impl FnOnce(&mut RICSServer, i32, &str, &[u8]) -> Option<Result<Vec<u8>, String>>) -> bool {
+        let request = match Request::parse(data) {
+            Some(request) => request,
+            None => return false,
+        };
+        let key = (request.source, request.id);
+        if let Some((_, response)) = self.answered.iter().find(|(x, _)| *x == key) {
+            debug!("Answering request {} of node {} again", request.id, request.source);
+            server.send_packet_to(response.clone(), request.source);
+            return true;
+        }
+        debug!("Call of {} from node {}", request.method, request.source);
+        let result = match self.handlers.get_mut(&request.method) {
+            Some(handler) => handler(server, request.source, &request.payload),
+            None => fallback(server, request.source, &request.method, &request.payload)
+                .unwrap_or_else(|| Err(format!("Unknown method {}", request.method))),
+        };
+        let response = response_packet(request.id, &result);
+        server.send_packet_to(response.clone(), request.source);
+        if self.answered.len() >= ANSWERED_MAX {
+            self.answered.pop_front();
+        }
+        self.answered.push_back((key, response));
+        true
+    }
+
+    /// Answer the requests received by the node, other packets are dropped
+    pub fn run(&mut self, server: &mut RICSServer) -> ! {
+        loop {
+            if let Some(data) = server.get_packet() {
+                self.handle(server, &data);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use protobuf::Message;
+
+    fn response(mut data: rics::RICS_Data) -> rics::RICS_Response {
+        data.set_source(3);
+        let mut resp = rics::RICS_Response::new();
+        resp.set_data(data);
+        resp
+    }
+
+    #[test]
+    #[cfg(target_family="unix")]
+    fn call_keeps_other_packets() {
+        let (client, mut peer) = std::os::unix::net::UnixStream::pair().unwrap();
+        let mut svr = RICSServer::new_from(client.try_clone().unwrap(), client);
+        std::thread::spawn(move || {
+            let mut input = peer.try_clone().unwrap();
+            let mut input = protobuf::CodedInputStream::new(&mut input);
+            let req = input.read_message::<rics::RICS_Request>().unwrap();
+            let request = Request::parse(req.get_data()).unwrap();
+            assert_eq!(req.get_data().get_target(), 3);
+            assert_eq!(request.method, "reverse");
+            let reversed = request.payload.iter().rev().cloned().collect();
+            for data in [server::datagram_packet(7, vec![1]), response_packet(request.id, &Ok(reversed))] {
+                response(data).write_length_delimited_to_writer(&mut peer).unwrap();
+            }
+        });
+
+        assert_eq!(svr.call(3, "reverse", &[1, 2, 3], Duration::from_secs(5)), Ok(vec![3, 2, 1]));
+        let data = svr.get_packet().unwrap();
+        assert_eq!(data.get_id(), 7);
+        assert_eq!(data.get_data(), &[1]);
+    }
+
+    #[test]
+    fn call_disconnected() {
+        let mut svr = RICSServer::new_from(std::io::empty(), std::io::sink());
+        let start = Instant::now();
+        assert_eq!(svr.call(3, "reverse", &[], Duration::from_secs(5)), Err(RpcError::Disconnected));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn packets() {
+        let data = request_packet(42, "reverse", &[1, 2]).unwrap();
+        let request = Request::parse(&data).unwrap();
+        assert_eq!((request.id, request.method.as_str(), request.payload.as_slice()), (42, "reverse", &[1, 2][..]));
+        assert!(request_packet(1, &"x".repeat(256), &[]).is_none());
+
+        assert_eq!(parse_response(&response_packet(42, &Ok(vec![5])), 42), Some(Ok(vec![5])));
+        assert_eq!(parse_response(&response_packet(42, &Err("failed".to_string())), 42), Some(Err(RpcError::Remote("failed".to_string()))));
+        assert_eq!(parse_response(&response_packet(42, &Ok(vec![5])), 43), None);
+        assert_eq!(parse_response(&data, 42), None);
+    }
+}
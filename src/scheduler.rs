@@ -0,0 +1,483 @@
+//! Cyclic packet transmission
+//!
+//! The scheduler thread owns a RICS node and sends each message at its own
+//! period, after an optional offset. Counters and checksums in the message
+//! data are updated before each transmission. Messages can be added,
+//! started, stopped and updated while the scheduler runs.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::dbc::{Dbc, Signal};
+use super::ecu::{toml_bytes, toml_int, toml_tables};
+use super::rics;
+use super::server::{self, RICSServer};
+
+/// Parse a duration such as `100ms`, `2s` or `500us`, plain numbers are milliseconds
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let value = s[..split].parse::<f64>().ok()?;
+    let seconds = match s[split..].trim() {
+        "" | "ms" => value / 1e3,
+        "s" => value,
+        "us" => value / 1e6,
+        _ => return None,
+    };
+    Some(Duration::from_secs_f64(seconds))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChecksumKind {
+    /// XOR of the covered bytes
+    Xor,
+    /// 8 bit sum of the covered bytes
+    Sum,
+    /// SAE J1850 CRC8, polynomial 0x1D
+    Crc8,
+}
+
+impl ChecksumKind {
+    pub fn from_name(name: &str) -> Option<ChecksumKind> {
+        Some(match name {
+            "xor" => ChecksumKind::Xor,
+            "sum" => ChecksumKind::Sum,
+            "crc8" => ChecksumKind::Crc8,
+            _ => return None,
+        })
+    }
+}
+
+/// Checksum byte computed over the other bytes of the message
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Checksum {
+    pub kind: ChecksumKind,
+    /// Position of the checksum byte
+    pub byte: usize,
+}
+
+impl Checksum {
+    pub fn compute(&self, dat: &[u8]) -> u8 {
+        let bytes = dat.iter().enumerate().filter(|(i, _)| *i != self.byte).map(|(_, x)| *x);
+        match self.kind {
+            ChecksumKind::Xor => bytes.fold(0, |acc, x| acc ^ x),
+            ChecksumKind::Sum => bytes.fold(0, |acc: u8, x| acc.wrapping_add(x)),
+            ChecksumKind::Crc8 => !bytes.fold(0xFF, |mut crc, x| {
+                crc ^= x;
+                for _ in 0..8 {
+                    crc = if crc & 0x80 != 0 { crc << 1 ^ 0x1D } else { crc << 1 };
+                }
+                crc
+            }),
+        }
+    }
+}
+
+/// Periodically sent packet
+#[derive(Clone, Debug)]
+pub struct CyclicMessage {
+    /// Packet sent, to its target if it has one
+    pub data: rics::RICS_Data,
+    pub period: Duration,
+    /// Delay of the first transmission after the message is started
+    pub offset: Duration,
+    /// Signal incremented at each transmission, wrapping at its size
+    pub counter: Option<Signal>,
+    pub checksum: Option<Checksum>,
+    /// Stop after this number of transmissions
+    pub count: Option<u64>,
+    /// Start the message as soon as it is added
+    pub enabled: bool,
+}
+
+impl CyclicMessage {
+    pub fn new(data: rics::RICS_Data, period: Duration) -> CyclicMessage {
+        CyclicMessage {
+            data: data,
+            period: period,
+            offset: Duration::from_secs(0),
+            counter: None,
+            checksum: None,
+            count: None,
+            enabled: true,
+        }
+    }
+}
+
+/// Function updating the data of a message before it is sent
+pub type Update = Box<dyn FnMut(&str, &mut Vec<u8>) + Send>;
+
+enum Command {
+    Add(String, CyclicMessage),
+    Remove(String),
+    Start(String),
+    Stop(String),
+    SetData(String, Vec<u8>),
+    SetPeriod(String, Duration),
+    /// Notify when the message is stopped
+    Wait(String, Sender<()>),
+    SetUpdate(Update),
+    Shutdown,
+}
+
+struct Job {
+    message: CyclicMessage,
+    /// Next transmission, None when stopped
+    next: Option<Instant>,
+    sent: u64,
+    counter: u64,
+    waiting: Vec<Sender<()>>,
+}
+
+impl Job {
+    fn stop(&mut self) {
+        self.next = None;
+        for waiting in self.waiting.drain(..) {
+            waiting.send(()).ok();
+        }
+    }
+}
+
+/// Cyclic transmission scheduler over a connected RICS node
+pub struct Scheduler {
+    commands: Sender<Command>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Scheduler {
+    /// The server must be connected, it is only used to send packets from now on
+    pub fn new(server: RICSServer) -> Scheduler {
+        let (commands, rx) = channel();
+        let thread = thread::spawn(move || run(server, rx));
+        Scheduler { commands: commands, thread: Some(thread) }
+    }
+
+    fn send(&self, command: Command) {
+        self.commands.send(command).expect("Scheduler thread stopped");
+    }
+
+    /// Add a message, replacing the one with the same name
+    pub fn add(&self, name: impl Into<String>, message: CyclicMessage) {
+        self.send(Command::Add(name.into(), message));
+    }
+
+    pub fn remove(&self, name: impl Into<String>) {
+        self.send(Command::Remove(name.into()));
+    }
+
+    /// Start a message, restarting its offset and transmission count
+    pub fn start(&self, name: impl Into<String>) {
+        self.send(Command::Start(name.into()));
+    }
+
+    pub fn stop(&self, name: impl Into<String>) {
+        self.send(Command::Stop(name.into()));
+    }
+
+    /// Change the data of a message from its next transmission
+    pub fn set_data(&self, name: impl Into<String>, dat: Vec<u8>) {
+        self.send(Command::SetData(name.into(), dat));
+    }
+
+    pub fn set_period(&self, name: impl Into<String>, period: Duration) {
+        self.send(Command::SetPeriod(name.into(), period));
+    }
+
+    /// Block until a message is stopped, or has sent its count of transmissions
+    pub fn wait(&self, name: impl Into<String>) {
+        let (tx, rx) = channel();
+        self.send(Command::Wait(name.into(), tx));
+        rx.recv().ok();
+    }
+
+    /// Call `update` with the name and data of each message before it is
+    /// sent, before its counter and checksum are updated
+    pub fn set_update(&self, update: impl FnMut(&str, &mut Vec<u8>) + Send + 'static) {
+        self.send(Command::SetUpdate(Box::new(update)));
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.commands.send(Command::Shutdown).ok();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+fn run(mut server: RICSServer, commands: Receiver<Command>) {
+    let mut jobs: HashMap<String, Job> = HashMap::new();
+    let mut update: Option<Update> = None;
+    loop {
+        let next = jobs.values().filter_map(|x| x.next).min();
+        let command = match next {
+            Some(next) => commands.recv_timeout(next.saturating_duration_since(Instant::now())),
+            None => commands.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match command {
+            Ok(Command::Add(name, message)) => {
+                let next = if message.enabled { Some(Instant::now() + message.offset) } else { None };
+                if let Some(mut job) = jobs.remove(&name) {
+                    job.stop();
+                }
+                jobs.insert(name, Job { message: message, next: next, sent: 0, counter: 0, waiting: vec![] });
+            },
+            Ok(Command::Remove(name)) => {
+                if let Some(mut job) = jobs.remove(&name) {
+                    job.stop();
+                }
+            },
+            Ok(Command::Start(name)) => match jobs.get_mut(&name) {
+                Some(job) => {
+                    job.next = Some(Instant::now() + job.message.offset);
+                    job.sent = 0;
+                },
+                None => warn!("Unknown cyclic message {}", name),
+            },
+            Ok(Command::Stop(name)) => match jobs.get_mut(&name) {
+                Some(job) => job.stop(),
+                None => warn!("Unknown cyclic message {}", name),
+            },
+            Ok(Command::SetData(name, dat)) => match jobs.get_mut(&name) {
+                Some(job) => job.message.data.set_data(dat),
+                None => warn!("Unknown cyclic message {}", name),
+            },
+            Ok(Command::SetPeriod(name, period)) => match jobs.get_mut(&name) {
+                Some(job) => {
+                    job.message.period = period;
+                    job.next = job.next.map(|_| Instant::now() + period);
+                },
+                None => warn!("Unknown cyclic message {}", name),
+            },
+            Ok(Command::Wait(name, tx)) => match jobs.get_mut(&name) {
+                Some(job) if job.next.is_some() => job.waiting.push(tx),
+                _ => { tx.send(()).ok(); },
+            },
+            Ok(Command::SetUpdate(f)) => update = Some(f),
+            Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => return,
+            Err(RecvTimeoutError::Timeout) => (),
+        }
+
+        let now = Instant::now();
+        for (name, job) in jobs.iter_mut() {
+            match job.next {
+                Some(next) if next <= now => (),
+                _ => continue,
+            }
+            if job.message.count.map(|x| job.sent >= x).unwrap_or(false) {
+                job.stop();
+                continue;
+            }
+            let mut data = job.message.data.clone();
+            let mut dat = data.get_data().to_vec();
+            if let Some(update) = update.as_mut() {
+                update(name, &mut dat);
+            }
+            if let Some(counter) = &job.message.counter {
+                counter.set_raw(&mut dat, job.counter);
+                job.counter = if counter.size >= 64 { job.counter.wrapping_add(1) } else { (job.counter + 1) % (1 << counter.size) };
+            }
+            if let Some(checksum) = &job.message.checksum {
+                if checksum.byte < dat.len() {
+                    dat[checksum.byte] = checksum.compute(&dat);
+                }
+            }
+            data.set_data(dat);
+            if data.has_target() {
+                let target = data.get_target();
+                server.send_packet_to(data, target);
+            } else {
+                server.send_packet(data);
+            }
+
+            job.sent += 1;
+            if job.message.count.map(|x| job.sent >= x).unwrap_or(false) {
+                job.stop();
+                continue;
+            }
+            // Keep the phase, unless too late to catch up
+            let next = job.next.unwrap() + job.message.period;
+            job.next = Some(if next < now { now + job.message.period } else { next });
+        }
+    }
+}
+
+/// Message of a schedule file
+#[derive(Clone, Debug)]
+pub struct ScheduleEntry {
+    pub name: String,
+    pub message: CyclicMessage,
+    /// DBC message name and signal values the data is encoded from
+    pub signals: Option<(String, Vec<(String, f64)>)>,
+}
+
+/// Cyclic messages of a TOML schedule file
+#[derive(Clone, Debug, Default)]
+pub struct Schedule {
+    pub dbc: Option<Dbc>,
+    pub entries: Vec<ScheduleEntry>,
+}
+
+/// Duration given in milliseconds or as a string such as "20ms"
+fn toml_duration(table: &toml::Value, key: &str) -> Result<Option<Duration>, String> {
+    match table.get(key) {
+        Some(toml::Value::Integer(ms)) if *ms >= 0 => Ok(Some(Duration::from_millis(*ms as u64))),
+        Some(toml::Value::String(s)) => parse_duration(s).map(Some).ok_or_else(|| format!("Invalid {} {}", key, s)),
+        Some(v) => Err(format!("Invalid {} {}", key, v)),
+        None => Ok(None),
+    }
+}
+
+fn toml_bool(table: &toml::Value, key: &str) -> Result<Option<bool>, String> {
+    match table.get(key) {
+        Some(v) => v.as_bool().map(Some).ok_or_else(|| format!("{} must be a boolean", key)),
+        None => Ok(None),
+    }
+}
+
+impl Schedule {
+    /// Parse a schedule, the DBC file path is relative to `dir`
+    pub fn parse(content: &str, dir: &std::path::Path) -> Result<Schedule, String> {
+        let value = content.parse::<toml::Value>().map_err(|e| e.to_string())?;
+        let dbc = match value.get("dbc").and_then(|x| x.as_str()) {
+            Some(path) => Some(Dbc::load(&dir.join(path).to_string_lossy())?),
+            None => None,
+        };
+        let mut entries = vec![];
+        for (i, table) in toml_tables(&value, "message").into_iter().enumerate() {
+            let entry = Schedule::parse_entry(table, dbc.as_ref()).map_err(|e| format!("Message {}: {}", i + 1, e))?;
+            if entries.iter().any(|x: &ScheduleEntry| x.name == entry.name) {
+                return Err(format!("Duplicate message name {}", entry.name));
+            }
+            entries.push(entry);
+        }
+        Ok(Schedule { dbc: dbc, entries: entries })
+    }
+
+    fn parse_entry(table: &toml::Value, dbc: Option<&Dbc>) -> Result<ScheduleEntry, String> {
+        let period = toml_duration(table, "period")?.ok_or("Missing period")?;
+        if period == Duration::from_secs(0) {
+            return Err("The period can't be zero".to_string());
+        }
+
+        let (data, signals, message) = if let Some(name) = table.get("message").and_then(|x| x.as_str()) {
+            let dbc = dbc.ok_or("DBC messages need a dbc file")?;
+            let message = dbc.message_by_name(name).ok_or_else(|| format!("Unknown message {}", name))?;
+            let mut values = vec![];
+            if let Some(signals) = table.get("signals").and_then(|x| x.as_table()) {
+                for (signal_name, value) in signals {
+                    let signal = message.signal(signal_name).ok_or_else(|| format!("Unknown signal {}", signal_name))?;
+                    let physical = match value {
+                        toml::Value::Integer(x) => *x as f64,
+                        toml::Value::Float(x) => *x,
+                        toml::Value::String(label) => signal.label_value(label).ok_or_else(|| format!("Unknown {} value {}", signal_name, label))?,
+                        _ => return Err(format!("Invalid value for {}", signal_name)),
+                    };
+                    values.push((signal_name.clone(), physical));
+                }
+            }
+            let refs: Vec<(&str, f64)> = values.iter().map(|(n, v)| (n.as_str(), *v)).collect();
+            (dbc.encode(name, &refs)?, Some((name.to_string(), values)), Some(message))
+        } else {
+            let id = toml_int(table, "id")?.ok_or("Missing id or message")? as i32;
+            let dat = table.get("data").map(toml_bytes).unwrap_or(Ok(vec![]))?;
+            if dat.len() > server::CANFD_MAX_DLEN {
+                return Err(format!("CAN messages can't carry more than {} bytes", server::CANFD_MAX_DLEN));
+            }
+            let flags = server::CanFlags {
+                extended: toml_bool(table, "extended")?.unwrap_or(false) || id > server::CAN_SFF_MAX,
+                fd: toml_bool(table, "fd")?.unwrap_or(false) || dat.len() > server::CAN_MAX_DLEN,
+                brs: toml_bool(table, "brs")?.unwrap_or(false),
+                ..Default::default()
+            };
+            (server::can_frame(id, dat, flags), None, None)
+        };
+
+        let counter = match table.get("counter") {
+            Some(toml::Value::String(name)) => {
+                let message = message.ok_or("Counter signals need a DBC message")?;
+                Some(message.signal(name).ok_or_else(|| format!("Unknown signal {}", name))?.clone())
+            },
+            Some(counter) => {
+                let start = toml_int(counter, "start")?.ok_or("Missing counter start")? as u32;
+                let size = toml_int(counter, "size")?.unwrap_or(4) as u32;
+                Some(Signal::new("counter", start, size, !toml_bool(counter, "big_endian")?.unwrap_or(false)))
+            },
+            None => None,
+        };
+        let checksum = match table.get("checksum") {
+            Some(checksum) => {
+                let kind = checksum.get("type").and_then(|x| x.as_str()).unwrap_or("xor");
+                Some(Checksum {
+                    kind: ChecksumKind::from_name(kind).ok_or_else(|| format!("Unknown checksum type {}", kind))?,
+                    byte: toml_int(checksum, "byte")?.ok_or("Missing checksum byte")? as usize,
+                })
+            },
+            None => None,
+        };
+
+        let name = table.get("name").and_then(|x| x.as_str()).map(|x| x.to_string())
+            .or_else(|| signals.as_ref().map(|(x, _)| x.clone()))
+            .unwrap_or_else(|| format!("{:x}", data.get_id()));
+        Ok(ScheduleEntry {
+            name: name,
+            message: CyclicMessage {
+                data: data,
+                period: period,
+                offset: toml_duration(table, "offset")?.unwrap_or(Duration::from_secs(0)),
+                counter: counter,
+                checksum: checksum,
+                count: match toml_int(table, "count")? {
+                    Some(count) if count <= 0 => return Err(format!("Invalid count {}", count)),
+                    count => count.map(|x| x as u64),
+                },
+                enabled: toml_bool(table, "enabled")?.unwrap_or(true),
+            },
+            signals: signals,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Schedule, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+        let dir = std::path::Path::new(path).parent().unwrap_or(std::path::Path::new("."));
+        Schedule::parse(&content, dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::testutil::Output;
+
+    /// Number of packets sent by a message with a transmission count
+    fn sent(count: u64) -> usize {
+        let output = Output::default();
+        let scheduler = Scheduler::new(RICSServer::new_from(std::io::empty(), output.clone()));
+        let mut message = CyclicMessage::new(server::can_packet(0x123, vec![1, 2]), Duration::from_millis(1));
+        message.count = Some(count);
+        scheduler.add("send", message);
+        scheduler.wait("send");
+        drop(scheduler);
+
+        let requests = output.requests();
+        assert!(requests.iter().all(|x| x.has_data()));
+        requests.len()
+    }
+
+    #[test]
+    fn transmission_count() {
+        assert_eq!(sent(3), 3);
+        assert_eq!(sent(1), 1);
+        assert_eq!(sent(0), 0);
+    }
+
+    #[test]
+    fn schedule_count() {
+        let parse = |count: i64| Schedule::parse(&format!("[[message]]\nid = 0x10\ndata = \"01\"\nperiod = \"10ms\"\ncount = {}\n", count), std::path::Path::new("."));
+        assert_eq!(parse(2).unwrap().entries[0].message.count, Some(2));
+        assert!(parse(0).is_err());
+    }
+}
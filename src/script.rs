@@ -19,6 +19,12 @@ pub trait ScriptingInterface {
     /// Can message rx callback
     fn can_rx(&self, _svr: &mut RICSServer, _id: u32, _data: Vec<u8>) -> bool { true }
 
+    /// Datagram rx callback, with the source node and the port it was sent to
+    fn datagram_rx(&self, _svr: &mut RICSServer, _source: i32, _port: i32, _data: Vec<u8>) -> bool { true }
+
+    /// RPC method call from the source node, None if the method is unknown
+    fn rpc_call(&self, _svr: &mut RICSServer, _source: i32, _method: &str, _payload: Vec<u8>) -> Option<Result<Vec<u8>, String>> { None }
+
     /// Generic slave update loop called as often as possible.
     /// This function should return as soon as possible if low latency is required.
     /// This function is only called when there is no more callbacks to call,
@@ -50,15 +56,26 @@ type DynRawSymbol<T> = libloading::os::windows::Symbol<T>;
 ///
 /// bool rics_can_callback(uint32_t id, usize_t len, uint8_t* dat)
 /// Called when a can message is detected by the program
+///
+/// bool rics_datagram_callback(int32_t source, int32_t port, usize_t len, uint8_t* dat)
+/// Optional, called when a datagram is received by the node
+///
+/// int32_t rics_rpc_callback(int32_t source, const char* method, usize_t len, const uint8_t* payload, usize_t response_max, uint8_t* response)
+/// Optional, writes the response of an RPC method and returns its length,
+/// -1 if the method is unknown or another negative value on failure
 pub struct DynlibScript {
     /// Needed to keep library alive while calling
     lib: Library,
     // /// Called after before init
-    // on_init: *const unsafe extern fn() -> i32,
+    // on_init: *const unsafe extern "C" fn() -> i32,
     // /// Called after system start
-    // on_start: *const unsafe extern fn(i32) -> i32,
+    // on_start: *const unsafe extern "C" fn(i32) -> i32,
     /// Called whenever a can message happen
-    on_can_msg: Option<DynRawSymbol<unsafe extern fn(u32, usize, *const u8) -> i32>>,
+    on_can_msg: Option<DynRawSymbol<unsafe extern "C" fn(u32, usize, *const u8) -> i32>>,
+    /// Called whenever a datagram is received
+    on_datagram: Option<DynRawSymbol<unsafe extern "C" fn(i32, i32, usize, *const u8) -> i32>>,
+    /// Called whenever an RPC method is called
+    on_rpc: Option<DynRawSymbol<unsafe extern "C" fn(i32, *const std::os::raw::c_char, usize, *const u8, usize, *mut u8) -> i32>>,
     // TODO Other callbacks
 }
 
@@ -68,22 +85,28 @@ impl DynlibScript {
         let lib = Library::new(path).unwrap();
 
             //let (fn_init, fn_start, fn_can_msg) = {
-            //    let func_init: Symbol<unsafe extern fn() -> i32> = lib.get(b"rics_init").unwrap();
-            //    let func_start: Symbol<unsafe extern fn(i32) -> i32> = lib.get(b"rics_start").unwrap();
-            //    let func_can_msg: Symbol<unsafe extern fn(u32, usize, *const u8) -> i32> = lib.get(b"rics_can_callback").unwrap();
+            //    let func_init: Symbol<unsafe extern "C" fn() -> i32> = lib.get(b"rics_init").unwrap();
+            //    let func_start: Symbol<unsafe extern "C" fn(i32) -> i32> = lib.get(b"rics_start").unwrap();
+            //    let func_can_msg: Symbol<unsafe extern "C" fn(u32, usize, *const u8) -> i32> = lib.get(b"rics_can_callback").unwrap();
             //    (&*func_init, &*func_start, &*func_can_msg)
             //};
 
             DynlibScript {
                 lib: lib,
                 on_can_msg: None,
+                on_datagram: None,
+                on_rpc: None,
             }
     }
 
     pub fn load(mut self) -> Self {
         unsafe {
-            let symbol: Symbol<unsafe extern fn(u32, usize, *const u8) -> i32> = self.lib.get(b"rics_can_callback").unwrap();
+            let symbol: Symbol<unsafe extern "C" fn(u32, usize, *const u8) -> i32> = self.lib.get(b"rics_can_callback").unwrap();
             self.on_can_msg = Some(symbol.into_raw());
+            let symbol: Option<Symbol<unsafe extern "C" fn(i32, i32, usize, *const u8) -> i32>> = self.lib.get(b"rics_datagram_callback").ok();
+            self.on_datagram = symbol.map(|x| x.into_raw());
+            let symbol: Option<Symbol<unsafe extern "C" fn(i32, *const std::os::raw::c_char, usize, *const u8, usize, *mut u8) -> i32>> = self.lib.get(b"rics_rpc_callback").ok();
+            self.on_rpc = symbol.map(|x| x.into_raw());
         }
         self
     }
@@ -100,6 +123,29 @@ impl ScriptingInterface for DynlibScript {
         let len = data.len();
         0 != unsafe {(&*self.on_can_msg.as_ref().unwrap())(id, len, data.as_ptr())}
     }
+    fn datagram_rx(&self, _svr: &mut RICSServer, source: i32, port: i32, data: Vec<u8>) -> bool {
+        match &self.on_datagram {
+            Some(callback) => 0 != unsafe {(**callback)(source, port, data.len(), data.as_ptr())},
+            None => true,
+        }
+    }
+    fn rpc_call(&self, _svr: &mut RICSServer, source: i32, method: &str, payload: Vec<u8>) -> Option<Result<Vec<u8>, String>> {
+        let callback = self.on_rpc.as_ref()?;
+        let name = match std::ffi::CString::new(method) {
+            Ok(name) => name,
+            Err(_) => return Some(Err(format!("Invalid method name {}", method))),
+        };
+        let mut response = vec![0u8; RPC_RESPONSE_MAX];
+        let len = unsafe {(**callback)(source, name.as_ptr(), payload.len(), payload.as_ptr(), response.len(), response.as_mut_ptr())};
+        match len {
+            -1 => None,
+            len if len < 0 || len as usize > RPC_RESPONSE_MAX => Some(Err(format!("{} failed", method))),
+            len => {
+                response.truncate(len as usize);
+                Some(Ok(response))
+            },
+        }
+    }
 
     /// No update function is implemented since a thread can simply be created in the start phase.
     fn update(&self, _svr: &mut RICSServer) -> bool {
@@ -125,6 +171,14 @@ impl rlua::UserData for ServerBox {
             Ok(())
         });
 
+        methods.add_method_mut("send_datagram", |_, this, (port, dat, dest): (i32, Vec<u8>, Option<i32>)| {
+            match dest {
+                Some(target) => unsafe {(*this.svr).send_packet_to(super::server::datagram_packet(port, dat), target); }
+                None => unsafe {(*this.svr).send_packet(super::server::datagram_packet(port, dat)); }
+            }
+            Ok(())
+        });
+
         methods.add_method("get_time_ms", |_, this, () | {
             Ok( std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).expect("Invalid current time").as_millis() )
         });
@@ -142,6 +196,8 @@ pub struct LuaScript {
     on_init: rlua::RegistryKey,
     on_start: rlua::RegistryKey,
     on_can_msg: rlua::RegistryKey,
+    on_datagram: Option<rlua::RegistryKey>,
+    on_rpc: Option<rlua::RegistryKey>,
     on_update: rlua::RegistryKey
 }
 
@@ -152,7 +208,7 @@ impl LuaScript {
 
         let content = std::fs::read_to_string(path).unwrap();
 
-        let (func_init, func_start, func_can_msg, func_update) = lua.context(move|ctx| {
+        let (func_init, func_start, func_can_msg, func_datagram, func_rpc, func_update) = lua.context(move|ctx| {
             if let Err(e) = ctx.load(&content).exec() {
                 error!("Lua error loading file: {}", e);
             }
@@ -173,6 +229,11 @@ impl LuaScript {
                 Err(e) => { error!("Lua error rics_can_callback undefined: {}", e);
                             ctx.create_function(|_, _:(u32,Vec<u8>)|Ok(true)).unwrap() }
             }).unwrap();
+            // Optional, most plugins only use CAN
+            let func_datagram = ctx.globals().get::<_, rlua::Function>("rics_datagram_callback").ok()
+                .map(|f| ctx.create_registry_value(f).unwrap());
+            let func_rpc = ctx.globals().get::<_, rlua::Function>("rics_rpc_callback").ok()
+                .map(|f| ctx.create_registry_value(f).unwrap());
             let func_update = ctx.create_registry_value(match ctx.globals().get("rics_update") {
                 Ok(f) => f,
                 Err(e) => { error!("Lua error rics_update undefined: {}", e);
@@ -185,7 +246,7 @@ impl LuaScript {
 
             ////////
 
-            (func_init, func_start, func_can_msg, func_update)
+            (func_init, func_start, func_can_msg, func_datagram, func_rpc, func_update)
         });
 
 
@@ -194,6 +255,8 @@ impl LuaScript {
             on_init: func_init,
             on_start: func_start,
             on_can_msg: func_can_msg,
+            on_datagram: func_datagram,
+            on_rpc: func_rpc,
             on_update: func_update,
         }
     }
@@ -223,6 +286,25 @@ impl ScriptingInterface for LuaScript {
         }})
     }
 
+    fn datagram_rx(&self, svr: &mut RICSServer, source: i32, port: i32, data: Vec<u8>) -> bool {
+        let callback = match &self.on_datagram {
+            Some(callback) => callback,
+            None => return true,
+        };
+        self.lua.context(|ctx| { match ctx.registry_value::<rlua::Function>(callback).unwrap().call((ServerBox {svr:svr as *mut _}, source, port, data)) {
+            Ok(o) => o,
+            Err(e) => { error!("Lua error datagram_callback: {}", e); false },
+        }})
+    }
+
+    fn rpc_call(&self, svr: &mut RICSServer, source: i32, method: &str, payload: Vec<u8>) -> Option<Result<Vec<u8>, String>> {
+        let callback = self.on_rpc.as_ref()?;
+        self.lua.context(|ctx| { match ctx.registry_value::<rlua::Function>(callback).unwrap().call::<_, Option<Vec<u8>>>((ServerBox {svr:svr as *mut _}, source, method, payload)) {
+            Ok(o) => o.map(Ok),
+            Err(e) => { error!("Lua error rpc_callback: {}", e); Some(Err(e.to_string())) },
+        }})
+    }
+
     fn update(&self, svr: &mut RICSServer) -> bool {
 
         self.lua.context(|ctx| { match ctx.registry_value::<rlua::Function>(&self.on_update).unwrap().call(ServerBox{ svr: svr as *mut RICSServer }) {
@@ -232,6 +314,132 @@ impl ScriptingInterface for LuaScript {
     }
 }
 
+/// Maximum response length of RPC methods
+const RPC_RESPONSE_MAX: usize = 4096;
+
+/// Maximum key length returned by key functions
+const UDS_KEY_MAX: usize = 256;
+
+/// UDS security access key function of a dynamic library
+///
+/// int32_t rics_uds_key(uint8_t level, usize_t seed_len, const uint8_t* seed, usize_t key_max, uint8_t* key)
+/// Writes the key of the seed, returns its length or a negative value on failure
+pub struct DynlibKey {
+    lib: Library,
+}
+
+impl DynlibKey {
+    pub fn new(path: String) -> Result<DynlibKey, String> {
+        trace!("Loading UDS key function from {}", path.clone());
+        let lib = Library::new(&path).map_err(|e| format!("Cannot load {}: {}", path, e))?;
+        Ok(DynlibKey { lib: lib })
+    }
+
+    pub fn key(&self, level: u8, seed: &[u8]) -> Option<Vec<u8>> {
+        let mut key = vec![0u8; UDS_KEY_MAX];
+        let len = unsafe {
+            let func: Symbol<unsafe extern "C" fn(u8, usize, *const u8, usize, *mut u8) -> i32> = self.lib.get(b"rics_uds_key").ok()?;
+            func(level, seed.len(), seed.as_ptr(), key.len(), key.as_mut_ptr())
+        };
+        if len < 0 || len as usize > UDS_KEY_MAX {
+            error!("rics_uds_key failed for level {}", level);
+            return None;
+        }
+        key.truncate(len as usize);
+        Some(key)
+    }
+}
+
+/// UDS functions of a Lua script
+///
+/// function rics_uds_key(level, seed)
+/// Returns the key of the seed as a table of bytes
+///
+/// function rics_uds_request(request, session)
+/// Returns the response of a simulated ECU as a table of bytes, an empty
+/// table to send nothing, or nil to let the ECU configuration answer
+#[cfg(feature="pluginlua")]
+pub struct LuaUds {
+    lua: rlua::Lua,
+}
+
+#[cfg(feature="pluginlua")]
+impl LuaUds {
+    pub fn new(path: String) -> LuaUds {
+        let lua = rlua::Lua::new();
+        let content = std::fs::read_to_string(path).unwrap();
+        lua.context(|ctx| {
+            if let Err(e) = ctx.load(&content).exec() {
+                error!("Lua error loading file: {}", e);
+            }
+        });
+        LuaUds { lua: lua }
+    }
+
+    pub fn key(&self, level: u8, seed: &[u8]) -> Option<Vec<u8>> {
+        self.lua.context(|ctx| {
+            let func: rlua::Function = match ctx.globals().get("rics_uds_key") {
+                Ok(f) => f,
+                Err(e) => { error!("Lua error rics_uds_key undefined: {}", e); return None; },
+            };
+            match func.call::<_, Vec<u8>>((level, seed.to_vec())) {
+                Ok(key) => Some(key),
+                Err(e) => { error!("Lua error rics_uds_key: {}", e); None },
+            }
+        })
+    }
+
+    pub fn request(&self, request: &[u8], session: u8) -> Option<Vec<u8>> {
+        self.lua.context(|ctx| {
+            let func: rlua::Function = match ctx.globals().get("rics_uds_request") {
+                Ok(f) => f,
+                Err(_) => return None,
+            };
+            match func.call::<_, Option<Vec<u8>>>((request.to_vec(), session)) {
+                Ok(resp) => resp,
+                Err(e) => { error!("Lua error rics_uds_request: {}", e); None },
+            }
+        })
+    }
+}
+
+/// Lua script overriding the signals of a rest-bus simulation
+///
+/// function rics_restbus_update(message)
+/// Called before each transmission of a simulated message, returns a table
+/// of signal names and physical values for this transmission, or nil
+#[cfg(feature="pluginlua")]
+pub struct LuaRestbus {
+    lua: rlua::Lua,
+}
+
+#[cfg(feature="pluginlua")]
+impl LuaRestbus {
+    pub fn new(path: String) -> LuaRestbus {
+        let lua = rlua::Lua::new();
+        let content = std::fs::read_to_string(path).unwrap();
+        lua.context(|ctx| {
+            if let Err(e) = ctx.load(&content).exec() {
+                error!("Lua error loading file: {}", e);
+            }
+        });
+        LuaRestbus { lua: lua }
+    }
+
+    pub fn update(&self, message: &str) -> Vec<(String, f64)> {
+        self.lua.context(|ctx| {
+            let func: rlua::Function = match ctx.globals().get("rics_restbus_update") {
+                Ok(f) => f,
+                Err(_) => return vec![],
+            };
+            match func.call::<_, Option<std::collections::HashMap<String, f64>>>(message) {
+                Ok(values) => values.unwrap_or_default().into_iter().collect(),
+                Err(e) => { error!("Lua error rics_restbus_update: {}", e); vec![] },
+            }
+        })
+    }
+}
+
 pub struct NoEngine;
 impl ScriptingInterface for NoEngine {}
 
@@ -1,21 +1,30 @@
 //! Server interaction functions
 
-use std::io::{Read, Write};
-use std::collections::HashMap;
-use protobuf::{Message, CodedInputStream};
+use std::io::{BufReader, ErrorKind, Read, Write};
+use std::collections::{HashMap, VecDeque};
+use protobuf::{Message, CodedInputStream, ProtobufError};
 #[cfg(target_family="unix")]
 use std::os::unix::net::{UnixStream};
 use std::net::TcpStream;
-use std::time::Duration;
-use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
 use super::rics;
+use super::rpc;
 
 #[cfg(target_family="windows")]
 type UnixStream = TcpStream;
 
+/// Read timeout of the server sockets
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
 pub struct RICSServer {
-    input: Box<dyn Read + Sync + Send>,
+    /// Buffered so that messages received in bursts are not lost between reads
+    input: BufReader<Box<dyn Read + Sync + Send>>,
     socket: Box<dyn Write + Sync + Send>,
+    /// Messages read by a reader thread, once a timed read needed one
+    responses: Option<Receiver<rics::RICS_Response>>,
+    /// Messages received while waiting for an RPC response, read first
+    pending: VecDeque<rics::RICS_Response>,
     //input: CodedInputStream<'a>,
     //output: CodedOutputStream<'a>,
     node_names: HashMap<i32, String>,
@@ -47,12 +56,77 @@ impl NodeName for i32 {
     }
 }
 
+#[derive(Clone)]
 pub enum ConnectTo {
     Default,
     Unix(String),
     Tcp(String),
 }
 
+/// Packets delivered to a node by the host regardless of routes
+#[derive(Clone, Debug, PartialEq)]
+pub enum Subscription {
+    /// CAN ids, from the first to the second included
+    Can(i32, i32),
+    /// DATAGRAM ports, from the first to the second included
+    Datagram(i32, i32),
+    /// Packets published on a topic
+    Topic(String),
+}
+
+impl Subscription {
+    /// Parse `can:ID[-ID]`, `datagram:PORT[-PORT]` or `topic:NAME`, ids being decimal or 0x prefixed hexadecimal
+    pub fn parse(s: &str) -> Option<Subscription> {
+        let parse_id = |s: &str| if s.starts_with("0x") || s.starts_with("0X") {
+            i32::from_str_radix(&s[2..], 16).ok()
+        } else {
+            s.parse::<i32>().ok()
+        };
+        let range = |s: &str| match s.find('-') {
+            Some(pos) => Some((parse_id(&s[..pos])?, parse_id(&s[pos+1..])?)),
+            None => parse_id(s).map(|x| (x, x)),
+        };
+        let pos = s.find(':')?;
+        match &s[..pos] {
+            "can" => range(&s[pos+1..]).map(|(min, max)| Subscription::Can(min, max)),
+            "datagram" => range(&s[pos+1..]).map(|(min, max)| Subscription::Datagram(min, max)),
+            "topic" => Some(Subscription::Topic(s[pos+1..].to_string())),
+            _ => None,
+        }
+    }
+
+    fn to_message(&self) -> rics::RICS_Subscription {
+        let mut msg = rics::RICS_Subscription::new();
+        match self {
+            Subscription::Can(min, max) => {
+                msg.set_field_type(rics::RICS_Subscription_RICS_SubscriptionType::CAN);
+                msg.set_id_min(*min);
+                msg.set_id_max(*max);
+            },
+            Subscription::Datagram(min, max) => {
+                msg.set_field_type(rics::RICS_Subscription_RICS_SubscriptionType::DATAGRAM);
+                msg.set_id_min(*min);
+                msg.set_id_max(*max);
+            },
+            Subscription::Topic(topic) => {
+                msg.set_field_type(rics::RICS_Subscription_RICS_SubscriptionType::TOPIC);
+                msg.set_topic(topic.clone());
+            },
+        }
+        msg
+    }
+}
+
+/// Packets accepted by a node, checked by the host. Empty lists accept everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReceiveFilter {
+    /// Id and mask pairs, an id matching when `id & mask` is the same for both
+    pub ids: Vec<(i32, i32)>,
+    pub types: Vec<rics::RICS_Data_RICS_DataType>,
+    /// Source nodes
+    pub sources: Vec<i32>,
+}
+
 impl RICSServer {
 
     /// Returns the default Unix domain connection type
@@ -94,18 +168,18 @@ impl RICSServer {
        let socket = RICSServer::try_default_socket()?;
 
        Ok(RICSServer {
-           input: Box::new(socket.try_clone()?),
+           input: BufReader::new(Box::new(socket.try_clone()?)),
            socket: Box::new(socket),
+           responses: None,
+           pending: VecDeque::new(),
            node_names: HashMap::new(),
            node: 0,
        })
     }
 
-    /// CPS server creation
-    pub fn with_server<T, F>(ct: ConnectTo, f: F) -> T
-    where F: FnOnce(RICSServer) -> T {
-        // Build server
-        let server = match ct {
+    /// Open a server connection of the given type
+    pub fn open(ct: ConnectTo) -> RICSServer {
+        match ct {
             ConnectTo::Default => RICSServer::new().expect("Failed to connect to server"),
             ConnectTo::Unix(path) => {
                 if cfg!(target_family="unix") {
@@ -123,16 +197,22 @@ impl RICSServer {
                 socket.set_write_timeout(Some(Duration::new(1,0))).expect("Can't change socket param");
                 RICSServer::new_from(socket.try_clone().unwrap(), socket)
             }
-        };
+        }
+    }
 
-        f(server)
+    /// CPS server creation
+    pub fn with_server<T, F>(ct: ConnectTo, f: F) -> T
+    where F: FnOnce(RICSServer) -> T {
+        f(RICSServer::open(ct))
     }
 
     /// New server connection using custom sockets
     pub fn new_from(a: impl 'static + Read + Send + Sync, b: impl 'static + Write + Sync + Send) -> RICSServer {
         RICSServer {
-            input: Box::new(a),
+            input: BufReader::new(Box::new(a)),
             socket: Box::new(b),
+            responses: None,
+            pending: VecDeque::new(),
             node_names: HashMap::new(),
             node: 0,
         }
@@ -154,10 +234,8 @@ impl RICSServer {
         msg.set_query(rics::RICS_Request_RICS_Query::WHO_AM_I);
         msg.write_length_delimited_to_writer(&mut self.socket).expect("WHO_AM_I query message fail");
 
-        let mut input = CodedInputStream::new(&mut self.input);
-
         trace!("Waiting for server response");
-        self.node = match input.read_message::<rics::RICS_Response>() {
+        self.node = match self.read_response(READ_TIMEOUT) {
             Ok(resp) => resp.get_node(),
             Err(err) => { warn!("WHO_AM_I bas response: {}", err); 0 },
         };
@@ -165,6 +243,15 @@ impl RICSServer {
         self.node
     }
 
+    /// Change the name of the current node
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        debug!("Setting node name to {}", name);
+        let mut msg = rics::RICS_Request::new();
+        msg.set_set_name(name);
+        msg.write_length_delimited_to_writer(&mut self.socket).expect("SET_NAME fail");
+    }
+
     /// Sets the CAN drop rate for the server
     /// A value of 0 means all messages are forwarded,
     /// while a value of 1 means that no messages are.
@@ -189,6 +276,62 @@ impl RICSServer {
         msg.write_length_delimited_to_writer(&mut self.socket).expect("SET/CLEAR_FLAG_CAN_BROADCAST fail");
     }
 
+    /// Enables the CAN error confinement emulation of the server.
+    /// Nodes then have transmit and receive error counters, unacknowledged
+    /// frames and injected faults raise them, and bus-off nodes can't transmit.
+    ///
+    /// The server does not send a confirmation.
+    pub fn set_can_errors(&mut self, v: bool) {
+        debug!("Changing can error emulation flag to {}", v);
+
+        let mut msg = rics::RICS_Request::new();
+        msg.set_query(if v { rics::RICS_Request_RICS_Query::SET_FLAG_CAN_ERRORS } else { rics::RICS_Request_RICS_Query::CLEAR_FLAG_CAN_ERRORS });
+        msg.write_length_delimited_to_writer(&mut self.socket).expect("SET/CLEAR_FLAG_CAN_ERRORS fail");
+    }
+
+    /// Inject CAN errors on a node. Each transmit error raises its TEC by 8,
+    /// each receive error raises its REC by 1.
+    pub fn inject_can_fault(&mut self, node: i32, tx_errors: u32, rx_errors: u32) {
+        let mut fault = rics::RICS_CanFault::new();
+        fault.set_node(node);
+        fault.set_tx_errors(tx_errors);
+        fault.set_rx_errors(rx_errors);
+        let mut msg = rics::RICS_Request::new();
+        msg.set_can_fault(fault);
+        self.send_request(msg);
+    }
+
+    /// Bring a bus-off node back to the error active state
+    pub fn recover_can_node(&mut self, node: i32) {
+        let mut fault = rics::RICS_CanFault::new();
+        fault.set_node(node);
+        fault.set_recover(true);
+        let mut msg = rics::RICS_Request::new();
+        msg.set_can_fault(fault);
+        self.send_request(msg);
+    }
+
+    /// Sets the delay after which bus-off nodes recover by themselves.
+    /// A delay of 0 means that nodes only recover with `recover_can_node`.
+    pub fn set_can_restart_ms(&mut self, ms: u32) {
+        let mut msg = rics::RICS_Request::new();
+        msg.set_can_restart_ms(ms);
+        self.send_request(msg);
+    }
+
+    /// Return the CAN error counters and state of every node
+    pub fn can_states(&mut self) -> Vec<rics::RICS_Response_RICS_CanState> {
+        debug!("Sending LIST_CAN_STATE query");
+        let mut msg = rics::RICS_Request::new();
+        msg.set_query(rics::RICS_Request_RICS_Query::LIST_CAN_STATE);
+        msg.write_length_delimited_to_writer(&mut self.socket).expect("LIST_CAN_STATE query message fail");
+
+        trace!("Waiting for server response");
+        match self.read_response(READ_TIMEOUT) {
+            Ok(resp) => resp.get_can_states().get_states().to_vec(),
+            Err(err) => { warn!("LIST_CAN_STATE bad response: {}", err); vec![] },
+        }
+    }
 
     /// Return the currently loaded nodes and their alias
     pub fn list_nodes(&mut self) -> &HashMap<i32, String> {
@@ -197,10 +340,8 @@ impl RICSServer {
         msg.set_query(rics::RICS_Request_RICS_Query::LIST_SINK);
         msg.write_length_delimited_to_writer(&mut self.socket).expect("LIST_SINK query message fail");
 
-        let mut input = CodedInputStream::new(&mut self.input);
-
         trace!("Waiting for server response");
-        self.node_names = match input.read_message::<rics::RICS_Response>() {
+        self.node_names = match self.read_response(READ_TIMEOUT) {
             Ok(resp) => resp.get_idlist().get_ids().iter().map(|id| (id.get_id(),id.get_name().into())).collect(),
             Err(err) => { warn!("LIST_SINK bad response: {}", err); HashMap::new() },
         };
@@ -208,6 +349,11 @@ impl RICSServer {
         &self.node_names
     }
 
+    /// Return the nodes and alias received by the last `list_nodes` call
+    pub fn list_nodes_cached(&self) -> &HashMap<i32, String> {
+        &self.node_names
+    }
+
     /// Stops the parent server
     pub fn stop_server(&mut self) {
         debug!("Stopping server");
@@ -288,6 +434,43 @@ impl RICSServer {
     }
 
 
+    /// Receive the packets matching a subscription, on top of those routed to the node
+    pub fn subscribe(&mut self, subscription: &Subscription) {
+        let mut req = rics::RICS_Request::new();
+        req.set_subscribe(subscription.to_message());
+        self.send_request(req);
+    }
+
+    /// Remove a subscription made with the same parameters
+    pub fn unsubscribe(&mut self, subscription: &Subscription) {
+        let mut req = rics::RICS_Request::new();
+        req.set_unsubscribe(subscription.to_message());
+        self.send_request(req);
+    }
+
+    /// Only receive the packets accepted by the filter from now on, an empty filter accepting everything again
+    pub fn set_receive_filter(&mut self, filter: &ReceiveFilter) {
+        let mut msg = rics::RICS_ReceiveFilter::new();
+        msg.set_ids(filter.ids.iter().map(|(id, mask)| {
+            let mut x = rics::RICS_ReceiveFilter_RICS_IdFilter::new();
+            x.set_id(*id);
+            x.set_mask(*mask);
+            x
+        }).collect());
+        msg.set_types(filter.types.clone());
+        msg.set_sources(filter.sources.clone());
+        let mut req = rics::RICS_Request::new();
+        req.set_receive_filter(msg);
+        self.send_request(req);
+    }
+
+    /// Send a packet to the default route and to the nodes subscribed to the topic
+    pub fn publish(&mut self, topic: impl Into<String>, data: rics::RICS_Data) {
+        let mut data = data;
+        data.set_topic(topic.into());
+        self.send_packet(data)
+    }
+
     /// Send a request to the server
     pub fn send_request(&mut self, msg: rics::RICS_Request) {
         debug!("Request sent as: {:?}", &msg);
@@ -314,31 +497,71 @@ impl RICSServer {
         self.send_request(msg)
     }
 
+    /// Next server message, waiting up to `timeout` when a reader thread
+    /// was started, or up to the socket read timeout otherwise
+    fn read_response(&mut self, timeout: Duration) -> Result<rics::RICS_Response, String> {
+        if let Some(resp) = self.pending.pop_front() {
+            return Ok(resp);
+        }
+        match &self.responses {
+            Some(responses) => responses.recv_timeout(timeout).map_err(|e| e.to_string()),
+            None => CodedInputStream::from_buffered_reader(&mut self.input).read_message::<rics::RICS_Response>().map_err(|e| e.to_string()),
+        }
+    }
+
     /// Blocks and wait for the next server message
     pub fn get_response(&mut self) -> Option<rics::RICS_Response> {
         debug!("Getting packet...");
 
-        let mut input = CodedInputStream::new(&mut self.input);
-
-        match input.read_message::<rics::RICS_Response>() {
+        match self.read_response(READ_TIMEOUT) {
             Ok(resp) => Some(resp),
             Err(err) => { warn!("GET_PACKET bad response: {}", err); None},
         }
     }
 
-    /// Read packets to a channel. The packets can't be read anymore elsewhere
+    /// Read packets to a channel. The packets can't be read anymore elsewhere.
+    /// The channel is disconnected once the server connection is closed.
     pub fn listen_response(&mut self) -> Receiver<rics::RICS_Response> {
-        
+        let responses = match self.responses.take() {
+            Some(responses) => responses,
+            None => self.spawn_reader(),
+        };
+        if self.pending.is_empty() {
+            return responses;
+        }
+
+        // The messages kept by `call` come first
+        let (tx, rx) = channel();
+        for resp in self.pending.drain(..) {
+            tx.send(resp).ok();
+        }
+        std::thread::spawn(move || for resp in responses {
+            if tx.send(resp).is_err() {
+                break;
+            }
+        });
+        rx
+    }
+
+    fn spawn_reader(&mut self) -> Receiver<rics::RICS_Response> {
         let (tx, rx) = channel();
 
-        let mut input_reader: Box<dyn Read + Sync + Send> = Box::new(std::io::empty());
+        let mut input_reader: BufReader<Box<dyn Read + Sync + Send>> = BufReader::new(Box::new(std::io::empty()));
         std::mem::swap(&mut input_reader, &mut self.input);
 
         std::thread::spawn(move|| {
-            let mut input = CodedInputStream::new(&mut input_reader);
+            let mut input = CodedInputStream::from_buffered_reader(&mut input_reader);
             loop {
-                if let Ok(resp) = input.read_message::<rics::RICS_Response>() {
-                    tx.send(resp).expect("Brocken listen_response channel");
+                match input.read_message::<rics::RICS_Response>() {
+                    Ok(resp) => if tx.send(resp).is_err() {
+                        break;
+                    },
+                    // The socket read timeout
+                    Err(ProtobufError::IoError(ref e)) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => (),
+                    Err(e) => {
+                        debug!("Server connection closed: {}", e);
+                        break;
+                    },
                 }
             }
         });
@@ -346,13 +569,68 @@ impl RICSServer {
         rx
     }
 
+    /// Read and drop every message, to keep the socket of a node that only
+    /// sends from filling up
+    pub fn drain(&mut self) {
+        let rx = self.listen_response();
+        std::thread::spawn(move || for _ in rx {});
+    }
+
+    /// Blocks until a DATAGRAM packet is received on `port`, other packets are dropped
+    pub fn recv_datagram(&mut self, port: i32) -> rics::RICS_Data {
+        loop {
+            if let Some(mut resp) = self.get_response() {
+                if resp.has_data() {
+                    let data = resp.take_data();
+                    if data.get_field_type() == rics::RICS_Data_RICS_DataType::DATAGRAM && data.get_id() == port {
+                        return data;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Call an RPC method of the target node and wait up to `timeout` for its
+    /// response. The request is sent again if the response is late, and the
+    /// other packets received meanwhile are kept for the next reads.
+    pub fn call(&mut self, target: i32, method: &str, payload: &[u8], timeout: Duration) -> Result<Vec<u8>, rpc::RpcError> {
+        let id = rand::random::<u32>();
+        let request = rpc::request_packet(id, method, payload).ok_or(rpc::RpcError::InvalidMethod)?;
+        // Reads have to end at the retry deadlines, not at the socket read timeout
+        if self.responses.is_none() {
+            self.responses = Some(self.spawn_reader());
+        }
+        let start = Instant::now();
+        for attempt in 1..=rpc::RPC_RETRIES + 1 {
+            self.send_packet_to(request.clone(), target);
+            let deadline = start + timeout * attempt / (rpc::RPC_RETRIES + 1);
+            loop {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                let resp = match self.responses.as_ref().map(|x| x.recv_timeout(deadline - now)) {
+                    Some(Ok(resp)) => resp,
+                    Some(Err(RecvTimeoutError::Timeout)) => break,
+                    Some(Err(RecvTimeoutError::Disconnected)) | None => return Err(rpc::RpcError::Disconnected),
+                };
+                if resp.has_data() && resp.get_data().get_source() == target {
+                    if let Some(result) = rpc::parse_response(resp.get_data(), id) {
+                        return result;
+                    }
+                }
+                self.pending.push_back(resp);
+            }
+            debug!("No response to request {} of {}", id, method);
+        }
+        Err(rpc::RpcError::Timeout)
+    }
+
     /// Blocks and wait for the next packet
     pub fn get_packet(&mut self) -> Option<rics::RICS_Data> {
         debug!("Getting packet...");
 
-        let mut input = CodedInputStream::new(&mut self.input);
-
-        match input.read_message::<rics::RICS_Response>() {
+        match self.read_response(READ_TIMEOUT) {
             Ok(resp) => if resp.has_data() {
                 let data = resp.get_data();
                 Some(data.clone())
@@ -364,14 +642,114 @@ impl RICSServer {
 }
 
 
-pub fn can_packet(id: i32, dat: Vec<u8>) -> rics::RICS_Data {
+/// Maximum data length of a classic CAN frame
+pub const CAN_MAX_DLEN: usize = 8;
+/// Maximum data length of a CAN FD frame
+pub const CANFD_MAX_DLEN: usize = 64;
+
+/// Smallest CAN FD data length that can hold `len` bytes.
+/// CAN FD frames above 8 bytes can only be 12, 16, 20, 24, 32, 48 or 64 bytes long.
+pub fn can_fd_len(len: usize) -> usize {
+    match len {
+        0..=8 => len,
+        9..=12 => 12,
+        13..=16 => 16,
+        17..=20 => 20,
+        21..=24 => 24,
+        25..=32 => 32,
+        33..=48 => 48,
+        _ => CANFD_MAX_DLEN,
+    }
+}
+
+/// CAN FD DLC code of a data length
+pub fn can_fd_dlc(len: usize) -> u8 {
+    match can_fd_len(len) {
+        12 => 9,
+        16 => 10,
+        20 => 11,
+        24 => 12,
+        32 => 13,
+        48 => 14,
+        64 => 15,
+        len => len as u8,
+    }
+}
+
+/// Data length of a CAN FD DLC code
+pub fn can_fd_dlc_len(dlc: u8) -> usize {
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64][(dlc & 0x0F) as usize]
+}
+
+/// Largest standard (11 bit) CAN identifier
+pub const CAN_SFF_MAX: i32 = 0x7FF;
+
+/// Frame flags of a CAN packet
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct CanFlags {
+    /// 29 bit identifier
+    pub extended: bool,
+    /// Remote frame, only the data length is meaningful
+    pub rtr: bool,
+    /// Error frame, the id holds the error class
+    pub error: bool,
+    /// CAN FD frame
+    pub fd: bool,
+    /// CAN FD bit rate switch
+    pub brs: bool,
+    /// CAN FD error state indicator
+    pub esi: bool,
+}
+
+/// Frame flags of a CAN packet
+pub fn can_flags(data: &rics::RICS_Data) -> CanFlags {
+    CanFlags {
+        extended: data.get_extended(),
+        rtr: data.get_rtr(),
+        error: data.get_error(),
+        fd: data.get_fd(),
+        brs: data.get_brs(),
+        esi: data.get_esi(),
+    }
+}
+
+/// CAN packet with explicit frame flags.
+/// CAN FD data is truncated to 64 bytes and padded with zeros up to a valid
+/// CAN FD length, and the content of remote frames is zeroed as they only
+/// carry their length.
+pub fn can_frame(id: i32, mut dat: Vec<u8>, flags: CanFlags) -> rics::RICS_Data {
+    if flags.fd {
+        if dat.len() > CANFD_MAX_DLEN {
+            warn!("CAN FD frames can't carry more than {} bytes, dropping {} bytes of frame {:X}", CANFD_MAX_DLEN, dat.len() - CANFD_MAX_DLEN, id);
+            dat.truncate(CANFD_MAX_DLEN);
+        }
+        let len = can_fd_len(dat.len());
+        dat.resize(len, 0);
+    }
+    if flags.rtr {
+        dat.iter_mut().for_each(|x| *x = 0);
+    }
     let mut data = rics::RICS_Data::new();
     data.set_id(id);
     data.set_data(dat);
     data.set_field_type(rics::RICS_Data_RICS_DataType::CAN);
+    if flags.extended { data.set_extended(true); }
+    if flags.rtr { data.set_rtr(true); }
+    if flags.error { data.set_error(true); }
+    if flags.fd {
+        data.set_fd(true);
+        data.set_brs(flags.brs);
+        data.set_esi(flags.esi);
+    }
     data
 }
 
+/// CAN packet, ids above 0x7FF are extended and frames longer than 8 bytes are sent as CAN FD frames
+pub fn can_packet(id: i32, dat: Vec<u8>) -> rics::RICS_Data {
+    let fd = dat.len() > CAN_MAX_DLEN;
+    can_frame(id, dat, CanFlags { extended: id > CAN_SFF_MAX, fd: fd, ..Default::default() })
+}
+
 pub fn stream_packet(dat: Vec<u8>) -> rics::RICS_Data {
     let mut data = rics::RICS_Data::new();
     data.set_data(dat);
@@ -379,20 +757,93 @@ pub fn stream_packet(dat: Vec<u8>) -> rics::RICS_Data {
     data
 }
 
+/// DATAGRAM packet, the id being the port of the service it is sent to
+pub fn datagram_packet(port: i32, dat: Vec<u8>) -> rics::RICS_Data {
+    let mut data = rics::RICS_Data::new();
+    data.set_data(dat);
+    data.set_id(port);
+    data.set_field_type(rics::RICS_Data_RICS_DataType::DATAGRAM);
+    data
+}
+
+/// Sync pulse, forwarded by the host to every other node
+pub fn sync_packet() -> rics::RICS_Data {
+    let mut data = rics::RICS_Data::new();
+    data.set_field_type(rics::RICS_Data_RICS_DataType::SYNC);
+    data
+}
+
+/// Time at which the host received the packet.
+/// Packets that were not stamped by the host use the current time.
+pub fn data_time(data: &rics::RICS_Data) -> SystemTime {
+    if data.has_timestamp() {
+        UNIX_EPOCH + Duration::from_nanos(data.get_timestamp())
+    } else {
+        SystemTime::now()
+    }
+}
+
+/// Sequence number given to the packet by the host, increasing by one for each packet of a source
+pub fn data_sequence(data: &rics::RICS_Data) -> Option<u32> {
+    if data.has_sequence() { Some(data.get_sequence()) } else { None }
+}
+
+/// Frame flags of a CAN packet, as a space separated list
+pub fn data_flags(data: &rics::RICS_Data) -> String {
+    let mut flags = vec![];
+    if data.get_extended() { flags.push("EXT"); }
+    if data.get_rtr() { flags.push("RTR"); }
+    if data.get_error() { flags.push("ERR"); }
+    if data.get_fd() { flags.push("FD"); }
+    if data.get_brs() { flags.push("BRS"); }
+    if data.get_esi() { flags.push("ESI"); }
+    flags.join(" ")
+}
+
+/// Set the frame flags of a CAN packet from a list formatted by `data_flags`
+pub fn set_data_flags(data: &mut rics::RICS_Data, flags: &str) {
+    for flag in flags.split_whitespace() {
+        match flag {
+            "EXT" => data.set_extended(true),
+            "RTR" => data.set_rtr(true),
+            "ERR" => data.set_error(true),
+            "FD" => data.set_fd(true),
+            "BRS" => data.set_brs(true),
+            "ESI" => data.set_esi(true),
+            _ => warn!("Unknown frame flag {}", flag),
+        }
+    }
+}
+
+/// Bytes of a hexadecimal string, whitespace between the digits is ignored
+pub fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = s.bytes().filter(|x| !x.is_ascii_whitespace()).collect();
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    digits.chunks(2).map(|x| Some((char::from(x[0]).to_digit(16)? << 4 | char::from(x[1]).to_digit(16)?) as u8)).collect()
+}
 
 pub fn data_to_string(data: &rics::RICS_Data) -> String {
-    format!("<{} -> {} ({:08x}) [{}]>", data.get_source(),
+    format!("<{} -> {} ({:08x}) [{}]{}{} #{}>", data.get_source(),
             data.get_target(),
             data.get_id(),
             data.get_data().iter().map(|x| format!("{:02x}", x))
-            .collect::<Vec<String>>().join(", "))
+            .collect::<Vec<String>>().join(", "),
+            Some(data_flags(data)).filter(|x| !x.is_empty()).map(|x| format!(" {}", x)).unwrap_or_default(),
+            if data.has_topic() { format!(" @{}", data.get_topic()) } else { String::new() },
+            data.get_sequence())
 }
 
+/// CSV log line of a packet. The frame flags, if any, follow an empty source
+/// column so that the line reads back as a recording.
 pub fn data_to_loggable_string(data: &rics::RICS_Data) -> String{
-    let time = std::time::SystemTime::now();
+    let time = data_time(data);
     let datetime: chrono::DateTime<chrono::offset::Local> = time.into();
-    format!("{},{:x},{},{}", datetime.format("%Y-%m-%d %T%.3f"), data.get_id(), data.get_data().len(), data.get_data().iter()
-             .map(|x|format!("{:x}",x)).collect::<Vec<String>>().join(","))
+    let line = format!("{},{:x},{},{}", datetime.format("%Y-%m-%d %T%.3f"), data.get_id(), data.get_data().len(), data.get_data().iter()
+             .map(|x|format!("{:x}",x)).collect::<Vec<String>>().join(","));
+    let flags = data_flags(data);
+    if flags.is_empty() { line } else { format!("{},,{}", line, flags) }
 }
 
 pub fn response_to_string(resp: &rics::RICS_Response) -> String {
@@ -404,3 +855,33 @@ pub fn response_to_string(resp: &rics::RICS_Response) -> String {
         format!("<???>")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_fd_lengths() {
+        assert_eq!(can_packet(0x123, vec![1; 9]).get_data().len(), 12);
+        assert_eq!(can_fd_dlc(9), 9);
+        assert_eq!(can_fd_dlc(64), 15);
+        assert_eq!(can_fd_dlc_len(can_fd_dlc(33)), 48);
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse_hex("01ab FF"), Some(vec![0x01, 0xAB, 0xFF]));
+        assert_eq!(parse_hex(""), Some(vec![]));
+        assert_eq!(parse_hex("123"), None);
+        assert_eq!(parse_hex("0g"), None);
+        assert_eq!(parse_hex("aé"), None);
+        assert_eq!(parse_hex("éé"), None);
+    }
+
+    #[test]
+    fn oversized_can_fd_frame() {
+        let data = can_packet(0x123, (0..100).collect());
+        assert!(data.get_fd());
+        assert_eq!(data.get_data(), &(0..64).collect::<Vec<u8>>()[..]);
+    }
+}
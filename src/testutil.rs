@@ -0,0 +1,47 @@
+//! Fixtures shared by the unit tests
+
+use std::io::{Cursor, Result, Write};
+
+use protobuf::Message;
+use std::sync::{Arc, Mutex};
+
+use super::rics;
+
+/// Writer keeping the requests sent to the server, shared by its clones
+#[derive(Clone, Default)]
+pub struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Output {
+    /// Requests written since the last call
+    pub fn requests(&self) -> Vec<rics::RICS_Request> {
+        let buffer: Vec<u8> = self.0.lock().unwrap().drain(..).collect();
+        let mut input = protobuf::CodedInputStream::from_bytes(&buffer);
+        let mut requests = vec![];
+        while !input.eof().unwrap() {
+            requests.push(input.read_message::<rics::RICS_Request>().unwrap());
+        }
+        requests
+    }
+}
+
+/// Server messages carrying packets, to read from with `RICSServer::new_from`
+pub fn responses(packets: Vec<rics::RICS_Data>) -> Cursor<Vec<u8>> {
+    let mut buffer = vec![];
+    for data in packets {
+        let mut resp = rics::RICS_Response::new();
+        resp.set_data(data);
+        resp.write_length_delimited_to_vec(&mut buffer).unwrap();
+    }
+    Cursor::new(buffer)
+}
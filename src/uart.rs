@@ -0,0 +1,123 @@
+//! Serial lines carried over STREAM packets
+//!
+//! Bytes read from a serial line are grouped into packets, ended by a
+//! delimiter byte, a maximum size, or a gap in the line longer than the read
+//! timeout of the port. Without a delimiter or a timeout, each read makes a
+//! packet.
+
+use std::io::Result;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+/// Serial line settings
+#[derive(Clone, Debug)]
+pub struct LineSettings {
+    pub baud: u32,
+    pub data_bits: u8,
+    pub parity: Parity,
+    pub stop_bits: u8,
+}
+
+impl Default for LineSettings {
+    fn default() -> LineSettings {
+        LineSettings { baud: 115200, data_bits: 8, parity: Parity::None, stop_bits: 1 }
+    }
+}
+
+impl LineSettings {
+    /// Set the data bits, parity and stop bits from a format such as `8N1` or `7E2`
+    pub fn with_format(mut self, format: &str) -> Option<LineSettings> {
+        let chars: Vec<char> = format.chars().collect();
+        if chars.len() != 3 {
+            return None;
+        }
+        self.data_bits = chars[0].to_digit(10).filter(|x| (5..=8).contains(x))? as u8;
+        self.parity = match chars[1].to_ascii_uppercase() {
+            'N' => Parity::None,
+            'E' => Parity::Even,
+            'O' => Parity::Odd,
+            _ => return None,
+        };
+        self.stop_bits = chars[2].to_digit(10).filter(|x| *x == 1 || *x == 2)? as u8;
+        Some(self)
+    }
+}
+
+/// Open a serial port, reads failing with `TimedOut` after `timeout` without data
+pub fn open(path: &str, settings: &LineSettings, timeout: Duration) -> Result<Box<dyn serialport::SerialPort>> {
+    let settings = serialport::SerialPortSettings {
+        baud_rate: settings.baud,
+        data_bits: match settings.data_bits {
+            5 => serialport::DataBits::Five,
+            6 => serialport::DataBits::Six,
+            7 => serialport::DataBits::Seven,
+            _ => serialport::DataBits::Eight,
+        },
+        flow_control: serialport::FlowControl::None,
+        parity: match settings.parity {
+            Parity::None => serialport::Parity::None,
+            Parity::Even => serialport::Parity::Even,
+            Parity::Odd => serialport::Parity::Odd,
+        },
+        stop_bits: if settings.stop_bits == 2 { serialport::StopBits::Two } else { serialport::StopBits::One },
+        timeout: timeout,
+    };
+    Ok(serialport::open_with_settings(path, &settings)?)
+}
+
+#[derive(Clone, Debug)]
+pub struct PacketOptions {
+    /// Gap ending a packet, implemented by the read timeout of the port
+    pub timeout: Option<Duration>,
+    /// Byte ending a packet, included in it
+    pub delimiter: Option<u8>,
+    pub max_size: usize,
+}
+
+impl Default for PacketOptions {
+    fn default() -> PacketOptions {
+        PacketOptions { timeout: None, delimiter: None, max_size: 2048 }
+    }
+}
+
+/// Groups the bytes read into packets
+pub struct Packetizer {
+    options: PacketOptions,
+    buffer: Vec<u8>,
+}
+
+impl Packetizer {
+    pub fn new(options: PacketOptions) -> Packetizer {
+        Packetizer { options: options, buffer: vec![] }
+    }
+
+    /// Add the bytes of a read, returning the packets they complete
+    pub fn push(&mut self, dat: &[u8]) -> Vec<Vec<u8>> {
+        let mut packets = vec![];
+        for byte in dat {
+            self.buffer.push(*byte);
+            if Some(*byte) == self.options.delimiter || self.buffer.len() >= self.options.max_size {
+                packets.push(std::mem::take(&mut self.buffer));
+            }
+        }
+        if self.options.timeout.is_none() && self.options.delimiter.is_none() {
+            packets.extend(self.flush());
+        }
+        packets
+    }
+
+    /// End the packet being received, after a gap or at the end of the input
+    pub fn flush(&mut self) -> Option<Vec<u8>> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.buffer))
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! UDS (ISO 14229) diagnostic client over ISO-TP
+//!
+//! Each request waits for its positive or negative response. Response pending
+//! negative responses (NRC 0x78) extend the wait to the P2* timeout until the
+//! final response arrives.
+
+use std::fmt;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use super::isotp::IsoTp;
+
+pub const SID_DIAGNOSTIC_SESSION_CONTROL: u8 = 0x10;
+pub const SID_ECU_RESET: u8 = 0x11;
+pub const SID_READ_DATA_BY_IDENTIFIER: u8 = 0x22;
+pub const SID_SECURITY_ACCESS: u8 = 0x27;
+pub const SID_WRITE_DATA_BY_IDENTIFIER: u8 = 0x2E;
+pub const SID_ROUTINE_CONTROL: u8 = 0x31;
+pub const SID_REQUEST_DOWNLOAD: u8 = 0x34;
+pub const SID_TRANSFER_DATA: u8 = 0x36;
+pub const SID_REQUEST_TRANSFER_EXIT: u8 = 0x37;
+pub const SID_TESTER_PRESENT: u8 = 0x3E;
+
+/// First byte of negative responses
+pub const NEGATIVE_RESPONSE: u8 = 0x7F;
+/// Positive responses echo the service id with this bit set
+pub const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+
+pub const NRC_GENERAL_REJECT: u8 = 0x10;
+pub const NRC_SERVICE_NOT_SUPPORTED: u8 = 0x11;
+pub const NRC_SUB_FUNCTION_NOT_SUPPORTED: u8 = 0x12;
+pub const NRC_INCORRECT_MESSAGE_LENGTH: u8 = 0x13;
+pub const NRC_CONDITIONS_NOT_CORRECT: u8 = 0x22;
+pub const NRC_REQUEST_SEQUENCE_ERROR: u8 = 0x24;
+pub const NRC_REQUEST_OUT_OF_RANGE: u8 = 0x31;
+pub const NRC_SECURITY_ACCESS_DENIED: u8 = 0x33;
+pub const NRC_INVALID_KEY: u8 = 0x35;
+pub const NRC_EXCEEDED_NUMBER_OF_ATTEMPTS: u8 = 0x36;
+pub const NRC_REQUIRED_TIME_DELAY_NOT_EXPIRED: u8 = 0x37;
+pub const NRC_UPLOAD_DOWNLOAD_NOT_ACCEPTED: u8 = 0x70;
+pub const NRC_TRANSFER_DATA_SUSPENDED: u8 = 0x71;
+pub const NRC_GENERAL_PROGRAMMING_FAILURE: u8 = 0x72;
+pub const NRC_WRONG_BLOCK_SEQUENCE_COUNTER: u8 = 0x73;
+pub const NRC_RESPONSE_PENDING: u8 = 0x78;
+pub const NRC_SUB_FUNCTION_NOT_SUPPORTED_IN_ACTIVE_SESSION: u8 = 0x7E;
+pub const NRC_SERVICE_NOT_SUPPORTED_IN_ACTIVE_SESSION: u8 = 0x7F;
+
+/// RoutineControl sub-functions
+pub const ROUTINE_START: u8 = 0x01;
+pub const ROUTINE_STOP: u8 = 0x02;
+pub const ROUTINE_RESULTS: u8 = 0x03;
+
+/// Name of a negative response code
+pub fn nrc_name(nrc: u8) -> &'static str {
+    match nrc {
+        NRC_GENERAL_REJECT => "general reject",
+        NRC_SERVICE_NOT_SUPPORTED => "service not supported",
+        NRC_SUB_FUNCTION_NOT_SUPPORTED => "sub-function not supported",
+        NRC_INCORRECT_MESSAGE_LENGTH => "incorrect message length or invalid format",
+        NRC_CONDITIONS_NOT_CORRECT => "conditions not correct",
+        NRC_REQUEST_SEQUENCE_ERROR => "request sequence error",
+        NRC_REQUEST_OUT_OF_RANGE => "request out of range",
+        NRC_SECURITY_ACCESS_DENIED => "security access denied",
+        NRC_INVALID_KEY => "invalid key",
+        NRC_EXCEEDED_NUMBER_OF_ATTEMPTS => "exceeded number of attempts",
+        NRC_REQUIRED_TIME_DELAY_NOT_EXPIRED => "required time delay not expired",
+        NRC_UPLOAD_DOWNLOAD_NOT_ACCEPTED => "upload download not accepted",
+        NRC_TRANSFER_DATA_SUSPENDED => "transfer data suspended",
+        NRC_GENERAL_PROGRAMMING_FAILURE => "general programming failure",
+        NRC_WRONG_BLOCK_SEQUENCE_COUNTER => "wrong block sequence counter",
+        NRC_RESPONSE_PENDING => "response pending",
+        NRC_SUB_FUNCTION_NOT_SUPPORTED_IN_ACTIVE_SESSION => "sub-function not supported in active session",
+        NRC_SERVICE_NOT_SUPPORTED_IN_ACTIVE_SESSION => "service not supported in active session",
+        _ => "unknown",
+    }
+}
+
+/// UDS request failure
+#[derive(Debug)]
+pub enum UdsError {
+    /// Transport error or timeout
+    Io(std::io::Error),
+    /// Negative response, with the service id and the negative response code
+    Negative(u8, u8),
+    /// Malformed response
+    Invalid(String),
+}
+
+impl fmt::Display for UdsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UdsError::Io(e) => write!(f, "{}", e),
+            UdsError::Negative(sid, nrc) => write!(f, "Service 0x{:02X} negative response 0x{:02X} ({})", sid, nrc, nrc_name(*nrc)),
+            UdsError::Invalid(msg) => write!(f, "Invalid response: {}", msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for UdsError {
+    fn from(e: std::io::Error) -> UdsError {
+        UdsError::Io(e)
+    }
+}
+
+pub type UdsResult<T> = Result<T, UdsError>;
+
+/// UDS client talking to a single ECU
+pub struct UdsClient {
+    isotp: IsoTp,
+    /// Time to wait for a response
+    pub p2: Duration,
+    /// Time to wait for a response after a response pending
+    pub p2_star: Duration,
+}
+
+impl UdsClient {
+    /// The default timeouts leave room for the latency of the RICS server
+    pub fn new(isotp: IsoTp) -> UdsClient {
+        UdsClient {
+            isotp: isotp,
+            p2: Duration::from_millis(1000),
+            p2_star: Duration::from_millis(5000),
+        }
+    }
+
+    /// Send a raw request and return its positive response, service id included
+    pub fn request(&mut self, req: &[u8]) -> UdsResult<Vec<u8>> {
+        let sid = *req.get(0).ok_or_else(|| UdsError::Invalid("Empty request".to_string()))?;
+        // Response service ids have bit 6 set, the negative response id 0x7F included
+        if sid & POSITIVE_RESPONSE_OFFSET != 0 {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, format!("0x{:02X} is not a request service id", sid)).into());
+        }
+        let positive = sid | POSITIVE_RESPONSE_OFFSET;
+        self.isotp.send(req)?;
+        let mut timeout = self.p2;
+        loop {
+            let resp = match self.isotp.recv(Some(timeout)) {
+                Ok(resp) => resp,
+                Err(ref e) if e.kind() == ErrorKind::TimedOut => {
+                    return Err(std::io::Error::new(ErrorKind::TimedOut, format!("No response to service 0x{:02X}", sid)).into());
+                },
+                Err(e) => return Err(e.into()),
+            };
+            match resp.get(0) {
+                Some(&NEGATIVE_RESPONSE) if resp.len() >= 3 && resp[1] == sid => {
+                    if resp[2] == NRC_RESPONSE_PENDING {
+                        debug!("Service 0x{:02X} response pending", sid);
+                        timeout = self.p2_star;
+                        continue;
+                    }
+                    return Err(UdsError::Negative(sid, resp[2]));
+                },
+                Some(&x) if x == positive => return Ok(resp),
+                _ => warn!("Ignoring unexpected UDS response {:02X?}", resp),
+            }
+        }
+    }
+
+    /// Request with a response of at least `len` bytes, returned without its first `skip` bytes
+    fn request_checked(&mut self, req: &[u8], len: usize, skip: usize) -> UdsResult<Vec<u8>> {
+        let resp = self.request(req)?;
+        if resp.len() < len || resp[1..skip] != req[1..skip] {
+            return Err(UdsError::Invalid(format!("{:02X?} doesn't match request {:02X?}", resp, req)));
+        }
+        Ok(resp[skip..].to_vec())
+    }
+
+    /// DiagnosticSessionControl, returns the session parameter record
+    pub fn session_control(&mut self, session: u8) -> UdsResult<Vec<u8>> {
+        self.request_checked(&[SID_DIAGNOSTIC_SESSION_CONTROL, session], 2, 2)
+    }
+
+    /// ECUReset, returns the power down time if any
+    pub fn ecu_reset(&mut self, reset_type: u8) -> UdsResult<Vec<u8>> {
+        self.request_checked(&[SID_ECU_RESET, reset_type], 2, 2)
+    }
+
+    /// TesterPresent, keeping the current session alive
+    pub fn tester_present(&mut self) -> UdsResult<()> {
+        self.request_checked(&[SID_TESTER_PRESENT, 0x00], 2, 2).map(|_| ())
+    }
+
+    /// SecurityAccess seed and key exchange for an odd `level`.
+    /// `key` computes the key from the level and the seed. A null seed means
+    /// the level is already unlocked and no key is sent.
+    pub fn security_access(&mut self, level: u8, key: &mut dyn FnMut(u8, &[u8]) -> Option<Vec<u8>>) -> UdsResult<()> {
+        if level % 2 == 0 || level > 0x7D {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, format!("0x{:02X} is not a security level, levels are odd from 0x01 to 0x7D", level)).into());
+        }
+        let seed = self.request_checked(&[SID_SECURITY_ACCESS, level], 2, 2)?;
+        if seed.iter().all(|x| *x == 0) {
+            info!("Security level 0x{:02X} already unlocked", level);
+            return Ok(());
+        }
+        let key = key(level, &seed).ok_or_else(|| UdsError::Invalid(format!("No key for seed {:02X?}", seed)))?;
+        let mut req = vec![SID_SECURITY_ACCESS, level + 1];
+        req.extend_from_slice(&key);
+        self.request_checked(&req, 2, 2).map(|_| ())
+    }
+
+    /// ReadDataByIdentifier, returns the data record
+    pub fn read_data_by_identifier(&mut self, did: u16) -> UdsResult<Vec<u8>> {
+        let did = did.to_be_bytes();
+        self.request_checked(&[SID_READ_DATA_BY_IDENTIFIER, did[0], did[1]], 3, 3)
+    }
+
+    /// WriteDataByIdentifier
+    pub fn write_data_by_identifier(&mut self, did: u16, data: &[u8]) -> UdsResult<()> {
+        let mut req = vec![SID_WRITE_DATA_BY_IDENTIFIER];
+        req.extend_from_slice(&did.to_be_bytes());
+        req.extend_from_slice(data);
+        self.request_checked(&req, 3, 3).map(|_| ())
+    }
+
+    /// RoutineControl, returns the routine status record
+    pub fn routine_control(&mut self, control: u8, routine: u16, options: &[u8]) -> UdsResult<Vec<u8>> {
+        let mut req = vec![SID_ROUTINE_CONTROL, control];
+        req.extend_from_slice(&routine.to_be_bytes());
+        req.extend_from_slice(options);
+        self.request_checked(&req, 4, 4)
+    }
+
+    /// RequestDownload with 32 bit address and size, returns the maximum TransferData request length
+    pub fn request_download(&mut self, address: u32, size: u32, data_format: u8) -> UdsResult<usize> {
+        let mut req = vec![SID_REQUEST_DOWNLOAD, data_format, 0x44];
+        req.extend_from_slice(&address.to_be_bytes());
+        req.extend_from_slice(&size.to_be_bytes());
+        let resp = self.request(&req)?;
+        let n = (*resp.get(1).unwrap_or(&0) >> 4) as usize;
+        if n == 0 || n > 8 || resp.len() < 2 + n {
+            return Err(UdsError::Invalid(format!("Bad length format in {:02X?}", resp)));
+        }
+        Ok(resp[2..2 + n].iter().fold(0, |acc, x| acc << 8 | *x as usize))
+    }
+
+    /// TransferData, returns the transfer response parameters
+    pub fn transfer_data(&mut self, block_counter: u8, data: &[u8]) -> UdsResult<Vec<u8>> {
+        let mut req = vec![SID_TRANSFER_DATA, block_counter];
+        req.extend_from_slice(data);
+        self.request_checked(&req, 2, 2)
+    }
+
+    /// RequestTransferExit, returns the transfer response parameters
+    pub fn request_transfer_exit(&mut self) -> UdsResult<Vec<u8>> {
+        self.request_checked(&[SID_REQUEST_TRANSFER_EXIT], 1, 1)
+    }
+
+    /// Download a whole memory block with RequestDownload, TransferData and RequestTransferExit
+    pub fn download(&mut self, address: u32, data: &[u8], data_format: u8) -> UdsResult<()> {
+        let max_len = self.request_download(address, data.len() as u32, data_format)?;
+        if max_len <= 2 {
+            return Err(UdsError::Invalid(format!("Maximum block length {} is too small", max_len)));
+        }
+        // The block length includes the service id and the block sequence counter
+        for (i, block) in data.chunks(max_len - 2).enumerate() {
+            debug!("Transferring block {} of {} bytes", i + 1, block.len());
+            self.transfer_data((i + 1) as u8, block)?;
+        }
+        self.request_transfer_exit().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::isotp::IsoTpOptions;
+    use super::super::server::{self, RICSServer};
+    use super::super::testutil::{self, Output};
+
+    /// Flow control frame letting the client send a whole request
+    const CONTINUE: &[u8] = &[0x30, 0x00, 0x00];
+
+    fn single_frame(payload: &[u8]) -> Vec<u8> {
+        let mut dat = vec![payload.len() as u8];
+        dat.extend_from_slice(payload);
+        dat
+    }
+
+    /// Client receiving the given ISO-TP frames from the ECU
+    fn client(frames: Vec<Vec<u8>>) -> (UdsClient, Output) {
+        let output = Output::default();
+        let input = testutil::responses(frames.into_iter().map(|x| server::can_packet(0x7E8, x)).collect());
+        let isotp = IsoTp::new(RICSServer::new_from(input, output.clone()), IsoTpOptions::new(0x7E0, 0x7E8));
+        (UdsClient::new(isotp), output)
+    }
+
+    /// Requests sent in single frames
+    fn requests(output: &Output) -> Vec<Vec<u8>> {
+        output.requests().iter().map(|x| x.get_data().get_data())
+            .filter(|x| x[0] >> 4 == 0).map(|x| x[1..=x[0] as usize].to_vec()).collect()
+    }
+
+    #[test]
+    fn response_pending() {
+        let (mut client, output) = client(vec![
+            single_frame(&[NEGATIVE_RESPONSE, SID_READ_DATA_BY_IDENTIFIER, NRC_RESPONSE_PENDING]),
+            single_frame(&[NEGATIVE_RESPONSE, SID_READ_DATA_BY_IDENTIFIER, NRC_RESPONSE_PENDING]),
+            single_frame(&[0x62, 0xF1, 0x90, 0x01, 0x02]),
+        ]);
+        assert_eq!(client.read_data_by_identifier(0xF190).unwrap(), vec![0x01, 0x02]);
+        assert_eq!(requests(&output), vec![vec![SID_READ_DATA_BY_IDENTIFIER, 0xF1, 0x90]]);
+    }
+
+    #[test]
+    fn negative_response() {
+        let (mut client, _) = client(vec![
+            // Responses to other services are ignored
+            single_frame(&[NEGATIVE_RESPONSE, SID_ECU_RESET, NRC_CONDITIONS_NOT_CORRECT]),
+            single_frame(&[NEGATIVE_RESPONSE, SID_DIAGNOSTIC_SESSION_CONTROL, NRC_SUB_FUNCTION_NOT_SUPPORTED]),
+        ]);
+        match client.session_control(0x05) {
+            Err(UdsError::Negative(SID_DIAGNOSTIC_SESSION_CONTROL, NRC_SUB_FUNCTION_NOT_SUPPORTED)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn no_response() {
+        let (mut client, _) = client(vec![]);
+        assert!(client.tester_present().is_err());
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let (mut client, output) = client(vec![]);
+        for sid in [0x40, 0x62, NEGATIVE_RESPONSE, 0xC0, 0xFF].iter() {
+            match client.request(&[*sid]) {
+                Err(UdsError::Io(ref e)) if e.kind() == ErrorKind::InvalidInput => (),
+                result => panic!("0x{:02X} accepted: {:?}", sid, result),
+            }
+        }
+        for level in [0x00, 0x02, 0x7E, 0x7F].iter() {
+            assert!(client.security_access(*level, &mut |_, _| Some(vec![])).is_err());
+        }
+        assert!(output.requests().is_empty());
+    }
+
+    #[test]
+    fn transfer_block_counter() {
+        // One byte per TransferData request
+        let mut frames = vec![CONTINUE.to_vec(), single_frame(&[0x74, 0x10, 0x03])];
+        for i in 1..=257 {
+            frames.push(single_frame(&[0x76, i as u8]));
+        }
+        frames.push(single_frame(&[0x77]));
+        let (mut client, output) = client(frames);
+        let data: Vec<u8> = (0..257).map(|x| x as u8).collect();
+        client.download(0x1000, &data, 0x00).unwrap();
+
+        let requests = requests(&output);
+        let transfers: Vec<&Vec<u8>> = requests.iter().filter(|x| x[0] == SID_TRANSFER_DATA).collect();
+        assert_eq!(transfers.len(), 257);
+        assert_eq!(*transfers[0], vec![SID_TRANSFER_DATA, 0x01, 0x00]);
+        assert_eq!(*transfers[254], vec![SID_TRANSFER_DATA, 0xFF, 0xFE]);
+        // The counter wraps to 0x00 after 0xFF
+        assert_eq!(*transfers[255], vec![SID_TRANSFER_DATA, 0x00, 0xFF]);
+        assert_eq!(*transfers[256], vec![SID_TRANSFER_DATA, 0x01, 0x00]);
+        assert_eq!(*requests.last().unwrap(), vec![SID_REQUEST_TRANSFER_EXIT]);
+    }
+}